@@ -21,7 +21,11 @@ async fn gather_spend_dag(client: &Client, root_dir: &Path) -> Result<SpendDag>
     let dag = match SpendDag::load_from_file(&dag_path) {
         Ok(mut dag) => {
             println!("Starting from the loaded spend dag on disk...");
-            client.spend_dag_continue_from_utxos(&mut dag).await?;
+            client
+                .spend_dag_continue_from_utxos(&mut dag, |spends| {
+                    println!("Collected {spends} new spends so far...")
+                })
+                .await?;
             dag
         }
         Err(err) => {
@@ -83,7 +87,7 @@ async fn redeem_royalties(
             let transfer = Transfer::NetworkRoyalties(batch.clone());
             batch.clear();
             println!("Current balance: {}", wallet.balance());
-            let cashnotes = client.receive(&transfer, &wallet).await?;
+            let (cashnotes, _memo) = client.receive(&transfer, &wallet).await?;
             wallet.deposit_and_store_to_disk(&cashnotes)?;
             println!("Successfully redeemed royalties from the Network.");
             println!("Current balance: {}", wallet.balance());
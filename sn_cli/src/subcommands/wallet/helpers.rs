@@ -134,14 +134,17 @@ pub async fn receive(
 
     println!("Verifying transfer with the Network...");
     let mut wallet = HotWallet::load_from(root_dir)?;
-    let cashnotes = match client.receive(&transfer, &wallet).await {
-        Ok(cashnotes) => cashnotes,
+    let (cashnotes, memo) = match client.receive(&transfer, &wallet).await {
+        Ok(received) => received,
         Err(err) => {
             println!("Failed to verify and redeem transfer: {err:?}");
             return Err(err.into());
         }
     };
     println!("Successfully verified transfer.");
+    if let Some(memo) = memo {
+        println!("Memo: {}", String::from_utf8_lossy(&memo));
+    }
 
     let old_balance = wallet.balance();
     wallet.deposit_and_store_to_disk(&cashnotes)?;
@@ -259,7 +259,7 @@ async fn send(
         }
         Err(err) => {
             match err {
-                ClientError::AmountIsZero => {
+                ClientError::Transfers(WalletError::ZeroAmount) => {
                     println!("Zero amount passed in. Nothing sent.");
                 }
                 ClientError::Transfers(WalletError::Transfer(TransferError::NotEnoughBalance(
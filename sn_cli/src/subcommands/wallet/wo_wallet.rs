@@ -8,7 +8,7 @@
 
 use super::{helpers::verify_spend, watch_only_wallet_from_pk, WalletApiHelper};
 
-use bls::{PublicKey, PK_SIZE};
+use bls::PublicKey;
 use clap::Parser;
 use color_eyre::{
     eyre::{bail, eyre},
@@ -16,10 +16,13 @@ use color_eyre::{
 };
 use dialoguer::Confirm;
 use sn_client::transfers::{
-    CashNoteRedemption, DerivationIndex, MainPubkey, NanoTokens, OfflineTransfer, SignedSpend,
-    UniquePubkey, WalletError, WatchOnlyWallet,
+    DerivationIndex, MainPubkey, NanoTokens, OfflineTransfer, SignedSpend, UniquePubkey,
+    WalletError, WatchOnlyWallet,
+};
+use sn_client::{
+    protocol::messages::{decode_transfer_notif, TransferNotif, ROYALTY_TRANSFER_NOTIF_TOPIC},
+    Client, ClientEvent,
 };
-use sn_client::{Client, ClientEvent};
 use std::{
     collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
@@ -28,7 +31,6 @@ use std::{
 use walkdir::WalkDir;
 
 const DEFAULT_RECEIVE_ONLINE_WALLET_DIR: &str = "receive_online";
-const ROYALTY_TRANSFER_NOTIF_TOPIC: &str = "ROYALTY_TRANSFER_NOTIFICATION";
 
 // Please do not remove the blank lines in these doc comments.
 // They are used for inserting line breaks when the help menu is rendered in the UI.
@@ -376,13 +378,17 @@ async fn listen_notifs_and_deposit(root_dir: &Path, client: &Client, pk_hex: Str
         let cash_notes = match event {
             ClientEvent::GossipsubMsg { topic, msg } => {
                 // we assume it's a notification of a transfer as that's the only topic we've subscribed to
-                match try_decode_transfer_notif(&msg) {
+                match decode_transfer_notif(&msg) {
                     Err(err) => {
                         println!("GossipsubMsg received on topic '{topic}' couldn't be decoded as transfer notif: {err:?}");
                         continue;
                     }
-                    Ok((key, _)) if key != pk => continue,
-                    Ok((key, cashnote_redemptions)) => {
+                    Ok(notif) if notif.key != pk => continue,
+                    Ok(TransferNotif {
+                        key,
+                        cashnote_redemptions,
+                        ..
+                    }) => {
                         println!("New transfer notification received for {key:?}, containing {} CashNoteRedemption/s.", cashnote_redemptions.len());
                         match client
                             .verify_cash_notes_redemptions(main_pk, &cashnote_redemptions)
@@ -436,14 +442,3 @@ async fn listen_notifs_and_deposit(root_dir: &Path, client: &Client, pk_hex: Str
 
     Ok(())
 }
-
-fn try_decode_transfer_notif(msg: &[u8]) -> Result<(PublicKey, Vec<CashNoteRedemption>)> {
-    let mut key_bytes = [0u8; PK_SIZE];
-    key_bytes.copy_from_slice(
-        msg.get(0..PK_SIZE)
-            .ok_or_else(|| eyre!("msg doesn't have enough bytes"))?,
-    );
-    let key = PublicKey::from_bytes(key_bytes)?;
-    let cashnote_redemptions: Vec<CashNoteRedemption> = rmp_serde::from_slice(&msg[PK_SIZE..])?;
-    Ok((key, cashnote_redemptions))
-}
@@ -9,12 +9,14 @@
 use super::{
     chunks::Error as ChunksError,
     error::{Error, Result},
+    files::download::unpack_head_chunk,
+    quote_cache::QuoteCache,
     Client, ClientEvent, ClientEventsBroadcaster, ClientEventsReceiver, ClientRegister,
-    WalletClient,
+    WalletClient, BATCH_SIZE,
 };
 use bls::{PublicKey, SecretKey, Signature};
 use bytes::Bytes;
-use futures::future::join_all;
+use futures::{future::join_all, stream, Stream, StreamExt};
 use libp2p::{
     identity::Keypair,
     kad::{Quorum, Record},
@@ -23,15 +25,19 @@ use libp2p::{
 #[cfg(feature = "open-metrics")]
 use prometheus_client::registry::Registry;
 use rand::{thread_rng, Rng};
+use self_encryption::DataMap;
 use sn_networking::{
-    multiaddr_is_global,
+    get_quorum_value_in_group, multiaddr_is_global,
     target_arch::{interval, spawn, timeout, Instant},
     Error as NetworkError, GetRecordCfg, GetRecordError, NetworkBuilder, NetworkEvent,
-    PutRecordCfg, VerificationKind, CLOSE_GROUP_SIZE,
+    PeerConnectionQuality, PutRecordCfg, QuoteSelection, VerificationKind, CLOSE_GROUP_SIZE,
 };
 use sn_protocol::{
     error::Error as ProtocolError,
-    messages::ChunkProof,
+    messages::{
+        decode_transfer_notif, transfer_notif_key, ChunkProof, Query, QueryResponse, Request,
+        Response, MAX_RECORD_LABEL_LEN, ROYALTY_TRANSFER_NOTIF_TOPIC,
+    },
     storage::{
         try_deserialize_record, try_serialize_record, Chunk, ChunkAddress, RecordHeader,
         RecordKind, RegisterAddress, RetryStrategy, SpendAddress,
@@ -39,13 +45,16 @@ use sn_protocol::{
     NetworkAddress, PrettyPrintRecordKey,
 };
 use sn_registers::{Permissions, SignedRegister};
-use sn_transfers::{CashNote, CashNoteRedemption, MainPubkey, NanoTokens, Payment, SignedSpend};
+use sn_transfers::{
+    CashNote, CashNoteRedemption, MainPubkey, NanoTokens, Payment, SignedSpend, Transfer,
+};
 use std::{
     collections::{HashMap, HashSet},
     num::NonZeroUsize,
     path::PathBuf,
+    time::SystemTime,
 };
-use tokio::time::Duration;
+use tokio::{sync::broadcast::error::RecvError, time::Duration};
 use tracing::trace;
 use xor_name::XorName;
 
@@ -55,6 +64,108 @@ const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
 /// The timeout duration for the client to receive any response from the network.
 const INACTIVITY_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// How far the clock can be off the nodes' clocks before the payments warn of it.
+pub(crate) const CLOCK_SKEW_WARNING_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+/// Whether a peer responsible for an address responded to a probe for its record, and if it holds it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerRecordStatus {
+    /// The peer responsible for the address.
+    pub peer_id: PeerId,
+    /// Whether the peer responded to the probe at all.
+    pub responded: bool,
+    /// Whether the peer returned the record.
+    pub holds_record: bool,
+}
+
+/// Why a node is reported by `Client::report_node`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MisbehaviorReason {
+    /// The node served data not matching the address it was requested at.
+    InvalidData,
+    /// The node refused to store a record that was paid for.
+    RefusedPaidPut,
+    /// The node repeatedly failed to respond to requests.
+    Unresponsive,
+    /// Any other misbehaviour, as described.
+    Other(String),
+}
+
+/// Which of the chunks of a file could be retrieved from the network, as probed by
+/// `Client::verify_file_stored`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileVerifyReport {
+    /// The number of chunks the file's content is split into.
+    pub total: usize,
+    /// The number of those chunks retrieved from the network.
+    pub present: usize,
+    /// The addresses of the chunks that couldn't be retrieved.
+    pub missing: Vec<ChunkAddress>,
+}
+
+impl FileVerifyReport {
+    /// Whether every chunk of the file was retrieved.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Settings of a `Client`, for deployments which differ from the public network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientConfig {
+    close_group_size: usize,
+    store_cost_cache_ttl: Option<Duration>,
+    quote_selection: QuoteSelection,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            close_group_size: CLOSE_GROUP_SIZE,
+            store_cost_cache_ttl: None,
+            quote_selection: QuoteSelection::Cheapest,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Sets how many of the peers closest to an address are queried for its store costs and
+    /// spends, and counted towards the quorums. Defaults to `CLOSE_GROUP_SIZE`.
+    ///
+    /// Private networks with fewer nodes than that have to lower it, or the operations would
+    /// wait on responses that can't arrive. Values below 1 are raised to 1.
+    pub fn close_group_size(mut self, close_group_size: usize) -> Self {
+        self.close_group_size = close_group_size.max(1);
+        self
+    }
+
+    /// Caches the store cost quotes for `ttl`, so that the repeated queries of the same address
+    /// within a burst, e.g. estimating then paying for an upload, or retrying a failed payment,
+    /// reuse the quotes received.
+    ///
+    /// Quotes are signed for an address, hence they can't be reused across addresses, even ones
+    /// the same peers are responsible for: a bulk upload of new chunks always misses the cache,
+    /// only requerying the same addresses benefits from it.
+    ///
+    /// A cached quote is never served once close to its expiry, but the longer the `ttl`, the
+    /// likelier the store costs have since risen, failing the payments made with the quote.
+    /// Defaults to no caching.
+    pub fn store_cost_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.store_cost_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets how the quote to pay for storing data is picked among the ones received from the
+    /// peers responsible for it. Defaults to `QuoteSelection::Cheapest`.
+    ///
+    /// `QuoteSelection::WellConnected` favours the peers this client is well connected to, as
+    /// told by `Client::peer_connection_quality`, which are likelier to keep the data available.
+    pub fn quote_selection(mut self, quote_selection: QuoteSelection) -> Self {
+        self.quote_selection = quote_selection;
+        self
+    }
+}
+
 impl Client {
     /// A quick client that only takes some peers to connect to
     pub async fn quick_start(peers: Option<Vec<Multiaddr>>) -> Result<Self> {
@@ -89,6 +200,41 @@ impl Client {
         enable_gossip: bool,
         connection_timeout: Option<Duration>,
         client_event_broadcaster: Option<ClientEventsBroadcaster>,
+    ) -> Result<Self> {
+        Self::new_with_config(
+            signer,
+            peers,
+            enable_gossip,
+            connection_timeout,
+            client_event_broadcaster,
+            ClientConfig::default(),
+        )
+        .await
+    }
+
+    /// Instantiate a new client with the given `ClientConfig`, e.g. to lower the close group size
+    /// on a private network with only a few nodes.
+    ///
+    /// See `Client::new` for the other arguments.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, ClientConfig, Error};
+    /// use bls::SecretKey;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let config = ClientConfig::default().close_group_size(3);
+    /// let client = Client::new_with_config(SecretKey::random(), None, false, None, None, config).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new_with_config(
+        signer: SecretKey,
+        peers: Option<Vec<Multiaddr>>,
+        enable_gossip: bool,
+        connection_timeout: Option<Duration>,
+        client_event_broadcaster: Option<ClientEventsBroadcaster>,
+        config: ClientConfig,
     ) -> Result<Self> {
         // If any of our contact peers has a global address, we'll assume we're in a global network.
         let local = match peers {
@@ -110,6 +256,8 @@ impl Client {
         if enable_gossip {
             network_builder.enable_gossip();
         }
+        network_builder.close_group_size(config.close_group_size);
+        network_builder.quote_selection(config.quote_selection);
 
         #[cfg(feature = "open-metrics")]
         network_builder.metrics_registry(Registry::default());
@@ -125,6 +273,9 @@ impl Client {
             network: network.clone(),
             events_broadcaster,
             signer,
+            quote_cache: config.store_cost_cache_ttl.map(QuoteCache::new),
+            gossip_enabled: enable_gossip,
+            record_label: None,
         };
 
         // subscribe to our events channel first, so we don't have intermittent
@@ -238,19 +389,20 @@ impl Client {
                 debug!("PeerAdded: {peer_id}");
                 *peers_added += 1;
 
-                // notify the listeners that we are waiting on CLOSE_GROUP_SIZE peers before emitting ConnectedToNetwork
+                // notify the listeners that we are waiting on close_group_size peers before emitting ConnectedToNetwork
+                let close_group_size = self.network.close_group_size();
                 self.events_broadcaster.broadcast(ClientEvent::PeerAdded {
-                    max_peers_to_connect: CLOSE_GROUP_SIZE,
+                    max_peers_to_connect: close_group_size,
                 });
                 // In case client running in non-local-discovery mode,
                 // it may take some time to fill up the RT.
                 // To avoid such delay may fail the query with RecordNotFound,
                 // wait till certain amount of peers populated into RT
-                if *peers_added >= CLOSE_GROUP_SIZE {
+                if *peers_added >= close_group_size {
                     self.events_broadcaster
                         .broadcast(ClientEvent::ConnectedToNetwork);
                 } else {
-                    debug!("{peers_added}/{CLOSE_GROUP_SIZE} initial peers found.",);
+                    debug!("{peers_added}/{close_group_size} initial peers found.",);
                 }
             }
             NetworkEvent::GossipsubMsgReceived { topic, msg }
@@ -378,6 +530,33 @@ impl Client {
         self.signer.public_key()
     }
 
+    /// Set the label the chunks stored from then on are indexed under by the nodes holding them,
+    /// to be listed by it later. `None` stops labelling them.
+    ///
+    /// The label is best-effort, it isn't part of the chunks and is lost along with the nodes
+    /// holding them.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let mut client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// client.set_record_label(Some("holiday photos".to_string()))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_record_label(&mut self, label: Option<String>) -> Result<()> {
+        if let Some(label) = &label {
+            if label.len() > MAX_RECORD_LABEL_LEN {
+                return Err(Error::RecordLabelTooLong(label.len()));
+            }
+        }
+        self.record_label = label;
+        Ok(())
+    }
+
     /// Get a register from network
     ///
     /// # Arguments
@@ -413,7 +592,6 @@ impl Client {
         address: RegisterAddress,
         is_verifying: bool,
     ) -> Result<SignedRegister> {
-        let key = NetworkAddress::from_register_address(address).to_record_key();
         let get_quorum = if is_verifying {
             Quorum::N(NonZeroUsize::new(2).ok_or(Error::NonZeroUsizeWasInitialisedAsZero)?)
         } else {
@@ -431,11 +609,86 @@ impl Client {
             expected_holders: Default::default(),
         };
 
-        let maybe_record = self.network.get_record_from_network(key, &get_cfg).await;
+        self.get_signed_register(address, &get_cfg, false).await
+    }
+
+    /// Get a signed register from the Network, only trusting the copies of it which were
+    /// returned by at least `get_quorum` of the responsible nodes.
+    ///
+    /// As registers can't be checked against their content hash, this guards against a single
+    /// node serving a bad copy. If the copies differ, only the versions that reached the quorum
+    /// are merged together.
+    ///
+    /// # Arguments
+    /// * 'address' - [RegisterAddress]
+    /// * 'get_quorum' - [Quorum]
+    ///
+    /// Return Type:
+    ///
+    /// Result<[SignedRegister]>
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// use libp2p::kad::Quorum;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// use xor_name::XorName;
+    /// use sn_registers::RegisterAddress;
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let mut rng = rand::thread_rng();
+    /// let owner = SecretKey::random().public_key();
+    /// let xorname = XorName::random(&mut rng);
+    /// let address = RegisterAddress::new(xorname, owner);
+    /// // Only trust a register that the majority of its holders agree on
+    /// let signed_register = client.get_signed_register_with_quorum(address, Quorum::Majority).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_signed_register_with_quorum(
+        &self,
+        address: RegisterAddress,
+        get_quorum: Quorum,
+    ) -> Result<SignedRegister> {
+        let get_cfg = GetRecordCfg {
+            get_quorum,
+            retry_strategy: Some(RetryStrategy::Balanced),
+            target_record: None,
+            expected_holders: Default::default(),
+        };
+
+        self.get_signed_register(address, &get_cfg, true).await
+    }
+
+    /// When `enforce_quorum` is set, diverging copies are only merged if enough holders agreed on them.
+    async fn get_signed_register(
+        &self,
+        address: RegisterAddress,
+        get_cfg: &GetRecordCfg,
+        enforce_quorum: bool,
+    ) -> Result<SignedRegister> {
+        let key = NetworkAddress::from_register_address(address).to_record_key();
+        let maybe_record = self.network.get_record_from_network(key, get_cfg).await;
         let record = match &maybe_record {
             Ok(r) => r,
             Err(NetworkError::GetRecordError(GetRecordError::SplitRecord { result_map })) => {
-                return merge_split_register_records(address, result_map)
+                if !enforce_quorum {
+                    return merge_split_register_records(address, result_map);
+                }
+                let trusted = filter_split_records_by_quorum(
+                    result_map,
+                    &get_cfg.get_quorum,
+                    self.network.close_group_size(),
+                );
+                if trusted.is_empty() {
+                    warn!(
+                        "No copy of the register at {address:?} reached the quorum of {:?}",
+                        get_cfg.get_quorum
+                    );
+                    return Err(ProtocolError::RegisterNotFound(Box::new(address)).into());
+                }
+                return merge_split_register_records(address, &trusted);
             }
             Err(e) => {
                 warn!("Failed to get record at {address:?} from the network: {e:?}");
@@ -484,7 +737,19 @@ impl Client {
     /// ```
     pub async fn get_register(&self, address: RegisterAddress) -> Result<ClientRegister> {
         info!("Retrieving a Register replica at {address}");
-        ClientRegister::retrieve(self.clone(), address).await
+        ClientRegister::retrieve(self.clone(), address, None).await
+    }
+
+    /// Retrieve a Register from the network, only trusting the copies of it which were returned
+    /// by at least `get_quorum` of the responsible nodes.
+    /// See [`Client::get_signed_register_with_quorum`].
+    pub async fn get_register_with_quorum(
+        &self,
+        address: RegisterAddress,
+        get_quorum: Quorum,
+    ) -> Result<ClientRegister> {
+        info!("Retrieving a Register replica at {address} with quorum {get_quorum:?}");
+        ClientRegister::retrieve(self.clone(), address, Some(get_quorum)).await
     }
 
     /// Create a new Register on the Network.
@@ -586,6 +851,137 @@ impl Client {
         Ok((reg, total_cost, total_royalties))
     }
 
+    /// Self-encrypt the bytes in memory, pay for and upload the resulting chunks, without any
+    /// filesystem I/O. The data map chunk is uploaded as well, so the data can be fetched back
+    /// from the returned address.
+    ///
+    /// Returns the address of the data map chunk, and the data map of the bytes.
+    ///
+    /// # Arguments
+    /// * 'data' - [Bytes] : at least `MIN_ENCRYPTABLE_BYTES` long
+    /// * 'wallet_client' - [WalletClient]
+    /// * 'verify_store' - Boolean
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, WalletClient, Error};
+    /// use tempfile::TempDir;
+    /// use bls::SecretKey;
+    /// use bytes::Bytes;
+    /// use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let tmp_path = TempDir::new()?.path().to_owned();
+    /// let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let mut wallet_client = WalletClient::new(client.clone(), wallet);
+    /// let data = Bytes::from(vec![1u8; 4096]);
+    /// let (address, _data_map) = client.store_bytes(data, &mut wallet_client, true).await?;
+    /// println!("Data stored at {address:?}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn store_bytes(
+        &self,
+        data: Bytes,
+        wallet_client: &mut WalletClient,
+        verify_store: bool,
+    ) -> Result<(XorName, DataMap)> {
+        let (data_map_chunk, data_map, mut chunks) = crate::chunks::encrypt_bytes(data)?;
+        let head_address = *data_map_chunk.name();
+        chunks.push(data_map_chunk);
+        info!(
+            "Storing {} chunks of in memory data at {head_address:?}",
+            chunks.len()
+        );
+
+        let _ = wallet_client
+            .pay_for_storage(chunks.iter().map(|chunk| chunk.network_address()))
+            .await?;
+        wallet_client.store_local_wallet()?;
+
+        for chunk in chunks {
+            let chunk_addr = chunk.network_address();
+            let (payment, payee) = wallet_client.get_payment_for_addr(&chunk_addr)?;
+            self.store_chunk(chunk, payee, payment, verify_store, None)
+                .await?;
+            wallet_client.remove_payment_for_addr(&chunk_addr)?;
+        }
+
+        Ok((head_address, data_map))
+    }
+
+    /// Store many chunks at once, paying for all of them in a single payment, then uploading
+    /// up to `BATCH_SIZE` of them concurrently. The chunks already stored on the network are
+    /// neither paid for nor uploaded again.
+    ///
+    /// # Arguments
+    /// * 'chunks' - [Vec]<[Chunk]>
+    /// * 'wallet_client' - [WalletClient]
+    /// * 'verify_store' - Boolean
+    ///
+    /// Return Type:
+    ///
+    /// Result<[Vec]<[ChunkAddress]>>, in the order of the chunks
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, WalletClient, Error};
+    /// use tempfile::TempDir;
+    /// use bls::SecretKey;
+    /// use bytes::Bytes;
+    /// use sn_protocol::storage::Chunk;
+    /// use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let tmp_path = TempDir::new()?.path().to_owned();
+    /// let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let mut wallet_client = WalletClient::new(client.clone(), wallet);
+    /// let chunks = vec![Chunk::new(Bytes::from("first")), Chunk::new(Bytes::from("second"))];
+    /// let addresses = client.store_chunks(chunks, &mut wallet_client, true).await?;
+    /// println!("Chunks stored at {addresses:?}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn store_chunks(
+        &self,
+        chunks: Vec<Chunk>,
+        wallet_client: &mut WalletClient,
+        verify_store: bool,
+    ) -> Result<Vec<ChunkAddress>> {
+        info!("Storing {} chunks", chunks.len());
+        let addresses: Vec<ChunkAddress> = chunks.iter().map(|chunk| *chunk.address()).collect();
+
+        let payment = wallet_client
+            .pay_for_storage(chunks.iter().map(|chunk| chunk.network_address()))
+            .await?;
+        wallet_client.store_local_wallet()?;
+
+        let wallet_client = &*wallet_client;
+        let results: Vec<Result<()>> = stream::iter(chunks)
+            .filter(|chunk| {
+                let already_stored = payment.skipped_chunks.contains(chunk.name());
+                async move { !already_stored }
+            })
+            .map(|chunk| async move {
+                let chunk_addr = chunk.network_address();
+                let (payment, payee) = wallet_client.get_payment_for_addr(&chunk_addr)?;
+                self.store_chunk(chunk, payee, payment, verify_store, None)
+                    .await?;
+                wallet_client.remove_payment_for_addr(&chunk_addr)?;
+                Ok::<_, Error>(())
+            })
+            .buffer_unordered(BATCH_SIZE)
+            .collect()
+            .await;
+        for result in results {
+            result?;
+        }
+
+        Ok(addresses)
+    }
+
     /// Store `Chunk` as a record. Protected method.
     ///
     /// # Arguments
@@ -647,6 +1043,7 @@ impl Client {
             retry_strategy,
             use_put_record_to: Some(vec![payee]),
             verification,
+            label: self.record_label.clone(),
         };
         Ok(self.network.put_record(record, &put_cfg).await?)
     }
@@ -719,6 +1116,176 @@ impl Client {
         }
     }
 
+    /// Get a chunk directly from a specific peer, bypassing the close group of the chunk.
+    ///
+    /// This is meant for recovery, when the close group of the chunk is unresponsive but a peer
+    /// known to hold it, e.g. from a prior [`Client::locate`] call, can still be reached.
+    /// The returned chunk is checked against the address, hence a peer can't serve other content.
+    ///
+    /// # Arguments
+    /// * 'address' - [ChunkAddress]
+    /// * 'peer' - [PeerId] of the peer holding the chunk
+    ///
+    /// Return Type:
+    ///
+    /// Result<[Chunk]>
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// use libp2p::PeerId;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// use xor_name::XorName;
+    /// use sn_protocol::{storage::ChunkAddress, NetworkAddress};
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let chunk_address = ChunkAddress::new(XorName::random(&mut rand::thread_rng()));
+    /// // find a peer still holding the chunk
+    /// let holders = client.locate(NetworkAddress::from_chunk_address(chunk_address)).await?;
+    /// if let Some(holder) = holders.iter().find(|status| status.holds_record) {
+    ///     let chunk = client.get_chunk_from_peer(chunk_address, holder.peer_id).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_chunk_from_peer(&self, address: ChunkAddress, peer: PeerId) -> Result<Chunk> {
+        info!("Getting chunk {address:?} from {peer:?}");
+        let record = self
+            .get_record_from_peer(NetworkAddress::from_chunk_address(address), peer)
+            .await?;
+        let header = RecordHeader::from_record(&record)?;
+        if let RecordKind::Chunk = header.kind {
+            let chunk: Chunk = try_deserialize_record(&record)?;
+            if chunk.address() != &address {
+                warn!(
+                    "{peer:?} returned {:?} for chunk {address:?}",
+                    chunk.address()
+                );
+                return Err(Error::ChunkContentMismatch {
+                    expected: address,
+                    got: *chunk.address(),
+                });
+            }
+            Ok(chunk)
+        } else {
+            Err(NetworkError::RecordKindMismatch(RecordKind::Chunk).into())
+        }
+    }
+
+    /// Retrieve the record at the address from the given peer only.
+    async fn get_record_from_peer(&self, address: NetworkAddress, peer: PeerId) -> Result<Record> {
+        let request = Request::Query(Query::GetRecordFromPeer(address.clone()));
+        let value = match self.network.send_request(request, peer).await? {
+            Response::Query(QueryResponse::GetRecordFromPeer(result)) => result?.1,
+            other => {
+                warn!("Unexpected response from {peer:?} while getting {address:?}: {other:?}");
+                return Err(Error::UnexpectedResponse(peer));
+            }
+        };
+
+        Ok(Record::new(address.to_record_key(), value.to_vec()))
+    }
+
+    /// Retrieve the self-encryption `DataMap` of a file from its head chunk.
+    /// If the head chunk holds an additional level `DataMap`, the chunks it points to
+    /// are fetched and decrypted until the first level `DataMap` is obtained.
+    /// The returned `DataMap` can then be used to fetch the file's chunks.
+    ///
+    /// # Arguments
+    /// * 'head' - [ChunkAddress]
+    ///
+    /// Return Type:
+    ///
+    /// Result<[DataMap]>
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// use xor_name::XorName;
+    /// use sn_protocol::storage::ChunkAddress;
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let mut rng = rand::thread_rng();
+    /// let head = ChunkAddress::new(XorName::random(&mut rng));
+    /// let data_map = client.get_data_map(head).await?;
+    /// for info in data_map.infos() {
+    ///     println!("Chunk {} is at {:?}", info.index, info.dst_hash);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_data_map(&self, head: ChunkAddress) -> Result<DataMap> {
+        let head_chunk = self.get_chunk(head, false, None).await?;
+        unpack_head_chunk(self, head_chunk).await
+    }
+
+    /// Verify that the whole file of the given head chunk can be retrieved from the network, by
+    /// fetching its `DataMap`, then every chunk it points to. Unlike checking the head chunk only,
+    /// this confirms that no chunk of the file is missing, e.g. before deleting the original.
+    ///
+    /// Errors if the `DataMap` itself can't be retrieved, as none of the chunks can then be.
+    ///
+    /// # Arguments
+    /// * 'head' - [ChunkAddress]
+    ///
+    /// Return Type:
+    ///
+    /// Result<[FileVerifyReport]>
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// use xor_name::XorName;
+    /// use sn_protocol::storage::ChunkAddress;
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let head = ChunkAddress::new(XorName::random(&mut rand::thread_rng()));
+    /// let report = client.verify_file_stored(head).await?;
+    /// if !report.is_complete() {
+    ///     println!("{} out of {} chunks are missing", report.missing.len(), report.total);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_file_stored(&self, head: ChunkAddress) -> Result<FileVerifyReport> {
+        let data_map = self.get_data_map(head).await?;
+        let addresses: Vec<ChunkAddress> = data_map
+            .infos()
+            .into_iter()
+            .map(|info| ChunkAddress::new(info.dst_hash))
+            .collect();
+        info!(
+            "Verifying the {} chunks of the file at {head:?} are retrievable",
+            addresses.len()
+        );
+
+        let missing: Vec<ChunkAddress> = stream::iter(addresses.iter().copied())
+            .map(|address| async move {
+                match self.get_chunk(address, false, None).await {
+                    Ok(_) => None,
+                    Err(err) => {
+                        warn!("Chunk {address:?} of the file at {head:?} is missing: {err:?}");
+                        Some(address)
+                    }
+                }
+            })
+            .buffer_unordered(BATCH_SIZE)
+            .filter_map(|missing| async move { missing })
+            .collect()
+            .await;
+
+        Ok(FileVerifyReport {
+            total: addresses.len(),
+            present: addresses.len() - missing.len(),
+            missing,
+        })
+    }
+
     /// Verify if a `Chunk` is stored by expected nodes on the network.
     /// Single local use. Marked Private.
     async fn verify_chunk_stored(&self, chunk: &Chunk) -> Result<()> {
@@ -829,6 +1396,7 @@ impl Client {
             retry_strategy: Some(RetryStrategy::Persistent),
             use_put_record_to: None,
             verification: Some((VerificationKind::Network, verification_cfg)),
+            label: None,
         };
         Ok(self.network.put_record(record, &put_cfg).await?)
     }
@@ -892,60 +1460,50 @@ impl Client {
             PrettyPrintRecordKey::from(&record.key)
         );
 
-        let header = RecordHeader::from_record(&record).map_err(|err| {
-            Error::CouldNotVerifyTransfer(format!(
-                "Can't parse RecordHeader for the spend at {address:?} with error {err:?}"
-            ))
-        })?;
-
-        if let RecordKind::Spend = header.kind {
-            let mut deserialized_record = try_deserialize_record::<Vec<SignedSpend>>(&record)
-                .map_err(|err| {
-                    Error::CouldNotVerifyTransfer(format!(
-                        "Can't deserialize record for the spend at {address:?} with error {err:?}"
-                    ))
-                })?;
-
-            match deserialized_record.len() {
-                0 => {
-                    trace!("Found no spend for {address:?}");
-                    Err(Error::CouldNotVerifyTransfer(format!(
-                        "Fetched record shows no spend for cash_note {address:?}."
-                    )))
-                }
-                1 => {
-                    let signed_spend = deserialized_record.remove(0);
-                    trace!("Spend get for address: {address:?} successful");
-                    if address == SpendAddress::from_unique_pubkey(signed_spend.unique_pubkey()) {
-                        match signed_spend.verify(signed_spend.spent_tx_hash()) {
-                            Ok(_) => {
-                                trace!("Verified signed spend got from network for {address:?}");
-                                Ok(signed_spend)
-                            }
-                            Err(err) => {
-                                warn!("Invalid signed spend got from network for {address:?}: {err:?}.");
-                                Err(Error::CouldNotVerifyTransfer(format!(
-                                "Spend failed verifiation for the unique_pubkey {address:?} with error {err:?}")))
-                            }
-                        }
-                    } else {
-                        warn!("Signed spend ({:?}) got from network mismatched the expected one {address:?}.", signed_spend.unique_pubkey());
-                        Err(Error::CouldNotVerifyTransfer(format!(
-                                "Signed spend ({:?}) got from network mismatched the expected one {address:?}.", signed_spend.unique_pubkey())))
-                    }
-                }
-                _ => {
-                    // each one is 0 as it shifts remaining elements
-                    let one = deserialized_record.remove(0);
-                    let two = deserialized_record.remove(0);
-                    error!("Found double spend for {address:?}");
-                    Err(Error::DoubleSpend(address, Box::new(one), Box::new(two)))
-                }
-            }
-        } else {
-            error!("RecordKind mismatch while trying to retrieve a cash_note spend");
-            Err(NetworkError::RecordKindMismatch(RecordKind::Spend).into())
-        }
+        signed_spend_from_record(address, &record)
+    }
+
+    /// Get a spend directly from the given peer, bypassing the network wide lookup.
+    /// This allows checking what each of the peers responsible for the address holds.
+    ///
+    /// # Arguments
+    /// * 'address' - [SpendAddress]
+    /// * 'peer' - [PeerId] of the peer holding the spend
+    ///
+    /// Return Type:
+    ///
+    /// Result<[SignedSpend]>
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// use libp2p::PeerId;
+    /// use xor_name::XorName;
+    /// use sn_transfers::SpendAddress;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// use sn_protocol::NetworkAddress;
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let spend_address = SpendAddress::new(XorName::random(&mut rand::thread_rng()));
+    /// // find a peer holding the spend
+    /// let holders = client.locate(NetworkAddress::from_spend_address(spend_address)).await?;
+    /// if let Some(holder) = holders.iter().find(|status| status.holds_record) {
+    ///     let spend = client.get_spend_from_peer(spend_address, holder.peer_id).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_spend_from_peer(
+        &self,
+        address: SpendAddress,
+        peer: PeerId,
+    ) -> Result<SignedSpend> {
+        info!("Getting spend {address:?} from {peer:?}");
+        let record = self
+            .get_record_from_peer(NetworkAddress::from_spend_address(address), peer)
+            .await?;
+        signed_spend_from_record(address, &record)
     }
 
     /// Subscribe to given gossipsub topic
@@ -1017,6 +1575,69 @@ impl Client {
         self.network.publish_on_topic(topic_id, msg);
     }
 
+    /// Watch for transfers sent to the given key.
+    ///
+    /// Subscribes to the royalty transfer notification topic, and returns a stream yielding
+    /// every notified transfer addressed to `key`. Notifications for other keys, or that
+    /// can't be decoded, are skipped. The stream ends once the client is dropped.
+    /// Returns `Error::GossipDisabled` if the client was not started with gossip enabled, as
+    /// no notification would ever be received.
+    ///
+    /// # Arguments
+    /// * 'key' - [PublicKey]
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// use futures::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// // gossip has to be enabled to receive the notifications
+    /// let client = Client::new(SecretKey::random(), None, true, None, None).await?;
+    /// let key = SecretKey::random().public_key();
+    /// let mut transfers = Box::pin(client.watch_transfers(key)?);
+    /// while let Some(transfer) = transfers.next().await {
+    ///     println!("Received transfer: {transfer:?}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch_transfers(&self, key: PublicKey) -> Result<impl Stream<Item = Transfer>> {
+        if !self.gossip_enabled {
+            return Err(Error::GossipDisabled);
+        }
+        // Take the receiver before subscribing, so no notification is missed in between.
+        let events = self.events_channel();
+        self.subscribe_to_topic(ROYALTY_TRANSFER_NOTIF_TOPIC.to_string());
+
+        Ok(stream::unfold(events, move |mut events| async move {
+            loop {
+                match events.recv().await {
+                    Ok(ClientEvent::GossipsubMsg { topic, msg })
+                        if topic == ROYALTY_TRANSFER_NOTIF_TOPIC =>
+                    {
+                        match try_decode_transfer_notif(&msg, &key) {
+                            Ok(Some(cashnote_redemptions)) => {
+                                return Some((
+                                    Transfer::NetworkRoyalties(cashnote_redemptions),
+                                    events,
+                                ));
+                            }
+                            Ok(None) => {}
+                            Err(err) => warn!("Failed to decode transfer notification: {err}"),
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Transfer watcher lagged behind, skipped {skipped} client events");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }))
+    }
+
     /// This function is used to receive a Vector of CashNoteRedemptions and turn them back into spendable CashNotes.
     /// For this we need a network connection.
     /// Verify CashNoteRedemptions and rebuild spendable currency from them.
@@ -1137,6 +1758,255 @@ impl Client {
 
         Ok(failed_chunks)
     }
+
+    /// Returns the close group of the given address, as known to this client, i.e. the peers
+    /// the store costs of a record at that address would be asked for.
+    ///
+    /// This helps diagnosing discrepancies between the client's and a node's view of which
+    /// peers are responsible for an address.
+    ///
+    /// # Arguments
+    /// * 'addr' - [NetworkAddress]
+    ///
+    /// Return Type:
+    ///
+    /// Result<[Vec]<[PeerId]>>, sorted by their distance to the address
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// use sn_protocol::{storage::ChunkAddress, NetworkAddress};
+    /// use xor_name::XorName;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let address = NetworkAddress::from_chunk_address(ChunkAddress::new(XorName::random(&mut rand::thread_rng())));
+    /// for peer_id in client.responsible_peers(address).await? {
+    ///     println!("{peer_id:?} is responsible for the address");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn responsible_peers(&self, addr: NetworkAddress) -> Result<Vec<PeerId>> {
+        Ok(self.network.client_get_closest_peers(&addr).await?)
+    }
+
+    /// Reports a node that misbehaved, e.g. served invalid data, so that the client avoids it for
+    /// the rest of the session: it is dropped from the RoutingTable, disconnected, and no longer
+    /// dialed, hence no longer asked for records nor store costs.
+    ///
+    /// The report is local to this client, it's neither persisted nor shared with other peers.
+    ///
+    /// # Arguments
+    /// * 'peer' - [PeerId]
+    /// * 'reason' - [MisbehaviorReason]
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error, MisbehaviorReason};
+    /// use bls::SecretKey;
+    /// use libp2p::PeerId;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let peer_id = PeerId::random();
+    /// client.report_node(peer_id, MisbehaviorReason::InvalidData);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn report_node(&self, peer: PeerId, reason: MisbehaviorReason) {
+        warn!("Avoiding {peer:?} for the rest of the session, as it was reported for: {reason:?}");
+        self.network.block_peer(peer);
+    }
+
+    /// Returns a rough estimate of the number of nodes in the network, from the density of the
+    /// peers in the client's RoutingTable buckets.
+    ///
+    /// The buckets closer than the first full one hold every peer within their part of the
+    /// address space, so the network size is estimated as those peers over the fraction of the
+    /// address space they cover. It's only an order of magnitude, e.g. to tell a tiny test
+    /// network from the main one, and is less accurate right after connecting.
+    ///
+    /// Return Type:
+    ///
+    /// Result<[usize]>
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let size = client.estimated_network_size().await?;
+    /// println!("Connected to a network of ~{size} nodes");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn estimated_network_size(&self) -> Result<usize> {
+        let snapshot = self.network.get_routing_table_snapshot().await?;
+        Ok(snapshot.estimated_network_size())
+    }
+
+    /// Estimates how far this client's clock is off the clocks of the nodes, as told by the
+    /// timestamps of the store cost quotes of the nodes responsible for a random address.
+    ///
+    /// Quotes expire after `QUOTE_EXPIRATION_SECS`, so a skewed clock makes valid quotes look
+    /// expired, or the other way around, failing the payments made with them.
+    ///
+    /// Return Type:
+    ///
+    /// Result<[Duration]>, the median of the skews to each node, whichever the direction
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let skew = client.clock_skew_estimate().await?;
+    /// println!("The clock is about {skew:?} off the network");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clock_skew_estimate(&self) -> Result<Duration> {
+        let address = NetworkAddress::from_chunk_address(ChunkAddress::new(XorName::random(
+            &mut thread_rng(),
+        )));
+        let sent_at = SystemTime::now();
+        let quotes = self.network.get_store_cost_quotes(&address).await?;
+        let received_at = SystemTime::now();
+
+        // the zero quotes of the nodes already holding a record are not timestamped by them
+        let mut skews: Vec<Duration> = quotes
+            .iter()
+            .filter(|(_, _, quote)| !quote.signature.is_empty())
+            .map(|(_, _, quote)| clock_skew(quote.timestamp, sent_at, received_at))
+            .collect();
+        if skews.is_empty() {
+            return Err(NetworkError::NoStoreCostResponses.into());
+        }
+        skews.sort();
+        let skew = skews[skews.len() / 2];
+        if skew > CLOCK_SKEW_WARNING_THRESHOLD {
+            warn!("The clock is about {skew:?} off the nodes' clocks, payments may fail");
+        }
+        Ok(skew)
+    }
+
+    /// Returns how well this client is connected to the peer, which
+    /// `QuoteSelection::WellConnected` ranks the peers quoting store costs by.
+    ///
+    /// # Arguments
+    /// * 'peer_id' - [PeerId]
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// use sn_protocol::{storage::ChunkAddress, NetworkAddress};
+    /// use xor_name::XorName;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let address = NetworkAddress::from_chunk_address(ChunkAddress::new(XorName::random(&mut rand::thread_rng())));
+    /// for peer_id in client.responsible_peers(address).await? {
+    ///     let quality = client.peer_connection_quality(peer_id).await?;
+    ///     println!("{peer_id:?} connected: {}", quality.connected);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn peer_connection_quality(&self, peer_id: PeerId) -> Result<PeerConnectionQuality> {
+        let mut qualities = self
+            .network
+            .get_peers_connection_quality(vec![peer_id])
+            .await?;
+        Ok(qualities.remove(&peer_id).unwrap_or_default())
+    }
+
+    /// Finds the peers responsible for the given address and probes each of them for its record.
+    ///
+    /// This helps diagnosing a failed GET, telling apart responsible peers that are unreachable
+    /// from the ones that are present but not holding the record.
+    ///
+    /// # Arguments
+    /// * 'addr' - [NetworkAddress]
+    ///
+    /// Return Type:
+    ///
+    /// Result<[Vec]<[PeerRecordStatus]>>
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// use sn_protocol::{storage::ChunkAddress, NetworkAddress};
+    /// use xor_name::XorName;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// let address = NetworkAddress::from_chunk_address(ChunkAddress::new(XorName::random(&mut rand::thread_rng())));
+    /// for status in client.locate(address).await? {
+    ///     println!("{:?} responded: {}, holds the record: {}", status.peer_id, status.responded, status.holds_record);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn locate(&self, addr: NetworkAddress) -> Result<Vec<PeerRecordStatus>> {
+        let close_nodes = self.network.get_closest_peers(&addr, true).await?;
+        let request = Request::Query(Query::GetReplicatedRecord {
+            requester: NetworkAddress::from_peer(self.network.peer_id),
+            key: addr.clone(),
+        });
+        let responses = self
+            .network
+            .send_and_get_responses(&close_nodes, &request, true)
+            .await;
+
+        let statuses = close_nodes
+            .into_iter()
+            .map(|peer_id| {
+                let (responded, holds_record) = match responses.get(&peer_id) {
+                    Some(Ok(Response::Query(QueryResponse::GetReplicatedRecord(result)))) => {
+                        (true, result.is_ok())
+                    }
+                    Some(Ok(other)) => {
+                        warn!("Unexpected response from {peer_id:?} while locating {addr:?}: {other:?}");
+                        (true, false)
+                    }
+                    Some(Err(err)) => {
+                        debug!("No response from {peer_id:?} while locating {addr:?}: {err:?}");
+                        (false, false)
+                    }
+                    None => (false, false),
+                };
+                PeerRecordStatus {
+                    peer_id,
+                    responded,
+                    holds_record,
+                }
+            })
+            .collect();
+
+        Ok(statuses)
+    }
+}
+
+/// Decodes a transfer notification, returning its CashNoteRedemptions if it is addressed to `filter`.
+///
+/// Both the unsigned and the signed notifications are understood, the signature of the latter
+/// being verified.
+fn try_decode_transfer_notif(
+    msg: &[u8],
+    filter: &PublicKey,
+) -> Result<Option<Vec<CashNoteRedemption>>> {
+    if &transfer_notif_key(msg)? != filter {
+        return Ok(None);
+    }
+    Ok(Some(decode_transfer_notif(msg)?.cashnote_redemptions))
 }
 
 fn get_register_from_record(record: &Record) -> Result<SignedRegister> {
@@ -1151,6 +2021,19 @@ fn get_register_from_record(record: &Record) -> Result<SignedRegister> {
     }
 }
 
+/// Keeps only the copies of a split record that were returned by enough holders to satisfy the quorum.
+fn filter_split_records_by_quorum(
+    map: &HashMap<XorName, (Record, HashSet<PeerId>)>,
+    quorum: &Quorum,
+    close_group_size: usize,
+) -> HashMap<XorName, (Record, HashSet<PeerId>)> {
+    let required_holders = get_quorum_value_in_group(quorum, close_group_size);
+    map.iter()
+        .filter(|(_, (_, holders))| holders.len() >= required_holders)
+        .map(|(xorname, entry)| (*xorname, entry.clone()))
+        .collect()
+}
+
 /// if multiple register records where found for a given key, merge them into a single register
 fn merge_split_register_records(
     address: RegisterAddress,
@@ -1191,14 +2074,107 @@ fn merge_split_register_records(
     Ok(register)
 }
 
+/// How far a node's clock, which read `node_time` while answering a request, is off ours.
+/// The request was sent at `sent_at` and answered at `received_at`, so the node's time is deemed
+/// right if within those.
+pub(crate) fn clock_skew(
+    node_time: SystemTime,
+    sent_at: SystemTime,
+    received_at: SystemTime,
+) -> Duration {
+    if let Ok(behind) = sent_at.duration_since(node_time) {
+        behind
+    } else {
+        node_time.duration_since(received_at).unwrap_or_default()
+    }
+}
+
+/// Parses the spend record fetched for the address, checking it holds a single spend, which is
+/// the valid spend of the address.
+fn signed_spend_from_record(address: SpendAddress, record: &Record) -> Result<SignedSpend> {
+    let header = RecordHeader::from_record(record).map_err(|err| {
+        Error::CouldNotVerifyTransfer(format!(
+            "Can't parse RecordHeader for the spend at {address:?} with error {err:?}"
+        ))
+    })?;
+
+    if let RecordKind::Spend = header.kind {
+        let mut deserialized_record =
+            try_deserialize_record::<Vec<SignedSpend>>(record).map_err(|err| {
+                Error::CouldNotVerifyTransfer(format!(
+                    "Can't deserialize record for the spend at {address:?} with error {err:?}"
+                ))
+            })?;
+
+        match deserialized_record.len() {
+            0 => {
+                trace!("Found no spend for {address:?}");
+                Err(Error::CouldNotVerifyTransfer(format!(
+                    "Fetched record shows no spend for cash_note {address:?}."
+                )))
+            }
+            1 => {
+                let signed_spend = deserialized_record.remove(0);
+                trace!("Spend get for address: {address:?} successful");
+                if address == SpendAddress::from_unique_pubkey(signed_spend.unique_pubkey()) {
+                    match signed_spend.verify(signed_spend.spent_tx_hash()) {
+                        Ok(_) => {
+                            trace!("Verified signed spend got from network for {address:?}");
+                            Ok(signed_spend)
+                        }
+                        Err(err) => {
+                            warn!(
+                                "Invalid signed spend got from network for {address:?}: {err:?}."
+                            );
+                            Err(Error::CouldNotVerifyTransfer(format!(
+                            "Spend failed verifiation for the unique_pubkey {address:?} with error {err:?}")))
+                        }
+                    }
+                } else {
+                    warn!("Signed spend ({:?}) got from network mismatched the expected one {address:?}.", signed_spend.unique_pubkey());
+                    Err(Error::CouldNotVerifyTransfer(format!(
+                            "Signed spend ({:?}) got from network mismatched the expected one {address:?}.", signed_spend.unique_pubkey())))
+                }
+            }
+            _ => {
+                // each one is 0 as it shifts remaining elements
+                let one = deserialized_record.remove(0);
+                let two = deserialized_record.remove(0);
+                error!("Found double spend for {address:?}");
+                Err(Error::DoubleSpend(address, Box::new(one), Box::new(two)))
+            }
+        }
+    } else {
+        error!("RecordKind mismatch while trying to retrieve a cash_note spend");
+        Err(NetworkError::RecordKindMismatch(RecordKind::Spend).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use bls::PK_SIZE;
     use std::collections::BTreeSet;
 
     use sn_registers::Register;
 
     use super::*;
 
+    #[test]
+    fn test_try_decode_transfer_notif() -> eyre::Result<()> {
+        let key = SecretKey::random().public_key();
+        let redemptions: Vec<CashNoteRedemption> = vec![];
+        let mut msg = key.to_bytes().to_vec();
+        msg.extend(rmp_serde::to_vec(&redemptions)?);
+
+        assert_eq!(try_decode_transfer_notif(&msg, &key)?, Some(redemptions));
+
+        let other_key = SecretKey::random().public_key();
+        assert_eq!(try_decode_transfer_notif(&msg, &other_key)?, None);
+
+        assert!(try_decode_transfer_notif(&msg[..PK_SIZE - 1], &key).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_merge_split_register_records() -> eyre::Result<()> {
         let mut rng = rand::thread_rng();
@@ -1283,4 +2259,79 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_filter_split_records_by_quorum() {
+        let record = |value: &[u8]| Record {
+            key: NetworkAddress::from_chunk_address(ChunkAddress::new(XorName::from_content(
+                value,
+            )))
+            .to_record_key(),
+            value: value.to_vec(),
+            publisher: None,
+            expires: None,
+        };
+        let peers = |count: usize| (0..count).map(|_| PeerId::random()).collect::<HashSet<_>>();
+        let agreed = XorName::from_content(b"agreed");
+        let lonely = XorName::from_content(b"lonely");
+        let map = HashMap::from_iter(vec![
+            (agreed, (record(b"agreed"), peers(3))),
+            (lonely, (record(b"lonely"), peers(1))),
+        ]);
+
+        let trusted = filter_split_records_by_quorum(&map, &Quorum::One, CLOSE_GROUP_SIZE);
+        assert_eq!(trusted.len(), 2);
+
+        let trusted = filter_split_records_by_quorum(&map, &Quorum::Majority, CLOSE_GROUP_SIZE);
+        assert_eq!(trusted.len(), 1);
+        assert!(trusted.contains_key(&agreed));
+
+        let trusted = filter_split_records_by_quorum(&map, &Quorum::All, CLOSE_GROUP_SIZE);
+        assert!(trusted.is_empty());
+    }
+
+    #[test]
+    fn split_records_quorum_follows_the_configured_close_group_size() {
+        let record = Record {
+            key: NetworkAddress::from_chunk_address(ChunkAddress::new(XorName::from_content(
+                b"agreed",
+            )))
+            .to_record_key(),
+            value: b"agreed".to_vec(),
+            publisher: None,
+            expires: None,
+        };
+        let holders = (0..3).map(|_| PeerId::random()).collect::<HashSet<_>>();
+        let agreed = XorName::from_content(b"agreed");
+        let map = HashMap::from_iter(vec![(agreed, (record, holders))]);
+
+        // all of a close group of 3 holding the record reach `Quorum::All`
+        let config = ClientConfig::default().close_group_size(3);
+        let trusted = filter_split_records_by_quorum(&map, &Quorum::All, config.close_group_size);
+        assert!(trusted.contains_key(&agreed));
+
+        // but not with the default close group size
+        let config = ClientConfig::default();
+        let trusted = filter_split_records_by_quorum(&map, &Quorum::All, config.close_group_size);
+        assert!(trusted.is_empty());
+    }
+
+    #[test]
+    fn clock_skew_is_measured_outside_of_the_request_time() {
+        let sent_at = SystemTime::now();
+        let received_at = sent_at + Duration::from_secs(2);
+
+        assert_eq!(
+            clock_skew(sent_at + Duration::from_secs(1), sent_at, received_at),
+            Duration::ZERO
+        );
+        assert_eq!(
+            clock_skew(sent_at - Duration::from_secs(60), sent_at, received_at),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            clock_skew(received_at + Duration::from_secs(60), sent_at, received_at),
+            Duration::from_secs(60)
+        );
+    }
 }
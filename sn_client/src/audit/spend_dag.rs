@@ -9,7 +9,10 @@
 use petgraph::dot::Dot;
 use petgraph::graph::{DiGraph, NodeIndex};
 use serde::{Deserialize, Serialize};
-use sn_transfers::{is_genesis_spend, CashNoteRedemption, NanoTokens, SignedSpend, SpendAddress};
+use sn_transfers::{
+    is_genesis_spend, CashNoteRedemption, DerivationIndex, MainPubkey, NanoTokens, SignedSpend,
+    SpendAddress, UniquePubkey,
+};
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 
@@ -17,6 +20,10 @@ use crate::error::{Error, Result};
 
 use super::dag_error::DagError;
 
+/// The version of the binary format produced by [`SpendDag::to_bytes`].
+/// It is written as the first byte, to detect archives written in an incompatible format.
+const SPEND_DAG_BYTES_VERSION: u8 = 1;
+
 /// A DAG representing the spends from a specific Spend all the way to the UTXOs.
 /// Starting from Genesis, this would encompass all the spends that have happened on the network
 /// at a certain point in time.
@@ -39,6 +46,26 @@ pub struct SpendDag {
     spends: BTreeMap<SpendAddress, Vec<(Option<SignedSpend>, usize)>>,
 }
 
+/// What was learnt when merging a partial DAG into ours with [`SpendDag::merge_serialized`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Spends that were not in our DAG yet
+    pub new_spends: BTreeSet<SpendAddress>,
+    /// Addresses where the partial DAG holds a different spend than ours, i.e. double spends
+    pub conflicts: BTreeSet<SpendAddress>,
+    /// Number of spends that we already knew about
+    pub already_known: usize,
+}
+
+/// How the tokens moved in a spend, relative to a key of interest
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum SpendDirection {
+    /// The key received tokens: it was one of the outputs of the spend
+    Inbound,
+    /// The key sent tokens: it was the input of the spend
+    Outbound,
+}
+
 /// The result of a get operation on the DAG
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum SpendDagGet {
@@ -72,6 +99,22 @@ impl SpendDag {
         Ok(())
     }
 
+    /// Serialize the DAG into a compact, versioned, binary format, suitable for archival.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![SPEND_DAG_BYTES_VERSION];
+        rmp_serde::encode::write(&mut bytes, &self)?;
+        Ok(bytes)
+    }
+
+    /// Deserialize a DAG from the binary format produced by [`SpendDag::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        match bytes.split_first() {
+            Some((&SPEND_DAG_BYTES_VERSION, dag_bytes)) => Ok(rmp_serde::from_slice(dag_bytes)?),
+            Some((version, _)) => Err(Error::UnsupportedSpendDagVersion(*version)),
+            None => Err(Error::InvalidDag),
+        }
+    }
+
     /// Insert a spend into the dag
     /// Creating edges (links) from its ancestors and to its descendants
     /// If the inserted spend is already known, it will be ignored
@@ -216,6 +259,31 @@ impl SpendDag {
         }
     }
 
+    /// Merges a partial DAG, serialized with [`SpendDag::to_bytes`] (e.g. by another machine
+    /// auditing a different part of the network), into ours.
+    /// Conflicting spends are kept alongside ours as double spends, and reported.
+    pub fn merge_serialized(&mut self, bytes: &[u8]) -> Result<MergeReport> {
+        let sub_dag = Self::from_bytes(bytes)?;
+        let mut report = MergeReport::default();
+        for (addr, spends) in sub_dag.spends {
+            // utxos are skipped, they are added along with their ancestors
+            for spend in spends.into_iter().filter_map(|(spend, _idx)| spend) {
+                match self.check_and_insert(addr, spend) {
+                    Ok(true) => {
+                        let _ = report.new_spends.insert(addr);
+                    }
+                    Ok(false) => report.already_known += 1,
+                    Err(Error::DoubleSpend(..)) => {
+                        warn!("Double spend found at {addr:?} while merging a partial DAG");
+                        let _ = report.conflicts.insert(addr);
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+        Ok(report)
+    }
+
     /// Get the spend at a given address
     pub fn get_spend(&self, addr: &SpendAddress) -> SpendDagGet {
         match self.spends.get(addr) {
@@ -254,6 +322,42 @@ impl SpendDag {
         Ok(royalties)
     }
 
+    /// Get all the spends involving the keys derived from `key` with the given derivation indexes,
+    /// i.e. the "account statement" of the key.
+    ///
+    /// The keys a `MainPubkey` derives can only be recognised knowing their `DerivationIndex`,
+    /// so the full history of the key takes the derivation indexes of all its CashNotes.
+    ///
+    /// A spend whose input and one of its outputs are both derived from `key`, e.g. one sending
+    /// change back to the key, is returned twice: once in each direction.
+    pub fn spends_for_derived_keys(
+        &self,
+        key: &MainPubkey,
+        derivation_indexes: &[DerivationIndex],
+    ) -> Vec<(SpendAddress, SignedSpend, SpendDirection)> {
+        let unique_pubkeys: BTreeSet<UniquePubkey> = derivation_indexes
+            .iter()
+            .map(|index| key.new_unique_pubkey(index))
+            .collect();
+
+        let mut spends = Vec::new();
+        for s in self.all_spends() {
+            let addr = SpendAddress::from_unique_pubkey(s.unique_pubkey());
+            if s.spend
+                .spent_tx
+                .outputs
+                .iter()
+                .any(|output| unique_pubkeys.contains(&output.unique_pubkey))
+            {
+                spends.push((addr, s.clone(), SpendDirection::Inbound));
+            }
+            if unique_pubkeys.contains(s.unique_pubkey()) {
+                spends.push((addr, s.clone(), SpendDirection::Outbound));
+            }
+        }
+        spends
+    }
+
     /// helper that returns the spend at a given address if it is unique (not double spend) and not an UTXO
     fn get_unique_spend_at(
         &self,
@@ -364,7 +468,26 @@ impl SpendDag {
     /// Returns a list of errors found in the DAG
     /// Note that the `MissingSource` error makes the entire DAG invalid
     pub fn verify(&self, source: &SpendAddress) -> Vec<DagError> {
-        info!("Verifying DAG starting off: {source:?}");
+        self.verify_skipping(source, &BTreeSet::new())
+    }
+
+    /// Verify the DAG, like [`SpendDag::verify`], but without verifying again the transactions
+    /// of the spends at `already_verified` addresses, e.g. those of a previously verified DAG
+    /// that was extended
+    ///
+    /// Skipping them is safe as long as they were verified from the same source without errors:
+    /// a transaction is verified against its ancestors only, which are already verified too, so
+    /// extending the DAG with descendants can't change the outcome. The addresses are still
+    /// checked for double spends, as extending the DAG can bring in another spend at them
+    pub fn verify_skipping(
+        &self,
+        source: &SpendAddress,
+        already_verified: &BTreeSet<SpendAddress>,
+    ) -> Vec<DagError> {
+        info!(
+            "Verifying DAG starting off: {source:?}, skipping {} already verified spends",
+            already_verified.len()
+        );
         let mut recorded_errors = Vec::new();
 
         // verify DAG source is unique (Genesis in case of a complete DAG)
@@ -390,6 +513,11 @@ impl SpendDag {
                 None => continue,
             };
 
+            if already_verified.contains(addr) {
+                debug!("Skip transaction verification for the already verified: {addr:?}");
+                continue;
+            }
+
             // skip if genesis
             if is_genesis_spend(spend) {
                 debug!("Skip transaction verification for Genesis at: {addr:?}");
@@ -433,9 +561,38 @@ impl SpendDag {
     }
 }
 
+impl PartialEq for SpendDag {
+    fn eq(&self, other: &Self) -> bool {
+        let nodes = |dag: &SpendDag| -> Vec<SpendAddress> {
+            dag.dag.raw_nodes().iter().map(|node| node.weight).collect()
+        };
+        let edges = |dag: &SpendDag| -> Vec<_> {
+            dag.dag
+                .raw_edges()
+                .iter()
+                .map(|edge| (edge.source(), edge.target(), edge.weight))
+                .collect()
+        };
+        self.spends == other.spends && nodes(self) == nodes(other) && edges(self) == edges(other)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sn_transfers::{MainSecretKey, NanoTokens, GENESIS_CASHNOTE};
+
+    /// A DAG made of the genesis spends, along with their addresses.
+    fn genesis_dag() -> (SpendDag, Vec<SpendAddress>) {
+        let mut dag = SpendDag::new();
+        let mut addrs = vec![];
+        for spend in GENESIS_CASHNOTE.signed_spends.iter() {
+            let addr = SpendAddress::from_unique_pubkey(spend.unique_pubkey());
+            dag.insert(addr, spend.clone());
+            addrs.push(addr);
+        }
+        (dag, addrs)
+    }
 
     #[test]
     fn test_spend_dag_serialisation() {
@@ -447,4 +604,109 @@ mod tests {
             rmp_serde::to_vec(&deserialized_instance).expect("Serialization failed");
         assert_eq!(reserialized_data, serialized_data);
     }
+
+    #[test]
+    fn test_spend_dag_bytes_round_trip() -> Result<()> {
+        let (dag, _) = genesis_dag();
+        assert!(!dag.all_spends().is_empty());
+
+        let bytes = dag.to_bytes()?;
+        assert_eq!(bytes[0], SPEND_DAG_BYTES_VERSION);
+        assert_eq!(SpendDag::from_bytes(&bytes)?, dag);
+
+        let empty = SpendDag::new();
+        assert_eq!(SpendDag::from_bytes(&empty.to_bytes()?)?, empty);
+
+        let mut unknown_version = bytes.clone();
+        unknown_version[0] = SPEND_DAG_BYTES_VERSION + 1;
+        assert!(matches!(
+            SpendDag::from_bytes(&unknown_version),
+            Err(Error::UnsupportedSpendDagVersion(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_spend_dag_merge_serialized() -> Result<()> {
+        let (partial, genesis_addrs) = genesis_dag();
+        let bytes = partial.to_bytes()?;
+
+        let mut dag = SpendDag::new();
+        let report = dag.merge_serialized(&bytes)?;
+        assert_eq!(report.new_spends.len(), genesis_addrs.len());
+        assert!(report.conflicts.is_empty());
+        assert_eq!(report.already_known, 0);
+        assert_eq!(dag.all_spends(), partial.all_spends());
+
+        // merging the same partial DAG again brings nothing new
+        let report = dag.merge_serialized(&bytes)?;
+        assert!(report.new_spends.is_empty());
+        assert!(report.conflicts.is_empty());
+        assert_eq!(report.already_known, genesis_addrs.len());
+
+        assert!(dag.merge_serialized(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_spends_for_derived_keys() {
+        let (dag, genesis_addrs) = genesis_dag();
+        // the genesis spend sends everything from the genesis key back to it
+        let genesis_key = GENESIS_CASHNOTE.main_pubkey();
+        let genesis_index = DerivationIndex([0u8; 32]);
+        let genesis_addr = genesis_addrs[0];
+        assert_eq!(
+            genesis_addr,
+            SpendAddress::from_unique_pubkey(&genesis_key.new_unique_pubkey(&genesis_index))
+        );
+
+        let spends = dag.spends_for_derived_keys(genesis_key, &[genesis_index]);
+        let movements: Vec<(SpendAddress, SpendDirection)> = spends
+            .into_iter()
+            .map(|(addr, _, direction)| (addr, direction))
+            .collect();
+        assert_eq!(
+            movements,
+            vec![
+                (genesis_addr, SpendDirection::Inbound),
+                (genesis_addr, SpendDirection::Outbound)
+            ]
+        );
+
+        let other_key = MainSecretKey::random().main_pubkey();
+        assert!(dag
+            .spends_for_derived_keys(&other_key, &[genesis_index])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_verify_skipping_still_reports_double_spends() {
+        let (mut dag, genesis_addrs) = genesis_dag();
+        let genesis_addr = genesis_addrs[0];
+        let genesis_spend = GENESIS_CASHNOTE
+            .signed_spends
+            .first()
+            .expect("genesis spend");
+
+        // two different spends turn up at an output of the already verified genesis spend
+        let output = genesis_spend
+            .spend
+            .spent_tx
+            .outputs
+            .first()
+            .expect("genesis output");
+        let output_addr = SpendAddress::from_unique_pubkey(&output.unique_pubkey);
+        let spend_with_token = |token: u64| {
+            let mut spend = genesis_spend.clone();
+            spend.spend.token = NanoTokens::from(token);
+            spend
+        };
+        dag.insert(output_addr, spend_with_token(1));
+        dag.insert(output_addr, spend_with_token(2));
+
+        let already_verified = BTreeSet::from([genesis_addr, output_addr]);
+        assert!(dag
+            .verify_skipping(&genesis_addr, &already_verified)
+            .contains(&DagError::DoubleSpend(output_addr)));
+    }
 }
@@ -6,11 +6,11 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{Client, SpendDag};
+use super::{Client, SpendDag, SpendDagGet};
 use crate::{Error, Result};
 
 use futures::future::join_all;
-use sn_transfers::{SignedSpend, SpendAddress, WalletError, WalletResult};
+use sn_transfers::{CashNote, SignedSpend, SpendAddress, WalletError, WalletResult};
 use std::collections::BTreeSet;
 use tokio::task::JoinSet;
 
@@ -19,18 +19,42 @@ impl Client {
     /// Started from Genesis this gives the entire SpendDag of the Network at a certain point in time
     /// Once the DAG collected, verifies all the transactions
     pub async fn spend_dag_build_from(&self, spend_addr: SpendAddress) -> WalletResult<SpendDag> {
+        self.spend_dag_build_from_baseline(spend_addr, SpendDag::new())
+            .await
+    }
+
+    /// Same as [`Client::spend_dag_build_from`], but starting off a previously built and verified DAG
+    /// The spends already in the baseline are neither fetched again nor verified again,
+    /// only their descendants which are not in the baseline yet are fetched and verified
+    ///
+    /// The baseline must have been built from the same `spend_addr` and verified without errors,
+    /// see [`SpendDag::verify_skipping`]. As its spends are not fetched again, a spend made later
+    /// at one of its addresses, i.e. a double spend, is only found by a full rebuild
+    pub async fn spend_dag_build_from_baseline(
+        &self,
+        spend_addr: SpendAddress,
+        baseline: SpendDag,
+    ) -> WalletResult<SpendDag> {
         info!("Building spend DAG from {spend_addr:?}");
-        let mut dag = SpendDag::new();
+        let already_verified: BTreeSet<SpendAddress> = baseline
+            .all_spends()
+            .into_iter()
+            .map(|spend| SpendAddress::from_unique_pubkey(spend.unique_pubkey()))
+            .collect();
+        let mut dag = baseline;
 
         // get first spend
-        let first_spend = match self.get_spend_from_network(spend_addr).await {
-            Ok(s) => s,
-            Err(Error::MissingSpendRecord(_)) => {
-                // the cashnote was not spent yet, so it's an UTXO
-                info!("UTXO at {spend_addr:?}");
-                return Ok(dag);
-            }
-            Err(e) => return Err(WalletError::FailedToGetSpend(e.to_string())),
+        let first_spend = match dag.get_spend(&spend_addr) {
+            SpendDagGet::Spend(spend) => *spend,
+            _ => match self.get_spend_from_network(spend_addr).await {
+                Ok(s) => s,
+                Err(Error::MissingSpendRecord(_)) => {
+                    // the cashnote was not spent yet, so it's an UTXO
+                    info!("UTXO at {spend_addr:?}");
+                    return Ok(dag);
+                }
+                Err(e) => return Err(WalletError::FailedToGetSpend(e.to_string())),
+            },
         };
         dag.insert(spend_addr, first_spend.clone());
 
@@ -55,12 +79,15 @@ impl Client {
                 let addrs_to_follow = descendant_keys.map(|k| SpendAddress::from_unique_pubkey(&k));
                 info!("Gen {gen} - Following descendant Tx : {descendant_tx_hash:?}");
 
-                let tasks_for_this_descendant: Vec<_> = addrs_to_follow
-                    .clone()
-                    .map(|a| self.get_spend_from_network(a))
-                    .collect();
-                tasks.extend(tasks_for_this_descendant);
-                addrs.extend(addrs_to_follow);
+                for addr in addrs_to_follow {
+                    // no need to fetch the spends we already have in the baseline
+                    if let SpendDagGet::Spend(spend) = dag.get_spend(&addr) {
+                        next_gen_tx.insert(spend.spend.spent_tx);
+                        continue;
+                    }
+                    tasks.push(self.get_spend_from_network(addr));
+                    addrs.push(addr);
+                }
             }
 
             // wait for tasks to complete
@@ -103,7 +130,7 @@ impl Client {
         // verify the DAG
         info!("Now verifying SpendDAG...");
         let start = std::time::Instant::now();
-        let recorded_errors = dag.verify(&spend_addr);
+        let recorded_errors = dag.verify_skipping(&spend_addr, &already_verified);
         warn!("SpendDAG verification recorded errors: {recorded_errors:?}");
         let elapsed = start.elapsed();
         info!("Finished verifying SpendDAG in {elapsed:?}");
@@ -135,6 +162,45 @@ impl Client {
         spend_addr: SpendAddress,
         new_spend: SignedSpend,
     ) -> WalletResult<()> {
+        let _depth = self
+            .spend_dag_extend_until_depth(dag, spend_addr, new_spend)
+            .await?;
+        Ok(())
+    }
+
+    /// Verifies that the whole ancestry of a CashNote traces back to Genesis, with no missing
+    /// or invalid spend on the way, on top of its parent spends matching the ones in the network.
+    /// Returns the number of generations verified, i.e. the depth of the CashNote below Genesis.
+    ///
+    /// This fetches and verifies every ancestor of the CashNote, which is much more expensive than
+    /// [`Client::verify_cashnote`], but gives a proof of provenance, e.g. before accepting a very
+    /// high value CashNote.
+    pub async fn verify_lineage(&self, cash_note: &CashNote) -> WalletResult<usize> {
+        self.verify_cashnote(cash_note).await?;
+
+        let mut dag = SpendDag::new();
+        let mut depth = 0;
+        for spend in cash_note.signed_spends.iter() {
+            let spend_addr = SpendAddress::from_unique_pubkey(spend.unique_pubkey());
+            let spend_depth = self
+                .spend_dag_extend_until_depth(&mut dag, spend_addr, spend.clone())
+                .await?;
+            depth = depth.max(spend_depth);
+        }
+        info!(
+            "Verified the lineage of CashNote {:?} back to Genesis, through {depth} generations",
+            cash_note.unique_pubkey()
+        );
+        Ok(depth)
+    }
+
+    /// Same as [`Client::spend_dag_extend_until`], returning the number of generations traced back.
+    async fn spend_dag_extend_until_depth(
+        &self,
+        dag: &mut SpendDag,
+        spend_addr: SpendAddress,
+        new_spend: SignedSpend,
+    ) -> WalletResult<usize> {
         // check existence of spend in dag
         let is_new_spend = dag
             .check_and_insert(spend_addr, new_spend.clone())
@@ -142,7 +208,7 @@ impl Client {
                 WalletError::CouldNotVerifyTransfer(format!("Failed to insert spend in DAG: {err}"))
             })?;
         if !is_new_spend {
-            return Ok(());
+            return Ok(0);
         }
 
         // use iteration instead of recursion to avoid stack overflow
@@ -225,12 +291,18 @@ impl Client {
         let elapsed = start.elapsed();
         let n = verified_tx.len();
         info!("Verified all the way to known spends or genesis! Through {depth} generations, verifying {n} transactions in {elapsed:?}");
-        Ok(())
+        Ok(depth)
     }
 
     /// Extends an existing SpendDag starting from the utxos in this DAG
     /// Covers the entirety of currently existing Spends if the DAG was built from Genesis
-    pub async fn spend_dag_continue_from_utxos(&self, dag: &mut SpendDag) -> WalletResult<()> {
+    /// Calls `on_subdag_merged` each time the DAG gathered from a utxo is merged in,
+    /// with the running total of spends collected so far, e.g. to report progress
+    pub async fn spend_dag_continue_from_utxos(
+        &self,
+        dag: &mut SpendDag,
+        on_subdag_merged: impl Fn(usize),
+    ) -> WalletResult<()> {
         info!("Gathering spend DAG from utxos...");
         let utxos = dag.get_utxos();
         let mut tasks = JoinSet::new();
@@ -239,13 +311,16 @@ impl Client {
             let self_clone = self.clone();
             tasks.spawn(async move { self_clone.spend_dag_build_from(utxo).await });
         }
+        let mut spends_collected = 0;
         while let Some(res) = tasks.join_next().await {
             let sub_dag = res.map_err(|e| {
                 WalletError::FailedToGetSpend(format!("DAG gathering task failed: {e}"))
             })??;
+            spends_collected += sub_dag.all_spends().len();
             dag.merge(sub_dag);
+            on_subdag_merged(spends_collected);
         }
-        info!("Done gathering spend DAG from utxos");
+        info!("Done gathering spend DAG from utxos, collected {spends_collected} spends");
         Ok(())
     }
 }
@@ -10,4 +10,4 @@ mod error;
 mod pac_man;
 
 pub(crate) use self::error::{Error, Result};
-pub(crate) use pac_man::{encrypt_large, DataMapLevel};
+pub(crate) use pac_man::{encrypt_bytes, encrypt_large, unpack_data_map, DataMapLevelFetcher};
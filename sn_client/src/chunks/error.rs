@@ -42,6 +42,9 @@ pub enum Error {
     #[error("File is too small to be encrypted, it is less than {MIN_ENCRYPTABLE_BYTES} bytes")]
     FileTooSmall,
 
+    #[error("Invalid data map packing params: {0}")]
+    InvalidDataMapPackingParams(String),
+
     #[error(
         "The provided bytes ({size}) is too large to store as a `SmallFile` which maximum can be \
         {maximum}. Store as a LargeFile instead."
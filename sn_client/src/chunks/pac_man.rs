@@ -6,10 +6,14 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Result;
+use super::{Error, Result};
+use async_trait::async_trait;
 use bytes::{BufMut, Bytes, BytesMut};
 use rayon::prelude::*;
-use self_encryption::{DataMap, StreamSelfEncryptor, MAX_CHUNK_SIZE};
+use self_encryption::{
+    decrypt_full_set, DataMap, EncryptedChunk, StreamSelfEncryptor, MAX_CHUNK_SIZE,
+    MIN_ENCRYPTABLE_BYTES,
+};
 use serde::{Deserialize, Serialize};
 use sn_protocol::storage::Chunk;
 use std::{
@@ -33,7 +37,7 @@ pub(crate) enum DataMapLevel {
 pub(crate) fn encrypt_from_path(path: &Path, output_dir: &Path) -> Result<(Chunk, Vec<XorName>)> {
     let (data_map, mut encrypted_chunks) = self_encryption::encrypt_from_file(path, output_dir)?;
 
-    let (data_map_chunk, additional_chunks) = pack_data_map(data_map)?;
+    let (data_map_chunk, additional_chunks) = pack_data_map(data_map, MAX_CHUNK_SIZE)?;
 
     for chunk in additional_chunks.iter() {
         encrypted_chunks.push(*chunk.name());
@@ -49,6 +53,7 @@ pub(crate) fn encrypt_from_path(path: &Path, output_dir: &Path) -> Result<(Chunk
 pub(crate) fn encrypt_large(
     file_path: &Path,
     output_dir: &Path,
+    max_data_map_chunk_size: usize,
 ) -> Result<(Chunk, Vec<(XorName, PathBuf)>)> {
     let mut encryptor = StreamSelfEncryptor::encrypt_from_file(
         Box::new(file_path.to_path_buf()),
@@ -76,7 +81,7 @@ pub(crate) fn encrypt_large(
         .collect();
 
     // Pack the datamap into chunks that under the same output folder as well.
-    let (data_map_chunk, additional_chunks) = pack_data_map(data_map)?;
+    let (data_map_chunk, additional_chunks) = pack_data_map(data_map, max_data_map_chunk_size)?;
     for chunk in additional_chunks.iter() {
         let file_path = output_dir.join(&hex::encode(chunk.name()));
         encrypted_chunks.push((*chunk.name(), file_path.to_path_buf()));
@@ -87,24 +92,44 @@ pub(crate) fn encrypt_large(
     Ok((data_map_chunk, encrypted_chunks))
 }
 
+/// Self-encrypts the bytes in memory, without writing anything to disk.
+///
+/// Returns the data map as a chunk, the data map of the bytes, and all the chunks to store,
+/// including the additional ones the data map had to be packed into.
+pub(crate) fn encrypt_bytes(data: Bytes) -> Result<(Chunk, DataMap, Vec<Chunk>)> {
+    if data.len() < MIN_ENCRYPTABLE_BYTES {
+        return Err(Error::FileTooSmall);
+    }
+    let (data_map, encrypted_chunks) = self_encryption::encrypt(data)?;
+    let mut chunks: Vec<_> = encrypted_chunks
+        .into_iter()
+        .map(|chunk| to_chunk(chunk.content))
+        .collect();
+
+    let (data_map_chunk, additional_chunks) = pack_data_map(data_map.clone(), MAX_CHUNK_SIZE)?;
+    chunks.extend(additional_chunks);
+
+    Ok((data_map_chunk, data_map, chunks))
+}
+
 pub(crate) fn to_chunk(chunk_content: Bytes) -> Chunk {
     Chunk::new(chunk_content)
 }
 
 // Produces a chunk out of the first `DataMap`, which is validated for its size.
 // If the chunk is too big, it is self-encrypted and the resulting (additional level) `DataMap` is put into a chunk.
-// The above step is repeated as many times as required until the chunk size is valid.
+// The above step is repeated as many times as required until the chunk size is within `max_chunk_size`.
 // In other words: If the chunk content is too big, it will be
 // self encrypted into additional chunks, and now we have a new `DataMap`
 // which points to all of those additional chunks.. and so on.
-fn pack_data_map(data_map: DataMap) -> Result<(Chunk, Vec<Chunk>)> {
+fn pack_data_map(data_map: DataMap, max_chunk_size: usize) -> Result<(Chunk, Vec<Chunk>)> {
     let mut chunks = vec![];
     let mut chunk_content = wrap_data_map(&DataMapLevel::First(data_map))?;
 
     let (data_map_chunk, additional_chunks) = loop {
         let chunk = to_chunk(chunk_content);
-        // If datamap chunk is less than `MAX_CHUNK_SIZE` return it so it can be directly sent to the network.
-        if MAX_CHUNK_SIZE >= chunk.serialised_size() {
+        // If datamap chunk is less than `max_chunk_size` return it so it can be directly sent to the network.
+        if max_chunk_size >= chunk.serialised_size() {
             chunks.reverse();
             // Returns the last datamap, and all the chunks produced.
             break (chunk, chunks);
@@ -127,6 +152,35 @@ fn pack_data_map(data_map: DataMap) -> Result<(Chunk, Vec<Chunk>)> {
     Ok((data_map_chunk, additional_chunks))
 }
 
+/// Fetches the chunks a data map points to, for `unpack_data_map` to decrypt the next level from.
+#[async_trait]
+pub(crate) trait DataMapLevelFetcher {
+    /// Returns the encrypted chunks of the data map, in the order of their index.
+    async fn fetch_level(&mut self, data_map: &DataMap) -> crate::Result<Vec<EncryptedChunk>>;
+}
+
+/// Extracts the `DataMap` of the content from a data map chunk.
+/// If the chunk holds an additional level instead, the chunk of the previous level is decrypted
+/// from the chunks the fetcher returns for it, until the first level is reached.
+pub(crate) async fn unpack_data_map(
+    mut chunk: Chunk,
+    fetcher: &mut impl DataMapLevelFetcher,
+) -> crate::Result<DataMap> {
+    loop {
+        match rmp_serde::from_slice(chunk.value()).map_err(Error::Deserialisation)? {
+            DataMapLevel::First(data_map) => {
+                return Ok(data_map);
+            }
+            DataMapLevel::Additional(data_map) => {
+                let encrypted_chunks = fetcher.fetch_level(&data_map).await?;
+                let serialized_chunk = decrypt_full_set(&data_map, &encrypted_chunks)
+                    .map_err(Error::SelfEncryption)?;
+                chunk = rmp_serde::from_slice(&serialized_chunk).map_err(Error::Deserialisation)?;
+            }
+        }
+    }
+}
+
 fn wrap_data_map(data_map: &DataMapLevel) -> Result<Bytes> {
     // we use an initial/starting size of 300 bytes as that's roughly the current size of a DataMapLevel instance.
     let mut bytes = BytesMut::with_capacity(300).writer();
@@ -134,3 +188,72 @@ fn wrap_data_map(data_map: &DataMapLevel) -> Result<Bytes> {
     data_map.serialize(&mut serialiser)?;
     Ok(bytes.into_inner().freeze())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataMapPackingParams, FilesApi, MIN_DATA_MAP_CHUNK_SIZE};
+    use std::fs;
+
+    /// Fetches the chunks from the dir they were written to when chunking the file.
+    struct ChunkDirFetcher {
+        chunk_dir: PathBuf,
+        levels_fetched: usize,
+    }
+
+    #[async_trait]
+    impl DataMapLevelFetcher for ChunkDirFetcher {
+        async fn fetch_level(&mut self, data_map: &DataMap) -> crate::Result<Vec<EncryptedChunk>> {
+            self.levels_fetched += 1;
+            data_map
+                .infos()
+                .into_iter()
+                .map(|info| {
+                    let content = fs::read(self.chunk_dir.join(hex::encode(info.dst_hash)))?;
+                    Ok(EncryptedChunk {
+                        index: info.index,
+                        content: Bytes::from(content),
+                    })
+                })
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn data_map_packed_in_additional_levels_is_unpacked() -> eyre::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let chunk_dir = temp_dir.path().join("chunks");
+        fs::create_dir_all(&chunk_dir)?;
+
+        // enough content chunks for their data map not to fit the smallest data map chunk
+        let file_path = temp_dir.path().join("file");
+        let content: Vec<u8> = (0..64 * MAX_CHUNK_SIZE).map(|i| (i % 251) as u8).collect();
+        fs::write(&file_path, &content)?;
+
+        let params = DataMapPackingParams {
+            max_data_map_chunk_size: MIN_DATA_MAP_CHUNK_SIZE,
+        };
+        let (_, data_map_chunk, file_size, _) =
+            FilesApi::chunk_file_with_params(&file_path, &chunk_dir, false, params)?;
+        assert_eq!(file_size, content.len() as u64);
+        assert!(data_map_chunk.serialised_size() <= MIN_DATA_MAP_CHUNK_SIZE);
+
+        let mut fetcher = ChunkDirFetcher {
+            chunk_dir: chunk_dir.clone(),
+            levels_fetched: 0,
+        };
+        let data_map = unpack_data_map(data_map_chunk, &mut fetcher).await?;
+        assert!(fetcher.levels_fetched > 0);
+
+        let encrypted_chunks = fetcher.fetch_level(&data_map).await?;
+        let decrypted = decrypt_full_set(&data_map, &encrypted_chunks)?;
+        assert_eq!(decrypted, Bytes::from(content));
+
+        // the params only affect the data map, the content chunks are the default ones
+        let (_, default_data_map_chunk, _, _) =
+            FilesApi::chunk_file(&file_path, &chunk_dir, false)?;
+        let default_data_map = unpack_data_map(default_data_map_chunk, &mut fetcher).await?;
+        assert_eq!(data_map.infos(), default_data_map.infos());
+        Ok(())
+    }
+}
@@ -9,7 +9,8 @@
 pub(crate) type Result<T> = std::result::Result<T, Error>;
 
 use super::ClientEvent;
-use sn_protocol::NetworkAddress;
+use libp2p::PeerId;
+use sn_protocol::{storage::ChunkAddress, NetworkAddress};
 use sn_registers::{Entry, EntryHash};
 use sn_transfers::{SignedSpend, SpendAddress};
 use std::collections::BTreeSet;
@@ -60,6 +61,8 @@ pub enum Error {
     DoubleSpend(SpendAddress, Box<SignedSpend>, Box<SignedSpend>),
     #[error("Invalid DAG")]
     InvalidDag,
+    #[error("Unsupported SpendDag binary format version: {0}")]
+    UnsupportedSpendDagVersion(u8),
     #[error("Serialization error: {0:?}")]
     Serialization(#[from] rmp_serde::encode::Error),
     #[error("Deserialization error: {0:?}")]
@@ -74,12 +77,24 @@ pub enum Error {
     )]
     ContentBranchDetected(BTreeSet<(EntryHash, Entry)>),
 
-    #[error("The provided amount contains zero nanos")]
-    AmountIsZero,
-
     #[error("The payee for the address {0:?} was not found.")]
     PayeeNotFound(NetworkAddress),
 
+    #[error("Gossip is not enabled on this client")]
+    GossipDisabled,
+
+    #[error("The record label is {0} bytes long, more than the {max} allowed", max = sn_protocol::messages::MAX_RECORD_LABEL_LEN)]
+    RecordLabelTooLong(usize),
+
+    #[error("Unexpected response from {0:?}")]
+    UnexpectedResponse(PeerId),
+
+    #[error("The chunk returned for {expected:?} has the content of {got:?}")]
+    ChunkContentMismatch {
+        expected: ChunkAddress,
+        got: ChunkAddress,
+    },
+
     /// CashNote add would overflow
     #[error("Total price exceed possible token amount")]
     TotalPriceTooHigh,
@@ -108,6 +123,18 @@ pub enum Error {
     #[error("Error occurred when access wallet file")]
     FailedToAccessWallet,
 
+    #[error("Invalid wallet name: {0:?}")]
+    InvalidWalletName(String),
+
+    #[error("A wallet named {0:?} already exists")]
+    WalletAlreadyExists(String),
+
+    #[error("The total balance of the wallets exceeds the possible token amount")]
+    TotalBalanceOverflow,
+
+    #[error("No wallet named {0:?} was found")]
+    WalletNotFound(String),
+
     #[error("Task completion notification channel is done")]
     FailedToReadFromNotificationChannel,
 }
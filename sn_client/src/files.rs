@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 pub(crate) mod download;
+pub(crate) mod resumable;
 pub(crate) mod upload;
 
 use crate::{
@@ -14,12 +15,13 @@ use crate::{
     WalletClient,
 };
 use bytes::Bytes;
-use self_encryption::{self, MIN_ENCRYPTABLE_BYTES};
+use futures::{stream, StreamExt};
+use self_encryption::{self, MAX_CHUNK_SIZE, MIN_ENCRYPTABLE_BYTES};
 use sn_protocol::{
     storage::{Chunk, ChunkAddress, RetryStrategy},
     NetworkAddress,
 };
-use sn_transfers::HotWallet;
+use sn_transfers::{HotWallet, NanoTokens, WalletError};
 use std::{
     fs::{self, create_dir_all, File},
     io::Write,
@@ -32,6 +34,44 @@ use xor_name::XorName;
 /// `BATCH_SIZE` determines the number of chunks that are processed in parallel during the payment and upload process.
 pub const BATCH_SIZE: usize = 16;
 
+/// The smallest cap on the size of the data map chunk. Even the data map of an additional level
+/// takes a few hundred bytes, so packing it under a smaller cap would never complete.
+pub const MIN_DATA_MAP_CHUNK_SIZE: usize = 4 * 1024;
+
+/// How the data map of a file is packed into chunks, as passed to
+/// `FilesApi::chunk_file_with_params`.
+///
+/// Only the data map is affected. The content chunks are sized and encrypted by self-encryption,
+/// from the file size alone and never over `self_encryption::MAX_CHUNK_SIZE`, which can't be tuned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataMapPackingParams {
+    /// The largest the chunk holding the data map can be, within `MIN_DATA_MAP_CHUNK_SIZE` and
+    /// `MAX_CHUNK_SIZE`. A bigger data map is self-encrypted into an additional level of chunks,
+    /// as many times as needed, trading a smaller head chunk for more chunks to store and fetch.
+    pub max_data_map_chunk_size: usize,
+}
+
+impl Default for DataMapPackingParams {
+    fn default() -> Self {
+        Self {
+            max_data_map_chunk_size: MAX_CHUNK_SIZE,
+        }
+    }
+}
+
+impl DataMapPackingParams {
+    fn validate(&self) -> Result<()> {
+        if !(MIN_DATA_MAP_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&self.max_data_map_chunk_size) {
+            return Err(ChunksError::InvalidDataMapPackingParams(format!(
+                "max_data_map_chunk_size must be within {MIN_DATA_MAP_CHUNK_SIZE} and {MAX_CHUNK_SIZE} bytes, got {}",
+                self.max_data_map_chunk_size
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
 /// File APIs.
 #[derive(Clone)]
 pub struct FilesApi {
@@ -39,6 +79,16 @@ pub struct FilesApi {
     pub(crate) wallet_dir: PathBuf,
 }
 
+/// The estimated cost of uploading a file, as returned by `FilesApi::estimate_upload_cost`.
+#[derive(Debug, Clone)]
+pub struct UploadCostEstimate {
+    /// The sum of the store costs of all the chunks.
+    pub total_cost: NanoTokens,
+    /// The store cost of each chunk, including the data map one. Chunks already stored on the
+    /// network cost nothing.
+    pub chunk_costs: Vec<(XorName, NanoTokens)>,
+}
+
 /// This is the (file xorname, datamap_data, filesize, and chunks)
 /// If the DataMapChunk exists and is not stored on the network, then it will not be accessible at this address of ChunkAddress(XorName) .
 type ChunkFileResult = Result<(ChunkAddress, Chunk, u64, Vec<(XorName, PathBuf)>)>;
@@ -48,12 +98,14 @@ impl FilesApi {
     pub fn new(client: Client, wallet_dir: PathBuf) -> Self {
         Self { client, wallet_dir }
     }
+    /// Create file apis instance, erroring with `WalletError::ZeroAmount` if the wallet has no
+    /// tokens to pay for uploads with.
     pub fn build(client: Client, wallet_dir: PathBuf) -> Result<FilesApi> {
         if HotWallet::load_from(wallet_dir.as_path())?
             .balance()
             .is_zero()
         {
-            Err(Error::AmountIsZero)
+            Err(WalletError::ZeroAmount.into())
         } else {
             Ok(FilesApi::new(client, wallet_dir))
         }
@@ -79,6 +131,26 @@ impl FilesApi {
         chunk_dir: &Path,
         include_data_map_in_chunks: bool,
     ) -> ChunkFileResult {
+        Self::chunk_file_with_params(
+            file_path,
+            chunk_dir,
+            include_data_map_in_chunks,
+            DataMapPackingParams::default(),
+        )
+    }
+
+    /// Same as `chunk_file`, packing the data map as per the given params rather than the
+    /// default ones. The content chunks are the same whichever the params.
+    ///
+    /// The resulting head address is retrieved through the usual download path, which unpacks
+    /// however many data map levels the params led to.
+    pub fn chunk_file_with_params(
+        file_path: &Path,
+        chunk_dir: &Path,
+        include_data_map_in_chunks: bool,
+        params: DataMapPackingParams,
+    ) -> ChunkFileResult {
+        params.validate()?;
         let file = File::open(file_path)?;
         let metadata = file.metadata()?;
         let file_size = metadata.len();
@@ -87,7 +159,8 @@ impl FilesApi {
             if file_size < MIN_ENCRYPTABLE_BYTES as u64 {
                 Err(ChunksError::FileTooSmall)?
             } else {
-                let (data_map_chunk, chunks) = encrypt_large(file_path, chunk_dir)?;
+                let (data_map_chunk, chunks) =
+                    encrypt_large(file_path, chunk_dir, params.max_data_map_chunk_size)?;
                 (*data_map_chunk.name(), data_map_chunk, chunks)
             };
 
@@ -162,6 +235,48 @@ impl FilesApi {
         Ok(res)
     }
 
+    /// Estimates the cost of uploading the file, without paying for anything.
+    ///
+    /// The file is chunked, including its data map chunk, and the store cost of every chunk is
+    /// queried from the network, up to `BATCH_SIZE` at once.
+    pub async fn estimate_upload_cost(&self, file_path: &Path) -> Result<UploadCostEstimate> {
+        let chunk_dir = tempdir()?;
+        let (_head_address, _data_map, _file_size, chunks_paths) =
+            Self::chunk_file(file_path, chunk_dir.path(), true)?;
+        info!(
+            "Estimating the upload cost of {file_path:?} over {} chunks",
+            chunks_paths.len()
+        );
+
+        let wallet_client = &self.wallet()?;
+        let mut quotes = stream::iter(chunks_paths)
+            .map(|(name, _path)| async move {
+                let address = NetworkAddress::ChunkAddress(ChunkAddress::new(name));
+                let quote = wallet_client.get_store_cost_at_address(address).await;
+                (name, quote)
+            })
+            .buffer_unordered(BATCH_SIZE);
+
+        let mut estimate = UploadCostEstimate {
+            total_cost: NanoTokens::zero(),
+            chunk_costs: Vec::new(),
+        };
+        while let Some((name, quote)) = quotes.next().await {
+            let (_peer_id, _payee, quote) = quote?;
+            estimate.total_cost = estimate
+                .total_cost
+                .checked_add(quote.cost)
+                .ok_or(Error::TotalPriceTooHigh)?;
+            estimate.chunk_costs.push((name, quote.cost));
+        }
+
+        debug!(
+            "Estimated the upload cost of {file_path:?} to be {}",
+            estimate.total_cost
+        );
+        Ok(estimate)
+    }
+
     // --------------------------------------------
     // ---------- Private helpers -----------------
     // --------------------------------------------
@@ -194,6 +309,14 @@ impl FilesApi {
 /// Does not store anything to the network.
 ///
 /// Returns data map as a chunk, and the resulting chunks
-fn encrypt_large(file_path: &Path, output_dir: &Path) -> Result<(Chunk, Vec<(XorName, PathBuf)>)> {
-    Ok(crate::chunks::encrypt_large(file_path, output_dir)?)
+fn encrypt_large(
+    file_path: &Path,
+    output_dir: &Path,
+    max_data_map_chunk_size: usize,
+) -> Result<(Chunk, Vec<(XorName, PathBuf)>)> {
+    Ok(crate::chunks::encrypt_large(
+        file_path,
+        output_dir,
+        max_data_map_chunk_size,
+    )?)
 }
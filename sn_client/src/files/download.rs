@@ -7,12 +7,13 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::{
-    chunks::{DataMapLevel, Error as ChunksError},
+    chunks::{unpack_data_map, DataMapLevelFetcher, Error as ChunksError},
     error::{Error as ClientError, Result},
     Client, FilesApi, BATCH_SIZE,
 };
+use async_trait::async_trait;
 use bytes::Bytes;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use itertools::Itertools;
 use self_encryption::{decrypt_full_set, DataMap, EncryptedChunk, StreamSelfDecryptor};
 use sn_networking::target_arch::Instant;
@@ -59,6 +60,99 @@ pub struct FilesDownload {
     logged_event_sender_absence: bool,
 }
 
+#[async_trait]
+impl DataMapLevelFetcher for FilesDownload {
+    async fn fetch_level(&mut self, data_map: &DataMap) -> Result<Vec<EncryptedChunk>> {
+        match self.read(data_map.clone(), None, true, true).await? {
+            DownloadReturnType::EncryptedChunks(encrypted_chunks) => Ok(encrypted_chunks),
+            _ => {
+                error!("IncorrectDownloadOption: we should be getting the encrypted chunks back.");
+                Err(ClientError::IncorrectDownloadOption)
+            }
+        }
+    }
+}
+
+/// Fetches the chunks of the `DataMap` levels with the client alone, without the events or the
+/// configuration of a `FilesDownload`.
+struct ClientDataMapLevelFetcher<'a> {
+    client: &'a Client,
+}
+
+#[async_trait]
+impl DataMapLevelFetcher for ClientDataMapLevelFetcher<'_> {
+    async fn fetch_level(&mut self, data_map: &DataMap) -> Result<Vec<EncryptedChunk>> {
+        let mut encrypted_chunks = fetch_chunks(
+            self.client,
+            data_map,
+            BATCH_SIZE,
+            false,
+            RetryStrategy::Quick,
+        )
+        .map(|result| result.map(|(_, _, encrypted_chunk)| encrypted_chunk))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+        encrypted_chunks.sort_by_key(|encrypted_chunk| encrypted_chunk.index);
+        Ok(encrypted_chunks)
+    }
+}
+
+/// Extracts the `DataMap` of a file from its head chunk, fetching the chunks of any additional
+/// `DataMap` level from the network.
+pub(crate) async fn unpack_head_chunk(client: &Client, head_chunk: Chunk) -> Result<DataMap> {
+    unpack_data_map(head_chunk, &mut ClientDataMapLevelFetcher { client }).await
+}
+
+/// Fetches the chunks the `DataMap` points to, `batch_size` of them at once. The chunks are
+/// yielded as they are retrieved, along with their index, hence not necessarily in order.
+fn fetch_chunks(
+    client: &Client,
+    data_map: &DataMap,
+    batch_size: usize,
+    show_holders: bool,
+    retry_strategy: RetryStrategy,
+) -> impl Stream<Item = std::result::Result<(ChunkAddress, usize, EncryptedChunk), ChunksError>> {
+    let client = client.clone();
+    futures::stream::iter(data_map.infos())
+        .map(move |chunk_info| {
+            get_chunk(
+                client.clone(),
+                chunk_info.dst_hash,
+                chunk_info.index,
+                show_holders,
+                retry_strategy,
+            )
+        })
+        .buffer_unordered(batch_size)
+}
+
+async fn get_chunk(
+    client: Client,
+    address: XorName,
+    index: usize,
+    show_holders: bool,
+    retry_strategy: RetryStrategy,
+) -> std::result::Result<(ChunkAddress, usize, EncryptedChunk), ChunksError> {
+    let chunk = client
+        .get_chunk(
+            ChunkAddress::new(address),
+            show_holders,
+            Some(retry_strategy),
+        )
+        .await
+        .map_err(|err| {
+            error!("Chunk missing {address:?} with {err:?}",);
+            ChunksError::ChunkMissing(address)
+        })?;
+    let encrypted_chunk = EncryptedChunk {
+        index,
+        content: chunk.value,
+    };
+    Ok((chunk.address, index, encrypted_chunk))
+}
+
 impl FilesDownload {
     /// Creates a new instance of `FilesDownload` with the default configuration.
     /// To modify the configuration, use the provided setter methods (`set_...` functions).
@@ -373,25 +467,18 @@ impl FilesDownload {
 
         let now = Instant::now();
 
-        let client_clone = self.api.client.clone();
-        let show_holders = self.show_holders;
-        let retry_strategy = self.retry_strategy;
         // the initial index is not always 0 as we might seek a range of bytes. So fetch the first index
         let mut current_index = chunk_infos
             .first()
             .ok_or_else(|| ClientError::EmptyDataMap)?
             .index;
-        let mut stream = futures::stream::iter(chunk_infos.into_iter())
-            .map(|chunk_info| {
-                Self::get_chunk(
-                    client_clone.clone(),
-                    chunk_info.dst_hash,
-                    chunk_info.index,
-                    show_holders,
-                    retry_strategy,
-                )
-            })
-            .buffer_unordered(self.batch_size);
+        let mut stream = fetch_chunks(
+            &self.api.client,
+            &data_map,
+            self.batch_size,
+            self.show_holders,
+            self.retry_strategy,
+        );
 
         let mut chunk_download_cache = HashMap::new();
 
@@ -474,25 +561,8 @@ impl FilesDownload {
     /// Extracts a file DataMapLevel from a chunk.
     /// If the DataMapLevel is not the first level mapping directly to the user's contents,
     /// the process repeats itself until it obtains the first level DataMapLevel.
-    pub async fn unpack_chunk(&mut self, mut chunk: Chunk) -> Result<DataMap> {
-        loop {
-            match rmp_serde::from_slice(chunk.value()).map_err(ChunksError::Deserialisation)? {
-                DataMapLevel::First(data_map) => {
-                    return Ok(data_map);
-                }
-                DataMapLevel::Additional(data_map) => {
-                    if let DownloadReturnType::DecryptedBytes(serialized_chunk) =
-                        self.read(data_map, None, false, true).await?
-                    {
-                        chunk = rmp_serde::from_slice(&serialized_chunk)
-                            .map_err(ChunksError::Deserialisation)?;
-                    } else {
-                        error!("IncorrectDownloadOption: we should be getting the decrypted bytes back.");
-                        return Err(ClientError::IncorrectDownloadOption);
-                    }
-                }
-            }
-        }
+    pub async fn unpack_chunk(&mut self, chunk: Chunk) -> Result<DataMap> {
+        unpack_data_map(chunk, self).await
     }
 
     async fn send_event(&mut self, event: FilesDownloadEvent) -> Result<()> {
@@ -507,29 +577,4 @@ impl FilesDownload {
         }
         Ok(())
     }
-
-    async fn get_chunk(
-        client: Client,
-        address: XorName,
-        index: usize,
-        show_holders: bool,
-        retry_strategy: RetryStrategy,
-    ) -> std::result::Result<(ChunkAddress, usize, EncryptedChunk), ChunksError> {
-        let chunk = client
-            .get_chunk(
-                ChunkAddress::new(address),
-                show_holders,
-                Some(retry_strategy),
-            )
-            .await
-            .map_err(|err| {
-                error!("Chunk missing {address:?} with {err:?}",);
-                ChunksError::ChunkMissing(address)
-            })?;
-        let encrypted_chunk = EncryptedChunk {
-            index,
-            content: chunk.value,
-        };
-        Ok((chunk.address, index, encrypted_chunk))
-    }
 }
@@ -0,0 +1,229 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{error::Result, FilesApi, WalletClient, BATCH_SIZE};
+use bytes::Bytes;
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sn_protocol::{
+    storage::{Chunk, ChunkAddress},
+    NetworkAddress,
+};
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tempfile::tempdir;
+use xor_name::XorName;
+
+/// The dir, within the wallet dir, where the manifests of the unfinished uploads are persisted.
+const UPLOAD_MANIFESTS_DIR_NAME: &str = "upload_manifests";
+
+/// The progress of a resumable upload, as reported to its `ProgressCb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadProgress {
+    /// The number of chunks confirmed stored, including the ones stored by previous attempts.
+    pub stored: usize,
+    /// The total number of chunks of the file, including its data map chunk.
+    pub total: usize,
+}
+
+/// Called every time more chunks of a resumable upload are confirmed stored.
+pub type ProgressCb = Box<dyn Fn(UploadProgress) + Send + Sync>;
+
+/// The chunks of a file confirmed stored so far, persisted until its upload completes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UploadManifest {
+    stored: BTreeSet<XorName>,
+}
+
+impl UploadManifest {
+    /// Loads the manifest, or starts an empty one if there is none.
+    fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let bytes = fs::read(path)?;
+        match rmp_serde::from_slice(&bytes) {
+            Ok(manifest) => Ok(manifest),
+            Err(err) => {
+                warn!("Ignoring the unreadable upload manifest {path:?}: {err:?}");
+                Ok(Self::default())
+            }
+        }
+    }
+
+    /// Persists the manifest, writing it to a temp file first so that a crash midway can't
+    /// leave a truncated manifest in place of the previous one.
+    fn store(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(&rmp_serde::to_vec(self)?)?;
+        file.sync_all()?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+}
+
+impl FilesApi {
+    /// Uploads the file, paying for its chunks with the wallet, in a way that can be resumed if
+    /// it fails partway.
+    ///
+    /// The chunks confirmed stored are recorded in a manifest persisted in the wallet dir, keyed
+    /// by the address of the file's data map, hence by its content. Uploading the same file
+    /// again only pays for and uploads the chunks missing from the manifest. The manifest is
+    /// removed once the upload completes.
+    ///
+    /// Returns the address of the data map chunk, to download the file from.
+    pub async fn upload_resumable(
+        &self,
+        file_path: &Path,
+        wallet: &mut WalletClient,
+        progress: ProgressCb,
+    ) -> Result<XorName> {
+        let chunk_dir = tempdir()?;
+        let (head_address, _data_map, _file_size, chunks_paths) =
+            Self::chunk_file(file_path, chunk_dir.path(), true)?;
+        let head_address = *head_address.xorname();
+
+        let manifest_path = self.upload_manifest_path(&head_address);
+        let mut manifest = UploadManifest::load(&manifest_path)?;
+        let total = chunks_paths.len();
+        let pending: Vec<(XorName, PathBuf)> = chunks_paths
+            .into_iter()
+            .filter(|(name, _path)| !manifest.stored.contains(name))
+            .collect();
+        info!(
+            "Uploading {file_path:?} resumably, {} out of {total} chunks are left to store",
+            pending.len()
+        );
+        progress(UploadProgress {
+            stored: total - pending.len(),
+            total,
+        });
+
+        for batch in pending.chunks(BATCH_SIZE) {
+            // the chunks paid for by a previous attempt still have their payment in the wallet
+            let unpaid: Vec<NetworkAddress> = batch
+                .iter()
+                .map(|(name, _path)| NetworkAddress::ChunkAddress(ChunkAddress::new(*name)))
+                .filter(|addr| wallet.get_payment_for_addr(addr).is_err())
+                .collect();
+            if !unpaid.is_empty() {
+                let payment = wallet.pay_for_storage(unpaid.into_iter()).await?;
+                wallet.store_local_wallet()?;
+                manifest.stored.extend(payment.skipped_chunks);
+            }
+
+            let wallet = &*wallet;
+            let results: Vec<(XorName, Result<()>)> =
+                stream::iter(batch)
+                    .filter(|(name, _path)| {
+                        let skipped = manifest.stored.contains(name);
+                        async move { !skipped }
+                    })
+                    .map(|(name, path)| async move {
+                        (*name, self.upload_paid_chunk(wallet, path).await)
+                    })
+                    .buffer_unordered(BATCH_SIZE)
+                    .collect()
+                    .await;
+
+            let mut failure = None;
+            for (name, result) in results {
+                match result {
+                    Ok(()) => {
+                        let _ = manifest.stored.insert(name);
+                    }
+                    Err(err) => {
+                        warn!("Failed to upload chunk {name:?} of {file_path:?}: {err:?}");
+                        failure = Some(err);
+                    }
+                }
+            }
+            manifest.store(&manifest_path)?;
+            progress(UploadProgress {
+                stored: manifest.stored.len(),
+                total,
+            });
+
+            if let Some(err) = failure {
+                return Err(err);
+            }
+        }
+
+        if let Err(err) = fs::remove_file(&manifest_path) {
+            // there is none when everything was already stored on the first attempt
+            trace!("No upload manifest removed at {manifest_path:?}: {err:?}");
+        }
+        info!("Completed uploading {file_path:?} at {head_address:?}");
+        Ok(head_address)
+    }
+
+    /// Uploads a chunk, using the payment for it held in the wallet.
+    async fn upload_paid_chunk(&self, wallet: &WalletClient, chunk_path: &Path) -> Result<()> {
+        let chunk = Chunk::new(Bytes::from(fs::read(chunk_path)?));
+        let chunk_addr = chunk.network_address();
+        let (payment, payee) = wallet.get_payment_for_addr(&chunk_addr)?;
+
+        self.client
+            .store_chunk(chunk, payee, payment, true, None)
+            .await?;
+
+        wallet.remove_payment_for_addr(&chunk_addr)?;
+        Ok(())
+    }
+
+    fn upload_manifest_path(&self, head_address: &XorName) -> PathBuf {
+        self.wallet_dir
+            .join(UPLOAD_MANIFESTS_DIR_NAME)
+            .join(hex::encode(head_address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_manifest_is_loaded_back() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(UPLOAD_MANIFESTS_DIR_NAME).join("manifest");
+
+        // there is no manifest before the first store
+        assert!(UploadManifest::load(&path)?.stored.is_empty());
+
+        let manifest = UploadManifest {
+            stored: [
+                XorName::from_content(b"first"),
+                XorName::from_content(b"second"),
+            ]
+            .into(),
+        };
+        manifest.store(&path)?;
+        assert_eq!(UploadManifest::load(&path)?.stored, manifest.stored);
+        // the temp file has been renamed into place
+        assert!(!path.with_extension("tmp").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_manifest_is_loaded_as_empty() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("manifest");
+        fs::write(&path, b"not a manifest")?;
+
+        assert!(UploadManifest::load(&path)?.stored.is_empty());
+        Ok(())
+    }
+}
@@ -447,10 +447,9 @@ impl FilesUpload {
         let client = self.api.client.clone();
         let _handle = tokio::spawn(async move {
             let cost = match client
-                .network
-                .get_store_costs_from_network(NetworkAddress::from_chunk_address(
-                    ChunkAddress::new(chunk_info.name),
-                ))
+                .get_store_costs(NetworkAddress::from_chunk_address(ChunkAddress::new(
+                    chunk_info.name,
+                )))
                 .await
             {
                 Ok(cost) => {
@@ -505,7 +504,9 @@ impl FilesUpload {
                 };
 
                 if make_payments {
-                    let result = match wallet_client.pay_for_records(&cost_map, verify_store).await
+                    let result = match wallet_client
+                        .pay_for_records(&cost_map, verify_store, false)
+                        .await
                     {
                         Ok((storage_cost, royalty_fees)) => {
                             trace!("Made payments for {} chunks", cost_map.len());
@@ -188,7 +188,7 @@ impl FoldersApi {
         wallet_dir: &Path,
         address: RegisterAddress,
     ) -> Result<Self> {
-        let register = ClientRegister::retrieve(client.clone(), address).await?;
+        let register = ClientRegister::retrieve(client.clone(), address, None).await?;
         Self::create(client, wallet_dir, register)
     }
 
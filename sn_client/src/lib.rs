@@ -17,8 +17,10 @@ mod event;
 mod faucet;
 mod files;
 mod folders;
+mod quote_cache;
 mod register;
 mod wallet;
+mod wallet_manager;
 
 // re-export used crates to make them available to app builders
 // this ensures the version of the crates used by the app builders are the same as the ones used by the client
@@ -29,18 +31,24 @@ pub use sn_registers as registers;
 pub use sn_transfers as transfers;
 
 pub use self::{
-    audit::{DagError, SpendDag, SpendDagGet},
+    api::{ClientConfig, FileVerifyReport, MisbehaviorReason, PeerRecordStatus},
+    audit::{DagError, MergeReport, SpendDag, SpendDagGet, SpendDirection},
     error::Error,
     event::{ClientEvent, ClientEventsBroadcaster, ClientEventsReceiver},
     faucet::{get_tokens_from_faucet, load_faucet_wallet_from_genesis_wallet},
     files::{
         download::{FilesDownload, FilesDownloadEvent},
+        resumable::{ProgressCb, UploadProgress},
         upload::{FileUploadEvent, FilesUpload},
-        FilesApi, BATCH_SIZE,
+        DataMapPackingParams, FilesApi, UploadCostEstimate, BATCH_SIZE, MIN_DATA_MAP_CHUNK_SIZE,
     },
     folders::{FolderEntry, FoldersApi, Metadata},
     register::ClientRegister,
-    wallet::{broadcast_signed_spends, send, StoragePaymentResult, WalletClient},
+    wallet::{
+        broadcast_signed_spends, send, CashNoteSummary, SendOpts, StoragePaymentOutcome,
+        StoragePaymentResult, TransferVerification, WalletAuditReport, WalletClient,
+    },
+    wallet_manager::WalletManager,
 };
 pub(crate) use error::Result;
 
@@ -116,4 +124,7 @@ pub struct Client {
     network: Network,
     events_broadcaster: ClientEventsBroadcaster,
     signer: bls::SecretKey,
+    quote_cache: Option<quote_cache::QuoteCache>,
+    gossip_enabled: bool,
+    record_label: Option<String>,
 }
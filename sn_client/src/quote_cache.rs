@@ -0,0 +1,191 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    api::{clock_skew, CLOCK_SKEW_WARNING_THRESHOLD},
+    Client,
+};
+use sn_networking::{target_arch::Instant, PayeeQuote};
+use sn_protocol::NetworkAddress;
+use sn_transfers::QUOTE_EXPIRATION_SECS;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+/// A quote is no longer served from the cache this long before it expires, leaving time to pay
+/// with it and upload the content.
+const QUOTE_EXPIRY_MARGIN: Duration = Duration::from_secs(300);
+
+/// The store cost quotes received from the network, kept for a while to skip requerying them.
+///
+/// The quotes are keyed by the address they were requested for, rather than by the peers
+/// responsible for it: nodes sign a quote for a given address, and reject paying for another
+/// address with it. Hence the cache only helps when the same address is quoted again, e.g. when
+/// an upload cost estimate is followed by the payment, or a failed payment is retried. Quoting
+/// the new chunks of an upload always misses it, however many share the same close group.
+#[derive(Clone, Debug)]
+pub(crate) struct QuoteCache {
+    ttl: Duration,
+    quotes: Arc<Mutex<CachedQuotes>>,
+}
+
+#[derive(Debug)]
+struct CachedQuotes {
+    quotes: HashMap<NetworkAddress, (Instant, PayeeQuote)>,
+    /// When the stale quotes were last dropped.
+    last_swept: Instant,
+}
+
+impl QuoteCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            quotes: Arc::new(Mutex::new(CachedQuotes {
+                quotes: HashMap::new(),
+                last_swept: Instant::now(),
+            })),
+        }
+    }
+
+    /// Returns the quote cached for the address, unless it is older than the ttl or about to
+    /// expire, in which case it is dropped.
+    fn get(&self, address: &NetworkAddress) -> Option<PayeeQuote> {
+        let mut cached = self.quotes.lock().ok()?;
+        let (received, quote) = cached.quotes.get(address)?;
+        if self.is_fresh(*received, quote) {
+            return Some(quote.clone());
+        }
+        let _ = cached.quotes.remove(address);
+        None
+    }
+
+    /// Caches the quote for the address. The stale quotes of the addresses not queried again
+    /// are dropped at most once per ttl, keeping the cache bound to the quotes of a ttl.
+    fn insert(&self, address: NetworkAddress, quote: PayeeQuote) {
+        let Ok(mut cached) = self.quotes.lock() else {
+            return;
+        };
+        if cached.last_swept.elapsed() >= self.ttl {
+            let CachedQuotes { quotes, last_swept } = &mut *cached;
+            quotes.retain(|_, (received, quote)| self.is_fresh(*received, quote));
+            *last_swept = Instant::now();
+        }
+        let _ = cached.quotes.insert(address, (Instant::now(), quote));
+    }
+
+    fn is_fresh(&self, received: Instant, (_, _, quote): &PayeeQuote) -> bool {
+        // the timestamp is taken by the node, treated as fresh if its clock runs ahead of ours
+        let expires_in = SystemTime::now()
+            .duration_since(quote.timestamp)
+            .ok()
+            .and_then(|age| Duration::from_secs(QUOTE_EXPIRATION_SECS).checked_sub(age))
+            .unwrap_or(Duration::from_secs(QUOTE_EXPIRATION_SECS));
+        received.elapsed() < self.ttl && expires_in > QUOTE_EXPIRY_MARGIN
+    }
+}
+
+impl Client {
+    /// Gets the store cost quote for the address, from the quote cache if it's enabled and holds
+    /// a fresh one, else from the network.
+    pub(crate) async fn get_store_costs(
+        &self,
+        address: NetworkAddress,
+    ) -> Result<PayeeQuote, sn_networking::Error> {
+        let Some(cache) = &self.quote_cache else {
+            return self.get_store_costs_from_network(address).await;
+        };
+        if let Some(quote) = cache.get(&address) {
+            trace!("Using the cached store cost quote for {address:?}");
+            return Ok(quote);
+        }
+
+        let quote = self.get_store_costs_from_network(address.clone()).await?;
+        cache.insert(address, quote.clone());
+        Ok(quote)
+    }
+
+    /// Gets the store cost quote for the address from the network, warning if the clock of the
+    /// quoting node is far off ours, as the payment may then fail its expiry check.
+    async fn get_store_costs_from_network(
+        &self,
+        address: NetworkAddress,
+    ) -> Result<PayeeQuote, sn_networking::Error> {
+        let sent_at = SystemTime::now();
+        let quote = self.network.get_store_costs_from_network(address).await?;
+        let received_at = SystemTime::now();
+
+        let (peer_id, _, payment_quote) = &quote;
+        // the zero quotes of the nodes already holding a record are not timestamped by them
+        if !payment_quote.signature.is_empty() {
+            let skew = clock_skew(payment_quote.timestamp, sent_at, received_at);
+            if skew > CLOCK_SKEW_WARNING_THRESHOLD {
+                warn!("The clock of {peer_id:?} is about {skew:?} off ours, the payment with its quote may fail");
+            }
+        }
+        Ok(quote)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+    use sn_transfers::{MainSecretKey, NanoTokens, PaymentQuote};
+    use xor_name::XorName;
+
+    fn payee_quote(timestamp: SystemTime) -> PayeeQuote {
+        let mut quote = PaymentQuote::test_dummy(XorName::default(), NanoTokens::from(10));
+        quote.timestamp = timestamp;
+        (
+            PeerId::random(),
+            MainSecretKey::random().main_pubkey(),
+            quote,
+        )
+    }
+
+    #[test]
+    fn cached_quotes_are_served_until_stale() {
+        let address = NetworkAddress::from_peer(PeerId::random());
+
+        let cache = QuoteCache::new(Duration::from_secs(60));
+        cache.insert(address.clone(), payee_quote(SystemTime::now()));
+        assert!(cache.get(&address).is_some());
+
+        // older than the ttl
+        let cache = QuoteCache::new(Duration::ZERO);
+        cache.insert(address.clone(), payee_quote(SystemTime::now()));
+        assert!(cache.get(&address).is_none());
+
+        // within the ttl, but expiring within the margin
+        let cache = QuoteCache::new(Duration::from_secs(60));
+        let near_expiry = SystemTime::now() - Duration::from_secs(QUOTE_EXPIRATION_SECS - 60);
+        cache.insert(address.clone(), payee_quote(near_expiry));
+        assert!(cache.get(&address).is_none());
+    }
+
+    #[test]
+    fn stale_quotes_are_dropped() {
+        let stale_address = NetworkAddress::from_peer(PeerId::random());
+        let address = NetworkAddress::from_peer(PeerId::random());
+
+        // the stale quote is dropped once requested
+        let cache = QuoteCache::new(Duration::ZERO);
+        cache.insert(stale_address.clone(), payee_quote(SystemTime::now()));
+        assert!(cache.get(&stale_address).is_none());
+        assert!(cache.quotes.lock().expect("lock").quotes.is_empty());
+
+        // or along with the others, by the sweep of a later insert
+        cache.insert(stale_address, payee_quote(SystemTime::now()));
+        cache.insert(address.clone(), payee_quote(SystemTime::now()));
+        let cached = cache.quotes.lock().expect("lock");
+        assert_eq!(cached.quotes.len(), 1);
+        assert!(cached.quotes.contains_key(&address));
+    }
+}
@@ -158,8 +158,22 @@ impl ClientRegister {
     }
 
     /// Retrieve a Register from the network to work on it offline.
-    pub(super) async fn retrieve(client: Client, address: RegisterAddress) -> Result<Self> {
-        let register = Self::get_register_from_network(&client, address).await?;
+    /// With a `get_quorum`, only the copies returned by at least that many holders are trusted.
+    pub(super) async fn retrieve(
+        client: Client,
+        address: RegisterAddress,
+        get_quorum: Option<Quorum>,
+    ) -> Result<Self> {
+        let register = match get_quorum {
+            Some(get_quorum) => {
+                let reg = client
+                    .get_signed_register_with_quorum(address, get_quorum)
+                    .await?;
+                reg.verify_with_address(address)?;
+                reg.register()?
+            }
+            None => Self::get_register_from_network(&client, address).await?,
+        };
 
         Ok(Self {
             client,
@@ -842,6 +856,7 @@ impl ClientRegister {
             retry_strategy: Some(RetryStrategy::Balanced),
             use_put_record_to: payee,
             verification: Some((VerificationKind::Network, verification_cfg)),
+            label: None,
         };
 
         // Register edits might exist, so we cannot be sure that just because we get a record back that this should fail
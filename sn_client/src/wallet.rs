@@ -9,15 +9,16 @@
 use crate::Error;
 
 use super::{error::Result, Client};
-use backoff::{backoff::Backoff, ExponentialBackoff};
-use futures::{future::join_all, TryFutureExt};
+use backoff::{backoff::Backoff, ExponentialBackoff, ExponentialBackoffBuilder};
+use futures::{future::join_all, stream, StreamExt, TryFutureExt};
 use libp2p::PeerId;
 use sn_networking::target_arch::Instant;
 use sn_networking::{GetRecordError, PayeeQuote};
 use sn_protocol::NetworkAddress;
 use sn_transfers::{
-    CashNote, DerivationIndex, HotWallet, MainPubkey, NanoTokens, Payment, PaymentQuote,
-    SignedSpend, SpendAddress, Transaction, Transfer, UniquePubkey, WalletError, WalletResult,
+    ActivitySummary, CashNote, CashNoteRedemption, DerivationIndex, Error as TransferError, Hash,
+    HotWallet, MainPubkey, NanoTokens, Payment, PaymentQuote, SignedSpend, SpendAddress,
+    Transaction, Transfer, UniquePubkey, WalletError, WalletResult, NETWORK_ROYALTIES_PK,
 };
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -27,12 +28,101 @@ use tokio::{
     task::JoinSet,
     time::{sleep, Duration},
 };
+use tokio_util::sync::CancellationToken;
 use xor_name::XorName;
 
+/// The max number of CashNotes checked at once when looking for orphans.
+const MAX_CONCURRENT_ORPHAN_CHECKS: usize = 8;
+
+/// The max number of addresses paid for at once by [`WalletClient::pay_for_storage_with_timeout`].
+const TIMED_STORAGE_PAYMENT_BATCH_SIZE: usize = 64;
+
+/// The max number of distinct sums of CashNotes [`WalletClient::can_pay_exact`] looks through,
+/// bounding its cost for wallets holding many CashNotes.
+const MAX_EXACT_PAYMENT_SUMS: usize = 100_000;
+
 /// A wallet client can be used to send and receive tokens to and from other wallets.
 pub struct WalletClient {
     client: Client,
     wallet: HotWallet,
+    send_opts: SendOpts,
+}
+
+/// How hard to try storing each spend of a send to the network.
+///
+/// A spend failing to be stored, e.g. due to a transient network issue, is retried after a
+/// delay growing exponentially from `initial_retry_delay` up to `max_retry_delay`, with some
+/// jitter added, until `spend_store_attempts` have been made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendOpts {
+    /// The max number of attempts to store each spend. A value of 1 disables the retries.
+    pub spend_store_attempts: u32,
+    /// The delay before the first retry.
+    pub initial_retry_delay: Duration,
+    /// The upper bound of the delay between two attempts.
+    pub max_retry_delay: Duration,
+}
+
+impl Default for SendOpts {
+    fn default() -> Self {
+        Self {
+            spend_store_attempts: 3,
+            initial_retry_delay: Duration::from_secs(1),
+            max_retry_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl SendOpts {
+    fn retry_backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoffBuilder::new()
+            .with_initial_interval(self.initial_retry_delay)
+            .with_max_interval(self.max_retry_delay)
+            .with_max_elapsed_time(None)
+            .build()
+    }
+}
+
+/// A CashNote held by a wallet, as listed by [`WalletClient::list_cash_notes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CashNoteSummary {
+    /// The unique public key of the CashNote.
+    pub unique_pubkey: UniquePubkey,
+    /// The value of the CashNote.
+    pub value: NanoTokens,
+    /// Whether the CashNote is spent by a spend the network has not confirmed yet, in which case
+    /// it can't be spent again until that spend is resolved.
+    pub pending_spend: bool,
+}
+
+/// The outcome of verifying all the CashNotes held by a wallet against the network.
+#[derive(Debug, Default)]
+pub struct WalletAuditReport {
+    /// CashNotes that were confirmed as valid by the network.
+    pub passed: BTreeSet<UniquePubkey>,
+    /// CashNotes that failed verification, along with the reason.
+    pub failed: BTreeMap<UniquePubkey, String>,
+}
+
+impl WalletAuditReport {
+    /// Total number of CashNotes audited.
+    pub fn total(&self) -> usize {
+        self.passed.len() + self.failed.len()
+    }
+
+    /// Returns true if every audited CashNote passed verification.
+    pub fn is_healthy(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// The outcome of verifying a Transfer against the network, without redeeming it.
+#[derive(Debug, Clone)]
+pub struct TransferVerification {
+    /// The parent spends of the Transfer, as found on the network.
+    pub parent_spends: BTreeSet<SignedSpend>,
+    /// The total value the Transfer is worth to its recipient.
+    pub total_value: NanoTokens,
 }
 
 /// The result of the payment made for a set of Content Addresses
@@ -42,6 +132,45 @@ pub struct StoragePaymentResult {
     pub skipped_chunks: Vec<XorName>,
 }
 
+/// The outcome of a storage payment that can be cancelled while the store costs are being fetched.
+pub enum StoragePaymentOutcome {
+    /// The store costs were all retrieved and the payment was made.
+    Paid(StoragePaymentResult),
+    /// The payment was cancelled before all the store costs were retrieved. Nothing was paid.
+    Cancelled {
+        /// The addresses whose store cost had been retrieved before the cancellation.
+        priced: Vec<NetworkAddress>,
+        /// The addresses whose store cost was still outstanding, and has been dropped.
+        pending: Vec<NetworkAddress>,
+    },
+    /// The deadline of the payment was reached before all the addresses were paid for.
+    TimedOut {
+        /// The payment made for the batches completed before the deadline.
+        paid: StoragePaymentResult,
+        /// The addresses which have not been paid for.
+        unpaid: Vec<NetworkAddress>,
+    },
+}
+
+impl StoragePaymentOutcome {
+    /// The payment made, i.e. for all the addresses when `Paid`, for none of them when
+    /// `Cancelled`, and for the batches completed before the deadline when `TimedOut`.
+    pub fn into_payment(self) -> StoragePaymentResult {
+        match self {
+            Self::Paid(payment_result)
+            | Self::TimedOut {
+                paid: payment_result,
+                ..
+            } => payment_result,
+            Self::Cancelled { .. } => StoragePaymentResult {
+                storage_cost: NanoTokens::zero(),
+                royalty_fees: NanoTokens::zero(),
+                skipped_chunks: vec![],
+            },
+        }
+    }
+}
+
 impl WalletClient {
     /// Create a new wallet client.
     ///
@@ -65,7 +194,37 @@ impl WalletClient {
     /// # }
     /// ```
     pub fn new(client: Client, wallet: HotWallet) -> Self {
-        Self { client, wallet }
+        Self {
+            client,
+            wallet,
+            send_opts: SendOpts::default(),
+        }
+    }
+
+    /// Sets how hard to try storing each spend when sending tokens from this wallet.
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error, SendOpts};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let mut wallet_client = WalletClient::new(client, wallet);
+    /// wallet_client.set_send_opts(SendOpts {
+    ///     spend_store_attempts: 5,
+    ///     initial_retry_delay: Duration::from_millis(500),
+    ///     max_retry_delay: Duration::from_secs(30),
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_send_opts(&mut self, send_opts: SendOpts) {
+        self.send_opts = send_opts;
     }
 
     /// Stores the wallet to the local wallet directory.
@@ -88,6 +247,29 @@ impl WalletClient {
         self.wallet.deposit_and_store_to_disk(&vec![])
     }
 
+    /// Derives and records a fresh address to receive a payment to, e.g. one per invoice.
+    /// The transfers sent to it are received and audited like the ones sent to the main address.
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let mut wallet_client = WalletClient::new(client, wallet);
+    /// let address = wallet_client.new_receive_address()?;
+    /// println!("Please pay to {}", address.to_hex());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_receive_address(&mut self) -> WalletResult<MainPubkey> {
+        self.wallet.new_receive_address()
+    }
+
     /// Display the wallet balance
     /// # Example
     /// ```no_run
@@ -109,6 +291,174 @@ impl WalletClient {
         self.wallet.balance()
     }
 
+    /// Lists the CashNotes held by the wallet along with their value, sorted by increasing value,
+    /// e.g. to show the user the "coins" the balance is made of, or to understand which inputs
+    /// a send picked.
+    ///
+    /// The CashNotes spent by a spend not confirmed by the network yet are flagged as such, as
+    /// they can't be spent for now. This only reads the wallet dir.
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let wallet_client = WalletClient::new(client, wallet);
+    /// for cash_note in wallet_client.list_cash_notes() {
+    ///     let pending = if cash_note.pending_spend { " (pending spend)" } else { "" };
+    ///     println!("{:?}: {}{pending}", cash_note.unique_pubkey, cash_note.value);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_cash_notes(&self) -> Vec<CashNoteSummary> {
+        let pending_spends: BTreeSet<_> = self
+            .wallet
+            .unconfirmed_spend_requests()
+            .iter()
+            .map(|signed_spend| *signed_spend.unique_pubkey())
+            .collect();
+
+        let mut summaries: Vec<_> = self
+            .wallet
+            .held_cash_notes()
+            .into_iter()
+            .filter_map(|cash_note| {
+                let unique_pubkey = cash_note.unique_pubkey();
+                match cash_note.value() {
+                    Ok(value) => Some(CashNoteSummary {
+                        unique_pubkey,
+                        value,
+                        pending_spend: pending_spends.contains(&unique_pubkey),
+                    }),
+                    Err(err) => {
+                        warn!("Skipping CashNote {unique_pubkey:?} whose value is unknown: {err}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        summaries.sort_by_key(|summary| (summary.value, summary.unique_pubkey));
+        summaries
+    }
+
+    /// Checks whether some of the CashNotes held sum up to exactly the amount, i.e. whether the
+    /// amount could be paid without creating a change CashNote, e.g. to show it in a payment UI.
+    ///
+    /// The CashNotes pending a spend confirmation are left out, as they can't be spent for now.
+    /// A zero amount can't be paid. If the wallet holds so many CashNotes that more than
+    /// `MAX_EXACT_PAYMENT_SUMS` sums would have to be looked through, `false` is returned.
+    ///
+    /// Note that this only tells whether such CashNotes are held: a send picks its inputs in
+    /// the order they are held, hence may still create change.
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// use sn_transfers::NanoTokens;
+    /// let wallet_client = WalletClient::new(client, wallet);
+    /// if !wallet_client.can_pay_exact(NanoTokens::from(10)) {
+    ///     println!("Paying 10 nanos would create change");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn can_pay_exact(&self, amount: NanoTokens) -> bool {
+        let values = self
+            .list_cash_notes()
+            .into_iter()
+            .filter(|cash_note| !cash_note.pending_spend)
+            .map(|cash_note| cash_note.value.as_nano());
+        let can_pay = sums_up_exactly(values, amount.as_nano());
+        if can_pay.is_none() {
+            warn!("Too many CashNotes held to check whether {amount} can be paid exactly");
+        }
+        can_pay.unwrap_or(false)
+    }
+
+    /// Summarises how many transactions the wallet took part in, and when it was last active,
+    /// e.g. to spot dormant wallets. This only reads the history stored in the wallet dir.
+    ///
+    /// If that history can't be read, an empty summary is returned.
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let wallet_client = WalletClient::new(client, wallet);
+    /// let summary = wallet_client.activity_summary();
+    /// println!("{} sends, {} receives, last active at {:?}", summary.total_sends, summary.total_receives, summary.last_activity);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn activity_summary(&self) -> ActivitySummary {
+        self.wallet.activity_summary().unwrap_or_else(|err| {
+            warn!("Failed to read the wallet history to summarise its activity: {err:?}");
+            ActivitySummary::default()
+        })
+    }
+
+    /// Returns the cost, on top of the amounts sent, of a transfer to the given recipients,
+    /// so the total to be paid can be shown before sending.
+    ///
+    /// Transfers don't carry any network fee today, so this is zero. Errors if the wallet
+    /// balance doesn't cover the amounts to send, as the transfer couldn't be made.
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey, NanoTokens};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let wallet_client = WalletClient::new(client, wallet);
+    /// let amount = NanoTokens::from(100);
+    /// let to = MainSecretKey::random().main_pubkey();
+    /// let fee = wallet_client.transfer_cost(&[(amount, to)])?;
+    /// println!("You'll pay {} to send {amount}", amount.checked_add(fee).unwrap_or(amount));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transfer_cost(
+        &self,
+        recipients: &[(NanoTokens, MainPubkey)],
+    ) -> WalletResult<NanoTokens> {
+        let total = recipients
+            .iter()
+            .try_fold(NanoTokens::zero(), |total, (amount, _)| {
+                total.checked_add(*amount)
+            })
+            .ok_or(WalletError::TotalPriceTooHigh)?;
+
+        let balance = self.balance();
+        if total > balance {
+            return Err(TransferError::NotEnoughBalance(balance, total).into());
+        }
+
+        Ok(NanoTokens::zero())
+    }
+
     /// See if any unconfirmed transactions exist.
     /// # Example
     /// ```no_run
@@ -261,14 +611,112 @@ impl WalletClient {
         to: MainPubkey,
         verify_store: bool,
     ) -> WalletResult<CashNote> {
-        let created_cash_notes = self.wallet.local_send(vec![(amount, to)], None)?;
+        self.send_cash_note_with_memo(amount, to, None, verify_store)
+            .await
+    }
+
+    /// Send tokens to another wallet, attaching an opaque memo (eg. an invoice reference) to the transfer.
+    /// The memo is carried as the reason of the spends, which the recipient can read back
+    /// from the received `CashNote`s via `cash_note.reason().memo()`.
+    /// Can also verify the store has been successful.
+    ///
+    /// # Arguments
+    /// * `amount` - [`NanoTokens`].
+    /// * `to` - [`MainPubkey`].
+    /// * `memo` - An optional memo of at most [`MAX_MEMO_LEN`](sn_transfers::MAX_MEMO_LEN) bytes.
+    /// * `verify_store` - A boolean to verify store. Set this to true for mandatory verification.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// use sn_transfers::NanoTokens;
+    /// let mut wallet_client = WalletClient::new(client, wallet);
+    /// let nano = NanoTokens::from(10);
+    /// let main_pub_key = MainSecretKey::random().main_pubkey();
+    /// let payment = wallet_client.send_cash_note_with_memo(nano, main_pub_key, Some(b"invoice-42"), true);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_cash_note_with_memo(
+        &mut self,
+        amount: NanoTokens,
+        to: MainPubkey,
+        memo: Option<&[u8]>,
+        verify_store: bool,
+    ) -> WalletResult<CashNote> {
+        let created_cash_notes = self
+            .send_cash_notes(vec![(amount, to)], memo, verify_store)
+            .await?;
+
+        // return the first CashNote (assuming there is only one because we only sent to one recipient)
+        match &created_cash_notes[..] {
+            [cashnote] => Ok(cashnote.clone()),
+            [_multiple, ..] => Err(WalletError::CouldNotSendMoney(
+                "Multiple CashNotes were returned from the transaction when only one was expected. This is a BUG."
+                    .into(),
+            )),
+            [] => Err(WalletError::CouldNotSendMoney(
+                "No CashNotes were returned from the wallet.".into(),
+            )),
+        }
+    }
+
+    /// Send tokens to several outputs within a single transaction, optionally attaching a memo.
+    /// Returns the created `CashNote`s, one per output.
+    /// Can also verify the store has been successful.
+    /// Errors with `WalletError::SendInProgress` if another send is in progress on the same wallet.
+    /// Errors with `WalletError::ZeroAmount` if any of the amounts is zero, before anything is spent.
+    ///
+    /// # Arguments
+    /// * `to` - [`Vec`]<([`NanoTokens`], [`MainPubkey`])>.
+    /// * `memo` - An optional memo of at most [`MAX_MEMO_LEN`](sn_transfers::MAX_MEMO_LEN) bytes.
+    /// * `verify_store` - A boolean to verify store. Set this to true for mandatory verification.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// use sn_transfers::NanoTokens;
+    /// let mut wallet_client = WalletClient::new(client, wallet);
+    /// let main_pub_key = MainSecretKey::random().main_pubkey();
+    /// let to = vec![(NanoTokens::from(10), main_pub_key), (NanoTokens::from(5), main_pub_key)];
+    /// let cash_notes = wallet_client.send_cash_notes(to, None, true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_cash_notes(
+        &mut self,
+        to: Vec<(NanoTokens, MainPubkey)>,
+        memo: Option<&[u8]>,
+        verify_store: bool,
+    ) -> WalletResult<Vec<CashNote>> {
+        // held until the spends are confirmed, so that no other send can clear them meanwhile
+        let _send_guard = self.wallet.try_lock_sends()?;
+        let reason_hash = memo.map(Hash::from_memo).transpose()?;
+        let created_cash_notes = self.wallet.local_send(to, reason_hash)?;
 
         // send to network
         if let Err(error) = self
             .client
-            .send_spends(
+            .send_spends_with_opts(
                 self.wallet.unconfirmed_spend_requests().iter(),
                 verify_store,
+                &self.send_opts,
             )
             .await
         {
@@ -280,17 +728,7 @@ impl WalletClient {
             self.wallet.clear_confirmed_spend_requests();
         }
 
-        // return the first CashNote (assuming there is only one because we only sent to one recipient)
-        match &created_cash_notes[..] {
-            [cashnote] => Ok(cashnote.clone()),
-            [_multiple, ..] => Err(WalletError::CouldNotSendMoney(
-                "Multiple CashNotes were returned from the transaction when only one was expected. This is a BUG."
-                    .into(),
-            )),
-            [] => Err(WalletError::CouldNotSendMoney(
-                "No CashNotes were returned from the wallet.".into(),
-            )),
-        }
+        Ok(created_cash_notes)
     }
 
     /// Send signed spends to another wallet.
@@ -305,6 +743,7 @@ impl WalletClient {
         output_details: BTreeMap<UniquePubkey, (MainPubkey, DerivationIndex)>,
         verify_store: bool,
     ) -> WalletResult<CashNote> {
+        let _send_guard = self.wallet.try_lock_sends()?;
         let created_cash_notes =
             self.wallet
                 .prepare_signed_transfer(signed_spends, tx, change_id, output_details)?;
@@ -312,9 +751,10 @@ impl WalletClient {
         // send to network
         if let Err(error) = self
             .client
-            .send_spends(
+            .send_spends_with_opts(
                 self.wallet.unconfirmed_spend_requests().iter(),
                 verify_store,
+                &self.send_opts,
             )
             .await
         {
@@ -376,8 +816,7 @@ impl WalletClient {
         address: NetworkAddress,
     ) -> WalletResult<PayeeQuote> {
         self.client
-            .network
-            .get_store_costs_from_network(address)
+            .get_store_costs(address)
             .await
             .map_err(|error| WalletError::CouldNotSendMoney(error.to_string()))
     }
@@ -418,8 +857,189 @@ impl WalletClient {
         &mut self,
         content_addrs: impl Iterator<Item = NetworkAddress>,
     ) -> WalletResult<StoragePaymentResult> {
+        // without a cancel token, hence nor a deadline, the payment is made for all the addresses
+        let outcome = self
+            .pay_for_storage_with_retries(content_addrs.collect(), None)
+            .await?;
+        Ok(outcome.into_payment())
+    }
+
+    /// Same as [`WalletClient::pay_for_storage`], but can be aborted through the `cancel_token`
+    /// while the store costs are still being retrieved, e.g. when a user aborts an upload.
+    /// The outstanding store cost tasks are then dropped and
+    /// [`StoragePaymentOutcome::Cancelled`] reports which addresses had already been priced.
+    ///
+    /// Once all the store costs are known the payment itself is no longer cancellable.
+    ///
+    /// # Example
+    ///```no_run
+    /// # use sn_client::{Client, WalletClient, Error, StoragePaymentOutcome};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # use xor_name::XorName;
+    /// use sn_protocol::NetworkAddress;
+    /// use sn_registers::RegisterAddress;
+    /// use tokio_util::sync::CancellationToken;
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let mut wallet_client = WalletClient::new(client.clone(), wallet);
+    /// let address = RegisterAddress::new(XorName::random(&mut rand::thread_rng()), client.signer_pk());
+    /// let cancel_token = CancellationToken::new();
+    /// // hand a clone of the token to whoever can abort the upload
+    /// let _abort_handle = cancel_token.clone();
+    ///
+    /// match wallet_client
+    ///     .pay_for_storage_cancellable(std::iter::once(NetworkAddress::from_register_address(address)), cancel_token)
+    ///     .await?
+    /// {
+    ///     StoragePaymentOutcome::Paid(result) => println!("Paid {}", result.storage_cost),
+    ///     StoragePaymentOutcome::Cancelled { priced, pending } => {
+    ///         println!("Cancelled with {} priced and {} pending", priced.len(), pending.len())
+    ///     }
+    ///     StoragePaymentOutcome::TimedOut { paid, unpaid } => {
+    ///         println!("Timed out after paying {}, {} left unpaid", paid.storage_cost, unpaid.len())
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    pub async fn pay_for_storage_cancellable(
+        &mut self,
+        content_addrs: impl Iterator<Item = NetworkAddress>,
+        cancel_token: CancellationToken,
+    ) -> WalletResult<StoragePaymentOutcome> {
+        self.pay_for_storage_with_retries(content_addrs.collect(), Some(&cancel_token))
+            .await
+    }
+
+    /// Same as [`WalletClient::pay_for_storage`], but the whole payment has to complete within
+    /// `timeout`, whichever the time taken by the store cost retrievals and the retries.
+    ///
+    /// The addresses are paid for in batches. Once the deadline is reached, the store costs still
+    /// outstanding are dropped, and [`StoragePaymentOutcome::TimedOut`] reports what the completed
+    /// batches paid and which addresses remain unpaid.
+    /// A batch whose store costs are all known is paid for regardless of the deadline, hence the
+    /// call can outlast the `timeout` by the time taken by that last payment.
+    ///
+    /// # Example
+    ///```no_run
+    /// # use sn_client::{Client, WalletClient, Error, StoragePaymentOutcome};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # use xor_name::XorName;
+    /// use sn_protocol::NetworkAddress;
+    /// use sn_registers::RegisterAddress;
+    /// use std::time::Duration;
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let mut wallet_client = WalletClient::new(client.clone(), wallet);
+    /// let address = RegisterAddress::new(XorName::random(&mut rand::thread_rng()), client.signer_pk());
+    ///
+    /// match wallet_client
+    ///     .pay_for_storage_with_timeout(
+    ///         std::iter::once(NetworkAddress::from_register_address(address)),
+    ///         Duration::from_secs(60),
+    ///     )
+    ///     .await?
+    /// {
+    ///     StoragePaymentOutcome::TimedOut { paid, unpaid } => {
+    ///         println!("Timed out after paying {}, {} left unpaid", paid.storage_cost, unpaid.len())
+    ///     }
+    ///     _ => println!("Paid for all the addresses"),
+    /// }
+    /// # Ok(())
+    /// # }
+    pub async fn pay_for_storage_with_timeout(
+        &mut self,
+        content_addrs: impl Iterator<Item = NetworkAddress>,
+        timeout: Duration,
+    ) -> WalletResult<StoragePaymentOutcome> {
+        let content_addrs: Vec<_> = content_addrs.collect();
+        let deadline = CancellationToken::new();
+        let timer = tokio::spawn({
+            let deadline = deadline.clone();
+            async move {
+                sleep(timeout).await;
+                deadline.cancel();
+            }
+        });
+
+        let mut paid = StoragePaymentResult {
+            storage_cost: NanoTokens::zero(),
+            royalty_fees: NanoTokens::zero(),
+            skipped_chunks: vec![],
+        };
+        let mut unpaid = vec![];
+        let mut batches = content_addrs.chunks(TIMED_STORAGE_PAYMENT_BATCH_SIZE);
+        while let Some(batch) = batches.next() {
+            let outcome = if deadline.is_cancelled() {
+                StoragePaymentOutcome::Cancelled {
+                    priced: vec![],
+                    pending: batch.to_vec(),
+                }
+            } else {
+                match self
+                    .pay_for_storage_with_retries(batch.to_vec(), Some(&deadline))
+                    .await
+                {
+                    Ok(outcome) => outcome,
+                    Err(err) => {
+                        timer.abort();
+                        return Err(err);
+                    }
+                }
+            };
+
+            match outcome {
+                StoragePaymentOutcome::Paid(res) => {
+                    paid.storage_cost = paid
+                        .storage_cost
+                        .checked_add(res.storage_cost)
+                        .ok_or(WalletError::from(TransferError::ExcessiveNanoValue))?;
+                    paid.royalty_fees = paid
+                        .royalty_fees
+                        .checked_add(res.royalty_fees)
+                        .ok_or(WalletError::from(TransferError::ExcessiveNanoValue))?;
+                    paid.skipped_chunks.extend(res.skipped_chunks);
+                }
+                StoragePaymentOutcome::Cancelled { priced, pending } => {
+                    unpaid.extend(priced);
+                    unpaid.extend(pending);
+                    unpaid.extend(batches.by_ref().flatten().cloned());
+                    break;
+                }
+                StoragePaymentOutcome::TimedOut {
+                    unpaid: batch_unpaid,
+                    ..
+                } => unpaid.extend(batch_unpaid),
+            }
+        }
+        timer.abort();
+
+        if unpaid.is_empty() {
+            Ok(StoragePaymentOutcome::Paid(paid))
+        } else {
+            warn!(
+                "Storage payment timed out after {timeout:?} with {} addresses unpaid",
+                unpaid.len()
+            );
+            Ok(StoragePaymentOutcome::TimedOut { paid, unpaid })
+        }
+    }
+
+    async fn pay_for_storage_with_retries(
+        &mut self,
+        c: Vec<NetworkAddress>,
+        cancel_token: Option<&CancellationToken>,
+    ) -> WalletResult<StoragePaymentOutcome> {
         let verify_store = true;
-        let c: Vec<_> = content_addrs.collect();
         // Using default ExponentialBackoff doesn't make sense,
         // as it will just fail after the first payment failure.
         let mut backoff = ExponentialBackoff::default();
@@ -428,14 +1048,23 @@ impl WalletClient {
         while let Some(delay) = backoff.next_backoff() {
             trace!("Paying for storage (w/backoff retries) for: {:?}", c);
             match self
-                .pay_for_storage_once(c.clone().into_iter(), verify_store)
+                .pay_for_storage_once(c.clone().into_iter(), verify_store, cancel_token)
                 .await
             {
-                Ok(payment_result) => return Ok(payment_result),
+                Ok(outcome) => return Ok(outcome),
                 Err(WalletError::CouldNotSendMoney(err)) => {
                     warn!("Attempt to pay for data failed: {err:?}");
                     last_err = err;
-                    sleep(delay).await;
+                    tokio::select! {
+                        _ = wait_for_cancellation(cancel_token) => {
+                            info!("Storage payment cancelled while waiting to retry");
+                            return Ok(StoragePaymentOutcome::Cancelled {
+                                priced: vec![],
+                                pending: c,
+                            });
+                        }
+                        _ = sleep(delay) => {}
+                    }
                 }
                 Err(err) => return Err(err),
             }
@@ -443,23 +1072,140 @@ impl WalletClient {
         Err(WalletError::CouldNotSendMoney(last_err))
     }
 
+    /// Verifies every CashNote held by the wallet against the network, with up to
+    /// `max_concurrent` verifications in flight at once.
+    /// A CashNote fails the audit if any of its parent spends can't be fetched
+    /// or differ from the ones in the network (e.g. due to a double spend).
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let wallet_client = WalletClient::new(client, wallet);
+    /// let report = wallet_client.audit(8).await?;
+    /// println!("{} of {} cash notes failed the audit", report.failed.len(), report.total());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn audit(&self, max_concurrent: usize) -> WalletResult<WalletAuditReport> {
+        let cash_notes = self.wallet.held_cash_notes();
+        info!(
+            "Auditing {} cash notes with up to {max_concurrent} concurrent verifications",
+            cash_notes.len()
+        );
+
+        let mut verifications = stream::iter(cash_notes.iter())
+            .map(|cash_note| async move {
+                (
+                    cash_note.unique_pubkey(),
+                    self.client.verify_cashnote(cash_note).await,
+                )
+            })
+            .buffer_unordered(max_concurrent.max(1));
+
+        let mut report = WalletAuditReport::default();
+        while let Some((unique_pubkey, result)) = verifications.next().await {
+            match result {
+                Ok(()) => {
+                    let _ = report.passed.insert(unique_pubkey);
+                }
+                Err(err) => {
+                    warn!("CashNote {unique_pubkey:?} failed the audit: {err}");
+                    let _ = report.failed.insert(unique_pubkey, err.to_string());
+                }
+            }
+        }
+
+        info!(
+            "Wallet audit completed: {} passed, {} failed",
+            report.passed.len(),
+            report.failed.len()
+        );
+        Ok(report)
+    }
+
+    /// Returns the CashNotes held by the wallet which are orphaned, i.e. whose parent spends
+    /// are missing from the network, double spent, or differ from the ones in the network.
+    /// Such CashNotes can't be spent.
+    ///
+    /// The CashNotes are checked in bulk, with a limited number of checks in flight at once.
+    /// A CashNote that couldn't be checked, e.g. due to a network error, is not reported.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let wallet_client = WalletClient::new(client, wallet);
+    /// for cash_note in wallet_client.find_orphaned().await? {
+    ///     println!("CashNote {:?} is orphaned", cash_note.unique_pubkey());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_orphaned(&self) -> WalletResult<Vec<CashNote>> {
+        let cash_notes = self.wallet.held_cash_notes();
+        info!("Looking for orphans among {} cash notes", cash_notes.len());
+
+        let mut checks = stream::iter(cash_notes)
+            .map(|cash_note| async move {
+                let is_orphaned = self.client.is_cashnote_orphaned(&cash_note).await;
+                (cash_note, is_orphaned)
+            })
+            .buffer_unordered(MAX_CONCURRENT_ORPHAN_CHECKS);
+
+        let mut orphans = vec![];
+        while let Some((cash_note, is_orphaned)) = checks.next().await {
+            match is_orphaned {
+                Ok(true) => {
+                    warn!("CashNote {:?} is orphaned", cash_note.unique_pubkey());
+                    orphans.push(cash_note);
+                }
+                Ok(false) => {}
+                Err(err) => warn!(
+                    "Could not check whether CashNote {:?} is orphaned: {err}",
+                    cash_note.unique_pubkey()
+                ),
+            }
+        }
+
+        info!("Found {} orphaned cash notes", orphans.len());
+        Ok(orphans)
+    }
+
     /// Existing chunks will have the store cost set to Zero.
     /// The payment procedure shall be skipped, and the chunk upload as well.
     /// Hence the list of existing chunks will be returned.
+    /// If the `cancel_token` fires while the store costs are being retrieved, nothing is paid.
     // TODO: Used only once in current file: Set to Private. No Docs issued.
     async fn pay_for_storage_once(
         &mut self,
         content_addrs: impl Iterator<Item = NetworkAddress>,
         verify_store: bool,
-    ) -> WalletResult<StoragePaymentResult> {
+        cancel_token: Option<&CancellationToken>,
+    ) -> WalletResult<StoragePaymentOutcome> {
         // get store cost from network in parallel
         let mut tasks = JoinSet::new();
+        let mut pending = vec![];
         for content_addr in content_addrs {
+            pending.push(content_addr.clone());
             let client = self.client.clone();
             tasks.spawn(async move {
                 let cost = client
-                    .network
-                    .get_store_costs_from_network(content_addr.clone())
+                    .get_store_costs(content_addr.clone())
                     .await
                     .map_err(|error| WalletError::CouldNotSendMoney(error.to_string()));
 
@@ -472,8 +1218,27 @@ impl WalletClient {
         // collect store costs
         let mut cost_map = BTreeMap::default();
         let mut skipped_chunks = vec![];
+        let mut priced = vec![];
         #[allow(clippy::mutable_key_type)]
-        while let Some(res) = tasks.join_next().await {
+        loop {
+            let res = tokio::select! {
+                _ = wait_for_cancellation(cancel_token) => {
+                    info!(
+                        "Storage payment cancelled with {} store costs retrieved and {} pending",
+                        priced.len(),
+                        pending.len()
+                    );
+                    tasks.abort_all();
+                    return Ok(StoragePaymentOutcome::Cancelled { priced, pending });
+                }
+                res = tasks.join_next() => match res {
+                    Some(res) => res,
+                    None => break,
+                },
+            };
+            if let Ok((content_addr, _)) = &res {
+                pending.retain(|addr| addr != content_addr);
+            }
             match res {
                 Ok((content_addr, Ok(cost))) => {
                     if let Some(xorname) = content_addr.as_xorname() {
@@ -487,6 +1252,7 @@ impl WalletClient {
                     } else {
                         warn!("Cannot get store cost for a content that is not a data type: {content_addr:?}");
                     }
+                    priced.push(content_addr);
                 }
                 Ok((content_addr, Err(err))) => {
                     warn!("Cannot get store cost for {content_addr:?} with error {err:?}");
@@ -502,13 +1268,14 @@ impl WalletClient {
         info!("Storecosts retrieved for all the provided content addrs");
 
         // pay for records
-        let (storage_cost, royalty_fees) = self.pay_for_records(&cost_map, verify_store).await?;
+        let (storage_cost, royalty_fees) =
+            self.pay_for_records(&cost_map, verify_store, false).await?;
         let res = StoragePaymentResult {
             storage_cost,
             royalty_fees,
             skipped_chunks,
         };
-        Ok(res)
+        Ok(StoragePaymentOutcome::Paid(res))
     }
 
     /// Send tokens to nodes closest to the data that we want to make storage payments for.
@@ -521,6 +1288,10 @@ impl WalletClient {
     /// # Params:
     /// * cost_map - [BTreeMap]([XorName],([MainPubkey], [PaymentQuote]))
     /// * verify_store - This optional check can verify if the store has been successful.
+    /// * dry_run - If true, the costs are summed up and checked, and the transfer is built,
+    ///   but nothing is spent nor sent to the network. Returns the amount that would be paid.
+    ///
+    /// Errors with `WalletError::SendInProgress` if another send is in progress on the same wallet.
     ///
     /// Verification will be attempted via GET request through a Spend on the network.
     ///
@@ -540,14 +1311,80 @@ impl WalletClient {
     /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
     /// let mut wallet_client = WalletClient::new(client, wallet);
     /// let mut cost_map:BTreeMap<XorName,(MainPubkey,PaymentQuote,Vec<u8>)> = BTreeMap::new();
-    /// wallet_client.pay_for_records(&cost_map,true).await?;
+    /// // check the amount to be paid first
+    /// let (storage_cost, royalties) = wallet_client.pay_for_records(&cost_map, true, true).await?;
+    /// wallet_client.pay_for_records(&cost_map, true, false).await?;
     /// # Ok(())
     /// # }
     pub async fn pay_for_records(
         &mut self,
         cost_map: &BTreeMap<XorName, (MainPubkey, PaymentQuote, Vec<u8>)>,
         verify_store: bool,
+        dry_run: bool,
+    ) -> WalletResult<(NanoTokens, NanoTokens)> {
+        if dry_run {
+            return self.wallet.local_send_storage_payment_dry_run(cost_map);
+        }
+        self.pay_for_records_from(cost_map, None, verify_store)
+            .await
+    }
+
+    /// Send tokens to nodes closest to the data that we want to make storage payments for,
+    /// funding the payment only with the provided input `CashNote`s.
+    ///
+    /// Errors before anything is sent to the network if any of the inputs is not available in the
+    /// wallet, or if the inputs don't cover the total cost (including the network royalties fee).
+    /// # Returns:
+    ///
+    /// * [WalletResult]<([NanoTokens], [NanoTokens])>
+    ///
+    /// This return contains the amount paid for storage. Including the network royalties fee paid.
+    ///
+    /// # Params:
+    /// * cost_map - [BTreeMap]([XorName],([MainPubkey], [PaymentQuote]))
+    /// * inputs - The [CashNote]s that shall fund the payment.
+    /// * verify_store - This optional check can verify if the store has been successful.
+    ///
+    /// # Example
+    ///```no_run
+    /// # use sn_client::{Client, WalletClient, Error};
+    /// # use tempfile::TempDir;
+    /// # use bls::SecretKey;
+    /// # use sn_transfers::{HotWallet, MainSecretKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// # use std::collections::BTreeMap;
+    /// use xor_name::XorName;
+    /// use sn_transfers::{CashNote, MainPubkey, PaymentQuote};
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let tmp_path = TempDir::new()?.path().to_owned();
+    /// # let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
+    /// let mut wallet_client = WalletClient::new(client, wallet);
+    /// let mut cost_map:BTreeMap<XorName,(MainPubkey,PaymentQuote,Vec<u8>)> = BTreeMap::new();
+    /// let inputs: Vec<CashNote> = vec![];
+    /// wallet_client.pay_for_records_with_inputs(&cost_map, inputs, true).await?;
+    /// # Ok(())
+    /// # }
+    pub async fn pay_for_records_with_inputs(
+        &mut self,
+        cost_map: &BTreeMap<XorName, (MainPubkey, PaymentQuote, Vec<u8>)>,
+        inputs: Vec<CashNote>,
+        verify_store: bool,
+    ) -> WalletResult<(NanoTokens, NanoTokens)> {
+        self.pay_for_records_from(cost_map, Some(inputs), verify_store)
+            .await
+    }
+
+    async fn pay_for_records_from(
+        &mut self,
+        cost_map: &BTreeMap<XorName, (MainPubkey, PaymentQuote, Vec<u8>)>,
+        inputs: Option<Vec<CashNote>>,
+        verify_store: bool,
     ) -> WalletResult<(NanoTokens, NanoTokens)> {
+        // held until the spends are confirmed or stored as unconfirmed, so that no other send
+        // can clear them meanwhile
+        let _send_guard = self.wallet.try_lock_sends()?;
+
         // Before wallet progress, there shall be no `unconfirmed_spend_requests`
         // Here, just re-upload again. The caller shall carry out a re-try later on.
         if self.wallet.unconfirmed_spend_requests_exist() {
@@ -561,7 +1398,12 @@ impl WalletClient {
         }
 
         let start = Instant::now();
-        let total_cost = self.wallet.local_send_storage_payment(cost_map)?;
+        let total_cost = match inputs {
+            Some(inputs) => self
+                .wallet
+                .local_send_storage_payment_with_inputs(cost_map, &inputs)?,
+            None => self.wallet.local_send_storage_payment(cost_map)?,
+        };
 
         trace!(
             "local_send_storage_payment of {} chunks completed in {:?}",
@@ -574,9 +1416,10 @@ impl WalletClient {
         let start = Instant::now();
         let spend_attempt_result = self
             .client
-            .send_spends(
+            .send_spends_with_opts(
                 self.wallet.unconfirmed_spend_requests().iter(),
                 verify_store,
+                &self.send_opts,
             )
             .await;
 
@@ -625,9 +1468,10 @@ impl WalletClient {
     async fn resend_pending_transactions(&mut self, verify_store: bool) {
         if self
             .client
-            .send_spends(
+            .send_spends_with_opts(
                 self.wallet.unconfirmed_spend_requests().iter(),
                 verify_store,
+                &self.send_opts,
             )
             .await
             .is_ok()
@@ -719,6 +1563,18 @@ impl Client {
         &self,
         spend_requests: impl Iterator<Item = &SignedSpend>,
         verify_store: bool,
+    ) -> WalletResult<()> {
+        self.send_spends_with_opts(spend_requests, verify_store, &SendOpts::default())
+            .await
+    }
+
+    /// Same as [`Client::send_spends`], retrying to store each spend as per the `send_opts`.
+    /// A spend is only considered sent once it's stored, and verified if `verify_store` is set.
+    pub async fn send_spends_with_opts(
+        &self,
+        spend_requests: impl Iterator<Item = &SignedSpend>,
+        verify_store: bool,
+        send_opts: &SendOpts,
     ) -> WalletResult<()> {
         let mut tasks = Vec::new();
 
@@ -732,7 +1588,11 @@ impl Client {
             let the_task = async move {
                 let cash_note_key = spend_request.unique_pubkey();
                 let result = self
-                    .network_store_spend(spend_request.clone(), verify_store)
+                    .network_store_spend_with_retries(
+                        spend_request.clone(),
+                        verify_store,
+                        send_opts,
+                    )
                     .await;
 
                 (cash_note_key, result)
@@ -786,14 +1646,49 @@ impl Client {
         Ok(())
     }
 
+    /// Stores the spend to the network, retrying with a jittered backoff on failures.
+    /// Double spends are not retried, as they won't go away.
+    async fn network_store_spend_with_retries(
+        &self,
+        spend: SignedSpend,
+        verify_store: bool,
+        send_opts: &SendOpts,
+    ) -> Result<()> {
+        let mut backoff = send_opts.retry_backoff();
+        let mut attempts = 1;
+        loop {
+            let err = match self.network_store_spend(spend.clone(), verify_store).await {
+                Ok(()) => return Ok(()),
+                Err(
+                    err @ Error::Network(sn_networking::Error::GetRecordError(
+                        GetRecordError::RecordDoesNotMatch(_) | GetRecordError::SplitRecord { .. },
+                    )),
+                ) => return Err(err),
+                Err(err) => err,
+            };
+            let delay = match backoff.next_backoff() {
+                Some(delay) if attempts < send_opts.spend_store_attempts => delay,
+                _ => return Err(err),
+            };
+            warn!(
+                "Failed to store spend {:?} at attempt {attempts}/{}, retrying in {delay:?}: {err}",
+                spend.unique_pubkey(),
+                send_opts.spend_store_attempts
+            );
+            sleep(delay).await;
+            attempts += 1;
+        }
+    }
+
     /// Receive a Transfer, verify and redeem CashNotes from the Network.
+    /// Returns them along with the memo attached by the sender, if any.
     ///
     /// # Arguments
     /// * transfer: &[Transfer] - Borrowed value for [Transfer]
     /// * wallet: &[HotWallet] - Borrowed value for [HotWallet]
     ///
     /// # Return Value
-    /// * [WalletResult]<[Vec]<[CashNote]>>
+    /// * [WalletResult]<([Vec]<[CashNote]>, [Option]<[Vec]<[u8]>>)>
     ///
     /// # Example
     /// ```no_run
@@ -810,8 +1705,8 @@ impl Client {
     /// let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
     /// let transfer = Transfer::from_hex("13abc").unwrap();
     /// // An example for using client.receive() for cashNotes
-    /// let cash_notes = match client.receive(&transfer, &wallet).await {
-    ///                 Ok(cash_notes) => cash_notes,
+    /// let (cash_notes, memo) = match client.receive(&transfer, &wallet).await {
+    ///                 Ok(received) => received,
     ///                 Err(err) => {
     ///                     println!("Failed to verify and redeem transfer: {err:?}");
     ///                     error!("Failed to verify and redeem transfer: {err:?}");
@@ -826,14 +1721,15 @@ impl Client {
         &self,
         transfer: &Transfer,
         wallet: &HotWallet,
-    ) -> WalletResult<Vec<CashNote>> {
+    ) -> WalletResult<(Vec<CashNote>, Option<Vec<u8>>)> {
         let cashnotes = self
             .network
             .verify_and_unpack_transfer(transfer, wallet)
             .map_err(|e| WalletError::CouldNotReceiveMoney(format!("{e:?}")))
             .await?;
+        let memo = transfer_memo(&cashnotes);
         let valuable_cashnotes = self.filter_out_already_spend_cash_notes(cashnotes).await?;
-        Ok(valuable_cashnotes)
+        Ok((valuable_cashnotes, memo))
     }
 
     /// Check that the redeemed CashNotes are not already spent
@@ -898,7 +1794,7 @@ impl Client {
     /// # let tmp_path = TempDir::new()?.path().to_owned();
     /// let mut wallet = HotWallet::load_from_path(&tmp_path,Some(MainSecretKey::new(SecretKey::random())))?;
     /// let transfer = Transfer::from_hex("").unwrap();
-    /// let cash_notes = client.receive(&transfer, &wallet).await?;
+    /// let (cash_notes, _memo) = client.receive(&transfer, &wallet).await?;
     /// // Verification:
     /// for cash_note in cash_notes {
     ///     println!("{:?}" , client.verify_cashnote(&cash_note).await.unwrap());
@@ -937,10 +1833,132 @@ impl Client {
             "The spends in network were not the same as the ones in the CashNote. The parents of this CashNote are probably double spends.".into(),
         ))
     }
+
+    /// Verify that a Transfer is well-formed and that its parent spends exist and are valid on
+    /// the network, reporting its total value. No wallet is needed, and nothing is redeemed.
+    ///
+    /// Only network royalties Transfers can be read without a key. The CashNoteRedemptions of an
+    /// encrypted Transfer have to be decrypted by its recipient first, e.g. with
+    /// `Transfer::cashnote_redemptions`, and verified with `verify_cash_note_redemptions`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error};
+    /// use bls::SecretKey;
+    /// use sn_transfers::{Transfer, WalletError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// // a network royalties Transfer, shared as hex
+    /// let transfer_hex = std::fs::read_to_string("royalties_transfer.hex")?;
+    /// let transfer = Transfer::from_hex(transfer_hex.trim()).map_err(WalletError::from)?;
+    /// let verification = client.verify_transfer(&transfer).await?;
+    /// println!("The transfer is worth {}", verification.total_value);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_transfer(&self, transfer: &Transfer) -> WalletResult<TransferVerification> {
+        match transfer {
+            Transfer::NetworkRoyalties(cashnote_redemptions) => {
+                self.verify_cash_note_redemptions(cashnote_redemptions, *NETWORK_ROYALTIES_PK)
+                    .await
+            }
+            Transfer::Encrypted(_) => Err(WalletError::CouldNotVerifyTransfer(
+                "An encrypted Transfer can only be read by its recipient, decrypt its CashNoteRedemptions and verify them instead".to_string(),
+            )),
+        }
+    }
+
+    /// Verify that the parent spends of the CashNoteRedemptions sent to the recipient exist and
+    /// are valid on the network, reporting the total value they redeem.
+    /// Only the public key of the recipient is needed, and nothing is redeemed.
+    pub async fn verify_cash_note_redemptions(
+        &self,
+        cashnote_redemptions: &[CashNoteRedemption],
+        recipient: MainPubkey,
+    ) -> WalletResult<TransferVerification> {
+        if cashnote_redemptions.is_empty() {
+            return Err(WalletError::CouldNotVerifyTransfer(
+                "The Transfer does not contain any CashNoteRedemption".to_string(),
+            ));
+        }
+
+        let parent_addrs: BTreeSet<SpendAddress> = cashnote_redemptions
+            .iter()
+            .map(|redemption| redemption.parent_spend)
+            .collect();
+        let tasks = parent_addrs
+            .iter()
+            .map(|addr| self.get_spend_from_network(*addr));
+        let mut parent_spends = BTreeSet::new();
+        for result in join_all(tasks).await {
+            let spend =
+                result.map_err(|err| WalletError::CouldNotVerifyTransfer(err.to_string()))?;
+            let _ = parent_spends.insert(spend);
+        }
+
+        let mut total_value = NanoTokens::zero();
+        for redemption in cashnote_redemptions {
+            let unique_pubkey = recipient.new_unique_pubkey(&redemption.derivation_index);
+            let output = parent_spends
+                .iter()
+                .filter(|spend| {
+                    SpendAddress::from_unique_pubkey(spend.unique_pubkey())
+                        == redemption.parent_spend
+                })
+                .flat_map(|spend| spend.spent_tx().outputs)
+                .find(|output| output.unique_pubkey() == &unique_pubkey)
+                .ok_or_else(|| {
+                    WalletError::CouldNotVerifyTransfer(format!(
+                        "The parent spend at {:?} has no output to the recipient",
+                        redemption.parent_spend
+                    ))
+                })?;
+            total_value = total_value.checked_add(output.amount).ok_or_else(|| {
+                WalletError::CouldNotVerifyTransfer("The total value overflows".to_string())
+            })?;
+        }
+
+        Ok(TransferVerification {
+            parent_spends,
+            total_value,
+        })
+    }
+
+    /// Returns true if any parent spend of the CashNote is missing from the network, double spent,
+    /// or differs from the one in the CashNote.
+    /// Other errors are returned, as they don't tell whether the CashNote is orphaned.
+    async fn is_cashnote_orphaned(&self, cash_note: &CashNote) -> Result<bool> {
+        let tasks = cash_note.signed_spends.iter().map(|spend| async move {
+            let address = SpendAddress::from_unique_pubkey(spend.unique_pubkey());
+            (spend, self.get_spend_from_network(address).await)
+        });
+
+        for (spend, result) in join_all(tasks).await {
+            match result {
+                Ok(network_spend) if &network_spend == spend => {}
+                Ok(_) => {
+                    debug!("Parent spend {spend:?} differs from the one in the network");
+                    return Ok(true);
+                }
+                Err(Error::MissingSpendRecord(address)) => {
+                    debug!("Parent spend at {address:?} is missing from the network");
+                    return Ok(true);
+                }
+                Err(Error::DoubleSpend(address, _, _)) => {
+                    debug!("Parent spend at {address:?} is double spent");
+                    return Ok(true);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(false)
+    }
 }
 
 /// Use the client to send a CashNote from a local wallet to an address.
-/// This marks the spent CashNote as spent in the Network
+/// This marks the spent CashNote as spent in the Network.
+/// Errors with `WalletError::ZeroAmount` if the amount is zero.
 ///
 /// # Arguments
 /// * from - [HotWallet]
@@ -982,7 +2000,7 @@ pub async fn send(
     verify_store: bool,
 ) -> Result<CashNote> {
     if amount.is_zero() {
-        return Err(Error::AmountIsZero);
+        return Err(WalletError::ZeroAmount.into());
     }
 
     let mut wallet_client = WalletClient::new(client.clone(), from);
@@ -1053,6 +2071,36 @@ pub async fn send(
     Ok(new_cash_note)
 }
 
+/// Whether some of the values sum up to exactly the target, which must be non zero. Only the sums
+/// up to the target are kept, and `None` is returned once more than `MAX_EXACT_PAYMENT_SUMS` of
+/// them would have to be looked through.
+fn sums_up_exactly(values: impl IntoIterator<Item = u64>, target: u64) -> Option<bool> {
+    if target == 0 {
+        return Some(false);
+    }
+
+    // the sums, up to the target, of the subsets of the values looked at so far
+    let mut sums = BTreeSet::from([0u64]);
+    for value in values {
+        if value > target {
+            continue;
+        }
+        let new_sums: Vec<u64> = sums
+            .iter()
+            .filter_map(|sum| sum.checked_add(value))
+            .filter(|sum| *sum <= target)
+            .collect();
+        if new_sums.contains(&target) {
+            return Some(true);
+        }
+        sums.extend(new_sums);
+        if sums.len() > MAX_EXACT_PAYMENT_SUMS {
+            return None;
+        }
+    }
+    Some(false)
+}
+
 /// Send tokens to another wallet. Can optionally verify the store has been successful.
 ///
 /// Verification will be attempted via GET request through a Spend on the network.
@@ -1175,3 +2223,98 @@ pub async fn broadcast_signed_spends(
 
     Ok(new_cash_note)
 }
+
+/// Resolves once the token is cancelled, or never if there is no token.
+async fn wait_for_cancellation(cancel_token: Option<&CancellationToken>) {
+    match cancel_token {
+        Some(cancel_token) => cancel_token.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// The memo the sender attached to the CashNotes of a transfer, i.e. the reason of their parent
+/// spends, or `None` if they were sent without one.
+fn transfer_memo(cash_notes: &[CashNote]) -> Option<Vec<u8>> {
+    let reason = cash_notes.first()?.reason();
+    (reason != Hash::default()).then(|| reason.memo())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_transfers::GENESIS_CASHNOTE;
+
+    fn with_reason(cash_note: &CashNote, reason: Hash) -> CashNote {
+        let mut cash_note = cash_note.clone();
+        cash_note.signed_spends = cash_note
+            .signed_spends
+            .into_iter()
+            .map(|mut spend| {
+                spend.spend.reason = reason;
+                spend
+            })
+            .collect();
+        cash_note
+    }
+
+    #[test]
+    fn the_payment_of_an_outcome_is_what_was_paid() {
+        let payment = |nanos| StoragePaymentResult {
+            storage_cost: NanoTokens::from(nanos),
+            royalty_fees: NanoTokens::from(nanos),
+            skipped_chunks: vec![],
+        };
+        let addr = NetworkAddress::from_chunk_address(sn_protocol::storage::ChunkAddress::new(
+            XorName([0; 32]),
+        ));
+
+        let paid = StoragePaymentOutcome::Paid(payment(10)).into_payment();
+        assert_eq!(paid.storage_cost, NanoTokens::from(10));
+        let timed_out = StoragePaymentOutcome::TimedOut {
+            paid: payment(5),
+            unpaid: vec![addr.clone()],
+        }
+        .into_payment();
+        assert_eq!(timed_out.storage_cost, NanoTokens::from(5));
+        let cancelled = StoragePaymentOutcome::Cancelled {
+            priced: vec![addr],
+            pending: vec![],
+        }
+        .into_payment();
+        assert_eq!(cancelled.storage_cost, NanoTokens::zero());
+        assert_eq!(cancelled.royalty_fees, NanoTokens::zero());
+    }
+
+    #[test]
+    fn the_memo_is_read_from_the_reason_of_the_cash_notes() -> eyre::Result<()> {
+        let reason = Hash::from_memo(b"invoice-42")?;
+        let cash_note = with_reason(&GENESIS_CASHNOTE, reason);
+        assert_eq!(transfer_memo(&[cash_note]), Some(b"invoice-42".to_vec()));
+
+        // sent without a memo
+        let cash_note = with_reason(&GENESIS_CASHNOTE, Hash::default());
+        assert_eq!(transfer_memo(&[cash_note]), None);
+        assert_eq!(transfer_memo(&[]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn exact_sums_are_found_among_the_values() {
+        assert_eq!(sums_up_exactly([5, 3, 9], 8), Some(true));
+        assert_eq!(sums_up_exactly([5, 3, 9], 17), Some(true));
+        assert_eq!(sums_up_exactly([5, 3, 9], 10), Some(false));
+        assert_eq!(sums_up_exactly([5, 3, 9], 0), Some(false));
+        assert_eq!(sums_up_exactly([u64::MAX, 1], u64::MAX), Some(true));
+    }
+
+    #[test]
+    fn exact_sums_are_bounded() {
+        // distinct powers of two give distinct subset sums, which can't all be kept
+        let values = (0..20).map(|exp| 1u64 << exp);
+        assert_eq!(sums_up_exactly(values, u64::MAX), None);
+
+        // only the sums up to the target are kept
+        let values = (0..1_000).map(|_| 1);
+        assert_eq!(sums_up_exactly(values, 2_000), Some(false));
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{Client, Error, Result, WalletClient};
+use sn_transfers::{CashNote, HotWallet, MainPubkey, MainSecretKey, NanoTokens};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Manages several named wallets kept side by side under one directory,
+/// e.g. a hot and a cold wallet, or one wallet per customer.
+///
+/// Each wallet lives in its own `<root_dir>/<name>` directory, laid out as a regular [`HotWallet`].
+pub struct WalletManager {
+    client: Client,
+    root_dir: PathBuf,
+}
+
+impl WalletManager {
+    /// Create a manager for the wallets under `root_dir`, creating the directory if needed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error, WalletManager};
+    /// use bls::SecretKey;
+    /// # use tempfile::TempDir;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let client = Client::new(SecretKey::random(), None, false, None, None).await?;
+    /// # let wallets_dir = TempDir::new()?.path().to_owned();
+    /// let manager = WalletManager::new(client, &wallets_dir)?;
+    /// let _hot = manager.create("hot")?;
+    /// let _cold = manager.create("cold")?;
+    /// for (name, balance) in manager.balance_all()? {
+    ///     println!("{name}: {balance}");
+    /// }
+    /// println!("Total: {}", manager.total_balance()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(client: Client, root_dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(root_dir)?;
+        Ok(Self {
+            client,
+            root_dir: root_dir.to_path_buf(),
+        })
+    }
+
+    /// Create a new wallet with a random key under the given name.
+    /// Errors if a wallet with that name already exists.
+    pub fn create(&self, name: &str) -> Result<WalletClient> {
+        let wallet_root = self.wallet_root(name)?;
+        if wallet_root.exists() {
+            return Err(Error::WalletAlreadyExists(name.to_string()));
+        }
+        let wallet = HotWallet::create_from_key(&wallet_root, MainSecretKey::random())?;
+        info!(
+            "Created wallet {name:?} with address {:?}",
+            wallet.address()
+        );
+        Ok(WalletClient::new(self.client.clone(), wallet))
+    }
+
+    /// Open an existing wallet by name.
+    pub fn open(&self, name: &str) -> Result<WalletClient> {
+        let wallet = self.load(name)?;
+        Ok(WalletClient::new(self.client.clone(), wallet))
+    }
+
+    /// The names of all the managed wallets, in alphabetical order.
+    pub fn list(&self) -> Result<Vec<String>> {
+        wallet_names(&self.root_dir)
+    }
+
+    /// The balance of every managed wallet, by name.
+    pub fn balance_all(&self) -> Result<BTreeMap<String, NanoTokens>> {
+        let mut balances = BTreeMap::new();
+        for name in self.list()? {
+            let balance = self.load(&name)?.balance();
+            let _ = balances.insert(name, balance);
+        }
+        Ok(balances)
+    }
+
+    /// The sum of the balances of all the managed wallets.
+    pub fn total_balance(&self) -> Result<NanoTokens> {
+        total_of(self.balance_all()?.into_values())
+    }
+
+    /// Send tokens from the named wallet to another wallet.
+    /// See [`WalletClient::send_cash_note`].
+    pub async fn send(
+        &self,
+        name: &str,
+        amount: NanoTokens,
+        to: MainPubkey,
+        verify_store: bool,
+    ) -> Result<CashNote> {
+        let mut wallet_client = self.open(name)?;
+        let cash_note = wallet_client
+            .send_cash_note(amount, to, verify_store)
+            .await?;
+        Ok(cash_note)
+    }
+
+    fn load(&self, name: &str) -> Result<HotWallet> {
+        let wallet_root = self.wallet_root(name)?;
+        if !wallet_root.is_dir() {
+            return Err(Error::WalletNotFound(name.to_string()));
+        }
+        Ok(HotWallet::try_load_from(&wallet_root)?)
+    }
+
+    fn wallet_root(&self, name: &str) -> Result<PathBuf> {
+        validate_wallet_name(name)?;
+        Ok(self.root_dir.join(name))
+    }
+}
+
+/// The names of the wallet directories under `root_dir`, in alphabetical order.
+fn wallet_names(root_dir: &Path) -> Result<Vec<String>> {
+    let mut names = vec![];
+    for entry in std::fs::read_dir(root_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(name) = entry.file_name().to_str() {
+            if validate_wallet_name(name).is_ok() {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Sums up the balances, erroring if the total can't be represented.
+fn total_of(balances: impl IntoIterator<Item = NanoTokens>) -> Result<NanoTokens> {
+    balances
+        .into_iter()
+        .try_fold(NanoTokens::zero(), |total, balance| {
+            total.checked_add(balance)
+        })
+        .ok_or(Error::TotalBalanceOverflow)
+}
+
+/// Wallet names are used as directory names, so they can't be empty or contain path components.
+fn validate_wallet_name(name: &str) -> Result<()> {
+    let is_valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if is_valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidWalletName(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wallet_names_are_validated() {
+        for name in ["hot", "cold-1", "customer_42", "v1.2"] {
+            assert!(
+                validate_wallet_name(name).is_ok(),
+                "{name:?} should be valid"
+            );
+        }
+        for name in ["", ".", "..", ".hidden", "a/b", "../escape", "with space"] {
+            assert!(
+                matches!(validate_wallet_name(name), Err(Error::InvalidWalletName(_))),
+                "{name:?} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn only_the_wallet_dirs_are_listed() -> Result<()> {
+        let root_dir = tempfile::tempdir()?;
+        for dir in ["hot", "cold", ".hidden"] {
+            std::fs::create_dir(root_dir.path().join(dir))?;
+        }
+        std::fs::write(root_dir.path().join("notes"), b"not a wallet")?;
+
+        assert_eq!(wallet_names(root_dir.path())?, vec!["cold", "hot"]);
+        Ok(())
+    }
+
+    #[test]
+    fn total_balance_overflow_is_reported() -> Result<()> {
+        let total = total_of([NanoTokens::from(1), NanoTokens::from(2)])?;
+        assert_eq!(total, NanoTokens::from(3));
+        assert_eq!(total_of([])?, NanoTokens::zero());
+
+        assert!(matches!(
+            total_of([NanoTokens::from(u64::MAX), NanoTokens::from(1)]),
+            Err(Error::TotalBalanceOverflow)
+        ));
+        Ok(())
+    }
+}
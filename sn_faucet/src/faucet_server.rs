@@ -36,17 +36,17 @@ use url::Url;
 ///
 /// # balance should be updated
 /// ```
-pub async fn run_faucet_server(client: &Client) -> Result<()> {
+pub async fn run_faucet_server(client: &Client, snapshot_tolerance: NanoTokens) -> Result<()> {
     claim_genesis(client).await.map_err(|err| {
         println!("Faucet Server couldn't start as we failed to claim Genesis");
         eprintln!("Faucet Server couldn't start as we failed to claim Genesis");
         error!("Faucet Server couldn't start as we failed to claim Genesis");
         err
     })?;
-    startup_server(client).await
+    startup_server(client, snapshot_tolerance).await
 }
 
-pub async fn restart_faucet_server(client: &Client) -> Result<()> {
+pub async fn restart_faucet_server(client: &Client, snapshot_tolerance: NanoTokens) -> Result<()> {
     let root_dir = get_faucet_data_dir();
     println!("Loading the previous wallet at {root_dir:?}");
     debug!("Loading the previous wallet at {root_dir:?}");
@@ -56,15 +56,16 @@ pub async fn restart_faucet_server(client: &Client) -> Result<()> {
     println!("Previous wallet loaded");
     debug!("Previous wallet loaded");
 
-    startup_server(client).await
+    startup_server(client, snapshot_tolerance).await
 }
 
-async fn startup_server(client: &Client) -> Result<()> {
+#[cfg_attr(not(feature = "distribution"), allow(unused_variables))]
+async fn startup_server(client: &Client, snapshot_tolerance: NanoTokens) -> Result<()> {
     #[allow(unused)]
     let mut balances = HashMap::<String, NanoTokens>::new();
     #[cfg(feature = "distribution")]
     {
-        balances = token_distribution::load_maid_snapshot()?;
+        balances = token_distribution::load_maid_snapshot(snapshot_tolerance)?;
         let keys = token_distribution::load_maid_claims()?;
         // Each distribution takes about 500ms to create, so for thousands of
         // initial distributions this takes many minutes. This is run in the
@@ -102,6 +103,9 @@ async fn startup_server(client: &Client) -> Result<()> {
         // http://<ip>/distribution?address=<addr>&publickey=<pkhex>
         // which returns the distribution for that maid address
         //
+        // http://<ip>/distribution/remaining
+        // which returns how much of the distribution is yet to be claimed
+        //
         // tiny_http request.url() excludes host, ie is only the path.
         // https://docs.rs/tiny_http/latest/tiny_http/struct.Request.html#method.url
         // Returns the resource requested by the client.
@@ -116,7 +120,24 @@ async fn startup_server(client: &Client) -> Result<()> {
                 continue;
             }
         };
-        if url.path() == "/distribution" {
+        if url.path() == "/distribution/remaining" {
+            #[cfg(feature = "distribution")]
+            let response = match token_distribution::handle_remaining_supply_req(&balances) {
+                Ok(remaining) => Response::from_string(remaining),
+                Err(err) => {
+                    eprintln!("Failed to get remaining supply: {err}");
+                    error!("Failed to get remaining supply: {err}");
+                    Response::from_string(format!("Failed to get remaining supply: {err}"))
+                        .with_status_code(500)
+                }
+            };
+            #[cfg(not(feature = "distribution"))]
+            let response = Response::from_string("Distribution feature disabled".to_string())
+                .with_status_code(500);
+            let _ = request
+                .respond(response)
+                .map_err(|err| eprintln!("Failed to send response: {err}"));
+        } else if url.path() == "/distribution" {
             // if distribution feature is enabled, return the distribution for
             // this address
             #[cfg(feature = "distribution")]
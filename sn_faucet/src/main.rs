@@ -71,7 +71,8 @@ async fn main() -> Result<()> {
     handle.await?;
     match result {
         Ok(client) => {
-            if let Err(err) = faucet_cmds(opt.cmd.clone(), &client).await {
+            let snapshot_tolerance = NanoTokens::from(opt.snapshot_tolerance);
+            if let Err(err) = faucet_cmds(opt.cmd.clone(), &client, snapshot_tolerance).await {
                 error!("Failed to run faucet cmd {:?} with err {err:?}", opt.cmd)
             }
         }
@@ -143,6 +144,13 @@ struct Opt {
     #[command(flatten)]
     peers: PeersArgs,
 
+    /// The number of nanos the maid snapshot total may differ from the expected supply by.
+    ///
+    /// A snapshot within this tolerance is accepted with a warning, rather than rejected.
+    /// Defaults to 0, requiring an exact match.
+    #[clap(long, default_value_t = 0)]
+    pub snapshot_tolerance: u64,
+
     /// Available sub commands.
     #[clap(subcommand)]
     pub cmd: SubCmd,
@@ -176,7 +184,7 @@ enum SubCmd {
     RestartServer,
 }
 
-async fn faucet_cmds(cmds: SubCmd, client: &Client) -> Result<()> {
+async fn faucet_cmds(cmds: SubCmd, client: &Client, snapshot_tolerance: NanoTokens) -> Result<()> {
     match cmds {
         SubCmd::ClaimGenesis => {
             claim_genesis(client).await?;
@@ -186,11 +194,11 @@ async fn faucet_cmds(cmds: SubCmd, client: &Client) -> Result<()> {
         }
         SubCmd::Server => {
             // shouldn't return except on error
-            run_faucet_server(client).await?;
+            run_faucet_server(client, snapshot_tolerance).await?;
         }
         SubCmd::RestartServer => {
             // shouldn't return except on error
-            restart_faucet_server(client).await?;
+            restart_faucet_server(client, snapshot_tolerance).await?;
         }
     }
     Ok(())
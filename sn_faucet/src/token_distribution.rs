@@ -11,11 +11,11 @@ use crate::send_tokens;
 use base64::Engine;
 use color_eyre::eyre::{eyre, Result};
 use serde::{Deserialize, Serialize};
-use sn_client::Client;
-use sn_transfers::{MainPubkey, NanoTokens};
+use sn_client::{load_faucet_wallet_from_genesis_wallet, Client, WalletClient};
+use sn_transfers::{CashNote, CashNoteRedemption, MainPubkey, NanoTokens, Transfer};
 use std::str::FromStr;
-use std::{collections::HashMap, path::PathBuf};
-use tracing::info;
+use std::{collections::HashMap, fmt, path::PathBuf};
+use tracing::{error, info, warn};
 use url::Url;
 
 const SNAPSHOT_FILENAME: &str = "snapshot.json";
@@ -23,6 +23,10 @@ const SNAPSHOT_URL: &str = "https://api.omniexplorer.info/ask.aspx?api=getproper
 const CLAIMS_URL: &str =
     "https://github.com/maidsafe/safe_network/raw/main/sn_faucet/maid_address_claims.csv";
 const HTTP_STATUS_OK: i32 = 200;
+// Upper bound on the number of cash notes a single split payout can create
+const MAX_SPLIT_OUTPUTS: u64 = 256;
+// Upper bound on the number of claims a single transaction of a batch dispense pays
+const MAX_BATCH_CLAIMS: usize = 256;
 
 type MaidAddress = String; // base58 encoded
 type Snapshot = HashMap<MaidAddress, NanoTokens>;
@@ -143,7 +147,9 @@ fn get_distributions_data_dir_path() -> Result<PathBuf> {
     Ok(dir.to_path_buf())
 }
 
-pub fn load_maid_snapshot() -> Result<Snapshot> {
+/// Loads the maid snapshot, accepting a total that differs from the expected
+/// supply by at most `tolerance`.
+pub fn load_maid_snapshot(tolerance: NanoTokens) -> Result<Snapshot> {
     // If the faucet restarts there will be an existing snapshot which should
     // be used to avoid conflicts in the balances between two different
     // snapshots.
@@ -152,19 +158,19 @@ pub fn load_maid_snapshot() -> Result<Snapshot> {
     let filename = root_dir.join(SNAPSHOT_FILENAME);
     if std::fs::metadata(filename.clone()).is_ok() {
         info!("Using existing maid snapshot from {:?}", filename);
-        maid_snapshot_from_file(filename)
+        maid_snapshot_from_file(filename, tolerance)
     } else {
         info!("Fetching snapshot from {}", SNAPSHOT_URL);
-        maid_snapshot_from_internet(filename)
+        maid_snapshot_from_internet(filename, tolerance)
     }
 }
 
-fn maid_snapshot_from_file(snapshot_path: PathBuf) -> Result<Snapshot> {
+fn maid_snapshot_from_file(snapshot_path: PathBuf, tolerance: NanoTokens) -> Result<Snapshot> {
     let content = std::fs::read_to_string(snapshot_path)?;
-    parse_snapshot(content)
+    parse_snapshot(content, tolerance)
 }
 
-fn maid_snapshot_from_internet(snapshot_path: PathBuf) -> Result<Snapshot> {
+fn maid_snapshot_from_internet(snapshot_path: PathBuf, tolerance: NanoTokens) -> Result<Snapshot> {
     // make the request
     let response = minreq::get(SNAPSHOT_URL).send()?;
     // check the request is ok
@@ -178,10 +184,10 @@ fn maid_snapshot_from_internet(snapshot_path: PathBuf) -> Result<Snapshot> {
     std::fs::write(snapshot_path.clone(), body)?;
     info!("Saved snapshot to {:?}", snapshot_path);
     // parse the json response
-    parse_snapshot(body.to_string())
+    parse_snapshot(body.to_string(), tolerance)
 }
 
-fn parse_snapshot(json_str: String) -> Result<Snapshot> {
+fn parse_snapshot(json_str: String, tolerance: NanoTokens) -> Result<Snapshot> {
     let balances: Vec<MaidBalance> = serde_json::from_str(&json_str)?;
     let mut balances_map: Snapshot = Snapshot::new();
     // verify the snapshot is ok
@@ -212,10 +218,17 @@ fn parse_snapshot(json_str: String) -> Result<Snapshot> {
         };
         balances_map.insert(b.address.clone(), address_balance);
     }
-    if total != supply {
+    // upstream data can lag or round, so allow the total to be off by the tolerance
+    let delta = total.as_nano().abs_diff(supply.as_nano());
+    if delta > tolerance.as_nano() {
         let msg = format!("Incorrect snapshot total, got {total} want {supply}");
         return Err(eyre!(msg));
     }
+    if delta != 0 {
+        warn!(
+            "Snapshot total {total} differs from the supply {supply} by {delta} nanos, within the tolerance of {tolerance}"
+        );
+    }
     // log the total number of balances that were parsed
     info!("Parsed {} maid balances from the snapshot", balances.len());
     Ok(balances_map)
@@ -364,14 +377,16 @@ pub async fn distribute_from_maid_to_tokens(
     snapshot: Snapshot,
     claims: HashMap<MaidAddress, MaidClaim>,
 ) {
+    let mut pending = vec![];
     for (addr, amount) in snapshot {
         // check if this snapshot address has a pubkey
         if !claims.contains_key(&addr) {
             continue;
         }
         let claim = &claims[&addr];
-        match create_distribution(&client, claim, &amount).await {
-            Ok(_) => {}
+        match pending_distribution(claim) {
+            Ok(Some(wallet)) => pending.push((claim, wallet, amount)),
+            Ok(None) => {}
             Err(err) => {
                 info!(
                     "Error creating distribution: {0} {err}",
@@ -380,6 +395,51 @@ pub async fn distribute_from_maid_to_tokens(
             }
         }
     }
+
+    info!("Distributing to {} claims in a batch", pending.len());
+    let batch = pending
+        .iter()
+        .map(|(_, wallet, amount)| (*wallet, *amount))
+        .collect();
+    let results = match dispense_batch(&client, batch, true).await {
+        Ok(results) => results,
+        Err(err) => {
+            error!("Error dispensing the batch of distributions: {err}");
+            return;
+        }
+    };
+    for ((claim, _, _), result) in pending.into_iter().zip(results) {
+        let saved = match result {
+            Ok(transfer) => transfer
+                .to_hex()
+                .map_err(|err| eyre!("Failed to encode transfer: {err}"))
+                .and_then(|transfer_hex| save_distribution(claim, &transfer_hex)),
+            Err(err) => Err(eyre!("Failed send for {0}: {err}", claim.address)),
+        };
+        if let Err(err) = saved {
+            info!(
+                "Error creating distribution: {0} {err}",
+                claim.to_csv_line()
+            );
+        }
+    }
+}
+
+/// Validates and saves the claim, returning the wallet to distribute to, unless the
+/// distribution has already been created.
+fn pending_distribution(claim: &MaidClaim) -> Result<Option<MainPubkey>> {
+    if claim.is_valid().is_err() {
+        let claim_csv = claim.to_csv_line();
+        let msg = format!("Not creating distribution for invalid claim: {claim_csv}");
+        info!(msg);
+        return Err(eyre!(msg));
+    }
+    claim.save_to_file()?;
+    let dist_path = get_distributions_data_dir_path()?.join(&claim.address);
+    if dist_path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(MainPubkey::from_hex(&claim.wallet)?))
 }
 
 pub async fn handle_distribution_req(
@@ -400,6 +460,10 @@ pub async fn handle_distribution_req(
         .get("signature")
         .ok_or(eyre!("Missing signature in querystring"))?
         .to_string();
+    let denomination = query
+        .get("denomination")
+        .map(|d| NanoTokens::from_str(d))
+        .transpose()?;
     let amount = balances
         .get(&address)
         .ok_or(eyre!("Address not in snapshot"))?;
@@ -408,13 +472,14 @@ pub async fn handle_distribution_req(
     let sig_bytes = base64::engine::general_purpose::URL_SAFE.decode(signature)?;
     let sig = base64::engine::general_purpose::STANDARD.encode(sig_bytes);
     let claim = MaidClaim::new(address, wallet, sig)?;
-    create_distribution(client, &claim, amount).await
+    create_distribution(client, &claim, amount, denomination).await
 }
 
 async fn create_distribution(
     client: &Client,
     claim: &MaidClaim,
     amount: &NanoTokens,
+    denomination: Option<NanoTokens>,
 ) -> Result<String> {
     // validate the claim
     if claim.is_valid().is_err() {
@@ -448,7 +513,14 @@ async fn create_distribution(
         amount, claim.address, claim.wallet
     );
     // create a transfer to the claim wallet
-    let transfer_hex = match send_tokens(client, &amount.to_string(), &claim.wallet).await {
+    let transfer = match denomination {
+        Some(denomination) => {
+            let address_pubkey = MainPubkey::from_hex(&claim.wallet)?;
+            dispense_split(client, address_pubkey, *amount, denomination).await
+        }
+        None => send_tokens(client, &amount.to_string(), &claim.wallet).await,
+    };
+    let transfer_hex = match transfer {
         Ok(t) => t,
         Err(err) => {
             let msg = format!("Failed send for {0}: {err}", claim.address);
@@ -456,7 +528,13 @@ async fn create_distribution(
             return Err(eyre!(msg));
         }
     };
-    let _ = match hex::decode(transfer_hex.clone()) {
+    save_distribution(claim, &transfer_hex)?;
+    Ok(transfer_hex)
+}
+
+/// Saves the transfer distributed for the claim, so that it is not distributed again.
+fn save_distribution(claim: &MaidClaim, transfer_hex: &str) -> Result<()> {
+    let _ = match hex::decode(transfer_hex) {
         Ok(t) => t,
         Err(err) => {
             let msg = format!("Failed to decode transfer for {0}: {err}", claim.address);
@@ -465,7 +543,8 @@ async fn create_distribution(
         }
     };
     // save the transfer
-    match std::fs::write(dist_path.clone(), transfer_hex.clone()) {
+    let dist_path = get_distributions_data_dir_path()?.join(&claim.address);
+    match std::fs::write(dist_path.clone(), transfer_hex) {
         Ok(_) => {}
         Err(err) => {
             let msg = format!(
@@ -479,9 +558,261 @@ async fn create_distribution(
             return Err(eyre!(msg));
         }
     };
+    Ok(())
+}
+
+/// Tracks the amounts already dispensed to each maid address.
+#[derive(Default)]
+pub struct ClaimTracker {
+    dispensed: HashMap<MaidAddress, NanoTokens>,
+}
+
+impl ClaimTracker {
+    /// Loads the distributions created so far. Each of them dispensed the
+    /// snapshot balance of its maid address.
+    pub fn load(snapshot: &Snapshot) -> Result<ClaimTracker> {
+        let mut tracker = ClaimTracker::default();
+        let root = get_distributions_data_dir_path()?;
+        for entry in std::fs::read_dir(root)? {
+            let address = entry?.file_name().to_string_lossy().to_string();
+            match snapshot.get(&address) {
+                Some(amount) => tracker.record(address, *amount),
+                None => {
+                    return Err(eyre!(
+                        "Distribution found for {address} which is not in the snapshot"
+                    ))
+                }
+            }
+        }
+        Ok(tracker)
+    }
+
+    /// Records `amount` as dispensed to `address`, replacing any previous amount.
+    pub fn record(&mut self, address: MaidAddress, amount: NanoTokens) {
+        let _ = self.dispensed.insert(address, amount);
+    }
+
+    /// The total amount dispensed so far.
+    pub fn total_dispensed(&self) -> Result<NanoTokens> {
+        self.dispensed
+            .values()
+            .try_fold(NanoTokens::zero(), |total, amount| {
+                total.checked_add(*amount)
+            })
+            .ok_or_else(|| eyre!("Overflow while summing up the dispensed amounts"))
+    }
+}
+
+/// The amount of the snapshot which is yet to be dispensed.
+/// Errors if more than the snapshot total has been dispensed, which is a bug.
+pub fn remaining_supply(snapshot: &Snapshot, claims: &ClaimTracker) -> Result<NanoTokens> {
+    let total = snapshot_total(snapshot)?;
+    let dispensed = claims.total_dispensed()?;
+    total.checked_sub(dispensed).ok_or_else(|| {
+        let msg = format!("Dispensed {dispensed} exceeds the snapshot total of {total}");
+        error!(msg);
+        eyre!(msg)
+    })
+}
+
+/// Reports the remaining supply along with the claimed percentage of the snapshot.
+pub fn handle_remaining_supply_req(snapshot: &Snapshot) -> Result<String> {
+    let claims = ClaimTracker::load(snapshot)?;
+    let total = snapshot_total(snapshot)?;
+    let remaining = remaining_supply(snapshot, &claims)?;
+    let claimed_percent = if total.is_zero() {
+        0.0
+    } else {
+        (total.as_nano() - remaining.as_nano()) as f64 * 100.0 / total.as_nano() as f64
+    };
+    Ok(format!(
+        "{remaining} of {total} remaining, {claimed_percent:.2}% of the distribution claimed"
+    ))
+}
+
+fn snapshot_total(snapshot: &Snapshot) -> Result<NanoTokens> {
+    snapshot
+        .values()
+        .try_fold(NanoTokens::zero(), |total, amount| {
+            total.checked_add(*amount)
+        })
+        .ok_or_else(|| eyre!("Overflow while summing up the snapshot balances"))
+}
+
+/// Pays `total` to `address_pubkey` as several cash notes of `denomination`,
+/// plus one more for the remainder (if any), so the recipient doesn't end up
+/// with a single huge cash note. Returns the hex of the resulting `Transfer`.
+pub async fn dispense_split(
+    client: &Client,
+    address_pubkey: MainPubkey,
+    total: NanoTokens,
+    denomination: NanoTokens,
+) -> Result<String> {
+    let outputs = split_into_denominations(total, denomination)?
+        .into_iter()
+        .map(|amount| (amount, address_pubkey))
+        .collect::<Vec<_>>();
+    info!(
+        "Dispensing {total} to {address_pubkey:?} as {} cash notes of up to {denomination}",
+        outputs.len()
+    );
+
+    let faucet_wallet = load_faucet_wallet_from_genesis_wallet(client).await?;
+    let mut wallet_client = WalletClient::new(client.clone(), faucet_wallet);
+    let cash_notes = wallet_client.send_cash_notes(outputs, None, true).await?;
+    wallet_client.store_local_wallet()?;
+
+    let cashnote_redemptions = cash_notes
+        .iter()
+        .map(CashNoteRedemption::from_cash_note)
+        .collect::<Result<Vec<_>, _>>()?;
+    let transfer_hex = Transfer::create(cashnote_redemptions, address_pubkey)?.to_hex()?;
+    println!("{transfer_hex}");
+
     Ok(transfer_hex)
 }
 
+/// Why a claim of a batch dispense was not paid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispenseError {
+    /// Nothing is dispensed for a zero amount.
+    ZeroAmount,
+    /// The transaction paying the claim, along with others of the batch, failed.
+    SendFailed(String),
+    /// The claim was paid, but its transfer couldn't be created.
+    TransferFailed(String),
+    /// The claim wasn't paid, as the faucet wallet couldn't be stored after paying others.
+    WalletNotStored(String),
+}
+
+impl fmt::Display for DispenseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DispenseError::ZeroAmount => write!(f, "Nothing to dispense for a zero amount"),
+            DispenseError::SendFailed(err) => write!(f, "Failed to send the tokens: {err}"),
+            DispenseError::TransferFailed(err) => {
+                write!(f, "Failed to create the transfer: {err}")
+            }
+            DispenseError::WalletNotStored(err) => {
+                write!(f, "Failed to store the faucet wallet: {err}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DispenseError {}
+
+/// Pays each of the claims, returning the `Transfer` of each, or why it wasn't paid, in the
+/// order of the claims. The claims are paid by as few transactions as possible, each paying up
+/// to `MAX_BATCH_CLAIMS` of them, so a failed transaction only fails the claims it pays.
+/// If the faucet wallet can't be stored after a transaction, the claims it paid are still
+/// returned, but the later ones are not paid.
+///
+/// Errors if the faucet wallet can't be loaded.
+pub async fn dispense_batch(
+    client: &Client,
+    claims: Vec<(MainPubkey, NanoTokens)>,
+    verify_store: bool,
+) -> Result<Vec<Result<Transfer, DispenseError>>> {
+    let faucet_wallet = load_faucet_wallet_from_genesis_wallet(client).await?;
+    let mut wallet_client = WalletClient::new(client.clone(), faucet_wallet);
+
+    // the results of the claims of a zero amount are left as is, the others are set below
+    let mut results: Vec<Result<Transfer, DispenseError>> = claims
+        .iter()
+        .map(|_| Err(DispenseError::ZeroAmount))
+        .collect();
+    let to_pay: Vec<usize> = (0..claims.len())
+        .filter(|index| !claims[*index].1.is_zero())
+        .collect();
+
+    for (batch, indexes) in to_pay.chunks(MAX_BATCH_CLAIMS).enumerate() {
+        let outputs = indexes
+            .iter()
+            .map(|index| (claims[*index].1, claims[*index].0))
+            .collect();
+        info!("Dispensing to {} claims in one transaction", indexes.len());
+        let mut cash_notes = match wallet_client
+            .send_cash_notes(outputs, None, verify_store)
+            .await
+        {
+            Ok(cash_notes) => cash_notes,
+            Err(err) => {
+                warn!("Failed to dispense to {} claims: {err}", indexes.len());
+                for index in indexes {
+                    results[*index] = Err(DispenseError::SendFailed(err.to_string()));
+                }
+                continue;
+            }
+        };
+        // the claims are paid by now, so their transfers are returned even if this fails
+        let stored = wallet_client.store_local_wallet();
+
+        for index in indexes {
+            let (address_pubkey, amount) = claims[*index];
+            results[*index] = take_cash_note_for(&mut cash_notes, address_pubkey, amount)
+                .ok_or_else(|| {
+                    DispenseError::TransferFailed(format!(
+                        "No cash note of {amount} created for {address_pubkey:?}"
+                    ))
+                })
+                .and_then(|cash_note| {
+                    Transfer::transfer_from_cash_note(&cash_note)
+                        .map_err(|err| DispenseError::TransferFailed(err.to_string()))
+                });
+        }
+
+        if let Err(err) = stored {
+            error!("Failed to store the faucet wallet, not dispensing to further claims: {err}");
+            for index in to_pay.iter().skip((batch + 1) * MAX_BATCH_CLAIMS) {
+                results[*index] = Err(DispenseError::WalletNotStored(err.to_string()));
+            }
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
+// Removes from `cash_notes` one paying `amount` to `address_pubkey`, if any.
+fn take_cash_note_for(
+    cash_notes: &mut Vec<CashNote>,
+    address_pubkey: MainPubkey,
+    amount: NanoTokens,
+) -> Option<CashNote> {
+    let position = cash_notes.iter().position(|cash_note| {
+        *cash_note.main_pubkey() == address_pubkey
+            && cash_note.value().is_ok_and(|value| value == amount)
+    })?;
+    Some(cash_notes.swap_remove(position))
+}
+
+// Splits `total` into as many `denomination` amounts as fit, followed by the remainder.
+fn split_into_denominations(
+    total: NanoTokens,
+    denomination: NanoTokens,
+) -> Result<Vec<NanoTokens>> {
+    if total.is_zero() || denomination.is_zero() {
+        return Err(eyre!(
+            "Cannot split {total} into cash notes of {denomination}"
+        ));
+    }
+    let count = total.as_nano() / denomination.as_nano();
+    let remainder = total.as_nano() % denomination.as_nano();
+    let outputs_count = count + u64::from(remainder != 0);
+    if outputs_count > MAX_SPLIT_OUTPUTS {
+        return Err(eyre!(
+            "Splitting {total} into cash notes of {denomination} would create {outputs_count} outputs, the max is {MAX_SPLIT_OUTPUTS}"
+        ));
+    }
+
+    let mut amounts = vec![denomination; count as usize];
+    if remainder != 0 {
+        amounts.push(NanoTokens::from(remainder));
+    }
+    Ok(amounts)
+}
+
 #[cfg(all(test, feature = "distribution"))]
 mod tests {
     use super::*;
@@ -493,7 +824,93 @@ mod tests {
         Address, Network, PublicKey,
     };
     use sn_logging::LogBuilder;
-    use sn_transfers::{HotWallet, MainSecretKey, Transfer};
+    use sn_transfers::{create_first_cash_note_from_key, HotWallet, MainSecretKey};
+
+    #[test]
+    fn remaining_supply_subtracts_dispensed_amounts() -> Result<()> {
+        let snapshot = Snapshot::from([
+            ("a".to_string(), NanoTokens::from(30)),
+            ("b".to_string(), NanoTokens::from(70)),
+        ]);
+        let mut claims = ClaimTracker::default();
+        assert_eq!(remaining_supply(&snapshot, &claims)?, NanoTokens::from(100));
+
+        claims.record("a".to_string(), NanoTokens::from(30));
+        assert_eq!(remaining_supply(&snapshot, &claims)?, NanoTokens::from(70));
+
+        // more than the snapshot total can't have been dispensed
+        claims.record("c".to_string(), NanoTokens::from(71));
+        assert!(remaining_supply(&snapshot, &claims).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_snapshot_accepts_total_within_tolerance() -> Result<()> {
+        let snapshot_json = |total: u64| {
+            format!(
+                r#"[{{"address":"a","balance":"{}","reserved":"0"}},{{"address":"b","balance":"0.000000001","reserved":"0"}}]"#,
+                NanoTokens::from(total - 1)
+            )
+        };
+        let supply = 452_552_412_000_000_000;
+
+        // exact match is accepted regardless of the tolerance
+        let snapshot = parse_snapshot(snapshot_json(supply), NanoTokens::zero())?;
+        assert_eq!(snapshot_total(&snapshot)?, NanoTokens::from(supply));
+
+        // an off total is rejected by default
+        assert!(parse_snapshot(snapshot_json(supply - 2), NanoTokens::zero()).is_err());
+        assert!(parse_snapshot(snapshot_json(supply + 2), NanoTokens::zero()).is_err());
+
+        // but accepted when within the tolerance
+        assert!(parse_snapshot(snapshot_json(supply - 2), NanoTokens::from(2)).is_ok());
+        assert!(parse_snapshot(snapshot_json(supply + 2), NanoTokens::from(2)).is_ok());
+        assert!(parse_snapshot(snapshot_json(supply + 3), NanoTokens::from(2)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn split_into_denominations_adds_remainder() -> Result<()> {
+        let amounts = split_into_denominations(NanoTokens::from(25), NanoTokens::from(10))?;
+        assert_eq!(
+            amounts,
+            vec![
+                NanoTokens::from(10),
+                NanoTokens::from(10),
+                NanoTokens::from(5)
+            ]
+        );
+
+        let amounts = split_into_denominations(NanoTokens::from(20), NanoTokens::from(10))?;
+        assert_eq!(amounts, vec![NanoTokens::from(10); 2]);
+
+        assert!(split_into_denominations(NanoTokens::from(10), NanoTokens::zero()).is_err());
+        assert!(split_into_denominations(NanoTokens::from(1000), NanoTokens::from(1)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn take_cash_note_for_removes_the_matching_cash_note() -> Result<()> {
+        let cash_note_a = create_first_cash_note_from_key(&MainSecretKey::random())?;
+        let cash_note_b = create_first_cash_note_from_key(&MainSecretKey::random())?;
+        let address_b = *cash_note_b.main_pubkey();
+        let amount = cash_note_b.value()?;
+        let mut cash_notes = vec![cash_note_a.clone(), cash_note_b.clone()];
+
+        // neither another amount nor another address match
+        assert!(take_cash_note_for(&mut cash_notes, address_b, NanoTokens::from(1)).is_none());
+        let address_c = MainSecretKey::random().main_pubkey();
+        assert!(take_cash_note_for(&mut cash_notes, address_c, amount).is_none());
+        assert_eq!(cash_notes.len(), 2);
+
+        let taken = take_cash_note_for(&mut cash_notes, address_b, amount);
+        assert_eq!(taken, Some(cash_note_b));
+        assert_eq!(cash_notes, vec![cash_note_a]);
+
+        // each cash note is only taken once
+        assert!(take_cash_note_for(&mut cash_notes, address_b, amount).is_none());
+        Ok(())
+    }
 
     // This test is to confirm fetching 'MAID snapshop` and `Maid claims` list from website
     // is working properly and giving consistent and expected result.
@@ -501,7 +918,7 @@ mod tests {
     // Note: the current list will grow as testnets collect more claims
     #[test]
     fn fetching_from_network() -> Result<()> {
-        let snapshot = load_maid_snapshot()?;
+        let snapshot = load_maid_snapshot(NanoTokens::zero())?;
         println!("Maid snapshot got {:?} entries", snapshot.len());
         assert!(!snapshot.is_empty());
 
@@ -542,7 +959,7 @@ mod tests {
             signature.to_string(),
         )?;
 
-        let transfer_hex = create_distribution(&client_token_issuer, &claim, &amount).await?;
+        let transfer_hex = create_distribution(&client_token_issuer, &claim, &amount, None).await?;
 
         let transfer = Transfer::from_hex(&transfer_hex)?;
 
@@ -556,7 +973,7 @@ mod tests {
         let receiver_wallet =
             HotWallet::load_from_path(&tmp_path, Some(MainSecretKey::new(wallet_sk)))?;
 
-        let mut cash_notes = receiver_client.receive(&transfer, &receiver_wallet).await?;
+        let (mut cash_notes, _memo) = receiver_client.receive(&transfer, &receiver_wallet).await?;
         assert_eq!(cash_notes.len(), 1);
         let cash_note = cash_notes.pop().unwrap();
 
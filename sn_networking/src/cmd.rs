@@ -9,13 +9,18 @@
 use crate::{
     driver::{PendingGetClosestType, SwarmDriver},
     error::{Error, Result},
-    multiaddr_pop_p2p, GetRecordCfg, GetRecordError, MsgResponder, NetworkEvent, CLOSE_GROUP_SIZE,
-    REPLICATE_RANGE,
+    multiaddr_pop_p2p,
+    quote_selection::PeerConnectionQuality,
+    record_labels::RECORD_LABELS_FILENAME,
+    record_store::{AccessStats, RecordAgeStats, RECORD_INSERTION_TIMES_FILENAME},
+    routing_table::KBucketEntry,
+    write_atomically, GetRecordCfg, GetRecordError, MsgResponder, NetworkEvent, CLOSE_GROUP_SIZE,
 };
 use bytes::Bytes;
 use libp2p::{
-    kad::{store::RecordStore, Quorum, Record, RecordKey},
-    swarm::dial_opts::DialOpts,
+    kad::{store::RecordStore, NodeStatus, Quorum, Record, RecordKey},
+    request_response,
+    swarm::{dial_opts::DialOpts, DialError},
     Multiaddr, PeerId,
 };
 use sn_protocol::{
@@ -25,13 +30,14 @@ use sn_protocol::{
 };
 use sn_transfers::NanoTokens;
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::Debug,
+    path::PathBuf,
 };
 use tokio::sync::oneshot;
 use xor_name::XorName;
 
-use crate::target_arch::Instant;
+use crate::target_arch::{spawn, Instant};
 
 const MAX_CONTINUOUS_HDD_WRITE_ERROR: usize = 5;
 
@@ -60,6 +66,16 @@ pub enum SwarmCmd {
     GetKBuckets {
         sender: oneshot::Sender<BTreeMap<u32, Vec<PeerId>>>,
     },
+    /// Get the entry of the peer in the local RoutingTable, if it is in there.
+    GetKBucketEntry {
+        peer_id: PeerId,
+        sender: oneshot::Sender<Option<KBucketEntry>>,
+    },
+    // Returns how well we are connected to each of the peers
+    GetPeersConnectionQuality {
+        peers: Vec<PeerId>,
+        sender: oneshot::Sender<HashMap<PeerId, PeerConnectionQuality>>,
+    },
     // Returns up to K_VALUE peers from all the k-buckets from the local Routing Table.
     // And our PeerId as well.
     GetClosestKLocalPeers {
@@ -102,6 +118,49 @@ pub enum SwarmCmd {
     GetAllLocalRecordAddresses {
         sender: oneshot::Sender<HashMap<NetworkAddress, RecordType>>,
     },
+    /// Get the addresses of the local records that were labelled with the given label
+    GetRecordAddressesWithLabel {
+        label: String,
+        sender: oneshot::Sender<HashSet<NetworkAddress>>,
+    },
+    /// Persist the labels of the local records, and the times they were stored at, to the given
+    /// root dir, sending back once written.
+    Checkpoint {
+        root_dir: PathBuf,
+        sender: oneshot::Sender<Result<()>>,
+    },
+    /// Remove the files of the local RecordStore that don't back any record anymore.
+    /// Sends back the number of bytes reclaimed.
+    CompactRecordStore {
+        sender: oneshot::Sender<u64>,
+    },
+    /// Get the number of records pending replication to us, queued or being fetched
+    GetReplicationQueueDepth {
+        sender: oneshot::Sender<usize>,
+    },
+    /// Get the records among the given ones of our peers that we are responsible for, but the
+    /// local RecordStore doesn't hold
+    GetMissingResponsibleRecords {
+        keys: Vec<(NetworkAddress, RecordType)>,
+        sender: oneshot::Sender<Vec<NetworkAddress>>,
+    },
+    /// Get how long the records of the local RecordStore have been held for
+    GetRecordAgeStats {
+        sender: oneshot::Sender<RecordAgeStats>,
+    },
+    /// Get how often a record of the local RecordStore has been served
+    GetRecordAccessStats {
+        key: RecordKey,
+        sender: oneshot::Sender<Option<AccessStats>>,
+    },
+    /// Exempt the record from being pruned when the RecordStore is full
+    PinRecord {
+        key: RecordKey,
+    },
+    /// Let the record be pruned again when the RecordStore is full
+    UnpinRecord {
+        key: RecordKey,
+    },
     /// Get Record from the Kad network
     GetNetworkRecord {
         key: RecordKey,
@@ -150,6 +209,16 @@ pub enum SwarmCmd {
     },
     /// Triggers interval repliation
     TriggerIntervalReplication,
+    /// Triggers a Kademlia bootstrap query to refresh the RoutingTable
+    RefreshRoutingTable,
+    /// Triggers a Kademlia query for a random address within the bucket at the given ilog2 distance,
+    /// to discover peers for that bucket
+    FillBucket(u32),
+    /// Re-advertise us to the peers closest to us, replying whether anything was done,
+    /// i.e. `false` if we are not bootstrapped yet
+    AnnouncePresence {
+        sender: oneshot::Sender<bool>,
+    },
     /// Subscribe to a given Gossipsub topic
     GossipsubSubscribe(String),
     /// Unsubscribe from a given Gossipsub topic
@@ -162,6 +231,11 @@ pub enum SwarmCmd {
         msg: Bytes,
     },
     GossipHandler,
+    /// Set whether the msgs received on a Gossipsub topic are forwarded to our peers
+    SetGossipTopicRelay {
+        topic_id: String,
+        relay: bool,
+    },
     /// Notify whether peer is in trouble
     SendNodeStatus {
         peer_id: PeerId,
@@ -173,6 +247,23 @@ pub enum SwarmCmd {
         target: NetworkAddress,
         sender: oneshot::Sender<bool>,
     },
+    /// Disconnect the peer and refuse any further connection with it
+    BlockPeer {
+        peer_id: PeerId,
+    },
+    /// Accept connections with a previously blocked peer again
+    UnblockPeer {
+        peer_id: PeerId,
+    },
+    /// Get the peers that are currently blocked
+    GetBlockedPeers {
+        sender: oneshot::Sender<HashSet<PeerId>>,
+    },
+    /// Get the connection error counters, optionally resetting them afterwards
+    GetConnectionStats {
+        reset: bool,
+        sender: oneshot::Sender<ConnStats>,
+    },
 }
 
 /// Debug impl for SwarmCmd to avoid printing full Record, instead only RecodKey
@@ -231,6 +322,12 @@ impl Debug for SwarmCmd {
             SwarmCmd::TriggerIntervalReplication => {
                 write!(f, "SwarmCmd::TriggerIntervalReplication")
             }
+            SwarmCmd::RefreshRoutingTable => {
+                write!(f, "SwarmCmd::RefreshRoutingTable")
+            }
+            SwarmCmd::FillBucket(ilog2) => {
+                write!(f, "SwarmCmd::FillBucket({ilog2})")
+            }
             SwarmCmd::GossipsubSubscribe(topic) => {
                 write!(f, "SwarmCmd::GossipsubSubscribe({topic:?})")
             }
@@ -272,12 +369,64 @@ impl Debug for SwarmCmd {
             SwarmCmd::GetAllLocalRecordAddresses { .. } => {
                 write!(f, "SwarmCmd::GetAllLocalRecordAddresses")
             }
+            SwarmCmd::GetRecordAddressesWithLabel { label, .. } => {
+                write!(f, "SwarmCmd::GetRecordAddressesWithLabel({label:?})")
+            }
+            SwarmCmd::Checkpoint { root_dir, .. } => {
+                write!(f, "SwarmCmd::Checkpoint({root_dir:?})")
+            }
+            SwarmCmd::CompactRecordStore { .. } => {
+                write!(f, "SwarmCmd::CompactRecordStore")
+            }
+            SwarmCmd::GetReplicationQueueDepth { .. } => {
+                write!(f, "SwarmCmd::GetReplicationQueueDepth")
+            }
+            SwarmCmd::AnnouncePresence { .. } => {
+                write!(f, "SwarmCmd::AnnouncePresence")
+            }
+            SwarmCmd::GetMissingResponsibleRecords { keys, .. } => {
+                write!(
+                    f,
+                    "SwarmCmd::GetMissingResponsibleRecords({} keys)",
+                    keys.len()
+                )
+            }
+            SwarmCmd::GetRecordAgeStats { .. } => {
+                write!(f, "SwarmCmd::GetRecordAgeStats")
+            }
+            SwarmCmd::GetRecordAccessStats { key, .. } => {
+                write!(
+                    f,
+                    "SwarmCmd::GetRecordAccessStats {{ key: {:?} }}",
+                    PrettyPrintRecordKey::from(key)
+                )
+            }
+            SwarmCmd::PinRecord { key } => {
+                write!(
+                    f,
+                    "SwarmCmd::PinRecord {{ key: {:?} }}",
+                    PrettyPrintRecordKey::from(key)
+                )
+            }
+            SwarmCmd::UnpinRecord { key } => {
+                write!(
+                    f,
+                    "SwarmCmd::UnpinRecord {{ key: {:?} }}",
+                    PrettyPrintRecordKey::from(key)
+                )
+            }
             SwarmCmd::GetAllLocalPeers { .. } => {
                 write!(f, "SwarmCmd::GetAllLocalPeers")
             }
             SwarmCmd::GetKBuckets { .. } => {
                 write!(f, "SwarmCmd::GetKBuckets")
             }
+            SwarmCmd::GetKBucketEntry { peer_id, .. } => {
+                write!(f, "SwarmCmd::GetKBucketEntry({peer_id:?})")
+            }
+            SwarmCmd::GetPeersConnectionQuality { peers, .. } => {
+                write!(f, "SwarmCmd::GetPeersConnectionQuality({peers:?})")
+            }
             SwarmCmd::GetSwarmLocalState { .. } => {
                 write!(f, "SwarmCmd::GetSwarmLocalState")
             }
@@ -297,6 +446,12 @@ impl Debug for SwarmCmd {
             SwarmCmd::GossipHandler => {
                 write!(f, "SwarmCmd::GossipHandler")
             }
+            SwarmCmd::SetGossipTopicRelay { topic_id, relay } => {
+                write!(
+                    f,
+                    "SwarmCmd::SetGossipTopicRelay {{ topic_id: {topic_id:?}, relay: {relay} }}"
+                )
+            }
             SwarmCmd::SendNodeStatus {
                 peer_id, is_bad, ..
             } => {
@@ -308,6 +463,18 @@ impl Debug for SwarmCmd {
             SwarmCmd::IsPeerInTrouble { target, .. } => {
                 write!(f, "SwarmCmd::IsPeerInTrouble target: {target:?}")
             }
+            SwarmCmd::BlockPeer { peer_id } => {
+                write!(f, "SwarmCmd::BlockPeer peer_id: {peer_id:?}")
+            }
+            SwarmCmd::UnblockPeer { peer_id } => {
+                write!(f, "SwarmCmd::UnblockPeer peer_id: {peer_id:?}")
+            }
+            SwarmCmd::GetBlockedPeers { .. } => {
+                write!(f, "SwarmCmd::GetBlockedPeers")
+            }
+            SwarmCmd::GetConnectionStats { reset, .. } => {
+                write!(f, "SwarmCmd::GetConnectionStats reset: {reset:?}")
+            }
         }
     }
 }
@@ -320,6 +487,49 @@ pub struct SwarmLocalState {
     pub listeners: Vec<Multiaddr>,
 }
 
+/// Counters of the connection errors encountered since start, or since they were last reset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnStats {
+    /// Number of failed dials, including those made to send a request.
+    pub dial_failures: u64,
+    /// Number of requests, inbound or outbound, that timed out.
+    pub timeouts: u64,
+    /// Number of requests that failed as the peer doesn't speak the protocol, or due to an io error.
+    pub protocol_errors: u64,
+}
+
+impl ConnStats {
+    /// Counts a failed outgoing connection, unless the dial was not attempted at all.
+    pub(crate) fn record_dial_error(&mut self, error: &DialError) {
+        if !matches!(error, DialError::DialPeerConditionFalse(_)) {
+            self.dial_failures += 1;
+        }
+    }
+
+    /// Counts a failed outbound request. A `DialFailure` is not counted, as the failed dial behind
+    /// it is already counted from its `OutgoingConnectionError`.
+    pub(crate) fn record_outbound_failure(&mut self, error: &request_response::OutboundFailure) {
+        match error {
+            request_response::OutboundFailure::Timeout => self.timeouts += 1,
+            request_response::OutboundFailure::UnsupportedProtocols
+            | request_response::OutboundFailure::Io(_) => self.protocol_errors += 1,
+            request_response::OutboundFailure::DialFailure
+            | request_response::OutboundFailure::ConnectionClosed => {}
+        }
+    }
+
+    /// Counts a failed inbound request.
+    pub(crate) fn record_inbound_failure(&mut self, error: &request_response::InboundFailure) {
+        match error {
+            request_response::InboundFailure::Timeout => self.timeouts += 1,
+            request_response::InboundFailure::UnsupportedProtocols
+            | request_response::InboundFailure::Io(_) => self.protocol_errors += 1,
+            request_response::InboundFailure::ConnectionClosed
+            | request_response::InboundFailure::ResponseOmission => {}
+        }
+    }
+}
+
 impl SwarmDriver {
     pub(crate) fn handle_cmd(&mut self, cmd: SwarmCmd) -> Result<(), Error> {
         let start = Instant::now();
@@ -329,6 +539,70 @@ impl SwarmDriver {
                 cmd_string = "TriggerIntervalReplication";
                 self.try_interval_replication()?;
             }
+            SwarmCmd::RefreshRoutingTable => {
+                cmd_string = "RefreshRoutingTable";
+                match self.swarm.behaviour_mut().kademlia.bootstrap() {
+                    Ok(query_id) => {
+                        info!("RoutingTable refresh started with {query_id:?}");
+                        let _ = self.pending_routing_table_refreshes.insert(query_id);
+                        self.bootstrap.initiated();
+                    }
+                    Err(err) => {
+                        warn!("Could not refresh the RoutingTable: {err:?}");
+                    }
+                }
+            }
+            SwarmCmd::AnnouncePresence { sender } => {
+                cmd_string = "AnnouncePresence";
+                let peers_in_rt: usize = self
+                    .swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .kbuckets()
+                    .map(|kbucket| kbucket.num_entries())
+                    .sum();
+                if peers_in_rt == 0 {
+                    info!("Not announcing our presence, we are not bootstrapped yet");
+                    let _ = sender.send(false);
+                } else {
+                    // The self lookup makes the peers closest to us aware of us again.
+                    match self.swarm.behaviour_mut().kademlia.bootstrap() {
+                        Ok(query_id) => {
+                            info!("Announcing our presence with {query_id:?}");
+                            let _ = self.pending_routing_table_refreshes.insert(query_id);
+                            self.bootstrap.initiated();
+                        }
+                        Err(err) => {
+                            warn!("Could not announce our presence: {err:?}");
+                        }
+                    }
+                    // The replication list tells our close group which records we hold.
+                    let result = self.try_interval_replication();
+                    let _ = sender.send(true);
+                    result?;
+                }
+            }
+            SwarmCmd::FillBucket(ilog2) => {
+                cmd_string = "FillBucket";
+                match self.network_discovery.candidate_for_bucket(ilog2) {
+                    Some(addr) => {
+                        // Tracked as a NetworkDiscovery query, so the peers found also refresh the candidates.
+                        let query_id = self
+                            .swarm
+                            .behaviour_mut()
+                            .kademlia
+                            .get_closest_peers(addr.as_bytes());
+                        info!("Filling bucket {ilog2} with {query_id:?}");
+                        let _ = self.pending_get_closest_peers.insert(
+                            query_id,
+                            (PendingGetClosestType::NetworkDiscovery, Default::default()),
+                        );
+                    }
+                    None => {
+                        warn!("Could not fill bucket {ilog2}, no address within that bucket could be generated");
+                    }
+                }
+            }
             SwarmCmd::GetNetworkRecord { key, sender, cfg } => {
                 cmd_string = "GetNetworkRecord";
                 let query_id = self.swarm.behaviour_mut().kademlia.get_record(key.clone());
@@ -387,7 +661,7 @@ impl SwarmDriver {
                     .behaviour_mut()
                     .kademlia
                     .store_mut()
-                    .get(&key)
+                    .get_unserved(&key)
                     .map(|rec| rec.into_owned());
                 let _ = sender.send(record);
             }
@@ -551,6 +825,114 @@ impl SwarmDriver {
                     .record_addresses();
                 let _ = sender.send(addresses);
             }
+            SwarmCmd::GetRecordAddressesWithLabel { label, sender } => {
+                cmd_string = "GetRecordAddressesWithLabel";
+                let addresses = self
+                    .swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .store_mut()
+                    .record_addresses_with_label(&label);
+                let _ = sender.send(addresses);
+            }
+            SwarmCmd::Checkpoint { root_dir, sender } => {
+                cmd_string = "Checkpoint";
+                let store = self.swarm.behaviour_mut().kademlia.store_mut();
+                let checkpoints = store.record_labels_to_bytes().and_then(|labels| {
+                    Ok([
+                        (root_dir.join(RECORD_LABELS_FILENAME), labels),
+                        (
+                            root_dir.join(RECORD_INSERTION_TIMES_FILENAME),
+                            store.insertion_times_to_bytes()?,
+                        ),
+                    ])
+                });
+                match checkpoints {
+                    Ok(checkpoints) => {
+                        // write off the driver's thread
+                        let _handle = spawn(async move {
+                            let mut result = Ok(());
+                            for (path, bytes) in checkpoints {
+                                if let Err(err) = write_atomically(&path, &bytes) {
+                                    error!("Failed to checkpoint {path:?}: {err:?}");
+                                    result = Err(err.into());
+                                }
+                            }
+                            let _ = sender.send(result);
+                        });
+                    }
+                    Err(err) => {
+                        let _ = sender.send(Err(err.into()));
+                    }
+                }
+            }
+            SwarmCmd::CompactRecordStore { sender } => {
+                cmd_string = "CompactRecordStore";
+                self.swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .store_mut()
+                    .compact(sender);
+            }
+            SwarmCmd::GetReplicationQueueDepth { sender } => {
+                cmd_string = "GetReplicationQueueDepth";
+                let _ = sender.send(self.replication_fetcher.queue_depth());
+            }
+            SwarmCmd::GetMissingResponsibleRecords { keys, sender } => {
+                cmd_string = "GetMissingResponsibleRecords";
+                let closest_k_peers = self.get_closest_k_value_local_peers();
+                let mut missing: BTreeSet<_> = self
+                    .select_non_existent_records_for_replications(&keys, &closest_k_peers)
+                    .into_iter()
+                    .map(|(address, _)| address)
+                    .collect();
+                // the records being replicated to us are missing until fetched too
+                let store = self.swarm.behaviour_mut().kademlia.store_mut();
+                missing.extend(
+                    self.replication_fetcher
+                        .pending_keys()
+                        .into_iter()
+                        .filter(|key| !store.contains(key))
+                        .map(|key| NetworkAddress::from_record_key(&key)),
+                );
+                let _ = sender.send(missing.into_iter().collect());
+            }
+            SwarmCmd::GetRecordAgeStats { sender } => {
+                cmd_string = "GetRecordAgeStats";
+                let stats = self
+                    .swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .store_mut()
+                    .record_age_stats();
+                let _ = sender.send(stats);
+            }
+            SwarmCmd::GetRecordAccessStats { key, sender } => {
+                cmd_string = "GetRecordAccessStats";
+                let stats = self
+                    .swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .store_mut()
+                    .access_stats(&key);
+                let _ = sender.send(stats);
+            }
+            SwarmCmd::PinRecord { key } => {
+                cmd_string = "PinRecord";
+                self.swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .store_mut()
+                    .pin_record(key);
+            }
+            SwarmCmd::UnpinRecord { key } => {
+                cmd_string = "UnpinRecord";
+                self.swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .store_mut()
+                    .unpin_record(&key);
+            }
 
             SwarmCmd::StartListening { addr, sender } => {
                 cmd_string = "StartListening";
@@ -620,6 +1002,45 @@ impl SwarmDriver {
                 }
                 let _ = sender.send(ilog2_kbuckets);
             }
+            SwarmCmd::GetKBucketEntry { peer_id, sender } => {
+                cmd_string = "GetKBucketEntry";
+                let mut kbucket_entry = None;
+                if let Some(kbucket) = self.swarm.behaviour_mut().kademlia.kbucket(peer_id) {
+                    if let Some(ilog2_distance) = kbucket.range().0.ilog2() {
+                        kbucket_entry = kbucket
+                            .iter()
+                            .find(|entry| entry.node.key.preimage() == &peer_id)
+                            .map(|entry| KBucketEntry {
+                                peer_id,
+                                ilog2_distance,
+                                addresses: entry.node.value.iter().cloned().collect(),
+                                connected: entry.status == NodeStatus::Connected,
+                            });
+                    }
+                }
+                let _ = sender.send(kbucket_entry);
+            }
+            SwarmCmd::GetPeersConnectionQuality { peers, sender } => {
+                cmd_string = "GetPeersConnectionQuality";
+                let mut qualities = HashMap::new();
+                for peer_id in peers {
+                    let mut quality = PeerConnectionQuality {
+                        connected: self.swarm.is_connected(&peer_id),
+                        ..Default::default()
+                    };
+                    if let Some(kbucket) = self.swarm.behaviour_mut().kademlia.kbucket(peer_id) {
+                        if let Some(entry) = kbucket
+                            .iter()
+                            .find(|entry| entry.node.key.preimage() == &peer_id)
+                        {
+                            quality.in_routing_table = true;
+                            quality.known_addresses = entry.node.value.len();
+                        }
+                    }
+                    let _ = qualities.insert(peer_id, quality);
+                }
+                let _ = sender.send(qualities);
+            }
             SwarmCmd::GetCloseGroupLocalPeers { key, sender } => {
                 cmd_string = "GetCloseGroupLocalPeers";
                 let key = key.as_kbucket_key();
@@ -740,6 +1161,15 @@ impl SwarmDriver {
             SwarmCmd::GossipHandler => {
                 self.is_gossip_handler = true;
             }
+            SwarmCmd::SetGossipTopicRelay { topic_id, relay } => {
+                cmd_string = "SetGossipTopicRelay";
+                info!("Setting the relay of the msgs received on gossipsub topic {topic_id:?} to {relay}");
+                if relay {
+                    let _ = self.non_relayed_gossip_topics.remove(&topic_id);
+                } else {
+                    let _ = self.non_relayed_gossip_topics.insert(topic_id);
+                }
+            }
             SwarmCmd::SendNodeStatus {
                 peer_id,
                 addrs,
@@ -783,6 +1213,43 @@ impl SwarmDriver {
                     let _ = sender.send(false);
                 }
             }
+            SwarmCmd::BlockPeer { peer_id } => {
+                cmd_string = "BlockPeer";
+                let _ = self.blocked_peers.insert(peer_id);
+                if self.close_group.contains(&peer_id) {
+                    warn!("Blocking {peer_id:?} which is a member of our close group, the records we share responsibility for will be replicated with one peer less");
+                } else {
+                    info!("Blocking {peer_id:?}");
+                }
+
+                if let Some(dead_peer) = self.swarm.behaviour_mut().kademlia.remove_peer(&peer_id) {
+                    self.connected_peers = self.connected_peers.saturating_sub(1);
+                    self.send_event(NetworkEvent::PeerRemoved(
+                        *dead_peer.node.key.preimage(),
+                        self.connected_peers,
+                    ));
+                    self.log_kbuckets(&peer_id);
+                    let _ = self.check_for_change_in_our_close_group();
+                }
+                let _ = self.swarm.disconnect_peer_id(peer_id);
+            }
+            SwarmCmd::UnblockPeer { peer_id } => {
+                cmd_string = "UnblockPeer";
+                if self.blocked_peers.remove(&peer_id) {
+                    info!("Unblocked {peer_id:?}");
+                }
+            }
+            SwarmCmd::GetBlockedPeers { sender } => {
+                cmd_string = "GetBlockedPeers";
+                let _ = sender.send(self.blocked_peers.clone());
+            }
+            SwarmCmd::GetConnectionStats { reset, sender } => {
+                cmd_string = "GetConnectionStats";
+                let _ = sender.send(self.conn_stats);
+                if reset {
+                    self.conn_stats = ConnStats::default();
+                }
+            }
         }
 
         self.log_handling(cmd_string.to_string(), start.elapsed());
@@ -801,11 +1268,11 @@ impl SwarmDriver {
             // Map KBucketKey<PeerId> to PeerId.
             .map(|key| key.into_preimage());
 
-        // Only grab the closest nodes within the REPLICATE_RANGE
+        // Only grab the closest nodes within the replication range
         let replicate_targets = closest_k_peers
             .into_iter()
             // add some leeway to allow for divergent knowledge
-            .take(REPLICATE_RANGE)
+            .take(self.replication_range)
             .collect::<Vec<_>>();
 
         let all_records: Vec<_> = self
@@ -842,3 +1309,45 @@ impl SwarmDriver {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::swarm::dial_opts::PeerCondition;
+
+    #[test]
+    fn a_failed_request_dial_is_counted_once() {
+        let mut conn_stats = ConnStats::default();
+
+        // a request to a peer that can't be dialed fails the dial, then the request
+        conn_stats.record_dial_error(&DialError::Aborted);
+        conn_stats.record_outbound_failure(&request_response::OutboundFailure::DialFailure);
+        assert_eq!(conn_stats.dial_failures, 1);
+
+        // a dial that was not attempted is not a failure
+        conn_stats.record_dial_error(&DialError::DialPeerConditionFalse(
+            PeerCondition::Disconnected,
+        ));
+        assert_eq!(conn_stats.dial_failures, 1);
+    }
+
+    #[test]
+    fn request_failures_are_counted_by_kind() {
+        let mut conn_stats = ConnStats::default();
+
+        conn_stats.record_outbound_failure(&request_response::OutboundFailure::Timeout);
+        conn_stats.record_inbound_failure(&request_response::InboundFailure::Timeout);
+        conn_stats
+            .record_outbound_failure(&request_response::OutboundFailure::UnsupportedProtocols);
+        conn_stats.record_inbound_failure(&request_response::InboundFailure::ConnectionClosed);
+
+        assert_eq!(
+            conn_stats,
+            ConnStats {
+                dial_failures: 0,
+                timeouts: 2,
+                protocol_errors: 1,
+            }
+        );
+    }
+}
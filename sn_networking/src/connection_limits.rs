@@ -0,0 +1,48 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use libp2p::connection_limits;
+
+/// The max number of connections the swarm holds, e.g. to stay within the file descriptors of a
+/// small host. `None` means unlimited.
+///
+/// The connections beyond the limits are refused straight away, or not dialed. The default only
+/// caps the incoming connections, far above what a node needs on a healthy network, so that it
+/// only kicks in when the node is being flooded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionLimits {
+    /// The max number of established connections dialed by our peers.
+    pub max_established_incoming: Option<u32>,
+    /// The max number of established connections we dialed ourselves.
+    pub max_established_outgoing: Option<u32>,
+    /// The max number of connections dialed by our peers that are still being negotiated.
+    pub max_pending_incoming: Option<u32>,
+    /// The max number of connections we dialed ourselves that are still being negotiated.
+    pub max_pending_outgoing: Option<u32>,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        Self {
+            max_established_incoming: Some(1024),
+            max_established_outgoing: None,
+            max_pending_incoming: Some(256),
+            max_pending_outgoing: None,
+        }
+    }
+}
+
+impl From<ConnectionLimits> for connection_limits::ConnectionLimits {
+    fn from(limits: ConnectionLimits) -> Self {
+        connection_limits::ConnectionLimits::default()
+            .with_max_established_incoming(limits.max_established_incoming)
+            .with_max_established_outgoing(limits.max_established_outgoing)
+            .with_max_pending_incoming(limits.max_pending_incoming)
+            .with_max_pending_outgoing(limits.max_pending_outgoing)
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::target_arch::Instant;
+use libp2p::PeerId;
+use rand::Rng;
+use std::collections::HashMap;
+use tokio::time::Duration;
+
+// The max number of peers we track dial failures for
+const MAX_TRACKED_PEERS: usize = 1024;
+
+/// How aggressively we redial peers that we have failed to connect to.
+///
+/// After the first failed dial to a peer, further dials to it are held back for `initial`,
+/// doubling on each consecutive failure up to `max`. A random extra of up to `jitter`
+/// (a fraction of the delay) is added so that nodes don't redial in lockstep.
+///
+/// The default has a zero `initial` backoff, which disables it, i.e. failed peers can be
+/// redialed straight away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DialBackoff {
+    /// The delay before we redial a peer after its first failed dial.
+    pub initial: Duration,
+    /// The upper bound of the delay, however many times the dials have failed.
+    pub max: Duration,
+    /// The fraction of the delay, between 0.0 and 1.0, that can be randomly added to it.
+    pub jitter: f64,
+}
+
+impl Default for DialBackoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::ZERO,
+            max: Duration::ZERO,
+            jitter: 0.0,
+        }
+    }
+}
+
+impl DialBackoff {
+    /// Returns true if failed dials are held back.
+    pub fn is_enabled(&self) -> bool {
+        !self.initial.is_zero()
+    }
+
+    /// The delay, without jitter, after the given number of consecutive failed dials.
+    pub fn delay(&self, failures: u32) -> Duration {
+        if failures == 0 || !self.is_enabled() {
+            return Duration::ZERO;
+        }
+        let factor = 2u32.saturating_pow(failures - 1);
+        self.initial
+            .checked_mul(factor)
+            .unwrap_or(self.max)
+            .min(self.max.max(self.initial))
+    }
+
+    fn delay_with_jitter(&self, failures: u32) -> Duration {
+        let delay = self.delay(failures);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 || delay.is_zero() {
+            return delay;
+        }
+        delay + delay.mul_f64(rand::thread_rng().gen_range(0.0..=jitter))
+    }
+}
+
+/// Tracks the peers that we have failed to dial, and until when they should not be redialed.
+#[derive(Debug, Default)]
+pub(crate) struct DialBackoffTracker {
+    config: DialBackoff,
+    failed_peers: HashMap<PeerId, (u32, Instant)>,
+}
+
+impl DialBackoffTracker {
+    pub(crate) fn new(config: DialBackoff) -> Self {
+        Self {
+            config,
+            failed_peers: Default::default(),
+        }
+    }
+
+    /// Returns true if the peer has failed recently and should not be dialed yet.
+    pub(crate) fn is_backing_off(&self, peer_id: &PeerId) -> bool {
+        self.failed_peers
+            .get(peer_id)
+            .is_some_and(|(_, redial_at)| Instant::now() < *redial_at)
+    }
+
+    /// Records a failed dial to the peer, pushing back the next time it can be dialed.
+    pub(crate) fn record_failure(&mut self, peer_id: PeerId) {
+        if !self.config.is_enabled() {
+            return;
+        }
+        if self.failed_peers.len() >= MAX_TRACKED_PEERS && !self.failed_peers.contains_key(&peer_id)
+        {
+            let now = Instant::now();
+            self.failed_peers
+                .retain(|_, (_, redial_at)| *redial_at > now);
+            if self.failed_peers.len() >= MAX_TRACKED_PEERS {
+                return;
+            }
+        }
+
+        let failures = self
+            .failed_peers
+            .get(&peer_id)
+            .map_or(1, |(failures, _)| failures.saturating_add(1));
+        let delay = self.config.delay_with_jitter(failures);
+        trace!("Backing off dials to {peer_id:?} for {delay:?} after {failures} failures");
+        let _ = self
+            .failed_peers
+            .insert(peer_id, (failures, Instant::now() + delay));
+    }
+
+    /// Clears any backoff for the peer, once we have connected to it.
+    pub(crate) fn record_success(&mut self, peer_id: &PeerId) {
+        let _ = self.failed_peers.remove(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_dial_backoff_never_holds_back_peers() {
+        let mut tracker = DialBackoffTracker::new(DialBackoff::default());
+        let peer_id = PeerId::random();
+
+        tracker.record_failure(peer_id);
+        tracker.record_failure(peer_id);
+
+        assert!(!tracker.is_backing_off(&peer_id));
+    }
+
+    #[test]
+    fn dial_backoff_doubles_up_to_max() {
+        let backoff = DialBackoff {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(5),
+            jitter: 0.0,
+        };
+
+        assert_eq!(backoff.delay(0), Duration::ZERO);
+        assert_eq!(backoff.delay(1), Duration::from_secs(1));
+        assert_eq!(backoff.delay(2), Duration::from_secs(2));
+        assert_eq!(backoff.delay(3), Duration::from_secs(4));
+        assert_eq!(backoff.delay(4), Duration::from_secs(5));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn dial_backoff_is_cleared_on_success() {
+        let mut tracker = DialBackoffTracker::new(DialBackoff {
+            initial: Duration::from_secs(60),
+            max: Duration::from_secs(600),
+            jitter: 0.5,
+        });
+        let peer_id = PeerId::random();
+
+        tracker.record_failure(peer_id);
+        assert!(tracker.is_backing_off(&peer_id));
+
+        tracker.record_success(&peer_id);
+        assert!(!tracker.is_backing_off(&peer_id));
+    }
+}
@@ -13,17 +13,26 @@ use crate::metrics_service::run_metrics_server;
 use crate::{
     bootstrap::{ContinuousBootstrap, BOOTSTRAP_INTERVAL},
     circular_vec::CircularVec,
-    cmd::SwarmCmd,
+    cmd::{ConnStats, SwarmCmd},
+    connection_limits::ConnectionLimits,
+    dial_backoff::{DialBackoff, DialBackoffTracker},
     error::{Error, Result},
     event::NetworkEvent,
     event::NodeEvent,
     get_record_handler::PendingGetRecord,
+    gossip_validation::{GossipValidator, GossipValidators},
     multiaddr_pop_p2p,
     network_discovery::NetworkDiscovery,
-    record_store::{ClientRecordStore, NodeRecordStore, NodeRecordStoreConfig},
+    quote_selection::QuoteSelection,
+    record_compression::CompressionAlgo,
+    record_labels::RECORD_LABELS_FILENAME,
+    record_store::{
+        ClientRecordStore, NodeRecordStore, NodeRecordStoreConfig, RECORD_INSERTION_TIMES_FILENAME,
+    },
     record_store_api::UnifiedRecordStore,
+    record_store_backend::{DiskRecordStoreBackend, RecordStoreBackend},
     replication_fetcher::ReplicationFetcher,
-    Network, CLOSE_GROUP_SIZE,
+    Network, CLOSE_GROUP_SIZE, REPLICATE_RANGE,
 };
 use futures::StreamExt;
 #[cfg(all(not(feature = "websockets"), not(target_arch = "wasm32")))]
@@ -61,12 +70,15 @@ use sn_protocol::{
     storage::RetryStrategy,
     NetworkAddress, PrettyPrintKBucketKey, PrettyPrintRecordKey,
 };
+#[cfg(feature = "open-metrics")]
+use std::sync::Mutex;
 use std::{
     collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::Debug,
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     num::NonZeroUsize,
     path::PathBuf,
+    sync::Arc,
 };
 use tiny_keccak::{Hasher, Sha3};
 use tokio::sync::{mpsc, oneshot};
@@ -107,6 +119,12 @@ const NETWORKING_CHANNEL_SIZE: usize = 10_000;
 /// Time before a Kad query times out if no response is received
 const KAD_QUERY_TIMEOUT_S: Duration = Duration::from_secs(10);
 
+/// The default gossipsub heartbeat interval, higher than the gossipsub default of 1sec.
+const GOSSIP_HEARTBEAT_INTERVAL_DEFAULT: Duration = Duration::from_secs(5);
+/// The shortest gossipsub heartbeat interval accepted, as shorter ones flood the peers with
+/// mesh maintenance traffic.
+pub const GOSSIP_HEARTBEAT_INTERVAL_MIN: Duration = Duration::from_millis(100);
+
 // Protocol support shall be downward compatible for patch only version update.
 // i.e. versions of `A.B.X` shall be considered as a same protocol of `A.B`
 pub(crate) fn truncate_patch_version(full_str: &str) -> &str {
@@ -173,6 +191,9 @@ pub struct PutRecordCfg {
     pub use_put_record_to: Option<Vec<PeerId>>,
     /// Enables verification after writing. The VerificationKind is used to determine the method to use.
     pub verification: Option<(VerificationKind, GetRecordCfg)>,
+    /// An optional label to be attached to the record once it has been PUT. This is best-effort: the label is
+    /// sent to the closest peers of the record, who keep it in a side index that is not part of the record itself.
+    pub label: Option<String>,
 }
 
 /// The methods in which verification on a PUT can be carried out.
@@ -191,6 +212,7 @@ pub enum VerificationKind {
 #[derive(NetworkBehaviour)]
 #[behaviour(to_swarm = "NodeEvent")]
 pub(super) struct NodeBehaviour {
+    pub(super) connection_limits: libp2p::connection_limits::Behaviour,
     pub(super) request_response: request_response::cbor::Behaviour<Request, Response>,
     pub(super) kademlia: kad::Behaviour<UnifiedRecordStore>,
     #[cfg(feature = "local-discovery")]
@@ -205,9 +227,20 @@ pub struct NetworkBuilder {
     local: bool,
     root_dir: PathBuf,
     listen_addr: Option<SocketAddr>,
+    listen_ips: Vec<IpAddr>,
     enable_gossip: bool,
+    gossip_validators: GossipValidators,
+    gossip_heartbeat_interval: Duration,
     request_timeout: Option<Duration>,
     concurrency_limit: Option<usize>,
+    dial_backoff: DialBackoff,
+    connection_limits: ConnectionLimits,
+    replication_range: usize,
+    close_group_size: usize,
+    quote_selection: QuoteSelection,
+    record_store_backend: Option<Arc<dyn RecordStoreBackend>>,
+    record_compression: CompressionAlgo,
+    capacity_alert_thresholds: Vec<f32>,
     #[cfg(feature = "open-metrics")]
     metrics_registry: Option<Registry>,
     #[cfg(feature = "open-metrics")]
@@ -221,9 +254,20 @@ impl NetworkBuilder {
             local,
             root_dir,
             listen_addr: None,
+            listen_ips: vec![],
             enable_gossip: false,
+            gossip_validators: Default::default(),
+            gossip_heartbeat_interval: GOSSIP_HEARTBEAT_INTERVAL_DEFAULT,
             request_timeout: None,
             concurrency_limit: None,
+            dial_backoff: Default::default(),
+            connection_limits: Default::default(),
+            replication_range: REPLICATE_RANGE,
+            close_group_size: CLOSE_GROUP_SIZE,
+            quote_selection: Default::default(),
+            record_store_backend: None,
+            record_compression: CompressionAlgo::None,
+            capacity_alert_thresholds: vec![],
             #[cfg(feature = "open-metrics")]
             metrics_registry: None,
             #[cfg(feature = "open-metrics")]
@@ -235,11 +279,30 @@ impl NetworkBuilder {
         self.listen_addr = Some(listen_addr);
     }
 
+    /// Listen on the given IP, on the port of the `listen_addr`, instead of the IP of the
+    /// `listen_addr`. Can be called several times to listen on several IPs.
+    pub fn listen_ip(&mut self, ip: IpAddr) {
+        self.listen_ips.push(ip);
+    }
+
     /// Enable gossip for the network
     pub fn enable_gossip(&mut self) {
         self.enable_gossip = true;
     }
 
+    /// Validates the gossip messages received on the topic before they are delivered or
+    /// forwarded to our peers. The messages on topics without a validator are accepted.
+    pub fn gossip_validator(&mut self, topic: String, validator: GossipValidator) {
+        self.gossip_validators.insert(topic, validator);
+    }
+
+    /// Sets how often gossipsub maintains its meshes and gossips about the recent messages.
+    /// Shorter intervals propagate the messages faster, at the cost of more traffic.
+    /// Defaults to 5 seconds, building the network fails if below `GOSSIP_HEARTBEAT_INTERVAL_MIN`.
+    pub fn gossip_heartbeat_interval(&mut self, interval: Duration) {
+        self.gossip_heartbeat_interval = interval;
+    }
+
     pub fn request_timeout(&mut self, request_timeout: Duration) {
         self.request_timeout = Some(request_timeout);
     }
@@ -248,6 +311,57 @@ impl NetworkBuilder {
         self.concurrency_limit = Some(concurrency_limit);
     }
 
+    /// Sets how failed peers are redialed. Defaults to redialing them straight away.
+    pub fn dial_backoff(&mut self, dial_backoff: DialBackoff) {
+        self.dial_backoff = dial_backoff;
+    }
+
+    /// Caps the connections of the swarm, the ones beyond being refused. Defaults to
+    /// `ConnectionLimits::default()`, which only caps the incoming ones, generously.
+    pub fn connection_limits(&mut self, connection_limits: ConnectionLimits) {
+        self.connection_limits = connection_limits;
+    }
+
+    /// Sets how many of the peers closest to a record it is replicated to, and accepted from.
+    /// Defaults to `REPLICATE_RANGE`. Small test networks can lower it below their node count.
+    pub fn replication_factor(&mut self, replication_factor: usize) {
+        self.replication_range = replication_factor.max(1);
+    }
+
+    /// Sets how many of the peers closest to an address are responsible for it, i.e. queried for
+    /// its store costs and records, and counted towards the quorums.
+    /// Defaults to `CLOSE_GROUP_SIZE`. Clients of small networks can lower it below their node count.
+    pub fn close_group_size(&mut self, close_group_size: usize) {
+        self.close_group_size = close_group_size.max(1);
+    }
+
+    /// Sets how the quote to pay is picked among the store costs received.
+    /// Defaults to `QuoteSelection::Cheapest`.
+    pub fn quote_selection(&mut self, quote_selection: QuoteSelection) {
+        self.quote_selection = quote_selection;
+    }
+
+    /// Sets where the node persists its records. Defaults to a `DiskRecordStoreBackend` under
+    /// the root dir.
+    pub fn record_store_backend(&mut self, backend: Arc<dyn RecordStoreBackend>) {
+        self.record_store_backend = Some(backend);
+    }
+
+    /// Sets how the records are compressed before being persisted. Defaults to no compression.
+    pub fn record_compression(&mut self, compression: CompressionAlgo) {
+        self.record_compression = compression;
+    }
+
+    /// Sets the fractions of the record store capacity, e.g. `0.9`, that emit a
+    /// `NetworkEvent::CapacityThresholdCrossed` when the store fills past them, or drops back
+    /// below them. Fractions outside of `(0, 1]` are ignored. Defaults to none.
+    pub fn capacity_alert_thresholds(&mut self, mut thresholds: Vec<f32>) {
+        thresholds.retain(|threshold| *threshold > 0.0 && *threshold <= 1.0);
+        thresholds.sort_by(|a, b| a.total_cmp(b));
+        thresholds.dedup();
+        self.capacity_alert_thresholds = thresholds;
+    }
+
     #[cfg(feature = "open-metrics")]
     pub fn metrics_registry(&mut self, metrics_registry: Registry) {
         self.metrics_registry = Some(metrics_registry);
@@ -311,11 +425,14 @@ impl NetworkBuilder {
             NodeRecordStoreConfig {
                 max_value_bytes: MAX_PACKET_SIZE, // TODO, does this need to be _less_ than MAX_PACKET_SIZE
                 storage_dir: storage_dir_path,
+                compression: self.record_compression,
+                capacity_alert_thresholds: self.capacity_alert_thresholds.clone(),
                 ..Default::default()
             }
         };
 
         let listen_addr = self.listen_addr;
+        let listen_ips = self.listen_ips.clone();
 
         let (network, events_receiver, mut swarm_driver) = self.build(
             kad_cfg,
@@ -327,25 +444,32 @@ impl NetworkBuilder {
 
         // Listen on the provided address
         let listen_socket_addr = listen_addr.ok_or(Error::ListenAddressNotProvided)?;
-
-        // Flesh out the multiaddress
-        let start_addr = Multiaddr::from(listen_socket_addr.ip());
-
-        let listen_addr = if cfg!(any(feature = "websockets", target_arch = "wasm32")) {
-            start_addr
-                .with(Protocol::Tcp(listen_socket_addr.port()))
-                .with(Protocol::Ws("/".into()))
+        let listen_ips = if listen_ips.is_empty() {
+            vec![listen_socket_addr.ip()]
         } else {
-            start_addr
-                .with(Protocol::Udp(listen_socket_addr.port()))
-                .with(Protocol::QuicV1)
+            listen_ips
         };
 
-        debug!("Attempting to listen on: {listen_addr:?}");
-        let _listener_id = swarm_driver
-            .swarm
-            .listen_on(listen_addr)
-            .expect("Failed to listen on the provided address");
+        for ip in listen_ips {
+            // Flesh out the multiaddress
+            let start_addr = Multiaddr::from(ip);
+
+            let listen_addr = if cfg!(any(feature = "websockets", target_arch = "wasm32")) {
+                start_addr
+                    .with(Protocol::Tcp(listen_socket_addr.port()))
+                    .with(Protocol::Ws("/".into()))
+            } else {
+                start_addr
+                    .with(Protocol::Udp(listen_socket_addr.port()))
+                    .with(Protocol::QuicV1)
+            };
+
+            debug!("Attempting to listen on: {listen_addr:?}");
+            let _listener_id = swarm_driver
+                .swarm
+                .listen_on(listen_addr)
+                .expect("Failed to listen on the provided address");
+        }
 
         Ok((network, events_receiver, swarm_driver))
     }
@@ -363,7 +487,8 @@ impl NetworkBuilder {
             .disjoint_query_paths(true)
             // How many nodes _should_ store data.
             .set_replication_factor(
-                NonZeroUsize::new(CLOSE_GROUP_SIZE).ok_or_else(|| Error::InvalidCloseGroupSize)?,
+                NonZeroUsize::new(self.close_group_size)
+                    .ok_or_else(|| Error::InvalidCloseGroupSize)?,
             );
 
         let (network, net_event_recv, driver) = self.build(
@@ -399,11 +524,12 @@ impl NetworkBuilder {
         );
 
         #[cfg(feature = "open-metrics")]
-        let network_metrics = {
+        let (network_metrics, metrics_registry) = {
             let mut metrics_registry = self.metrics_registry.unwrap_or_default();
             let metrics = NetworkMetrics::new(&mut metrics_registry);
-            run_metrics_server(metrics_registry, self.metrics_server_port);
-            metrics
+            let metrics_registry = Arc::new(Mutex::new(metrics_registry));
+            run_metrics_server(metrics_registry.clone(), self.metrics_server_port);
+            (metrics, metrics_registry)
         };
 
         // RequestResponse Behaviour
@@ -427,12 +553,21 @@ impl NetworkBuilder {
         let kademlia = {
             match record_store_cfg {
                 Some(store_cfg) => {
-                    let node_record_store = NodeRecordStore::with_config(
+                    let backend = self.record_store_backend.clone().unwrap_or_else(|| {
+                        Arc::new(DiskRecordStoreBackend::new(store_cfg.storage_dir.clone()))
+                    });
+                    let node_record_store = NodeRecordStore::with_backend(
                         peer_id,
                         store_cfg,
+                        backend,
                         network_event_sender.clone(),
                         swarm_cmd_sender.clone(),
                     );
+                    let node_record_store = node_record_store
+                        .with_checkpointed_insertion_times(
+                            &self.root_dir.join(RECORD_INSERTION_TIMES_FILENAME),
+                        )
+                        .with_checkpointed_labels(&self.root_dir.join(RECORD_LABELS_FILENAME));
                     #[cfg(feature = "open-metrics")]
                     let node_record_store = node_record_store
                         .set_record_count_metric(network_metrics.records_stored.clone());
@@ -503,8 +638,16 @@ impl NetworkBuilder {
             .boxed();
 
         let gossipsub = if self.enable_gossip {
+            if self.gossip_heartbeat_interval < GOSSIP_HEARTBEAT_INTERVAL_MIN {
+                return Err(Error::GossipsubConfigError(format!(
+                    "the heartbeat interval of {:?} is below the minimum of {GOSSIP_HEARTBEAT_INTERVAL_MIN:?}",
+                    self.gossip_heartbeat_interval
+                )));
+            }
+
             // Gossipsub behaviour
-            let gossipsub_config = libp2p::gossipsub::ConfigBuilder::default()
+            let mut gossipsub_config = libp2p::gossipsub::ConfigBuilder::default();
+            let _ = gossipsub_config
                 // we don't currently require source peer id and/or signing
                 .validation_mode(libp2p::gossipsub::ValidationMode::Permissive)
                 // we use the hash of the msg content as the msg id to deduplicate them
@@ -515,12 +658,15 @@ impl NetworkBuilder {
                     sha3.finalize(&mut msg_id);
                     msg_id.into()
                 })
-                // set the heartbeat interval to be higher than default 1sec
-                .heartbeat_interval(Duration::from_secs(5))
+                .heartbeat_interval(self.gossip_heartbeat_interval)
                 // default is 3sec, increase to 10sec to avoid false alert
                 .iwant_followup_time(Duration::from_secs(10))
                 // default is 10sec, increase to 60sec to reduce the risk of looping
-                .published_message_ids_cache_time(Duration::from_secs(60))
+                .published_message_ids_cache_time(Duration::from_secs(60));
+            // hold back the received msgs until they are validated, and checked against the
+            // topics not to relay, as reported on receipt
+            let _ = gossipsub_config.validate_messages();
+            let gossipsub_config = gossipsub_config
                 .build()
                 .map_err(|err| Error::GossipsubConfigError(err.to_string()))?;
 
@@ -547,6 +693,9 @@ impl NetworkBuilder {
         };
 
         let behaviour = NodeBehaviour {
+            connection_limits: libp2p::connection_limits::Behaviour::new(
+                self.connection_limits.into(),
+            ),
             request_response,
             kademlia,
             identify,
@@ -565,7 +714,11 @@ impl NetworkBuilder {
         let swarm = Swarm::new(transport, behaviour, peer_id, swarm_config);
 
         let bootstrap = ContinuousBootstrap::new();
-        let replication_fetcher = ReplicationFetcher::new(peer_id, network_event_sender.clone());
+        #[allow(unused_mut)]
+        let mut replication_fetcher =
+            ReplicationFetcher::new(peer_id, network_event_sender.clone());
+        #[cfg(feature = "open-metrics")]
+        replication_fetcher.set_queue_depth_metric(network_metrics.replication_queue_depth.clone());
 
         let swarm_driver = SwarmDriver {
             swarm,
@@ -583,10 +736,16 @@ impl NetworkBuilder {
             pending_get_closest_peers: Default::default(),
             pending_requests: Default::default(),
             pending_get_record: Default::default(),
+            pending_routing_table_refreshes: Default::default(),
             // We use 255 here which allows covering a network larger than 64k without any rotating.
             // This is based on the libp2p kad::kBuckets peers distribution.
             dialed_peers: CircularVec::new(255),
+            dial_backoff: DialBackoffTracker::new(self.dial_backoff),
+            replication_range: self.replication_range,
+            close_group_size: self.close_group_size,
             is_gossip_handler: false,
+            gossip_validators: self.gossip_validators,
+            non_relayed_gossip_topics: Default::default(),
             network_discovery: NetworkDiscovery::new(&peer_id),
             bootstrap_peers: Default::default(),
             live_connected_peers: Default::default(),
@@ -595,6 +754,8 @@ impl NetworkBuilder {
             hard_disk_write_error: 0,
             bad_nodes: Default::default(),
             bad_nodes_ongoing_verifications: Default::default(),
+            blocked_peers: Default::default(),
+            conn_stats: Default::default(),
         };
 
         Ok((
@@ -603,6 +764,11 @@ impl NetworkBuilder {
                 peer_id,
                 root_dir_path: self.root_dir,
                 keypair: self.keypair,
+                replication_range: self.replication_range,
+                close_group_size: self.close_group_size,
+                quote_selection: self.quote_selection,
+                #[cfg(feature = "open-metrics")]
+                metrics_registry,
             },
             network_event_receiver,
             swarm_driver,
@@ -632,12 +798,24 @@ pub struct SwarmDriver {
     pub(crate) pending_requests:
         HashMap<OutboundRequestId, Option<oneshot::Sender<Result<Response>>>>,
     pub(crate) pending_get_record: PendingGetRecord,
+    /// The Kademlia bootstrap queries requested to refresh the RoutingTable.
+    pub(crate) pending_routing_table_refreshes: HashSet<QueryId>,
     /// A list of the most recent peers we have dialed ourselves.
     pub(crate) dialed_peers: CircularVec<PeerId>,
+    /// The number of peers closest to a record that it is replicated to, and accepted from.
+    pub(crate) replication_range: usize,
+    /// The number of peers closest to an address that are responsible for it.
+    pub(crate) close_group_size: usize,
+    /// The peers we have failed to dial, and are holding back redials to.
+    pub(crate) dial_backoff: DialBackoffTracker,
     // For normal nodes, though they subscribe to the gossip topic
     // (to ensure no miss-up by carrying out libp2p low level gossip forwarding),
     // they are not supposed to process the gossip msg that received from libp2p.
     pub(crate) is_gossip_handler: bool,
+    /// The application level checks of the gossip msgs received, per topic.
+    pub(crate) gossip_validators: GossipValidators,
+    /// The gossip topics whose received msgs are delivered to us, but not forwarded to our peers.
+    pub(crate) non_relayed_gossip_topics: HashSet<String>,
     // A list of random `PeerId` candidates that falls into kbuckets,
     // This is to ensure a more accurate network discovery.
     pub(crate) network_discovery: NetworkDiscovery,
@@ -651,6 +829,10 @@ pub struct SwarmDriver {
     pub(crate) hard_disk_write_error: usize,
     pub(crate) bad_nodes: BTreeSet<PeerId>,
     pub(crate) bad_nodes_ongoing_verifications: BTreeSet<PeerId>,
+    /// Peers blocked by the user, that we neither dial nor keep connections with.
+    pub(crate) blocked_peers: HashSet<PeerId>,
+    /// The connection errors encountered, as reported through `SwarmCmd::GetConnectionStats`.
+    pub(crate) conn_stats: ConnStats,
 }
 
 impl SwarmDriver {
@@ -752,11 +934,21 @@ impl SwarmDriver {
     }
 
     /// Dials the given multiaddress. If address contains a peer ID, simultaneous
-    /// dials to that peer are prevented.
+    /// dials to that peer are prevented, as are dials to it while it is being backed off.
     pub(crate) fn dial(&mut self, mut addr: Multiaddr) -> Result<(), DialError> {
         trace!(%addr, "Dialing manually");
 
         let peer_id = multiaddr_pop_p2p(&mut addr);
+        if let Some(peer_id) = peer_id {
+            if self.blocked_peers.contains(&peer_id) {
+                trace!(%addr, "Not dialing {peer_id:?}, it is blocked");
+                return Ok(());
+            }
+            if self.dial_backoff.is_backing_off(&peer_id) {
+                trace!(%addr, "Not dialing {peer_id:?}, backing off after failed dials");
+                return Ok(());
+            }
+        }
         let opts = match peer_id {
             Some(peer_id) => DialOpts::peer_id(peer_id)
                 // If we have a peer ID, we can prevent simultaneous dials.
@@ -773,6 +965,17 @@ impl SwarmDriver {
     pub(crate) fn dial_with_opts(&mut self, opts: DialOpts) -> Result<(), DialError> {
         trace!(?opts, "Dialing manually");
 
+        if let Some(peer_id) = opts.get_peer_id() {
+            if self.blocked_peers.contains(&peer_id) {
+                trace!("Not dialing {peer_id:?}, it is blocked");
+                return Ok(());
+            }
+            if self.dial_backoff.is_backing_off(&peer_id) {
+                trace!("Not dialing {peer_id:?}, backing off after failed dials");
+                return Ok(());
+            }
+        }
+
         self.swarm.dial(opts)
     }
 
@@ -9,8 +9,8 @@
 use crate::{
     driver::{truncate_patch_version, PendingGetClosestType, SwarmDriver},
     error::{Error, Result},
+    gossip_validation::ValidationOutcome,
     multiaddr_is_global, multiaddr_strip_p2p, sort_peers_by_address, CLOSE_GROUP_SIZE,
-    REPLICATE_RANGE,
 };
 use bytes::Bytes;
 use core::fmt;
@@ -19,12 +19,13 @@ use itertools::Itertools;
 #[cfg(feature = "local-discovery")]
 use libp2p::mdns;
 use libp2p::{
+    gossipsub::MessageAcceptance,
     kad::{self, GetClosestPeersError, InboundRequest, QueryResult, Record, RecordKey, K_VALUE},
     multiaddr::Protocol,
     request_response::{self, Message, ResponseChannel as PeerResponseChannel},
     swarm::{
         dial_opts::{DialOpts, PeerCondition},
-        DialError, SwarmEvent,
+        DialError, ListenError, SwarmEvent,
     },
     Multiaddr, PeerId, TransportError,
 };
@@ -89,6 +90,13 @@ impl From<libp2p::gossipsub::Event> for NodeEvent {
     }
 }
 
+// The connection limits behaviour never emits any event.
+impl From<void::Void> for NodeEvent {
+    fn from(event: void::Void) -> Self {
+        void::unreachable(event)
+    }
+}
+
 #[derive(CustomDebug)]
 /// Channel to send the `Response` through.
 pub enum MsgResponder {
@@ -146,6 +154,19 @@ pub enum NetworkEvent {
         peer_id: PeerId,
         addrs: HashSet<Multiaddr>,
     },
+    /// A requested Kademlia bootstrap query, to refresh the RoutingTable, has completed.
+    RoutingTableRefreshed,
+    /// The record store filled past one of its capacity alert thresholds, or dropped back below it.
+    CapacityThresholdCrossed {
+        /// The fraction of the store capacity crossed
+        threshold: f32,
+        /// Whether the store filled past the threshold, rather than dropped back below it
+        upward: bool,
+        /// The bytes of the stored record values
+        used_bytes: u64,
+        /// The bytes the record values could fill the store with at most
+        max_bytes: u64,
+    },
 }
 
 // Manually implement Debug as `#[debug(with = "unverified_record_fmt")]` not working as expected.
@@ -193,6 +214,17 @@ impl Debug for NetworkEvent {
             NetworkEvent::BadNodeVerification { peer_id, .. } => {
                 write!(f, "NetworkEvent::BadNodeVerification({peer_id:?})")
             }
+            NetworkEvent::RoutingTableRefreshed => {
+                write!(f, "NetworkEvent::RoutingTableRefreshed")
+            }
+            NetworkEvent::CapacityThresholdCrossed {
+                threshold, upward, ..
+            } => {
+                write!(
+                    f,
+                    "NetworkEvent::CapacityThresholdCrossed({threshold}, upward: {upward})"
+                )
+            }
         }
     }
 }
@@ -223,6 +255,15 @@ impl SwarmDriver {
                     libp2p::identify::Event::Received { peer_id, info } => {
                         trace!(%peer_id, ?info, "identify: received info");
 
+                        if self.blocked_peers.contains(&peer_id) {
+                            trace!(%peer_id, "identify: ignoring blocked peer");
+                            trace!(
+                                "SwarmEvent handled in {:?}: {event_string:?}",
+                                start.elapsed()
+                            );
+                            return Ok(());
+                        }
+
                         let has_dialed = self.dialed_peers.contains(&peer_id);
                         let peer_is_agent = info
                             .agent_version
@@ -278,7 +319,7 @@ impl SwarmDriver {
                                 (true, None)
                             };
 
-                            if !kbucket_full {
+                            if !kbucket_full && !self.dial_backoff.is_backing_off(&peer_id) {
                                 info!(%peer_id, ?addrs, "received identify info from undialed peer for not full kbucket {:?}, dail back to confirm external accesable", ilog2);
                                 self.dialed_peers
                                     .push(peer_id)
@@ -376,20 +417,48 @@ impl SwarmDriver {
             SwarmEvent::Behaviour(NodeEvent::Gossipsub(event)) => {
                 event_string = "gossip";
 
-                if self.is_gossip_handler {
-                    match *event {
-                        libp2p::gossipsub::Event::Message {
-                            message,
-                            message_id,
-                            ..
-                        } => {
-                            info!("Gossipsub message received, id: {message_id:?}");
+                match *event {
+                    libp2p::gossipsub::Event::Message {
+                        message,
+                        message_id,
+                        propagation_source,
+                    } => {
+                        info!("Gossipsub message received, id: {message_id:?}");
+                        let outcome = self
+                            .gossip_validators
+                            .validate(message.topic.as_str(), &message.data);
+                        // a valid msg on a topic we don't relay is still delivered to us, but
+                        // ignoring it stops gossipsub from forwarding it to our peers
+                        let acceptance = if outcome == ValidationOutcome::Accept
+                            && self
+                                .non_relayed_gossip_topics
+                                .contains(message.topic.as_str())
+                        {
+                            MessageAcceptance::Ignore
+                        } else {
+                            outcome.into()
+                        };
+                        if let Some(gossip) = self.swarm.behaviour_mut().gossipsub.as_mut() {
+                            if let Err(err) = gossip.report_message_validation_result(
+                                &message_id,
+                                &propagation_source,
+                                acceptance,
+                            ) {
+                                warn!("Failed to report the validation of gossipsub message {message_id:?}: {err:?}");
+                            }
+                        }
+                        if outcome != ValidationOutcome::Accept {
+                            debug!("Gossipsub message {message_id:?} from {propagation_source:?} failed validation: {outcome:?}");
+                            return Ok(());
+                        }
+
+                        if self.is_gossip_handler {
                             let topic = message.topic.into_string();
                             let msg = Bytes::from(message.data);
                             self.send_event(NetworkEvent::GossipsubMsgReceived { topic, msg });
                         }
-                        other => trace!("Gossipsub Event has been ignored: {other:?}"),
                     }
+                    other => trace!("Gossipsub Event has been ignored: {other:?}"),
                 }
             }
             SwarmEvent::NewListenAddr { address, .. } => {
@@ -434,17 +503,24 @@ impl SwarmDriver {
             } => {
                 event_string = "ConnectionEstablished";
                 trace!(%peer_id, num_established, "ConnectionEstablished ({connection_id:?}): {}", endpoint_str(&endpoint));
-                // info!(%peer_id, ?connection_id, "ConnectionEstablished {:?}", self.swarm.network_info());
 
-                let _ = self.live_connected_peers.insert(
-                    connection_id,
-                    (peer_id, Instant::now() + Duration::from_secs(60)),
-                );
+                // info!(%peer_id, ?connection_id, "ConnectionEstablished {:?}", self.swarm.network_info());
 
-                if endpoint.is_dialer() {
-                    self.dialed_peers
-                        .push(peer_id)
-                        .map_err(|_| Error::CircularVecPopFrontError)?;
+                if self.blocked_peers.contains(&peer_id) {
+                    info!(%peer_id, "Closing connection ({connection_id:?}) with blocked peer");
+                    let _ = self.swarm.close_connection(connection_id);
+                } else {
+                    let _ = self.live_connected_peers.insert(
+                        connection_id,
+                        (peer_id, Instant::now() + Duration::from_secs(60)),
+                    );
+
+                    if endpoint.is_dialer() {
+                        self.dial_backoff.record_success(&peer_id);
+                        self.dialed_peers
+                            .push(peer_id)
+                            .map_err(|_| Error::CircularVecPopFrontError)?;
+                    }
                 }
             }
             SwarmEvent::ConnectionClosed {
@@ -466,6 +542,10 @@ impl SwarmDriver {
             } => {
                 event_string = "OutgoingConnErr";
                 warn!("OutgoingConnectionError to {failed_peer_id:?} on {connection_id:?} - {error:?}");
+                if !matches!(error, DialError::DialPeerConditionFalse(_)) {
+                    self.dial_backoff.record_failure(failed_peer_id);
+                }
+                self.conn_stats.record_dial_error(&error);
 
                 // we need to decide if this was a critical error and the peer should be removed from the routing table
                 let should_clean_peer = match error {
@@ -570,7 +650,12 @@ impl SwarmDriver {
             } => {
                 // info!("{:?}", self.swarm.network_info());
                 event_string = "Incoming ConnErr";
-                error!("IncomingConnectionError from local_addr:?{local_addr:?}, send_back_addr {send_back_addr:?} on {connection_id:?} with error {error:?}");
+                if let ListenError::Denied { cause } = &error {
+                    // e.g. refused as we are at our connection limits
+                    debug!("Refused the incoming connection from send_back_addr {send_back_addr:?} on {connection_id:?}: {cause}");
+                } else {
+                    error!("IncomingConnectionError from local_addr:?{local_addr:?}, send_back_addr {send_back_addr:?} on {connection_id:?} with error {error:?}");
+                }
             }
             SwarmEvent::Dialing {
                 peer_id,
@@ -648,6 +733,25 @@ impl SwarmDriver {
 
                             self.add_keys_to_replication_fetcher(holder, keys);
                         }
+                        Request::Cmd(sn_protocol::messages::Cmd::LabelRecord {
+                            address,
+                            label,
+                        }) => {
+                            let response = Response::Cmd(
+                                sn_protocol::messages::CmdResponse::LabelRecord(Ok(())),
+                            );
+                            self.swarm
+                                .behaviour_mut()
+                                .request_response
+                                .send_response(channel, response)
+                                .map_err(|_| Error::InternalMsgChannelDropped)?;
+
+                            self.swarm
+                                .behaviour_mut()
+                                .kademlia
+                                .store_mut()
+                                .label_record(address.to_record_key(), label);
+                        }
                         Request::Query(query) => {
                             self.send_event(NetworkEvent::QueryRequestReceived {
                                 query,
@@ -671,7 +775,11 @@ impl SwarmDriver {
                                 .send(Ok(response))
                                 .map_err(|_| Error::InternalMsgChannelDropped)?,
                             None => {
-                                if let Response::Cmd(CmdResponse::Replicate(Ok(()))) = response {
+                                if let Response::Cmd(
+                                    CmdResponse::Replicate(Ok(()))
+                                    | CmdResponse::LabelRecord(Ok(())),
+                                ) = response
+                                {
                                     // Nothing to do, response was fine
                                     // This only exists to ensure we dont drop the handle and
                                     // exit early, potentially logging false connection woes
@@ -695,6 +803,7 @@ impl SwarmDriver {
                 error,
                 peer,
             } => {
+                self.conn_stats.record_outbound_failure(&error);
                 if let Some(sender) = self.pending_requests.remove(&request_id) {
                     match sender {
                         Some(sender) => {
@@ -717,6 +826,7 @@ impl SwarmDriver {
                 request_id,
                 error,
             } => {
+                self.conn_stats.record_inbound_failure(&error);
                 warn!("RequestResponse: InboundFailure for request_id: {request_id:?} and peer: {peer:?}, with error: {error:?}");
             }
             request_response::Event::ResponseSent { peer, request_id } => {
@@ -927,7 +1037,7 @@ impl SwarmDriver {
                     PrettyPrintRecordKey::from(&put_record_ok.key)
                 );
             }
-            // Shall no longer receive this event
+            // Only received for the bootstrap queries requested via `SwarmCmd::RefreshRoutingTable`
             kad::Event::OutboundQueryProgressed {
                 id,
                 result: QueryResult::Bootstrap(bootstrap_result),
@@ -938,6 +1048,10 @@ impl SwarmDriver {
                 // here BootstrapOk::num_remaining refers to the remaining random peer IDs to query, one per
                 // bucket that still needs refreshing.
                 trace!("Kademlia Bootstrap with {id:?} progressed with {bootstrap_result:?} and step {step:?}");
+                if step.last && self.pending_routing_table_refreshes.remove(&id) {
+                    info!("RoutingTable refresh with {id:?} completed");
+                    self.send_event(NetworkEvent::RoutingTableRefreshed);
+                }
             }
             kad::Event::RoutingUpdated {
                 peer,
@@ -1180,7 +1294,7 @@ impl SwarmDriver {
 
     /// Checks suggested records against what we hold, so we only
     /// enqueue what we do not have
-    fn select_non_existent_records_for_replications(
+    pub(crate) fn select_non_existent_records_for_replications(
         &mut self,
         incoming_keys: &[(NetworkAddress, RecordType)],
         closest_k_peers: &Vec<PeerId>,
@@ -1233,12 +1347,12 @@ impl SwarmDriver {
     // Hence, the ilog2 calculation based on close_range cannot cover such case.
     // And have to sort all nodes to figure out whether self is among the close_group to the target.
     fn is_in_close_range(&self, target: &NetworkAddress, all_peers: &Vec<PeerId>) -> bool {
-        if all_peers.len() <= REPLICATE_RANGE {
+        if all_peers.len() <= self.replication_range {
             return true;
         }
 
         // Margin of 2 to allow our RT being bit lagging.
-        match sort_peers_by_address(all_peers, target, REPLICATE_RANGE) {
+        match sort_peers_by_address(all_peers, target, self.replication_range) {
             Ok(close_group) => close_group.contains(&&self.self_peer_id),
             Err(err) => {
                 warn!("Could not get sorted peers for {target:?} with error {err:?}");
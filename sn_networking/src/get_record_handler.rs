@@ -6,9 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{
-    get_quorum_value, Error, GetRecordCfg, GetRecordError, Result, SwarmDriver, CLOSE_GROUP_SIZE,
-};
+use crate::{get_quorum_value_in_group, Error, GetRecordCfg, GetRecordError, Result, SwarmDriver};
 use libp2p::{
     kad::{self, PeerRecord, ProgressStep, QueryId, QueryStats, Record},
     PeerId,
@@ -95,7 +93,8 @@ impl SwarmDriver {
                     1
                 };
 
-            let expected_answers = get_quorum_value(&cfg.get_quorum);
+            let expected_answers =
+                get_quorum_value_in_group(&cfg.get_quorum, self.close_group_size);
 
             trace!("Expecting {expected_answers:?} answers for record {pretty_key:?} task {query_id:?}, received {responded_peers} so far");
 
@@ -121,7 +120,7 @@ impl SwarmDriver {
                 if let Some(mut query) = self.swarm.behaviour_mut().kademlia.query_mut(&query_id) {
                     query.finish();
                 }
-            } else if usize::from(step.count) >= CLOSE_GROUP_SIZE {
+            } else if usize::from(step.count) >= self.close_group_size {
                 debug!("For record {pretty_key:?} task {query_id:?}, got {:?} with {} versions so far.",
                    step.count, result_map.len());
             }
@@ -156,7 +155,7 @@ impl SwarmDriver {
                 let result = if num_of_versions == 1 {
                     Err(GetRecordError::NotEnoughCopies {
                         record: record.clone(),
-                        expected: get_quorum_value(&cfg.get_quorum),
+                        expected: get_quorum_value_in_group(&cfg.get_quorum, self.close_group_size),
                         got: from_peers.len(),
                     })
                 } else {
@@ -246,7 +245,8 @@ impl SwarmDriver {
                         }
                     })?;
 
-                let required_response_count = get_quorum_value(&cfg.get_quorum);
+                let required_response_count =
+                    get_quorum_value_in_group(&cfg.get_quorum, self.close_group_size);
 
                 // if we've a split over the result xorname, then we don't attempt to resolve this here.
                 // Retry and resolve through normal flows without a timeout.
@@ -0,0 +1,108 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use libp2p::gossipsub::MessageAcceptance;
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// The verdict of a gossip validator on a received message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationOutcome {
+    /// The message is valid, it is delivered and forwarded to our peers.
+    Accept,
+    /// The message is invalid, it is dropped and the peer that sent it is penalised.
+    Reject,
+    /// The message is dropped, without penalising the peer that sent it.
+    Ignore,
+}
+
+impl From<ValidationOutcome> for MessageAcceptance {
+    fn from(outcome: ValidationOutcome) -> Self {
+        match outcome {
+            ValidationOutcome::Accept => MessageAcceptance::Accept,
+            ValidationOutcome::Reject => MessageAcceptance::Reject,
+            ValidationOutcome::Ignore => MessageAcceptance::Ignore,
+        }
+    }
+}
+
+/// A function validating the payload of a gossip message.
+type ValidatorFn = Arc<dyn Fn(&[u8]) -> ValidationOutcome + Send + Sync>;
+
+/// An application level check of the payload of the gossip messages received on a topic.
+#[derive(Clone)]
+pub struct GossipValidator(ValidatorFn);
+
+impl GossipValidator {
+    /// Wraps the function validating the payload of a message.
+    pub fn new(validate: impl Fn(&[u8]) -> ValidationOutcome + Send + Sync + 'static) -> Self {
+        Self(Arc::new(validate))
+    }
+
+    /// Runs the validator against the payload of a message.
+    pub fn validate(&self, data: &[u8]) -> ValidationOutcome {
+        (self.0)(data)
+    }
+}
+
+impl fmt::Debug for GossipValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GossipValidator").finish()
+    }
+}
+
+/// The validators registered per gossip topic.
+///
+/// Gossipsub holds back the received messages until they are reported on, hence the messages on
+/// topics without a validator are accepted as they are.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GossipValidators(HashMap<String, GossipValidator>);
+
+impl GossipValidators {
+    pub(crate) fn insert(&mut self, topic: String, validator: GossipValidator) {
+        let _ = self.0.insert(topic, validator);
+    }
+
+    /// Validates a message received on the topic.
+    pub(crate) fn validate(&self, topic: &str, data: &[u8]) -> ValidationOutcome {
+        self.0
+            .get(topic)
+            .map_or(ValidationOutcome::Accept, |validator| {
+                validator.validate(data)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_validated_against_their_topic_validator() {
+        let mut validators = GossipValidators::default();
+        validators.insert(
+            "topic".to_string(),
+            GossipValidator::new(|data| {
+                if data.is_empty() {
+                    ValidationOutcome::Reject
+                } else {
+                    ValidationOutcome::Accept
+                }
+            }),
+        );
+
+        assert_eq!(validators.validate("topic", &[]), ValidationOutcome::Reject);
+        assert_eq!(
+            validators.validate("topic", &[1]),
+            ValidationOutcome::Accept
+        );
+        assert_eq!(
+            validators.validate("other_topic", &[]),
+            ValidationOutcome::Accept
+        );
+    }
+}
@@ -12,18 +12,26 @@ extern crate tracing;
 mod bootstrap;
 mod circular_vec;
 mod cmd;
+mod connection_limits;
+mod dial_backoff;
 mod driver;
 mod error;
 mod event;
 mod get_record_handler;
+mod gossip_validation;
 #[cfg(feature = "open-metrics")]
 mod metrics;
 #[cfg(feature = "open-metrics")]
 mod metrics_service;
 mod network_discovery;
+mod quote_selection;
+mod record_compression;
+mod record_labels;
 mod record_store;
 mod record_store_api;
+mod record_store_backend;
 mod replication_fetcher;
+mod routing_table;
 pub mod target_arch;
 mod transfers;
 
@@ -31,15 +39,25 @@ mod transfers;
 pub use target_arch::{interval, sleep, spawn, Instant, Interval};
 
 pub use self::{
-    cmd::SwarmLocalState,
-    driver::{GetRecordCfg, NetworkBuilder, PutRecordCfg, SwarmDriver, VerificationKind},
+    cmd::{ConnStats, SwarmLocalState},
+    connection_limits::ConnectionLimits,
+    dial_backoff::DialBackoff,
+    driver::{
+        GetRecordCfg, NetworkBuilder, PutRecordCfg, SwarmDriver, VerificationKind,
+        GOSSIP_HEARTBEAT_INTERVAL_MIN,
+    },
     error::{Error, GetRecordError},
     event::{MsgResponder, NetworkEvent},
-    record_store::NodeRecordStore,
+    gossip_validation::{GossipValidator, ValidationOutcome},
+    quote_selection::{PeerConnectionQuality, QuoteSelection},
+    record_compression::CompressionAlgo,
+    record_store::{AccessStats, NodeRecordStore, RecordAgeStats, MAX_RECORDS_COUNT},
+    record_store_backend::{DiskRecordStoreBackend, MemoryRecordStoreBackend, RecordStoreBackend},
+    routing_table::{KBucketEntry, RoutingDiff, RoutingTableSnapshot},
     transfers::get_singed_spends_from_record,
 };
 
-use self::{cmd::SwarmCmd, error::Result};
+use self::{cmd::SwarmCmd, error::Result, quote_selection::select_well_connected_quote};
 use backoff::{Error as BackoffError, ExponentialBackoff};
 use bytes::Bytes;
 use futures::future::select_all;
@@ -52,14 +70,14 @@ use libp2p::{
 use rand::Rng;
 use sn_protocol::{
     error::Error as ProtocolError,
-    messages::{ChunkProof, Nonce, Query, QueryResponse, Request, Response},
+    messages::{ChunkProof, Cmd, Nonce, Query, QueryResponse, Request, Response},
     storage::{RecordType, RetryStrategy},
     NetworkAddress, PrettyPrintKBucketKey, PrettyPrintRecordKey,
 };
 use sn_transfers::{MainPubkey, NanoTokens, PaymentQuote};
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use tokio::sync::{
     mpsc::{self, Sender},
@@ -91,6 +109,12 @@ pub const fn close_group_majority() -> usize {
     CLOSE_GROUP_SIZE / 2 + 1
 }
 
+/// Majority of a close group of the given size (i.e. > 1/2).
+#[inline]
+pub const fn majority_of(close_group_size: usize) -> usize {
+    close_group_size / 2 + 1
+}
+
 /// Max duration to wait for verification.
 const MAX_WAIT_BEFORE_READING_A_PUT: Duration = Duration::from_millis(750);
 /// Min duration to wait for verification
@@ -114,14 +138,26 @@ pub fn sort_peers_by_key<'a, T>(
     peers: &'a Vec<PeerId>,
     key: &KBucketKey<T>,
     expected_entries: usize,
+) -> Result<Vec<&'a PeerId>> {
+    sort_peers_by_key_requiring(peers, key, expected_entries, CLOSE_GROUP_SIZE)
+}
+
+/// Sort the provided peers by their distance to the given `KBucketKey`, erroring if there are
+/// fewer than `required` peers.
+#[allow(clippy::result_large_err)]
+fn sort_peers_by_key_requiring<'a, T>(
+    peers: &'a Vec<PeerId>,
+    key: &KBucketKey<T>,
+    expected_entries: usize,
+    required: usize,
 ) -> Result<Vec<&'a PeerId>> {
     // Check if there are enough peers to satisfy the request.
     // bail early if that's not the case
-    if CLOSE_GROUP_SIZE > peers.len() {
+    if required > peers.len() {
         warn!("Not enough peers in the k-bucket to satisfy the request");
         return Err(Error::NotEnoughPeers {
             found: peers.len(),
-            required: CLOSE_GROUP_SIZE,
+            required,
         });
     }
 
@@ -155,14 +191,47 @@ pub struct Network {
     pub peer_id: PeerId,
     pub root_dir_path: PathBuf,
     keypair: Keypair,
+    replication_range: usize,
+    close_group_size: usize,
+    quote_selection: QuoteSelection,
+    #[cfg(feature = "open-metrics")]
+    metrics_registry: metrics_service::SharedRegistry,
 }
 
 impl Network {
+    /// The number of peers closest to a record that it is replicated to, and accepted from.
+    pub fn replication_range(&self) -> usize {
+        self.replication_range
+    }
+
+    /// The number of peers closest to an address that are responsible for it, i.e. queried for
+    /// its store costs and records.
+    pub fn close_group_size(&self) -> usize {
+        self.close_group_size
+    }
+
+    /// Majority of our close group (i.e. > 1/2).
+    pub fn close_group_majority(&self) -> usize {
+        majority_of(self.close_group_size)
+    }
+
+    /// Renders the current metrics to the OpenMetrics text exposition format, regardless of the
+    /// metrics server being reachable.
+    #[cfg(feature = "open-metrics")]
+    pub fn metrics_snapshot(&self) -> Result<String> {
+        metrics_service::encode_metrics(&self.metrics_registry)
+    }
+
     /// Signs the given data with the node's keypair.
     pub fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
         self.keypair.sign(msg).map_err(Error::from)
     }
 
+    /// The public key of the node's keypair, e.g. for others to verify what it signed.
+    pub fn public_key(&self) -> libp2p::identity::PublicKey {
+        self.keypair.public()
+    }
+
     /// Verifies a signature for the given data and the node's public key.
     pub fn verify(&self, msg: &[u8], sig: &[u8]) -> bool {
         self.keypair.public().verify(msg, sig)
@@ -200,6 +269,33 @@ impl Network {
             .map_err(|_e| Error::InternalMsgChannelDropped)
     }
 
+    /// Returns the entry of the peer in the local RoutingTable, if it is in there.
+    pub async fn get_kbucket_entry(&self, peer_id: PeerId) -> Result<Option<KBucketEntry>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetKBucketEntry { peer_id, sender });
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
+    /// Returns how well we are connected to each of the peers.
+    pub async fn get_peers_connection_quality(
+        &self,
+        peers: Vec<PeerId>,
+    ) -> Result<HashMap<PeerId, PeerConnectionQuality>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetPeersConnectionQuality { peers, sender });
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
+    /// Returns a `RoutingTableSnapshot` of the current state of the local RoutingTable.
+    /// Does not include self
+    pub async fn get_routing_table_snapshot(&self) -> Result<RoutingTableSnapshot> {
+        Ok(RoutingTableSnapshot::new(self.get_kbuckets().await?))
+    }
+
     /// Returns the closest peers to the given `NetworkAddress` that is fetched from the local
     /// Routing Table. It is ordered by increasing distance of the peers
     /// Note self peer_id is not included in the result.
@@ -276,7 +372,7 @@ impl Network {
             .unwrap_or(0);
 
         let pretty_key = PrettyPrintRecordKey::from(&chunk_address.to_record_key()).into_owned();
-        let expected_n_verified = get_quorum_value(&quorum);
+        let expected_n_verified = get_quorum_value_in_group(&quorum, self.close_group_size);
 
         let mut close_nodes = Vec::new();
         let mut retry_attempts = 0;
@@ -337,15 +433,16 @@ impl Network {
         Err(Error::FailedToVerifyChunkProof(chunk_address.clone()))
     }
 
-    /// Get the store costs from the majority of the closest peers to the provided RecordKey.
-    /// Record already exists will have a cost of zero to be returned.
-    pub async fn get_store_costs_from_network(
+    /// Get the store cost quotes of the closest peers to the provided address, along with the
+    /// address and payment key of each quoting peer. A peer already holding the record quotes
+    /// a `PaymentQuote::zero()`.
+    pub async fn get_store_cost_quotes(
         &self,
-        record_address: NetworkAddress,
-    ) -> Result<PayeeQuote> {
-        // The requirement of having at least CLOSE_GROUP_SIZE
+        record_address: &NetworkAddress,
+    ) -> Result<Vec<(NetworkAddress, MainPubkey, PaymentQuote)>> {
+        // The requirement of having at least `close_group_size`
         // close nodes will be checked internally automatically.
-        let close_nodes = self.get_closest_peers(&record_address, true).await?;
+        let close_nodes = self.get_closest_peers(record_address, true).await?;
 
         let request = Request::Query(Query::GetStoreCost(record_address.clone()));
         let responses = self
@@ -380,6 +477,18 @@ impl Network {
             }
         }
 
+        Ok(all_costs)
+    }
+
+    /// Get the store costs from the majority of the closest peers to the provided RecordKey,
+    /// returning the quote picked by the `QuoteSelection` the network was built with.
+    /// Record already exists will have a cost of zero to be returned.
+    pub async fn get_store_costs_from_network(
+        &self,
+        record_address: NetworkAddress,
+    ) -> Result<PayeeQuote> {
+        let mut all_costs = self.get_store_cost_quotes(&record_address).await?;
+
         // Sort all_costs by the NetworkAddress proximity to record_address
         all_costs.sort_by(|(peer_address_a, _, _), (peer_address_b, _, _)| {
             record_address
@@ -390,9 +499,39 @@ impl Network {
         // Ensure we dont have any further out nodes than `close_group_majority()`
         // This should ensure that if we didnt get all responses from close nodes,
         // we're less likely to be paying a node that is not in the CLOSE_GROUP
-        let all_costs = all_costs.into_iter().take(close_group_majority()).collect();
+        let all_costs: Vec<_> = all_costs
+            .into_iter()
+            .take(self.close_group_majority())
+            .collect();
+
+        match self.quote_selection {
+            QuoteSelection::Cheapest => get_fees_from_store_cost_responses(all_costs),
+            QuoteSelection::WellConnected {
+                max_premium_percent,
+            } => {
+                let mut quotes: Vec<PayeeQuote> = all_costs
+                    .into_iter()
+                    .filter_map(|(peer_address, payment_address, quote)| {
+                        Some((peer_address.as_peer_id()?, payment_address, quote))
+                    })
+                    .collect();
+                // sort by cost, lowest to highest, breaking ties by peer as the cheapest path does
+                quotes.sort_by(|(peer_a, _, quote_a), (peer_b, _, quote_b)| {
+                    match quote_a.cost.cmp(&quote_b.cost) {
+                        std::cmp::Ordering::Equal => NetworkAddress::from_peer(*peer_a)
+                            .cmp(&NetworkAddress::from_peer(*peer_b)),
+                        other => other,
+                    }
+                });
+                let peers = quotes.iter().map(|(peer_id, ..)| *peer_id).collect();
+                let qualities = self.get_peers_connection_quality(peers).await?;
 
-        get_fees_from_store_cost_responses(all_costs)
+                let payee = select_well_connected_quote(quotes, &qualities, max_premium_percent)
+                    .ok_or(Error::NoStoreCostResponses)?;
+                info!("Final fees selected from a well connected peer as: {payee:?}");
+                Ok(payee)
+            }
+        }
     }
 
     /// Subscribe to given gossipsub topic
@@ -405,6 +544,12 @@ impl Network {
         self.send_swarm_cmd(SwarmCmd::GossipsubUnsubscribe(topic_id));
     }
 
+    /// Set whether the msgs received on a given topic are forwarded to our peers.
+    /// The msgs are still received while relaying is disabled, and ours still published.
+    pub fn set_gossip_topic_relay(&self, topic_id: String, relay: bool) {
+        self.send_swarm_cmd(SwarmCmd::SetGossipTopicRelay { topic_id, relay });
+    }
+
     /// Publish a msg on a given topic
     pub fn publish_on_topic(&self, topic_id: String, msg: Bytes) {
         self.send_swarm_cmd(SwarmCmd::GossipsubPublish { topic_id, msg });
@@ -548,6 +693,36 @@ impl Network {
             .map_err(|_e| Error::InternalMsgChannelDropped)
     }
 
+    /// Disconnect the peer and refuse any further connection with it, until it is unblocked.
+    pub fn block_peer(&self, peer_id: PeerId) {
+        self.send_swarm_cmd(SwarmCmd::BlockPeer { peer_id });
+    }
+
+    /// Accept connections with a previously blocked peer again.
+    pub fn unblock_peer(&self, peer_id: PeerId) {
+        self.send_swarm_cmd(SwarmCmd::UnblockPeer { peer_id });
+    }
+
+    /// Returns the peers that are currently blocked.
+    pub async fn get_blocked_peers(&self) -> Result<HashSet<PeerId>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetBlockedPeers { sender });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
+    /// Returns the connection error counters, resetting them afterwards if `reset` is set.
+    pub async fn get_connection_stats(&self, reset: bool) -> Result<ConnStats> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetConnectionStats { reset, sender });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
     /// Put `Record` to network
     /// Optionally verify the record is stored after putting it to network
     /// If verify is on, retry multiple times within MAX_PUT_RETRY_DURATION duration.
@@ -654,9 +829,26 @@ impl Network {
                 }
             }
         }
+        if let (Ok(()), Some(label)) = (&response, &cfg.label) {
+            let address = NetworkAddress::from_record_key(&record_key);
+            if let Err(err) = self.label_record(address, label.clone()).await {
+                warn!("Failed to label record {pretty_key:?} with {label:?}: {err:?}");
+            }
+        }
         response
     }
 
+    /// Asks the closest peers of the record to index it under the given label. This is best-effort,
+    /// the peers are not awaited for a response.
+    async fn label_record(&self, address: NetworkAddress, label: String) -> Result<()> {
+        let close_peers = self.get_closest_peers(&address, true).await?;
+        let request = Request::Cmd(Cmd::LabelRecord { address, label });
+        for peer in close_peers {
+            self.send_req_ignore_reply(request.clone(), peer);
+        }
+        Ok(())
+    }
+
     /// Put `Record` to the local RecordStore
     /// Must be called after the validations are performed on the Record
     pub fn put_local_record(&self, record: Record) {
@@ -693,6 +885,102 @@ impl Network {
             .map_err(|_e| Error::InternalMsgChannelDropped)
     }
 
+    /// Removes the files of the local RecordStore that don't back any record anymore.
+    /// Returns the number of bytes reclaimed.
+    pub async fn compact_record_store(&self) -> Result<u64> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::CompactRecordStore { sender });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
+    /// Returns the number of records pending replication to us, queued or being fetched.
+    pub async fn get_replication_queue_depth(&self) -> Result<usize> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetReplicationQueueDepth { sender });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
+    /// Returns the addresses of the given records of our peers that we are among the peers
+    /// responsible for, but the local RecordStore doesn't hold.
+    pub async fn get_missing_responsible_records(
+        &self,
+        keys: Vec<(NetworkAddress, RecordType)>,
+    ) -> Result<Vec<NetworkAddress>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetMissingResponsibleRecords { keys, sender });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
+    /// Returns how long the records of the local RecordStore have been held for.
+    pub async fn get_record_age_stats(&self) -> Result<RecordAgeStats> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetRecordAgeStats { sender });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
+    /// Returns how often the record has been served from the local RecordStore since the node
+    /// started, or `None` if it hasn't been or isn't held.
+    pub async fn get_record_access_stats(&self, key: RecordKey) -> Result<Option<AccessStats>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetRecordAccessStats { key, sender });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
+    /// Exempt the record from being pruned when the local RecordStore is full.
+    pub fn pin_record(&self, key: RecordKey) {
+        self.send_swarm_cmd(SwarmCmd::PinRecord { key });
+    }
+
+    /// Let a previously pinned record be pruned again when the local RecordStore is full.
+    pub fn unpin_record(&self, key: RecordKey) {
+        self.send_swarm_cmd(SwarmCmd::UnpinRecord { key });
+    }
+
+    /// Persists the labels of the locally held records, and the times they were stored at, to the
+    /// root dir, to be loaded back on restart. Returns once they are written.
+    pub async fn checkpoint(&self) -> Result<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::Checkpoint {
+            root_dir: self.root_dir_path.clone(),
+            sender,
+        });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)?
+    }
+
+    /// Returns the addresses of the locally held records that were labelled with the given label.
+    pub async fn get_record_addresses_with_label(
+        &self,
+        label: &str,
+    ) -> Result<HashSet<NetworkAddress>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetRecordAddressesWithLabel {
+            label: label.to_string(),
+            sender,
+        });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
     /// Send `Request` to the given `PeerId` and await for the response. If `self` is the recipient,
     /// then the `Request` is forwarded to itself and handled, and a corresponding `Response` is created
     /// and returned to itself. Hence the flow remains the same and there is no branching at the upper
@@ -735,6 +1023,30 @@ impl Network {
         self.send_swarm_cmd(SwarmCmd::GossipHandler)
     }
 
+    /// Triggers a Kademlia bootstrap query to refresh the RoutingTable.
+    /// Returns once the query is requested, `NetworkEvent::RoutingTableRefreshed` is emitted on completion.
+    pub fn refresh_routing_table(&self) {
+        self.send_swarm_cmd(SwarmCmd::RefreshRoutingTable)
+    }
+
+    /// Triggers a Kademlia query for a random address within the bucket at the given ilog2 distance
+    /// from us, to discover peers for a sparsely populated bucket.
+    pub fn fill_bucket(&self, ilog2_distance: u32) {
+        self.send_swarm_cmd(SwarmCmd::FillBucket(ilog2_distance))
+    }
+
+    /// Re-advertises us to the peers closest to us, through a Kademlia self lookup, and sends them
+    /// the list of records we hold. Returns `false`, having done nothing, if our RoutingTable is
+    /// still empty, i.e. we are not bootstrapped yet.
+    pub async fn announce_presence(&self) -> Result<bool> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::AnnouncePresence { sender });
+
+        receiver
+            .await
+            .map_err(|_e| Error::InternalMsgChannelDropped)
+    }
+
     pub fn trigger_interval_replication(&self) {
         self.send_swarm_cmd(SwarmCmd::TriggerIntervalReplication)
     }
@@ -767,7 +1079,7 @@ impl Network {
         });
         let k_bucket_peers = receiver.await?;
 
-        // Count self in if among the CLOSE_GROUP_SIZE closest and sort the result
+        // Count self in if among the `close_group_size` closest and sort the result
         let mut closest_peers = k_bucket_peers;
         // ensure we're not including self here
         if client {
@@ -788,7 +1100,19 @@ impl Network {
             trace!("Network knowledge of close peers to {key:?} are: {close_peers_pretty_print:?}");
         }
 
-        let closest_peers = sort_peers_by_address(&closest_peers, key, CLOSE_GROUP_SIZE)?;
+        // a client can be configured with a close group smaller than `CLOSE_GROUP_SIZE`, to be
+        // usable on networks with fewer nodes than that
+        let required = if client {
+            self.close_group_size.min(CLOSE_GROUP_SIZE)
+        } else {
+            CLOSE_GROUP_SIZE
+        };
+        let closest_peers = sort_peers_by_key_requiring(
+            &closest_peers,
+            &key.as_kbucket_key(),
+            self.close_group_size,
+            required,
+        )?;
         Ok(closest_peers.into_iter().cloned().collect())
     }
 
@@ -869,9 +1193,14 @@ fn get_fees_from_store_cost_responses(
 
 /// Get the value of the provided Quorum
 pub fn get_quorum_value(quorum: &Quorum) -> usize {
+    get_quorum_value_in_group(quorum, CLOSE_GROUP_SIZE)
+}
+
+/// Get the value of the provided Quorum, for a close group of the given size
+pub fn get_quorum_value_in_group(quorum: &Quorum, close_group_size: usize) -> usize {
     match quorum {
-        Quorum::Majority => close_group_majority(),
-        Quorum::All => CLOSE_GROUP_SIZE,
+        Quorum::Majority => majority_of(close_group_size),
+        Quorum::All => close_group_size,
         Quorum::N(v) => v.get(),
         Quorum::One => 1,
     }
@@ -932,6 +1261,16 @@ pub(crate) fn send_swarm_cmd(swarm_cmd_sender: Sender<SwarmCmd>, cmd: SwarmCmd)
     });
 }
 
+/// Writes the bytes to a temp file next to `path`, then renames it over `path`, so that a crash
+/// midway leaves either the previous content or the new one, never a partial write.
+pub(crate) fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    std::fs::write(&tmp_path, bytes)?;
+    std::fs::rename(&tmp_path, path)
+}
+
 #[cfg(test)]
 mod tests {
     use eyre::bail;
@@ -939,6 +1278,23 @@ mod tests {
     use super::*;
     use sn_transfers::PaymentQuote;
 
+    #[test]
+    fn a_smaller_close_group_requires_fewer_peers() -> Result<()> {
+        let peers: Vec<PeerId> = (0..3).map(|_| PeerId::random()).collect();
+        let key = NetworkAddress::from_peer(PeerId::random()).as_kbucket_key();
+
+        // nodes always require a full close group
+        assert!(matches!(
+            sort_peers_by_key(&peers, &key, 3),
+            Err(Error::NotEnoughPeers { found: 3, required }) if required == CLOSE_GROUP_SIZE
+        ));
+
+        // while a client with a close group of 3 is satisfied by 3 peers
+        let sorted = sort_peers_by_key_requiring(&peers, &key, 3, 3)?;
+        assert_eq!(sorted.len(), 3);
+        Ok(())
+    }
+
     #[test]
     fn test_get_fee_from_store_cost_responses() -> Result<()> {
         // for a vec of different costs of CLOSE_GROUP size
@@ -23,6 +23,7 @@ pub(crate) struct NetworkMetrics {
 
     // metrics from sn_networking
     pub(crate) records_stored: Gauge,
+    pub(crate) replication_queue_depth: Gauge,
 
     // system info
     process_memory_used_mb: Gauge,
@@ -41,6 +42,13 @@ impl NetworkMetrics {
             records_stored.clone(),
         );
 
+        let replication_queue_depth = Gauge::default();
+        sub_registry.register(
+            "replication_queue_depth",
+            "The number of records pending replication to this node, queued or being fetched",
+            replication_queue_depth.clone(),
+        );
+
         let process_memory_used_mb = Gauge::default();
         sub_registry.register(
             "process_memory_used_mb",
@@ -58,6 +66,7 @@ impl NetworkMetrics {
         let network_metrics = Self {
             libp2p_metrics,
             records_stored,
+            replication_queue_depth,
             process_memory_used_mb,
             process_cpu_usage_percentage,
         };
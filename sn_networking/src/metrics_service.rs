@@ -18,12 +18,23 @@ use std::{
 
 const METRICS_CONTENT_TYPE: &str = "application/openmetrics-text;charset=utf-8;version=1.0.0";
 
-pub(crate) fn run_metrics_server(registry: Registry, port: u16) {
+pub(crate) type SharedRegistry = Arc<Mutex<Registry>>;
+
+pub(crate) fn run_metrics_server(registry: SharedRegistry, port: u16) {
     // The server should not bind to localhost/127.0.0.1 as it will not accept connections from containers.
     let addr = ([0, 0, 0, 0], port).into();
 
     tokio::spawn(async move {
-        let server = Server::bind(&addr).serve(MakeMetricService::new(registry));
+        // Not being able to bind (e.g. within a sandbox) shall not take the node down, as the
+        // metrics can still be obtained through `Network::metrics_snapshot`.
+        let builder = match Server::try_bind(&addr) {
+            Ok(builder) => builder,
+            Err(err) => {
+                error!("Failed to bind the metrics server to {addr:?}: {err}");
+                return;
+            }
+        };
+        let server = builder.serve(MakeMetricService::new(registry));
         info!("Metrics server on http://{}/metrics", server.local_addr());
         println!("Metrics server on http://{}/metrics", server.local_addr());
         // run the server forever
@@ -33,12 +44,21 @@ pub(crate) fn run_metrics_server(registry: Registry, port: u16) {
     });
 }
 
+/// Renders the registry to the OpenMetrics text exposition format.
+pub(crate) fn encode_metrics(registry: &SharedRegistry) -> Result<String> {
+    let reg = registry.lock().map_err(|_| Error::NetworkMetricError)?;
+    let mut metrics = String::new();
+    encode(&mut metrics, &reg).map_err(|err| {
+        error!("Failed to encode the metrics Registry {err:?}");
+        Error::NetworkMetricError
+    })?;
+    Ok(metrics)
+}
+
 pub(crate) struct MetricService {
     reg: Arc<Mutex<Registry>>,
 }
 
-type SharedRegistry = Arc<Mutex<Registry>>;
-
 impl MetricService {
     fn get_reg(&mut self) -> SharedRegistry {
         Arc::clone(&self.reg)
@@ -53,12 +73,7 @@ impl MetricService {
                 .map_err(|_| Error::NetworkMetricError)?,
         );
 
-        let reg = self.get_reg();
-        let reg = reg.lock().map_err(|_| Error::NetworkMetricError)?;
-        encode(&mut response.body_mut(), &reg).map_err(|err| {
-            error!("Failed to encode the metrics Registry {err:?}");
-            Error::NetworkMetricError
-        })?;
+        *response.body_mut() = encode_metrics(&self.get_reg())?;
 
         *response.status_mut() = StatusCode::OK;
 
@@ -110,10 +125,8 @@ pub(crate) struct MakeMetricService {
 }
 
 impl MakeMetricService {
-    pub(crate) fn new(registry: Registry) -> MakeMetricService {
-        MakeMetricService {
-            reg: Arc::new(Mutex::new(registry)),
-        }
+    pub(crate) fn new(registry: SharedRegistry) -> MakeMetricService {
+        MakeMetricService { reg: registry }
     }
 }
 
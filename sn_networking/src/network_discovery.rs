@@ -19,6 +19,10 @@ const INITIAL_GENERATION_ATTEMPTS: usize = 10_000;
 const GENERATION_ATTEMPTS: usize = 1_000;
 // The max number of PeerId to keep per bucket
 const MAX_PEERS_PER_BUCKET: usize = 5;
+// The max number of PeerId to generate while looking for a candidate of a given bucket
+const BUCKET_GENERATION_ATTEMPTS: usize = 100_000;
+// The ilog2 distance of the farthest bucket, for a 256 bits key space
+const MAX_BUCKET_ILOG2: u32 = 255;
 
 /// Keep track of NetworkAddresses belonging to every bucket (if we can generate them with reasonable effort)
 /// which we can then query using Kad::GetClosestPeers to effectively fill our RT.
@@ -98,6 +102,30 @@ impl NetworkDiscovery {
         op
     }
 
+    /// Returns a random candidate from the bucket at the given ilog2 distance. If we have none yet,
+    /// tries to generate one within that bucket.
+    ///
+    /// As the kbucket keys are hashes, a candidate can only be found by trial and the odds of
+    /// landing in a bucket halve with every step closer to us. Hence `None` is returned for the
+    /// closest buckets, which are better filled by a lookup of our own key, and for an `ilog2`
+    /// out of the 0..=255 range of the bucket indices.
+    pub(crate) fn candidate_for_bucket(&mut self, ilog2: u32) -> Option<NetworkAddress> {
+        if ilog2 > MAX_BUCKET_ILOG2 {
+            warn!("There is no bucket at ilog2 distance {ilog2}");
+            return None;
+        }
+
+        if let Some(candidates) = self.candidates.get(&ilog2) {
+            let random_index = thread_rng().gen::<usize>() % candidates.len();
+            return candidates.get(random_index).cloned();
+        }
+
+        let candidate =
+            Self::generate_candidate_for_bucket(&self.self_key, ilog2, BUCKET_GENERATION_ATTEMPTS)?;
+        self.insert_candidates(ilog2, vec![candidate.clone()]);
+        Some(candidate)
+    }
+
     /// Tries to refresh our current candidate list. We replace the old ones with new if we find any.
     fn try_refresh_candidates(&mut self) {
         let candidates_vec = Self::generate_candidates(&self.self_key, GENERATION_ATTEMPTS);
@@ -131,6 +159,20 @@ impl NetworkDiscovery {
         }
     }
 
+    /// Generates a candidate within the bucket at the given ilog2 distance from us, giving up after
+    /// `max_attempts`. Uses rayon to parallelize the generation.
+    fn generate_candidate_for_bucket(
+        self_key: &KBucketKey<PeerId>,
+        ilog2: u32,
+        max_attempts: usize,
+    ) -> Option<NetworkAddress> {
+        (0..max_attempts).into_par_iter().find_map_any(|_| {
+            let candidate = NetworkAddress::from_peer(PeerId::random());
+            let candidate_key = candidate.as_kbucket_key();
+            (candidate_key.distance(self_key).ilog2() == Some(ilog2)).then_some(candidate)
+        })
+    }
+
     /// Uses rayon to parallelize the generation
     fn generate_candidates(
         self_key: &KBucketKey<PeerId>,
@@ -171,3 +213,38 @@ impl NetworkDiscovery {
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidate_for_bucket_is_at_the_requested_distance() {
+        let self_peer_id = PeerId::random();
+        let self_key = KBucketKey::from(self_peer_id);
+        let mut network_discovery = NetworkDiscovery {
+            self_key: self_key.clone(),
+            candidates: BTreeMap::new(),
+        };
+
+        for ilog2 in [MAX_BUCKET_ILOG2, 250, 245] {
+            let candidate = network_discovery
+                .candidate_for_bucket(ilog2)
+                .expect("a candidate should be generated for a far bucket");
+            assert_eq!(
+                candidate.as_kbucket_key().distance(&self_key).ilog2(),
+                Some(ilog2)
+            );
+            assert!(network_discovery.candidates.contains_key(&ilog2));
+        }
+    }
+
+    #[test]
+    fn candidate_for_bucket_out_of_range_is_rejected() {
+        let mut network_discovery = NetworkDiscovery::new(&PeerId::random());
+        assert!(network_discovery
+            .candidate_for_bucket(MAX_BUCKET_ILOG2 + 1)
+            .is_none());
+        assert!(network_discovery.candidate_for_bucket(u32::MAX).is_none());
+    }
+}
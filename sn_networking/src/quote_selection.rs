@@ -0,0 +1,106 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::PayeeQuote;
+use libp2p::PeerId;
+use std::collections::HashMap;
+
+/// How the quote to pay is picked among the ones received from the peers closest to an address.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuoteSelection {
+    /// The cheapest quote.
+    #[default]
+    Cheapest,
+    /// The quote of the best connected peer, among the ones costing at most `max_premium_percent`
+    /// more than the cheapest. A well connected peer is likelier to keep the data available,
+    /// which durability sensitive uploads may be worth paying a bit more for.
+    WellConnected {
+        /// How much more than the cheapest quote, in percent, can be paid.
+        max_premium_percent: u64,
+    },
+}
+
+/// How well this peer is connected to another peer, as far as it can tell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerConnectionQuality {
+    /// Whether the peer is in the local RoutingTable.
+    pub in_routing_table: bool,
+    /// Whether a connection is established with the peer.
+    pub connected: bool,
+    /// The number of addresses of the peer in the RoutingTable, i.e. of routes to reach it.
+    pub known_addresses: usize,
+}
+
+impl PeerConnectionQuality {
+    /// Peers in the RoutingTable rank first, then the connected ones, then the ones with the
+    /// most addresses.
+    fn rank(&self) -> (bool, bool, usize) {
+        (self.in_routing_table, self.connected, self.known_addresses)
+    }
+}
+
+/// Picks the quote of the best connected peer among the ones within the premium of the cheapest
+/// quote. Ties go to the cheaper quote. `quotes` must be sorted from the cheapest.
+pub(crate) fn select_well_connected_quote(
+    quotes: Vec<PayeeQuote>,
+    qualities: &HashMap<PeerId, PeerConnectionQuality>,
+    max_premium_percent: u64,
+) -> Option<PayeeQuote> {
+    let cheapest = quotes.first()?.2.cost.as_nano();
+    let max_cost = cheapest.saturating_add(cheapest.saturating_mul(max_premium_percent) / 100);
+
+    let mut selected: Option<(PayeeQuote, PeerConnectionQuality)> = None;
+    for quote in quotes
+        .into_iter()
+        .take_while(|(_, _, quote)| quote.cost.as_nano() <= max_cost)
+    {
+        let quality = qualities.get(&quote.0).copied().unwrap_or_default();
+        if selected
+            .as_ref()
+            .is_none_or(|(_, best)| quality.rank() > best.rank())
+        {
+            selected = Some((quote, quality));
+        }
+    }
+    selected.map(|(quote, _)| quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_transfers::{MainPubkey, NanoTokens, PaymentQuote};
+
+    fn quote(cost: u64) -> PayeeQuote {
+        (
+            PeerId::random(),
+            MainPubkey::new(bls::SecretKey::random().public_key()),
+            PaymentQuote::test_dummy(Default::default(), NanoTokens::from(cost)),
+        )
+    }
+
+    #[test]
+    fn well_connected_quote_is_selected_within_the_premium() {
+        let quotes = vec![quote(100), quote(105), quote(120)];
+        let connected = PeerConnectionQuality {
+            in_routing_table: true,
+            connected: true,
+            known_addresses: 1,
+        };
+        let qualities = HashMap::from([(quotes[1].0, connected), (quotes[2].0, connected)]);
+
+        let selected = select_well_connected_quote(quotes.clone(), &qualities, 10);
+        assert_eq!(selected.map(|(peer_id, ..)| peer_id), Some(quotes[1].0));
+
+        // with no peer known to be better connected, the cheapest is kept
+        let selected = select_well_connected_quote(quotes.clone(), &HashMap::new(), 10);
+        assert_eq!(selected.map(|(peer_id, ..)| peer_id), Some(quotes[0].0));
+
+        let selected = select_well_connected_quote(quotes.clone(), &qualities, 0);
+        assert_eq!(selected.map(|(peer_id, ..)| peer_id), Some(quotes[0].0));
+    }
+}
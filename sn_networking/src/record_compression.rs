@@ -0,0 +1,98 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::io;
+
+/// The magic number starting every zstd frame.
+/// The records are serialised with a `RecordHeader` first, so they never start with it themselves.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// The zstd level the records are compressed with, `0` being zstd's default.
+#[cfg(not(target_arch = "wasm32"))]
+const ZSTD_LEVEL: i32 = 0;
+
+/// How the record values are compressed before being persisted.
+///
+/// Compression is only a storage concern: records are always handed out decompressed, whichever
+/// algorithm they were written with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompressionAlgo {
+    /// The records are persisted as they are.
+    #[default]
+    None,
+    /// The records are compressed with zstd, unless that doesn't make them any smaller, as is
+    /// the case of the self-encrypted chunks.
+    Zstd,
+}
+
+/// Compresses the value of a record with the algorithm, returning it as is if that doesn't make
+/// it smaller.
+pub(crate) fn compress(algo: CompressionAlgo, value: Vec<u8>) -> Vec<u8> {
+    match algo {
+        CompressionAlgo::None => value,
+        #[cfg(not(target_arch = "wasm32"))]
+        CompressionAlgo::Zstd => match zstd::stream::encode_all(value.as_slice(), ZSTD_LEVEL) {
+            Ok(compressed) if compressed.len() < value.len() => compressed,
+            Ok(_) => value,
+            Err(err) => {
+                warn!("Failed to compress a record, persisting it uncompressed: {err:?}");
+                value
+            }
+        },
+        #[cfg(target_arch = "wasm32")]
+        CompressionAlgo::Zstd => value,
+    }
+}
+
+/// Decompresses the persisted value of a record, if it was compressed.
+pub(crate) fn decompress(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    if bytes.starts_with(&ZSTD_MAGIC) {
+        zstd_decode(&bytes)
+    } else {
+        Ok(bytes)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn zstd_decode(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    zstd::stream::decode_all(bytes)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn zstd_decode(_bytes: &[u8]) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "zstd compressed records are not supported",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_records_are_decompressed() -> io::Result<()> {
+        let value = vec![7u8; 4096];
+
+        let compressed = compress(CompressionAlgo::Zstd, value.clone());
+        assert!(compressed.len() < value.len());
+
+        assert_eq!(decompress(compressed)?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn incompressible_and_uncompressed_records_are_kept_as_they_are() -> io::Result<()> {
+        let value: Vec<u8> = (0..=255).collect();
+
+        assert_eq!(compress(CompressionAlgo::Zstd, value.clone()), value);
+        assert_eq!(compress(CompressionAlgo::None, value.clone()), value);
+        assert_eq!(decompress(value.clone())?, value);
+        Ok(())
+    }
+}
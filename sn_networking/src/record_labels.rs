@@ -0,0 +1,156 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use libp2p::kad::RecordKey;
+use sn_protocol::{messages::MAX_RECORD_LABEL_LEN, PrettyPrintRecordKey};
+use std::{collections::HashMap, fs, io, path::Path};
+
+// The max number of records we keep a label for
+const MAX_LABELLED_RECORDS: usize = 16 * 1024;
+
+/// The file, in the root dir, the labels are checkpointed to.
+pub(crate) const RECORD_LABELS_FILENAME: &str = "record_labels";
+
+/// A best-effort side index of the client supplied labels of records.
+/// The labels are not part of the records, they only survive a restart if checkpointed.
+#[derive(Debug, Default)]
+pub(crate) struct RecordLabels {
+    labels: HashMap<RecordKey, String>,
+}
+
+impl RecordLabels {
+    /// Labels the record, replacing any previous label it had.
+    /// Returns false if the label is too long or if the index is full.
+    pub(crate) fn insert(&mut self, key: RecordKey, label: String) -> bool {
+        if label.len() > MAX_RECORD_LABEL_LEN {
+            debug!(
+                "Ignoring label of {} bytes for {:?}",
+                label.len(),
+                PrettyPrintRecordKey::from(&key)
+            );
+            return false;
+        }
+        if self.labels.len() >= MAX_LABELLED_RECORDS && !self.labels.contains_key(&key) {
+            debug!(
+                "Ignoring label for {:?}, already holding {MAX_LABELLED_RECORDS} labels",
+                PrettyPrintRecordKey::from(&key)
+            );
+            return false;
+        }
+
+        let _ = self.labels.insert(key, label);
+        true
+    }
+
+    /// Drops the label of the record, if it has one.
+    pub(crate) fn remove(&mut self, key: &RecordKey) {
+        let _ = self.labels.remove(key);
+    }
+
+    /// Loads the labels checkpointed in a previous run. A missing or unreadable file means no
+    /// record is labelled.
+    pub(crate) fn load(path: &Path) -> Self {
+        let mut record_labels = Self::default();
+        let Ok(bytes) = fs::read(path) else {
+            return record_labels;
+        };
+        match rmp_serde::from_slice::<Vec<(Vec<u8>, String)>>(&bytes) {
+            Ok(labels) => {
+                for (key, label) in labels {
+                    let _ = record_labels.insert(RecordKey::from(key), label);
+                }
+            }
+            Err(err) => warn!("Ignoring the unreadable record labels at {path:?}: {err:?}"),
+        }
+        record_labels
+    }
+
+    /// Serialises the labels, to be checkpointed with `load` reading them back.
+    pub(crate) fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let labels: Vec<(Vec<u8>, &String)> = self
+            .labels
+            .iter()
+            .map(|(key, label)| (key.to_vec(), label))
+            .collect();
+        rmp_serde::to_vec(&labels).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Returns the label of the record, if it has one.
+    pub(crate) fn label(&self, key: &RecordKey) -> Option<&str> {
+        self.labels.get(key).map(String::as_str)
+    }
+
+    /// Returns the keys of all the records with the given label.
+    pub(crate) fn keys_with_label<'a>(
+        &'a self,
+        label: &'a str,
+    ) -> impl Iterator<Item = &'a RecordKey> + 'a {
+        self.labels
+            .iter()
+            .filter(move |(_, record_label)| record_label.as_str() == label)
+            .map(|(key, _)| key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::write_atomically;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn labels_can_be_listed_and_replaced() {
+        let mut labels = RecordLabels::default();
+        let key_a = RecordKey::new(&b"a");
+        let key_b = RecordKey::new(&b"b");
+
+        assert!(labels.insert(key_a.clone(), "photos".to_string()));
+        assert!(labels.insert(key_b.clone(), "photos".to_string()));
+        assert_eq!(labels.keys_with_label("photos").count(), 2);
+
+        assert!(labels.insert(key_b.clone(), "docs".to_string()));
+        assert_eq!(
+            labels.keys_with_label("photos").collect::<Vec<_>>(),
+            vec![&key_a]
+        );
+        assert_eq!(
+            labels.keys_with_label("docs").collect::<Vec<_>>(),
+            vec![&key_b]
+        );
+
+        assert!(!labels.insert(key_a.clone(), "x".repeat(MAX_RECORD_LABEL_LEN + 1)));
+        assert_eq!(labels.keys_with_label("photos").count(), 1);
+
+        labels.remove(&key_a);
+        assert_eq!(labels.keys_with_label("photos").count(), 0);
+    }
+
+    #[test]
+    fn checkpointed_labels_are_loaded_back() -> io::Result<()> {
+        let mut labels = RecordLabels::default();
+        let key = RecordKey::new(&b"a");
+        assert!(labels.insert(key.clone(), "photos".to_string()));
+
+        let temp_dir = TempDir::new().expect("Should be able to create a temp dir.");
+        let path = temp_dir.path().join(RECORD_LABELS_FILENAME);
+        assert_eq!(
+            RecordLabels::load(&path).keys_with_label("photos").count(),
+            0
+        );
+
+        write_atomically(&path, &labels.to_bytes()?)?;
+        let loaded = RecordLabels::load(&path);
+        assert_eq!(
+            loaded.keys_with_label("photos").collect::<Vec<_>>(),
+            vec![&key]
+        );
+        // only the checkpoint is left, the temp file it was written to has been renamed
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+        Ok(())
+    }
+}
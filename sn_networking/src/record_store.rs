@@ -8,7 +8,14 @@
 #![allow(clippy::mutable_key_type)] // for the Bytes in NetworkAddress
 
 use crate::target_arch::{spawn, Instant};
-use crate::{cmd::SwarmCmd, event::NetworkEvent, send_swarm_cmd};
+use crate::{
+    cmd::SwarmCmd,
+    event::NetworkEvent,
+    record_compression::{self, CompressionAlgo},
+    record_labels::RecordLabels,
+    record_store_backend::{DiskRecordStoreBackend, RecordStoreBackend},
+    send_swarm_cmd,
+};
 use aes_gcm_siv::{
     aead::{Aead, KeyInit, OsRng},
     Aes256GcmSiv, Nonce,
@@ -31,24 +38,86 @@ use sn_protocol::{
 use sn_transfers::NanoTokens;
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
-    fs,
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
     vec,
 };
-use tokio::sync::mpsc;
-use walkdir::WalkDir;
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::Duration,
+};
 use xor_name::XorName;
 
 /// Max number of records a node can store
-const MAX_RECORDS_COUNT: usize = 2048;
+pub const MAX_RECORDS_COUNT: usize = 2048;
+
+/// The file, in the root dir, the times the records were stored at are checkpointed to.
+pub(crate) const RECORD_INSERTION_TIMES_FILENAME: &str = "record_insertion_times";
+
+/// The upper bounds of the age buckets of `RecordAgeStats::histogram`.
+const RECORD_AGE_BUCKETS: [Duration; 5] = [
+    Duration::from_secs(60),
+    Duration::from_secs(60 * 60),
+    Duration::from_secs(24 * 60 * 60),
+    Duration::from_secs(7 * 24 * 60 * 60),
+    Duration::from_secs(30 * 24 * 60 * 60),
+];
+
+/// How long the records held by the store have been held for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordAgeStats {
+    /// When the oldest record was stored.
+    pub oldest: Option<SystemTime>,
+    /// When the newest record was stored.
+    pub newest: Option<SystemTime>,
+    /// The number of records per age, keyed by the upper bound of their age bucket:
+    /// a minute, an hour, a day, a week, a month, and `Duration::MAX` for the older ones.
+    pub histogram: BTreeMap<Duration, usize>,
+}
+
+/// How often a record held by the store has been served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessStats {
+    /// The number of GET requests the record was served to.
+    pub gets_served: u64,
+    /// When the record was last served.
+    pub last_accessed: SystemTime,
+}
 
-/// A `RecordStore` that stores records on disk.
+impl RecordAgeStats {
+    /// Computes the stats of the records stored at the given times.
+    pub(crate) fn from_insertion_times(
+        insertion_times: impl Iterator<Item = SystemTime>,
+        now: SystemTime,
+    ) -> Self {
+        let mut stats = Self::default();
+        for inserted_at in insertion_times {
+            stats.oldest = Some(stats.oldest.map_or(inserted_at, |t| t.min(inserted_at)));
+            stats.newest = Some(stats.newest.map_or(inserted_at, |t| t.max(inserted_at)));
+
+            // records stored "in the future", e.g. after a clock change, are deemed brand new
+            let age = now.duration_since(inserted_at).unwrap_or_default();
+            let bucket = RECORD_AGE_BUCKETS
+                .into_iter()
+                .find(|bound| age < *bound)
+                .unwrap_or(Duration::MAX);
+            *stats.histogram.entry(bucket).or_default() += 1;
+        }
+        stats
+    }
+}
+
+/// A `RecordStore` that stores records on disk, or in the `RecordStoreBackend` it is given.
 pub struct NodeRecordStore {
     /// The identity of the peer owning the store.
     local_key: KBucketKey<PeerId>,
     /// The configuration of the store.
     config: NodeRecordStoreConfig,
+    /// Where the bytes of the records are persisted.
+    backend: Arc<dyn RecordStoreBackend>,
     /// A set of keys, each corresponding to a data `Record` stored on disk.
     records: HashMap<Key, (NetworkAddress, RecordType)>,
     /// Send network events to the node layer.
@@ -63,6 +132,23 @@ pub struct NodeRecordStore {
     record_count_metric: Option<Gauge>,
     /// Counting how many times got paid
     received_payment_count: usize,
+    /// The records that are never pruned, even when the store is full.
+    pinned_records: HashSet<Key>,
+    /// When each record was stored.
+    insertion_times: HashMap<Key, SystemTime>,
+    /// The client supplied labels of the records held.
+    record_labels: RecordLabels,
+    /// The size of each record value, before compression and encryption.
+    record_sizes: HashMap<Key, usize>,
+    /// The size of each record value being written, moved to `record_sizes` once it is stored.
+    pending_record_sizes: HashMap<Key, usize>,
+    /// How many of the `capacity_alert_thresholds` the store is filled past.
+    crossed_thresholds: usize,
+    /// Forwards the capacity threshold events to the node off thread, in the order they occur.
+    capacity_event_sender: Option<mpsc::UnboundedSender<NetworkEvent>>,
+    /// How often each record has been served since the node started, as the records are read
+    /// through `RecordStore::get`, which only takes `&self`.
+    access_stats: Mutex<HashMap<Key, AccessStats>>,
     /// Encyption cipher for the records, randomly generated at node startup
     /// Plus a 4 byte nonce starter
     encryption_details: (Aes256GcmSiv, [u8; 4]),
@@ -77,6 +163,10 @@ pub struct NodeRecordStoreConfig {
     pub max_records: usize,
     /// The maximum size of record values, in bytes.
     pub max_value_bytes: usize,
+    /// How the record values are compressed before being persisted.
+    pub compression: CompressionAlgo,
+    /// The fractions of the capacity, sorted ascending, past which the store notifies the node.
+    pub capacity_alert_thresholds: Vec<f32>,
 }
 
 impl Default for NodeRecordStoreConfig {
@@ -85,6 +175,8 @@ impl Default for NodeRecordStoreConfig {
             storage_dir: std::env::temp_dir(),
             max_records: MAX_RECORDS_COUNT,
             max_value_bytes: 65 * 1024,
+            compression: CompressionAlgo::None,
+            capacity_alert_thresholds: vec![],
         }
     }
 }
@@ -100,69 +192,45 @@ fn generate_nonce_for_record(nonce_starter: &[u8; 4], key: &Key) -> Nonce {
 }
 
 impl NodeRecordStore {
-    /// If a directory for our node already exists, repopulate the records from the files in the dir
+    /// If the backend already holds records, e.g. from a previous run, repopulate the records from it
+    /// The size of each record value is added to `record_sizes`.
     pub fn update_records_from_an_existing_store(
-        config: &NodeRecordStoreConfig,
+        backend: &dyn RecordStoreBackend,
         encryption_details: &(Aes256GcmSiv, [u8; 4]),
+        record_sizes: &mut HashMap<Key, usize>,
     ) -> HashMap<Key, (NetworkAddress, RecordType)> {
         let mut records = HashMap::default();
 
         info!("Attempting to repopulate records from existing store...");
-        for entry in WalkDir::new(&config.storage_dir)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if path.is_file() {
-                info!("Existing record found: {path:?}");
-                // if we've got a file, lets try and read it
-                if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                    // get the record key from the filename
-                    if let Some(key) = Self::get_data_from_filename(filename) {
-                        let record = match fs::read(path) {
-                            Ok(bytes) => {
-                                // and the stored record
-                                Self::get_record_from_bytes(bytes, &key, encryption_details)
-                            }
-                            Err(err) => {
-                                error!(
-                                    "Error while reading file. filename: {filename}, error: {err:?}"
-                                );
-                                None
-                            }
-                        };
-
-                        if let Some(record) = record {
-                            let record_type = match RecordHeader::is_record_of_type_chunk(&record) {
-                                Ok(true) => RecordType::Chunk,
-                                Ok(false) => {
-                                    let xorname_hash = XorName::from_content(&record.value);
-                                    RecordType::NonChunk(xorname_hash)
-                                }
-                                Err(error) => {
-                                    warn!("Failed to parse record type from record: {:?}", error);
-                                    continue;
-                                }
-                            };
+        for key in backend.keys() {
+            let pretty_key = PrettyPrintRecordKey::from(&key).into_owned();
+            info!("Existing record found: {pretty_key:?}");
+            // and the stored record
+            let record = match backend.get(&key) {
+                Ok(bytes) => Self::get_record_from_bytes(bytes, &key, encryption_details),
+                Err(err) => {
+                    error!("Error while reading record {pretty_key:?}, error: {err:?}");
+                    None
+                }
+            };
 
-                            let address = NetworkAddress::from_record_key(&key);
-                            records.insert(key, (address, record_type));
-                            info!("Existing record loaded: {path:?}");
-                        }
-                    };
-                } else {
-                    // warn and remove this file as it's not a valid record
-                    warn!(
-                        "Found a file in the storage dir that is not a valid record: {:?}",
-                        path
-                    );
-                    if let Err(e) = fs::remove_file(path) {
-                        warn!(
-                            "Failed to remove invalid record file from storage dir: {:?}",
-                            e
-                        );
+            if let Some(record) = record {
+                let record_type = match RecordHeader::is_record_of_type_chunk(&record) {
+                    Ok(true) => RecordType::Chunk,
+                    Ok(false) => {
+                        let xorname_hash = XorName::from_content(&record.value);
+                        RecordType::NonChunk(xorname_hash)
                     }
-                }
+                    Err(error) => {
+                        warn!("Failed to parse record type from record: {:?}", error);
+                        continue;
+                    }
+                };
+
+                let address = NetworkAddress::from_record_key(&key);
+                let _ = record_sizes.insert(key.clone(), record.value.len());
+                records.insert(key, (address, record_type));
+                info!("Existing record loaded: {pretty_key:?}");
             }
         }
 
@@ -175,6 +243,25 @@ impl NodeRecordStore {
         config: NodeRecordStoreConfig,
         network_event_sender: mpsc::Sender<NetworkEvent>,
         swarm_cmd_sender: mpsc::Sender<SwarmCmd>,
+    ) -> Self {
+        let backend = Arc::new(DiskRecordStoreBackend::new(config.storage_dir.clone()));
+        Self::with_backend(
+            local_id,
+            config,
+            backend,
+            network_event_sender,
+            swarm_cmd_sender,
+        )
+    }
+
+    /// Creates a new store persisting the records in the given backend.
+    /// The `storage_dir` of the config is not used.
+    pub fn with_backend(
+        local_id: PeerId,
+        config: NodeRecordStoreConfig,
+        backend: Arc<dyn RecordStoreBackend>,
+        network_event_sender: mpsc::Sender<NetworkEvent>,
+        swarm_cmd_sender: mpsc::Sender<SwarmCmd>,
     ) -> Self {
         let key = Aes256GcmSiv::generate_key(&mut OsRng);
         let cipher = Aes256GcmSiv::new(&key);
@@ -182,10 +269,21 @@ impl NodeRecordStore {
         OsRng.fill_bytes(&mut nonce_starter);
 
         let encryption_details = (cipher, nonce_starter);
-        let records = Self::update_records_from_an_existing_store(&config, &encryption_details);
+        let mut record_sizes = HashMap::new();
+        let records = Self::update_records_from_an_existing_store(
+            backend.as_ref(),
+            &encryption_details,
+            &mut record_sizes,
+        );
+        let now = SystemTime::now();
+        let insertion_times = records
+            .keys()
+            .map(|key| (key.clone(), backend.inserted_at(key).unwrap_or(now)))
+            .collect();
         NodeRecordStore {
             local_key: KBucketKey::from(local_id),
             config,
+            backend,
             records,
             network_event_sender,
             swarm_cmd_sender,
@@ -193,6 +291,14 @@ impl NodeRecordStore {
             #[cfg(feature = "open-metrics")]
             record_count_metric: None,
             received_payment_count: 0,
+            pinned_records: Default::default(),
+            insertion_times,
+            record_labels: Default::default(),
+            record_sizes,
+            pending_record_sizes: Default::default(),
+            crossed_thresholds: 0,
+            capacity_event_sender: None,
+            access_stats: Default::default(),
             encryption_details,
         }
     }
@@ -204,25 +310,97 @@ impl NodeRecordStore {
         self
     }
 
+    /// Restores the times the held records were stored at, as checkpointed in a previous run,
+    /// in place of the ones read from the backend. A missing or unreadable file restores none.
+    pub(crate) fn with_checkpointed_insertion_times(mut self, path: &Path) -> Self {
+        let Ok(bytes) = fs::read(path) else {
+            return self;
+        };
+        match rmp_serde::from_slice::<Vec<(Vec<u8>, SystemTime)>>(&bytes) {
+            Ok(insertion_times) => {
+                for (key, inserted_at) in insertion_times {
+                    let key = Key::from(key);
+                    if self.records.contains_key(&key) {
+                        let _ = self.insertion_times.insert(key, inserted_at);
+                    }
+                }
+            }
+            Err(err) => {
+                warn!("Ignoring the unreadable record insertion times at {path:?}: {err:?}")
+            }
+        }
+        self
+    }
+
+    /// Restores the labels of the held records, as checkpointed in a previous run.
+    /// A missing or unreadable file restores none.
+    pub(crate) fn with_checkpointed_labels(mut self, path: &Path) -> Self {
+        let record_labels = RecordLabels::load(path);
+        for key in self.records.keys() {
+            if let Some(label) = record_labels.label(key) {
+                let _ = self.record_labels.insert(key.clone(), label.to_string());
+            }
+        }
+        self
+    }
+
+    /// Labels the record, replacing any previous label it had. A record that is not held is not
+    /// labelled, the labels are dropped along with the records.
+    pub(crate) fn label_record(&mut self, key: Key, label: String) {
+        if !self.records.contains_key(&key) {
+            debug!(
+                "Not labelling {:?}, as we don't hold it",
+                PrettyPrintRecordKey::from(&key)
+            );
+            return;
+        }
+        let _ = self.record_labels.insert(key, label);
+    }
+
+    /// Returns the addresses of the held records that were labelled with the given label.
+    #[allow(clippy::mutable_key_type)] // for the Bytes in NetworkAddress
+    pub(crate) fn record_addresses_with_label(&self, label: &str) -> HashSet<NetworkAddress> {
+        self.record_labels
+            .keys_with_label(label)
+            .filter_map(|key| self.records.get(key).map(|(addr, _)| addr.clone()))
+            .collect()
+    }
+
+    /// Serialises the labels of the records, to be checkpointed with `with_checkpointed_labels`
+    /// reading them back.
+    pub(crate) fn record_labels_to_bytes(&self) -> io::Result<Vec<u8>> {
+        self.record_labels.to_bytes()
+    }
+
+    /// Serialises the times the records were stored at, to be checkpointed with
+    /// `with_checkpointed_insertion_times` reading them back.
+    pub(crate) fn insertion_times_to_bytes(&self) -> io::Result<Vec<u8>> {
+        let insertion_times: Vec<(Vec<u8>, SystemTime)> = self
+            .insertion_times
+            .iter()
+            .map(|(key, inserted_at)| (key.to_vec(), *inserted_at))
+            .collect();
+        rmp_serde::to_vec(&insertion_times)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
     /// Returns the current distance range
     pub fn get_distance_range(&self) -> Option<Distance> {
         self.distance_range
     }
 
     // Converts a Key into a Hex string.
-    fn generate_filename(key: &Key) -> String {
+    pub(crate) fn generate_filename(key: &Key) -> String {
         hex::encode(key.as_ref())
     }
 
-    // Converts a Hex string back into a Key.
-    fn get_data_from_filename(hex_str: &str) -> Option<Key> {
-        match hex::decode(hex_str) {
-            Ok(bytes) => Some(Key::from(bytes)),
-            Err(error) => {
-                error!("Error decoding hex string: {:?}", error);
-                None
-            }
-        }
+    /// The max number of records, bound by the config and the capacity of the backend.
+    fn max_records(&self) -> usize {
+        self.backend
+            .capacity()
+            .map_or(self.config.max_records, |capacity| {
+                capacity.min(self.config.max_records)
+            })
     }
 
     /// Upon read perform any data transformations required to return a `Record`.
@@ -238,37 +416,42 @@ impl NodeRecordStore {
             expires: None,
         };
 
-        // if we're not encrypting, lets just return the record
-        if !cfg!(feature = "encrypt-records") {
-            return Some(Cow::Owned(record));
-        }
+        // if we're encrypting, the record is compressed before being encrypted
+        if cfg!(feature = "encrypt-records") {
+            let (cipher, nonce_starter) = encryption_details;
+            let nonce = generate_nonce_for_record(nonce_starter, key);
 
-        let (cipher, nonce_starter) = encryption_details;
-        let nonce = generate_nonce_for_record(nonce_starter, key);
+            match cipher.decrypt(&nonce, record.value.as_ref()) {
+                Ok(value) => record.value = value,
+                Err(error) => {
+                    error!("Error while decrypting record. key: {key:?}: {error:?}");
+                    return None;
+                }
+            }
+        }
 
-        match cipher.decrypt(&nonce, record.value.as_ref()) {
+        match record_compression::decompress(record.value) {
             Ok(value) => {
                 record.value = value;
-                return Some(Cow::Owned(record));
+                Some(Cow::Owned(record))
             }
             Err(error) => {
-                error!("Error while decrypting record. key: {key:?}: {error:?}");
+                error!("Error while decompressing record. key: {key:?}: {error:?}");
                 None
             }
         }
     }
 
-    fn read_from_disk<'a>(
+    fn read_from_backend<'a>(
         encryption_details: &(Aes256GcmSiv, [u8; 4]),
         key: &Key,
-        storage_dir: &Path,
+        backend: &dyn RecordStoreBackend,
     ) -> Option<Cow<'a, Record>> {
         let start = Instant::now();
         let filename = Self::generate_filename(key);
-        let file_path = storage_dir.join(&filename);
 
         // we should only be reading if we know the record is written to disk properly
-        match fs::read(file_path) {
+        match backend.get(key) {
             Ok(bytes) => {
                 // vdash metric (if modified please notify at https://github.com/happybeing/vdash/issues):
                 info!(
@@ -287,16 +470,33 @@ impl NodeRecordStore {
 
     /// Prune the records in the store to ensure that we free up space
     /// for the incoming record.
-    fn prune_storage_if_needed_for_record(&mut self) {
+    /// Errors with `Error::MaxRecords` if the store is full of pinned records, leaving no room for
+    /// a record with the given key, unless it is already held.
+    fn prune_storage_if_needed_for_record(&mut self, key: &Key) -> Result<()> {
         let num_records = self.records.len();
 
         // we're not full, so we don't need to prune
-        if num_records < self.config.max_records {
-            return;
+        if num_records < self.max_records() {
+            return Ok(());
         }
 
-        // sort records by distance to our local key
-        let mut sorted_records: Vec<_> = self.records.keys().cloned().collect();
+        // sort records by distance to our local key, leaving out the pinned ones
+        let mut sorted_records: Vec<_> = self
+            .records
+            .keys()
+            .filter(|key| !self.pinned_records.contains(*key))
+            .cloned()
+            .collect();
+        if sorted_records.is_empty() {
+            // an update of a held record doesn't take any more room
+            if self.records.contains_key(key) {
+                return Ok(());
+            }
+            warn!("The store is full, but all its {num_records} records are pinned, none can be pruned");
+            return Err(Error::MaxRecords);
+        }
+        // sorting will be costive, hence pruning in a batch of 10
+        let prune_from = sorted_records.len().saturating_sub(10);
         let self_address = NetworkAddress::from_peer(self.local_key.clone().into_preimage());
         sorted_records.sort_by(|key_a, key_b| {
             let a = NetworkAddress::from_record_key(key_a);
@@ -305,17 +505,17 @@ impl NodeRecordStore {
         });
 
         let distance_range = self_address.distance(&NetworkAddress::from_record_key(
-            &sorted_records[sorted_records.len() - 10],
+            &sorted_records[prune_from],
         ));
         self.distance_range = Some(distance_range);
-        // sorting will be costive, hence pruning in a batch of 10
-        (sorted_records.len() - 10..sorted_records.len()).for_each(|i| {
+        (prune_from..sorted_records.len()).for_each(|i| {
             info!(
                 "Record {i} {:?} will be pruned to free up space for new records",
                 PrettyPrintRecordKey::from(&sorted_records[i])
             );
             self.remove(&sorted_records[i]);
         });
+        Ok(())
     }
 }
 
@@ -340,30 +540,136 @@ impl NodeRecordStore {
         &self.records
     }
 
+    /// Exempt the record from being pruned when the store is full.
+    /// The record doesn't have to be held yet.
+    pub(crate) fn pin_record(&mut self, key: Key) {
+        trace!("Pinning record {:?}", PrettyPrintRecordKey::from(&key));
+        let _ = self.pinned_records.insert(key);
+    }
+
+    /// Let the record be pruned again when the store is full.
+    pub(crate) fn unpin_record(&mut self, key: &Key) {
+        trace!("Unpinning record {:?}", PrettyPrintRecordKey::from(key));
+        let _ = self.pinned_records.remove(key);
+    }
+
+    /// Returns how often the record has been served, if it has been since the node started.
+    pub(crate) fn access_stats(&self, key: &Key) -> Option<AccessStats> {
+        self.access_stats.lock().ok()?.get(key).copied()
+    }
+
+    /// Reads the record from the backend, without accounting it as served, e.g. for the node's
+    /// own use of the record.
+    pub(crate) fn get_unserved(&self, k: &Key) -> Option<Cow<'_, Record>> {
+        if !self.records.contains_key(k) {
+            return None;
+        }
+        Self::read_from_backend(&self.encryption_details, k, self.backend.as_ref())
+    }
+
+    /// Returns how long the records held have been held for.
+    pub(crate) fn record_age_stats(&self) -> RecordAgeStats {
+        RecordAgeStats::from_insertion_times(
+            self.insertion_times.values().copied(),
+            SystemTime::now(),
+        )
+    }
+
     /// The follow up to `put_verified`, this only registers the RecordKey
     /// in the RecordStore records set. After this it should be safe
     /// to return the record as stored.
     pub(crate) fn mark_as_stored(&mut self, key: Key, record_type: RecordType) {
+        // an updated record, e.g. a register, keeps the time it was first stored
+        let _ = self
+            .insertion_times
+            .entry(key.clone())
+            .or_insert_with(SystemTime::now);
+        if let Some(size) = self.pending_record_sizes.remove(&key) {
+            let _ = self.record_sizes.insert(key.clone(), size);
+        }
         let _ = self.records.insert(
             key.clone(),
             (NetworkAddress::from_record_key(&key), record_type),
         );
+        self.check_capacity_thresholds();
+    }
+
+    /// Notifies the node of the capacity alert thresholds crossed since the last check, upward
+    /// when filling up, downward when records were removed.
+    fn check_capacity_thresholds(&mut self) {
+        let thresholds = &self.config.capacity_alert_thresholds;
+        if thresholds.is_empty() {
+            return;
+        }
+        let max_records = self.max_records();
+        let filled = self.records.len() as f32 / max_records.max(1) as f32;
+        let crossed = thresholds
+            .iter()
+            .filter(|threshold| filled >= **threshold)
+            .count();
+        if crossed == self.crossed_thresholds {
+            return;
+        }
+
+        let used_bytes = self.record_sizes.values().map(|size| *size as u64).sum();
+        let max_bytes = (max_records * self.config.max_value_bytes) as u64;
+        let events: Vec<_> = if crossed > self.crossed_thresholds {
+            thresholds[self.crossed_thresholds..crossed]
+                .iter()
+                .map(|threshold| (*threshold, true))
+                .collect()
+        } else {
+            thresholds[crossed..self.crossed_thresholds]
+                .iter()
+                .rev()
+                .map(|threshold| (*threshold, false))
+                .collect()
+        };
+        self.crossed_thresholds = crossed;
+
+        // a single forwarding task keeps the events of successive checks in order
+        let event_sender = self.capacity_event_sender.get_or_insert_with(|| {
+            let (sender, mut receiver) = mpsc::unbounded_channel();
+            let network_event_sender = self.network_event_sender.clone();
+            let _handle = spawn(async move {
+                while let Some(event) = receiver.recv().await {
+                    if let Err(error) = network_event_sender.send(event).await {
+                        error!("SwarmDriver failed to send event: {}", error);
+                    }
+                }
+            });
+            sender
+        });
+        for (threshold, upward) in events {
+            info!("Record store crossed the capacity threshold {threshold}, upward: {upward}, {used_bytes} of {max_bytes} bytes used");
+            let event = NetworkEvent::CapacityThresholdCrossed {
+                threshold,
+                upward,
+                used_bytes,
+                max_bytes,
+            };
+            if let Err(error) = event_sender.send(event) {
+                error!("Failed to forward the capacity threshold event: {error}");
+            }
+        }
     }
 
     /// Prepare record bytes for storage
-    /// If feats are enabled, this will eg, encrypt the record for storage
+    /// This compresses the record as configured and, if feats are enabled, eg encrypts it for storage
     fn prepare_record_bytes(
         record: Record,
         encryption_details: (Aes256GcmSiv, [u8; 4]),
+        compression: CompressionAlgo,
     ) -> Option<Vec<u8>> {
+        let value = record_compression::compress(compression, record.value);
         if !cfg!(feature = "encrypt-records") {
-            return Some(record.value);
+            return Some(value);
         }
 
         let (cipher, nonce_starter) = encryption_details;
         let nonce = generate_nonce_for_record(&nonce_starter, &record.key);
 
-        match cipher.encrypt(&nonce, record.value.as_ref()) {
+        match cipher.encrypt(&nonce, value.as_ref()) {
             Ok(value) => Some(value),
             Err(error) => {
                 warn!(
@@ -384,10 +690,13 @@ impl NodeRecordStore {
         let record_key = PrettyPrintRecordKey::from(&r.key).into_owned();
         trace!("PUT a verified Record: {record_key:?}");
 
-        self.prune_storage_if_needed_for_record();
+        self.prune_storage_if_needed_for_record(&r.key)?;
 
         let filename = Self::generate_filename(&r.key);
-        let file_path = self.config.storage_dir.join(&filename);
+        let backend = self.backend.clone();
+        let _ = self
+            .pending_record_sizes
+            .insert(r.key.clone(), r.value.len());
 
         #[cfg(feature = "open-metrics")]
         if let Some(metric) = &self.record_count_metric {
@@ -395,11 +704,12 @@ impl NodeRecordStore {
         }
 
         let encryption_details = self.encryption_details.clone();
+        let compression = self.config.compression;
         let cloned_cmd_sender = self.swarm_cmd_sender.clone();
         spawn(async move {
             let key = r.key.clone();
-            if let Some(bytes) = Self::prepare_record_bytes(r, encryption_details) {
-                let cmd = match fs::write(&file_path, bytes) {
+            if let Some(bytes) = Self::prepare_record_bytes(r, encryption_details, compression) {
+                let cmd = match backend.put(&key, bytes) {
                     Ok(_) => {
                         // vdash metric (if modified please notify at https://github.com/happybeing/vdash/issues):
                         info!("Wrote record {record_key:?} to disk! filename: {filename}");
@@ -421,6 +731,78 @@ impl NodeRecordStore {
         Ok(())
     }
 
+    /// Reclaims the space used in the backend by what doesn't back any record, e.g. the records
+    /// of a previous run, which can't be decrypted anymore, or the ones that failed to be removed.
+    /// Sends back the number of bytes reclaimed.
+    ///
+    /// The space is reclaimed off thread, so this can be run while the node keeps serving.
+    pub(crate) fn compact(&self, sender: oneshot::Sender<u64>) {
+        // the records being written are not marked as stored yet, but are just as live
+        let live_keys: HashSet<Key> = self
+            .records
+            .keys()
+            .chain(self.pending_record_sizes.keys())
+            .cloned()
+            .collect();
+        let backend = self.backend.clone();
+
+        let _handle = spawn(async move {
+            let reclaimed = backend.reclaim(&live_keys);
+            info!("Compacted the record store, reclaiming {reclaimed} bytes");
+            let _ = sender.send(reclaimed);
+        });
+    }
+
+    // Removes the files in the storage dir that are not in `live_filenames` and are older than
+    // `min_age`. Returns the number of bytes removed.
+    pub(crate) fn remove_dead_files(
+        storage_dir: &Path,
+        live_filenames: &HashSet<String>,
+        min_age: Duration,
+    ) -> u64 {
+        let entries = match fs::read_dir(storage_dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                error!("Failed to read the storage dir {storage_dir:?} for compaction: {err:?}");
+                return 0;
+            }
+        };
+
+        let mut reclaimed = 0;
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let is_live = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|filename| live_filenames.contains(filename));
+            if is_live {
+                continue;
+            }
+            let metadata = match entry.metadata() {
+                Ok(metadata) if metadata.is_file() => metadata,
+                _ => continue,
+            };
+            let is_old_enough = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age >= min_age);
+            if !is_old_enough {
+                continue;
+            }
+
+            let path = entry.path();
+            match fs::remove_file(&path) {
+                Ok(_) => {
+                    trace!("Removed dead record file {path:?} during compaction");
+                    reclaimed += metadata.len();
+                }
+                Err(err) => warn!("Failed to remove dead record file {path:?}: {err:?}"),
+            }
+        }
+
+        reclaimed
+    }
+
     /// Calculate the cost to store data for our current store state
     #[allow(clippy::mutable_key_type)]
     pub(crate) fn store_cost(&self) -> NanoTokens {
@@ -428,7 +810,7 @@ impl NodeRecordStore {
         let cost = calculate_cost_for_records(
             stored_records,
             self.received_payment_count,
-            self.config.max_records,
+            self.max_records(),
         );
 
         // vdash metric (if modified please notify at https://github.com/happybeing/vdash/issues):
@@ -489,7 +871,17 @@ impl RecordStore for NodeRecordStore {
 
         debug!("GET request for Record key: {key}");
 
-        Self::read_from_disk(&self.encryption_details, k, &self.config.storage_dir)
+        let record = Self::read_from_backend(&self.encryption_details, k, self.backend.as_ref())?;
+        if let Ok(mut access_stats) = self.access_stats.lock() {
+            let now = SystemTime::now();
+            let stats = access_stats.entry(k.clone()).or_insert(AccessStats {
+                gets_served: 0,
+                last_accessed: now,
+            });
+            stats.gets_served += 1;
+            stats.last_accessed = now;
+        }
+        Some(record)
     }
 
     fn put(&mut self, record: Record) -> Result<()> {
@@ -556,16 +948,25 @@ impl RecordStore for NodeRecordStore {
 
     fn remove(&mut self, k: &Key) {
         let _ = self.records.remove(k);
+        let _ = self.insertion_times.remove(k);
+        self.record_labels.remove(k);
+        let _ = self.record_sizes.remove(k);
+        let _ = self.pending_record_sizes.remove(k);
+        if let Ok(mut access_stats) = self.access_stats.lock() {
+            let _ = access_stats.remove(k);
+        }
+        self.check_capacity_thresholds();
         #[cfg(feature = "open-metrics")]
         if let Some(metric) = &self.record_count_metric {
             let _ = metric.set(self.records.len() as i64);
         }
 
         let filename = Self::generate_filename(k);
-        let file_path = self.config.storage_dir.join(&filename);
+        let backend = self.backend.clone();
+        let key = k.clone();
 
         let _handle = spawn(async move {
-            match fs::remove_file(file_path) {
+            match backend.remove(&key) {
                 Ok(_) => {
                     info!("Removed record from disk! filename: {filename}");
                 }
@@ -740,6 +1141,39 @@ mod tests {
         }
     }
 
+    /// A store holding its records in memory, with the given config.
+    fn memory_store(config: NodeRecordStoreConfig) -> NodeRecordStore {
+        memory_store_with_events(config).0
+    }
+
+    /// Same as `memory_store`, also returning the receiver of the network events of the store.
+    fn memory_store_with_events(
+        config: NodeRecordStoreConfig,
+    ) -> (NodeRecordStore, mpsc::Receiver<NetworkEvent>) {
+        let (network_event_sender, network_event_receiver) = mpsc::channel(10);
+        let (swarm_cmd_sender, _) = mpsc::channel(1);
+        let store = NodeRecordStore::with_backend(
+            PeerId::random(),
+            config,
+            Arc::new(crate::MemoryRecordStoreBackend::default()),
+            network_event_sender,
+            swarm_cmd_sender,
+        );
+        (store, network_event_receiver)
+    }
+
+    /// A chunk record at a random address.
+    fn chunk_record() -> Record {
+        let value = try_serialize_record(&Bytes::from_static(b"data"), RecordKind::Chunk)
+            .expect("Failed to serialize the chunk record");
+        Record {
+            key: NetworkAddress::from_peer(PeerId::random()).to_record_key(),
+            value: value.to_vec(),
+            publisher: None,
+            expires: None,
+        }
+    }
+
     #[test]
     fn test_calculate_cost_for_records() {
         let sut = calculate_cost_for_records(2049, 2050, 2048);
@@ -898,10 +1332,10 @@ mod tests {
                     // Confirm the pruned_key got removed, looping to allow async disk ops to complete.
                     let mut iteration = 0;
                     while iteration < max_iterations {
-                        if NodeRecordStore::read_from_disk(
+                        if NodeRecordStore::read_from_backend(
                             &store.encryption_details,
                             &pruned_key,
-                            &store_config.storage_dir,
+                            store.backend.as_ref(),
                         )
                         .is_none()
                         {
@@ -940,6 +1374,311 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn compaction_removes_only_dead_files() -> eyre::Result<()> {
+        let storage_dir = std::env::temp_dir().join(format!(
+            "record_store_compaction_{}",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+        fs::create_dir_all(&storage_dir)?;
+
+        let live_key = NetworkAddress::from_peer(PeerId::random()).to_record_key();
+        let dead_key = NetworkAddress::from_peer(PeerId::random()).to_record_key();
+        let live_filename = NodeRecordStore::generate_filename(&live_key);
+        let dead_filename = NodeRecordStore::generate_filename(&dead_key);
+        fs::write(storage_dir.join(&live_filename), [0u8; 10])?;
+        fs::write(storage_dir.join(&dead_filename), [0u8; 20])?;
+        let live_filenames = HashSet::from([live_filename.clone()]);
+
+        // too recent to be reclaimed, it might still be in the middle of being written
+        let reclaimed = NodeRecordStore::remove_dead_files(
+            &storage_dir,
+            &live_filenames,
+            Duration::from_secs(3600),
+        );
+        assert_eq!(reclaimed, 0);
+        assert!(storage_dir.join(&dead_filename).exists());
+
+        let reclaimed =
+            NodeRecordStore::remove_dead_files(&storage_dir, &live_filenames, Duration::ZERO);
+        assert_eq!(reclaimed, 20);
+        assert!(storage_dir.join(&live_filename).exists());
+        assert!(!storage_dir.join(&dead_filename).exists());
+
+        fs::remove_dir_all(&storage_dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn records_are_persisted_in_the_given_backend() -> eyre::Result<()> {
+        let backend = Arc::new(crate::MemoryRecordStoreBackend::new(Some(10)));
+        let (network_event_sender, _) = mpsc::channel(1);
+        let (swarm_cmd_sender, _) = mpsc::channel(1);
+        let mut store = NodeRecordStore::with_backend(
+            PeerId::random(),
+            Default::default(),
+            backend.clone(),
+            network_event_sender,
+            swarm_cmd_sender,
+        );
+        // the backend capacity is lower than the configured max_records
+        assert_eq!(store.max_records(), 10);
+
+        let record = chunk_record();
+        let record_key = record.key.clone();
+        store.put_verified(record.clone(), RecordType::Chunk)?;
+        store.mark_as_stored(record_key.clone(), RecordType::Chunk);
+
+        // the write is done off thread, so give it some time
+        let mut iteration = 0;
+        while backend.keys().is_empty() && iteration < 10 {
+            sleep(Duration::from_millis(100)).await;
+            iteration += 1;
+        }
+        assert_eq!(backend.keys(), vec![record_key.clone()]);
+        assert_eq!(store.get(&record_key), Some(Cow::Owned(record)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn compaction_keeps_the_records_being_written() -> eyre::Result<()> {
+        let mut store = memory_store(Default::default());
+        let record = chunk_record();
+        let record_key = record.key.clone();
+        store.put_verified(record.clone(), RecordType::Chunk)?;
+
+        // the write is done off thread, so give it some time
+        let mut iteration = 0;
+        while store.backend.keys().is_empty() && iteration < 10 {
+            sleep(Duration::from_millis(100)).await;
+            iteration += 1;
+        }
+        // compacting before the record is marked as stored
+        let (sender, receiver) = oneshot::channel();
+        store.compact(sender);
+        assert_eq!(receiver.await?, 0);
+
+        store.mark_as_stored(record_key.clone(), RecordType::Chunk);
+        assert_eq!(store.get(&record_key), Some(Cow::Owned(record)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn checkpointed_insertion_times_are_restored() -> eyre::Result<()> {
+        let mut store = memory_store(Default::default());
+        let record = chunk_record();
+        let record_key = record.key.clone();
+        store.put_verified(record, RecordType::Chunk)?;
+        store.mark_as_stored(record_key.clone(), RecordType::Chunk);
+        let stored_at = store.record_age_stats().oldest;
+        let checkpoint = store.insertion_times_to_bytes()?;
+
+        // the record was stored a day before, and the other one is not held
+        let day_before = SystemTime::now() - Duration::from_secs(24 * 60 * 60);
+        let not_held_key = NetworkAddress::from_peer(PeerId::random()).to_record_key();
+        let previous_checkpoint = rmp_serde::to_vec(&vec![
+            (record_key.to_vec(), day_before),
+            (not_held_key.to_vec(), day_before),
+        ])?;
+
+        let temp_dir = assert_fs::TempDir::new()?;
+        let path = temp_dir.path().join(RECORD_INSERTION_TIMES_FILENAME);
+        crate::write_atomically(&path, &previous_checkpoint)?;
+        let mut store = store.with_checkpointed_insertion_times(&path);
+        assert_eq!(store.record_age_stats().oldest, Some(day_before));
+        assert_eq!(store.insertion_times.len(), 1);
+
+        crate::write_atomically(&path, &checkpoint)?;
+        store = store.with_checkpointed_insertion_times(&path);
+        assert_eq!(store.record_age_stats().oldest, stored_at);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn labels_are_dropped_with_their_records() -> eyre::Result<()> {
+        let mut store = memory_store(Default::default());
+        let record = chunk_record();
+        let record_key = record.key.clone();
+        let address = NetworkAddress::from_record_key(&record_key);
+
+        // a record not held is not labelled
+        store.label_record(record_key.clone(), "photos".to_string());
+        assert!(store.record_addresses_with_label("photos").is_empty());
+
+        store.put_verified(record, RecordType::Chunk)?;
+        store.mark_as_stored(record_key.clone(), RecordType::Chunk);
+        store.label_record(record_key.clone(), "photos".to_string());
+        assert_eq!(
+            store.record_addresses_with_label("photos"),
+            HashSet::from([address])
+        );
+
+        store.remove(&record_key);
+        assert!(store.record_addresses_with_label("photos").is_empty());
+        assert!(store.record_labels.label(&record_key).is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pinned_records_are_not_pruned() -> eyre::Result<()> {
+        let store_config = NodeRecordStoreConfig {
+            max_records: 10,
+            ..Default::default()
+        };
+        let mut store = memory_store(store_config);
+
+        let mut keys = vec![];
+        for i in 0..11 {
+            let record = chunk_record();
+            let key = record.key.clone();
+            store.put_verified(record, RecordType::Chunk)?;
+            store.mark_as_stored(key.clone(), RecordType::Chunk);
+            // pin half of the records filling up the store
+            if i < 5 {
+                store.pin_record(key.clone());
+            }
+            keys.push(key);
+        }
+
+        // storing the 11th record pruned the unpinned ones only
+        for key in &keys[..5] {
+            assert!(store.contains(key));
+        }
+        for key in &keys[5..10] {
+            assert!(!store.contains(key));
+        }
+        assert!(store.contains(&keys[10]));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn a_store_full_of_pinned_records_rejects_new_ones() -> eyre::Result<()> {
+        let store_config = NodeRecordStoreConfig {
+            max_records: 10,
+            ..Default::default()
+        };
+        let mut store = memory_store(store_config);
+
+        let mut keys = vec![];
+        for _ in 0..10 {
+            let record = chunk_record();
+            let key = record.key.clone();
+            store.put_verified(record, RecordType::Chunk)?;
+            store.mark_as_stored(key.clone(), RecordType::Chunk);
+            store.pin_record(key.clone());
+            keys.push(key);
+        }
+
+        let record = chunk_record();
+        assert!(matches!(
+            store.put_verified(record, RecordType::Chunk),
+            Err(Error::MaxRecords)
+        ));
+        assert_eq!(store.record_addresses_ref().len(), 10);
+
+        // a held record can still be updated
+        let record = Record {
+            key: keys[0].clone(),
+            ..chunk_record()
+        };
+        store.put_verified(record, RecordType::Chunk)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn served_records_are_accounted() -> eyre::Result<()> {
+        let mut store = memory_store(Default::default());
+
+        let record = chunk_record();
+        let key = record.key.clone();
+        store.put_verified(record, RecordType::Chunk)?;
+        store.mark_as_stored(key.clone(), RecordType::Chunk);
+        // let the record be written to the backend
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(store.get_unserved(&key).is_some());
+        assert_eq!(store.access_stats(&key), None);
+
+        assert!(store.get(&key).is_some());
+        assert!(store.get(&key).is_some());
+        assert_eq!(
+            store.access_stats(&key).map(|stats| stats.gets_served),
+            Some(2)
+        );
+
+        store.remove(&key);
+        assert_eq!(store.access_stats(&key), None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn capacity_thresholds_are_notified_when_crossed() -> eyre::Result<()> {
+        let store_config = NodeRecordStoreConfig {
+            max_records: 10,
+            capacity_alert_thresholds: vec![0.5, 0.8],
+            ..Default::default()
+        };
+        let (mut store, mut network_event_receiver) = memory_store_with_events(store_config);
+
+        let mut keys = vec![];
+        for _ in 0..8 {
+            let record = chunk_record();
+            let key = record.key.clone();
+            store.put_verified(record, RecordType::Chunk)?;
+            store.mark_as_stored(key.clone(), RecordType::Chunk);
+            keys.push(key);
+        }
+        // dropping back below 0.8 only
+        store.remove(&keys[0]);
+
+        let mut crossed = vec![];
+        for _ in 0..3 {
+            match network_event_receiver.recv().await {
+                Some(NetworkEvent::CapacityThresholdCrossed {
+                    threshold, upward, ..
+                }) => crossed.push((threshold, upward)),
+                event => panic!("Unexpected network event {event:?}"),
+            }
+        }
+        assert_eq!(crossed, vec![(0.5, true), (0.8, true), (0.8, false)]);
+        Ok(())
+    }
+
+    #[test]
+    fn record_ages_are_bucketed_in_the_histogram() {
+        let now = SystemTime::now();
+        let inserted_at = |secs_ago: u64| now - Duration::from_secs(secs_ago);
+
+        let stats = RecordAgeStats::from_insertion_times(
+            [
+                inserted_at(10),
+                inserted_at(30),
+                inserted_at(2 * 60 * 60),
+                inserted_at(365 * 24 * 60 * 60),
+                // stored after `now`, e.g. following a clock change
+                now + Duration::from_secs(10),
+            ]
+            .into_iter(),
+            now,
+        );
+
+        assert_eq!(stats.oldest, Some(inserted_at(365 * 24 * 60 * 60)));
+        assert_eq!(stats.newest, Some(now + Duration::from_secs(10)));
+        assert_eq!(
+            stats.histogram,
+            BTreeMap::from([
+                (Duration::from_secs(60), 3),
+                (Duration::from_secs(24 * 60 * 60), 1),
+                (Duration::MAX, 1),
+            ])
+        );
+
+        assert_eq!(
+            RecordAgeStats::from_insertion_times(std::iter::empty(), now),
+            RecordAgeStats::default()
+        );
+    }
+
     #[tokio::test]
     #[allow(clippy::mutable_key_type)]
     async fn get_records_within_distance_range() -> eyre::Result<()> {
@@ -7,14 +7,19 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 #![allow(clippy::mutable_key_type)] // for the Bytes in NetworkAddress
 
-use crate::record_store::{ClientRecordStore, NodeRecordStore};
+use crate::record_store::{AccessStats, ClientRecordStore, NodeRecordStore, RecordAgeStats};
 use libp2p::kad::{
     store::{RecordStore, Result},
     KBucketDistance as Distance, ProviderRecord, Record, RecordKey,
 };
 use sn_protocol::{storage::RecordType, NetworkAddress};
 use sn_transfers::NanoTokens;
-use std::{borrow::Cow, collections::HashMap};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io,
+};
+use tokio::sync::oneshot;
 
 pub enum UnifiedRecordStore {
     Client(ClientRecordStore),
@@ -141,6 +146,101 @@ impl UnifiedRecordStore {
         }
     }
 
+    pub(crate) fn compact(&self, sender: oneshot::Sender<u64>) {
+        match self {
+            Self::Client(_) => {
+                warn!("Calling compact at Client. This should not happen");
+                let _ = sender.send(0);
+            }
+            Self::Node(store) => store.compact(sender),
+        }
+    }
+
+    pub(crate) fn pin_record(&mut self, key: RecordKey) {
+        match self {
+            Self::Client(_) => {
+                warn!("Calling pin_record at Client. This should not happen");
+            }
+            Self::Node(store) => store.pin_record(key),
+        }
+    }
+
+    pub(crate) fn unpin_record(&mut self, key: &RecordKey) {
+        match self {
+            Self::Client(_) => {
+                warn!("Calling unpin_record at Client. This should not happen");
+            }
+            Self::Node(store) => store.unpin_record(key),
+        }
+    }
+
+    pub(crate) fn record_age_stats(&self) -> RecordAgeStats {
+        match self {
+            Self::Client(_) => {
+                warn!("Calling record_age_stats at Client. This should not happen");
+                RecordAgeStats::default()
+            }
+            Self::Node(store) => store.record_age_stats(),
+        }
+    }
+
+    pub(crate) fn label_record(&mut self, key: RecordKey, label: String) {
+        match self {
+            Self::Client(_) => {
+                warn!("Calling label_record at Client. This should not happen");
+            }
+            Self::Node(store) => store.label_record(key, label),
+        }
+    }
+
+    pub(crate) fn record_addresses_with_label(&self, label: &str) -> HashSet<NetworkAddress> {
+        match self {
+            Self::Client(_) => {
+                warn!("Calling record_addresses_with_label at Client. This should not happen");
+                HashSet::new()
+            }
+            Self::Node(store) => store.record_addresses_with_label(label),
+        }
+    }
+
+    pub(crate) fn record_labels_to_bytes(&self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Client(_) => {
+                warn!("Calling record_labels_to_bytes at Client. This should not happen");
+                Ok(vec![])
+            }
+            Self::Node(store) => store.record_labels_to_bytes(),
+        }
+    }
+
+    pub(crate) fn insertion_times_to_bytes(&self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Client(_) => {
+                warn!("Calling insertion_times_to_bytes at Client. This should not happen");
+                Ok(vec![])
+            }
+            Self::Node(store) => store.insertion_times_to_bytes(),
+        }
+    }
+
+    pub(crate) fn access_stats(&self, key: &RecordKey) -> Option<AccessStats> {
+        match self {
+            Self::Client(_) => {
+                warn!("Calling access_stats at Client. This should not happen");
+                None
+            }
+            Self::Node(store) => store.access_stats(key),
+        }
+    }
+
+    /// Gets the record for our own use, not accounting it as served.
+    pub(crate) fn get_unserved(&self, k: &RecordKey) -> Option<Cow<'_, Record>> {
+        match self {
+            Self::Client(store) => store.get(k),
+            Self::Node(store) => store.get_unserved(k),
+        }
+    }
+
     /// Mark the record as stored in the store.
     /// This adds it to records set, so it can now be retrieved
     /// (to be done after writes are finalised)
@@ -0,0 +1,256 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+#![allow(clippy::mutable_key_type)] // for the Bytes in RecordKey
+
+use crate::record_store::NodeRecordStore;
+use libp2p::kad::RecordKey as Key;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    fs, io,
+    path::PathBuf,
+    sync::Mutex,
+    time::SystemTime,
+};
+use tokio::time::Duration;
+use walkdir::WalkDir;
+
+/// The files not backing any record are only reclaimed by a compaction once they are at least this old.
+/// This leaves alone the files of records which are still being written.
+const COMPACTION_MIN_DEAD_FILE_AGE: Duration = Duration::from_secs(60);
+
+/// The storage holding the bytes of the records of a `NodeRecordStore`.
+///
+/// The `NodeRecordStore` keeps track of which records are held, prices and prunes them, while
+/// the backend only persists their (possibly encrypted) bytes. The methods are called off thread,
+/// hence the backend has to be shareable.
+pub trait RecordStoreBackend: Debug + Send + Sync {
+    /// Returns the bytes stored under the key.
+    fn get(&self, key: &Key) -> io::Result<Vec<u8>>;
+
+    /// Stores the bytes under the key, replacing any previous ones.
+    fn put(&self, key: &Key, bytes: Vec<u8>) -> io::Result<()>;
+
+    /// Removes the bytes stored under the key.
+    fn remove(&self, key: &Key) -> io::Result<()>;
+
+    /// Returns the keys of all the stored records, e.g. to restore the store after a restart.
+    fn keys(&self) -> Vec<Key>;
+
+    /// When the record was stored, if the backend knows, e.g. to restore the age of the records
+    /// after a restart.
+    fn inserted_at(&self, _key: &Key) -> Option<SystemTime> {
+        None
+    }
+
+    /// The max number of records the backend can hold. `None` means it is only bound by the
+    /// `max_records` the store is configured with.
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Reclaims the space used by anything stored that is not one of the `live_keys`.
+    /// Returns the number of bytes reclaimed.
+    fn reclaim(&self, _live_keys: &HashSet<Key>) -> u64 {
+        0
+    }
+}
+
+/// The default backend, storing each record in its own file named by the hex encoded key.
+#[derive(Debug, Clone)]
+pub struct DiskRecordStoreBackend {
+    storage_dir: PathBuf,
+}
+
+impl DiskRecordStoreBackend {
+    /// Creates a backend storing the records under `storage_dir`.
+    pub fn new(storage_dir: PathBuf) -> Self {
+        Self { storage_dir }
+    }
+}
+
+impl RecordStoreBackend for DiskRecordStoreBackend {
+    fn get(&self, key: &Key) -> io::Result<Vec<u8>> {
+        fs::read(
+            self.storage_dir
+                .join(NodeRecordStore::generate_filename(key)),
+        )
+    }
+
+    fn put(&self, key: &Key, bytes: Vec<u8>) -> io::Result<()> {
+        fs::write(
+            self.storage_dir
+                .join(NodeRecordStore::generate_filename(key)),
+            bytes,
+        )
+    }
+
+    fn remove(&self, key: &Key) -> io::Result<()> {
+        fs::remove_file(
+            self.storage_dir
+                .join(NodeRecordStore::generate_filename(key)),
+        )
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        let mut keys = vec![];
+        for entry in WalkDir::new(&self.storage_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
+                match hex::decode(filename) {
+                    Ok(bytes) => keys.push(Key::from(bytes)),
+                    Err(error) => error!("Error decoding hex string: {:?}", error),
+                }
+            } else {
+                // warn and remove this file as it's not a valid record
+                warn!(
+                    "Found a file in the storage dir that is not a valid record: {:?}",
+                    path
+                );
+                if let Err(e) = fs::remove_file(path) {
+                    warn!(
+                        "Failed to remove invalid record file from storage dir: {:?}",
+                        e
+                    );
+                }
+            }
+        }
+        keys
+    }
+
+    fn inserted_at(&self, key: &Key) -> Option<SystemTime> {
+        fs::metadata(
+            self.storage_dir
+                .join(NodeRecordStore::generate_filename(key)),
+        )
+        .and_then(|metadata| metadata.modified())
+        .ok()
+    }
+
+    fn reclaim(&self, live_keys: &HashSet<Key>) -> u64 {
+        let live_filenames: HashSet<String> = live_keys
+            .iter()
+            .map(NodeRecordStore::generate_filename)
+            .collect();
+        NodeRecordStore::remove_dead_files(
+            &self.storage_dir,
+            &live_filenames,
+            COMPACTION_MIN_DEAD_FILE_AGE,
+        )
+    }
+}
+
+/// A backend holding the records in memory, e.g. to speed up tests. Nothing survives a restart.
+#[derive(Debug, Default)]
+pub struct MemoryRecordStoreBackend {
+    records: Mutex<HashMap<Key, Vec<u8>>>,
+    capacity: Option<usize>,
+}
+
+impl MemoryRecordStoreBackend {
+    /// Creates an empty backend, optionally capped to `capacity` records.
+    pub fn new(capacity: Option<usize>) -> Self {
+        Self {
+            records: Default::default(),
+            capacity,
+        }
+    }
+
+    fn lock(&self) -> io::Result<std::sync::MutexGuard<'_, HashMap<Key, Vec<u8>>>> {
+        self.records
+            .lock()
+            .map_err(|_| io::Error::other("record store lock poisoned"))
+    }
+}
+
+impl RecordStoreBackend for MemoryRecordStoreBackend {
+    fn get(&self, key: &Key) -> io::Result<Vec<u8>> {
+        self.lock()?
+            .get(key)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "record not found"))
+    }
+
+    fn put(&self, key: &Key, bytes: Vec<u8>) -> io::Result<()> {
+        let _ = self.lock()?.insert(key.clone(), bytes);
+        Ok(())
+    }
+
+    fn remove(&self, key: &Key) -> io::Result<()> {
+        match self.lock()?.remove(key) {
+            Some(_) => Ok(()),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "record not found")),
+        }
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        self.lock()
+            .map(|records| records.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn reclaim(&self, live_keys: &HashSet<Key>) -> u64 {
+        let Ok(mut records) = self.lock() else {
+            return 0;
+        };
+        let mut reclaimed = 0;
+        records.retain(|key, bytes| {
+            let is_live = live_keys.contains(key);
+            if !is_live {
+                reclaimed += bytes.len() as u64;
+            }
+            is_live
+        });
+        reclaimed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_backend_put_get_remove() -> io::Result<()> {
+        let backend = MemoryRecordStoreBackend::default();
+        let key = Key::new(&[1u8; 32]);
+
+        backend.put(&key, vec![1, 2, 3])?;
+        assert_eq!(backend.get(&key)?, vec![1, 2, 3]);
+        assert_eq!(backend.keys(), vec![key.clone()]);
+
+        backend.remove(&key)?;
+        assert!(backend.get(&key).is_err());
+        assert!(backend.keys().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn memory_backend_reclaims_dead_records() -> io::Result<()> {
+        let backend = MemoryRecordStoreBackend::default();
+        let live_key = Key::new(&[1u8; 32]);
+        let dead_key = Key::new(&[2u8; 32]);
+        backend.put(&live_key, vec![0; 10])?;
+        backend.put(&dead_key, vec![0; 20])?;
+
+        let reclaimed = backend.reclaim(&HashSet::from([live_key.clone()]));
+
+        assert_eq!(reclaimed, 20);
+        assert_eq!(backend.keys(), vec![live_key]);
+        Ok(())
+    }
+}
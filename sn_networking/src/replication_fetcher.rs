@@ -13,8 +13,10 @@ use libp2p::{
     kad::{KBucketDistance as Distance, RecordKey, K_VALUE},
     PeerId,
 };
+#[cfg(feature = "open-metrics")]
+use prometheus_client::metrics::gauge::Gauge;
 use sn_protocol::{storage::RecordType, NetworkAddress, PrettyPrintRecordKey};
-use std::collections::{hash_map::Entry, BTreeSet, HashMap};
+use std::collections::{hash_map::Entry, BTreeSet, HashMap, HashSet};
 use tokio::{sync::mpsc, time::Duration};
 
 // Max parallel fetches that can be undertaken at the same time.
@@ -41,6 +43,9 @@ pub(crate) struct ReplicationFetcher {
     event_sender: mpsc::Sender<NetworkEvent>,
     // Distance range that the incoming key shall be fetched
     distance_range: Option<Distance>,
+    // Reports the number of records pending replication to the metrics server.
+    #[cfg(feature = "open-metrics")]
+    queue_depth_metric: Option<Gauge>,
 }
 
 impl ReplicationFetcher {
@@ -52,9 +57,42 @@ impl ReplicationFetcher {
             on_going_fetches: HashMap::new(),
             event_sender,
             distance_range: None,
+            #[cfg(feature = "open-metrics")]
+            queue_depth_metric: None,
         }
     }
 
+    /// Set the metric reporting the number of records pending replication to the metrics server.
+    #[cfg(feature = "open-metrics")]
+    pub(crate) fn set_queue_depth_metric(&mut self, metric: Gauge) {
+        self.queue_depth_metric = Some(metric);
+    }
+
+    /// The number of records our peers asked us to replicate which we don't hold yet, either
+    /// still queued or being fetched.
+    pub(crate) fn queue_depth(&self) -> usize {
+        self.to_be_fetched
+            .keys()
+            .map(|(key, record_type, _holder)| (key, record_type))
+            .chain(
+                self.on_going_fetches
+                    .keys()
+                    .map(|(key, record_type)| (key, record_type)),
+            )
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// The keys of the records our peers asked us to replicate which we don't hold yet, either
+    /// still queued or being fetched.
+    pub(crate) fn pending_keys(&self) -> HashSet<RecordKey> {
+        self.to_be_fetched
+            .keys()
+            .map(|(key, _record_type, _holder)| key.clone())
+            .chain(self.on_going_fetches.keys().map(|(key, _)| key.clone()))
+            .collect()
+    }
+
     /// Set the distance range.
     pub(crate) fn set_distance_range(&mut self, distance_range: Distance) {
         self.distance_range = Some(distance_range);
@@ -187,14 +225,21 @@ impl ReplicationFetcher {
             );
         }
 
-        data_to_fetch
+        let keys_to_fetch = data_to_fetch
             .iter()
             .map(|(holder, key, t)| {
                 let entry_key = (key.clone(), t.clone(), *holder);
                 let _ = self.to_be_fetched.remove(&entry_key);
                 (*holder, key.clone())
             })
-            .collect()
+            .collect();
+
+        #[cfg(feature = "open-metrics")]
+        if let Some(metric) = &self.queue_depth_metric {
+            let _ = metric.set(self.queue_depth() as i64);
+        }
+
+        keys_to_fetch
     }
 
     // Just remove outdated entries in `on_going_fetch`, indicates a failure to fetch from network.
@@ -293,6 +338,29 @@ mod tests {
     use std::{collections::HashMap, time::Duration};
     use tokio::{sync::mpsc, time::sleep};
 
+    #[tokio::test]
+    async fn pending_keys_include_the_queued_and_ongoing_fetches() {
+        let (event_sender, _event_receiver) = mpsc::channel(4);
+        let mut replication_fetcher = ReplicationFetcher::new(PeerId::random(), event_sender);
+
+        let incoming_keys: Vec<_> = (0..MAX_PARALLEL_FETCH + 1)
+            .map(|_| {
+                let random_data: Vec<u8> = (0..50).map(|_| rand::random::<u8>()).collect();
+                let key = NetworkAddress::from_record_key(&RecordKey::from(random_data));
+                (key, RecordType::Chunk)
+            })
+            .collect();
+        let keys_to_fetch =
+            replication_fetcher.add_keys(PeerId::random(), incoming_keys.clone(), &HashMap::new());
+        assert_eq!(keys_to_fetch.len(), MAX_PARALLEL_FETCH);
+
+        let pending_keys = replication_fetcher.pending_keys();
+        assert_eq!(pending_keys.len(), incoming_keys.len());
+        for (key, _) in incoming_keys {
+            assert!(pending_keys.contains(&key.to_record_key()));
+        }
+    }
+
     #[tokio::test]
     async fn verify_max_parallel_fetches() -> Result<()> {
         //random peer_id
@@ -346,4 +414,30 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn queue_depth_counts_queued_and_ongoing_fetches() {
+        let (event_sender, _event_receiver) = mpsc::channel(4);
+        let mut replication_fetcher = ReplicationFetcher::new(PeerId::random(), event_sender);
+        let locally_stored_keys = HashMap::new();
+
+        let incoming_keys: Vec<_> = (0..MAX_PARALLEL_FETCH * 2)
+            .map(|_| {
+                let random_data: Vec<u8> = (0..50).map(|_| rand::random::<u8>()).collect();
+                let key = NetworkAddress::from_record_key(&RecordKey::from(random_data));
+                (key, RecordType::Chunk)
+            })
+            .collect();
+        let keys_to_fetch =
+            replication_fetcher.add_keys(PeerId::random(), incoming_keys, &locally_stored_keys);
+        assert_eq!(replication_fetcher.queue_depth(), MAX_PARALLEL_FETCH * 2);
+
+        // a fetched record is no longer pending
+        let (_holder, fetched_key) = keys_to_fetch[0].clone();
+        let _ = replication_fetcher.notify_about_new_put(fetched_key, RecordType::Chunk);
+        assert_eq!(
+            replication_fetcher.queue_depth(),
+            MAX_PARALLEL_FETCH * 2 - 1
+        );
+    }
 }
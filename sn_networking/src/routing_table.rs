@@ -0,0 +1,188 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use libp2p::{kad::K_VALUE, Multiaddr, PeerId};
+use std::collections::BTreeMap;
+
+/// A peer as held in the local RoutingTable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KBucketEntry {
+    pub peer_id: PeerId,
+    /// The ilog2 distance of the KBucket the peer is in.
+    pub ilog2_distance: u32,
+    /// The addresses we know the peer by.
+    pub addresses: Vec<Multiaddr>,
+    /// Whether the peer is considered connected, as opposed to pending eviction if the bucket
+    /// is full.
+    pub connected: bool,
+}
+
+/// A point in time view of the local RoutingTable.
+/// Each key is the ilog2 distance of that KBucket and each value is the peers in that bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutingTableSnapshot {
+    pub buckets: BTreeMap<u32, Vec<PeerId>>,
+}
+
+/// The changes between two `RoutingTableSnapshot`s.
+/// Each peer is listed along with the ilog2 distance of the bucket(s) it was found in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutingDiff {
+    /// Peers present in the newer snapshot only.
+    pub added: BTreeMap<PeerId, u32>,
+    /// Peers present in the older snapshot only.
+    pub dropped: BTreeMap<PeerId, u32>,
+    /// Peers present in both snapshots, but in different buckets, as `(from, to)`.
+    pub moved: BTreeMap<PeerId, (u32, u32)>,
+}
+
+impl RoutingTableSnapshot {
+    pub fn new(buckets: BTreeMap<u32, Vec<PeerId>>) -> Self {
+        Self { buckets }
+    }
+
+    /// Total number of peers across all the buckets.
+    pub fn peers_count(&self) -> usize {
+        self.buckets.values().map(|peers| peers.len()).sum()
+    }
+
+    /// A rough estimate of the number of peers in the network, from the density of the peers in
+    /// the buckets.
+    ///
+    /// The bucket at ilog2 distance `i` covers `2^i` addresses of the `2^256` address space.
+    /// Up to the first full bucket, the buckets hold every peer within their range, hence their
+    /// peers over the fraction of the address space they cover estimates the network size. As
+    /// the full bucket holds at least its peers, the estimate is floored by its density too.
+    /// With no full bucket, the network is assumed to be the peers we know of.
+    pub fn estimated_network_size(&self) -> usize {
+        let mut known_closer = 0;
+        for (ilog2, peers) in &self.buckets {
+            if peers.len() < K_VALUE.get() {
+                known_closer += peers.len();
+                continue;
+            }
+            // the buckets below cover 2^ilog2 addresses, this one as many again
+            let scale = 2f64.powi(256 - *ilog2 as i32);
+            let estimate = (known_closer as f64 * scale)
+                .max((known_closer + peers.len()) as f64 * scale / 2.0);
+            return estimate.min(usize::MAX as f64) as usize;
+        }
+        known_closer
+    }
+
+    /// Reports the peers that have been added, dropped or moved between buckets
+    /// when going from `self` to the (newer) `other` snapshot.
+    pub fn diff(&self, other: &RoutingTableSnapshot) -> RoutingDiff {
+        let before = self.peer_buckets();
+        let after = other.peer_buckets();
+
+        let mut diff = RoutingDiff::default();
+        for (peer, ilog2) in before.iter() {
+            match after.get(peer) {
+                None => {
+                    let _ = diff.dropped.insert(*peer, *ilog2);
+                }
+                Some(new_ilog2) if new_ilog2 != ilog2 => {
+                    let _ = diff.moved.insert(*peer, (*ilog2, *new_ilog2));
+                }
+                Some(_) => {}
+            }
+        }
+        for (peer, ilog2) in after {
+            if !before.contains_key(&peer) {
+                let _ = diff.added.insert(peer, ilog2);
+            }
+        }
+
+        diff
+    }
+
+    fn peer_buckets(&self) -> BTreeMap<PeerId, u32> {
+        self.buckets
+            .iter()
+            .flat_map(|(ilog2, peers)| peers.iter().map(|peer| (*peer, *ilog2)))
+            .collect()
+    }
+}
+
+impl From<BTreeMap<u32, Vec<PeerId>>> for RoutingTableSnapshot {
+    fn from(buckets: BTreeMap<u32, Vec<PeerId>>) -> Self {
+        Self::new(buckets)
+    }
+}
+
+impl RoutingDiff {
+    /// Returns true if both snapshots had the same peers in the same buckets.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.dropped.is_empty() && self.moved.is_empty()
+    }
+
+    /// Number of peers that have either joined or left the RoutingTable.
+    pub fn churn(&self) -> usize {
+        self.added.len() + self.dropped.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_added_dropped_and_moved_peers() {
+        let kept = PeerId::random();
+        let moved = PeerId::random();
+        let dropped = PeerId::random();
+        let added = PeerId::random();
+
+        let before = RoutingTableSnapshot::new(BTreeMap::from([
+            (250, vec![kept, moved]),
+            (252, vec![dropped]),
+        ]));
+        let after = RoutingTableSnapshot::new(BTreeMap::from([
+            (250, vec![kept]),
+            (251, vec![moved]),
+            (253, vec![added]),
+        ]));
+
+        let diff = before.diff(&after);
+
+        assert_eq!(diff.added, BTreeMap::from([(added, 253)]));
+        assert_eq!(diff.dropped, BTreeMap::from([(dropped, 252)]));
+        assert_eq!(diff.moved, BTreeMap::from([(moved, (250, 251))]));
+        assert_eq!(diff.churn(), 2);
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn network_size_is_estimated_from_the_bucket_density() {
+        let peers = |count: usize| (0..count).map(|_| PeerId::random()).collect::<Vec<_>>();
+
+        assert_eq!(RoutingTableSnapshot::default().estimated_network_size(), 0);
+
+        // no full bucket, every peer is known
+        let snapshot =
+            RoutingTableSnapshot::new(BTreeMap::from([(253, peers(3)), (254, peers(5))]));
+        assert_eq!(snapshot.estimated_network_size(), 8);
+
+        // the 25 peers known below bucket 254 are a quarter of the network
+        let snapshot = RoutingTableSnapshot::new(BTreeMap::from([
+            (252, peers(10)),
+            (253, peers(15)),
+            (254, peers(K_VALUE.get())),
+            (255, peers(K_VALUE.get())),
+        ]));
+        assert_eq!(snapshot.estimated_network_size(), 100);
+
+        // floored by the density of the full bucket, when few peers are known below it
+        let snapshot = RoutingTableSnapshot::new(BTreeMap::from([
+            (254, peers(1)),
+            (255, peers(K_VALUE.get())),
+        ]));
+        assert_eq!(snapshot.estimated_network_size(), 21);
+    }
+}
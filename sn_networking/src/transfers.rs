@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{close_group_majority, driver::GetRecordCfg, Error, GetRecordError, Network, Result};
+use crate::{driver::GetRecordCfg, Error, GetRecordError, Network, Result};
 use libp2p::kad::{Quorum, Record};
 use sn_protocol::{
     storage::{try_deserialize_record, RecordHeader, RecordKind, RetryStrategy, SpendAddress},
@@ -84,7 +84,7 @@ impl Network {
                 got,
             })) => {
                 // if majority holds the spend, it might be worth it to try again.
-                if got >= close_group_majority() {
+                if got >= self.close_group_majority() {
                     debug!("At least a majority nodes hold the spend {address:?}, so trying to get it again.");
                     get_cfg.retry_strategy = Some(RetryStrategy::Persistent);
                     self.get_record_from_network(key, &get_cfg).await?
@@ -110,7 +110,7 @@ impl Network {
     /// Needs Network connection.
     /// Verify Transfer and rebuild spendable currency from it
     /// Returns an `Error::FailedToDecypherTransfer` if the transfer cannot be decyphered
-    /// (This means the transfer is not for us as it was not encrypted to our key)
+    /// (This means the transfer is not for us as it was not encrypted to any of our addresses)
     /// Returns an `Error::InvalidTransfer` if the transfer is not valid
     /// Else returns a list of CashNotes that can be deposited to our wallet and spent
     pub async fn verify_and_unpack_transfer(
@@ -120,9 +120,9 @@ impl Network {
     ) -> Result<Vec<CashNote>> {
         // get CashNoteRedemptions from encrypted Transfer
         trace!("Decyphering Transfer");
-        let cashnote_redemptions = wallet.unwrap_transfer(transfer)?;
+        let (address, cashnote_redemptions) = wallet.unwrap_transfer_with_address(transfer)?;
 
-        self.verify_cash_notes_redemptions(wallet.address(), &cashnote_redemptions)
+        self.verify_cash_notes_redemptions(address, &cashnote_redemptions)
             .await
     }
 
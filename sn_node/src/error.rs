@@ -32,6 +32,9 @@ pub enum Error {
     #[error("Transfers Error {0}")]
     Transfers(#[from] sn_transfers::Error),
 
+    #[error("IO error {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("Failed to parse NodeEvent")]
     NodeEventParsingFailed,
 
@@ -91,6 +94,12 @@ pub enum Error {
     #[error("Failed to generate a reward key")]
     FailedToGenerateRewardKey,
 
+    // ---------- Identity Errors
+    #[error("The identity is invalid: {0}")]
+    InvalidIdentity(String),
+    #[error("The passphrase doesn't decrypt the identity")]
+    WrongIdentityPassphrase,
+
     // ---------- Miscellaneous Errors
     #[error("Failed to obtain node's current port")]
     FailedToGetNodePort,
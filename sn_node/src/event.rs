@@ -9,8 +9,12 @@
 use crate::error::{Error, Result};
 use bls::PublicKey;
 use bytes::Bytes;
-use serde::{Deserialize, Serialize};
-use sn_protocol::storage::{ChunkAddress, RegisterAddress};
+use libp2p::PeerId;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sn_protocol::{
+    storage::{ChunkAddress, RegisterAddress},
+    NetworkAddress,
+};
 use sn_transfers::{CashNoteRedemption, UniquePubkey};
 use tokio::sync::broadcast;
 
@@ -65,6 +69,15 @@ pub enum NodeEvent {
     RegisterEdited(RegisterAddress),
     /// A CashNote Spend has been stored in local storage
     SpendStored(UniquePubkey),
+    /// A record has been validated and stored in local storage
+    PutAccepted {
+        /// The address of the stored record
+        address: NetworkAddress,
+        /// Whether a valid payment to us came with the record
+        payment_verified: bool,
+        /// Whether the record was not PUT by a client, e.g. it was replicated from other nodes
+        is_replication: bool,
+    },
     /// One of the sub event channel closed and unrecoverable.
     ChannelClosed,
     /// Gossipsub message received
@@ -81,9 +94,91 @@ pub enum NodeEvent {
         key: PublicKey,
         /// The cashnote redemptions of the transfers
         cashnote_redemptions: Vec<CashNoteRedemption>,
+        /// The node which published the notification, if it signed it
+        #[serde(
+            default,
+            serialize_with = "serialize_publisher",
+            deserialize_with = "deserialize_publisher"
+        )]
+        publisher: Option<PeerId>,
     },
     /// Terminates the node
     TerminateNode,
+    /// A requested refresh of the RoutingTable has completed
+    RoutingTableRefreshed,
+    /// A record has been replicated in from another node and stored in local storage.
+    /// Under heavy replication only a sample of these events is emitted.
+    ReplicationIn {
+        /// The address of the replicated record
+        address: NetworkAddress,
+        /// The node the record was fetched from
+        #[serde(
+            serialize_with = "serialize_peer",
+            deserialize_with = "deserialize_peer"
+        )]
+        from: PeerId,
+    },
+    /// A record has been replicated out to another node that requested it.
+    /// Under heavy replication only a sample of these events is emitted.
+    ReplicationOut {
+        /// The address of the replicated record
+        address: NetworkAddress,
+        /// The node the record was sent to
+        #[serde(
+            serialize_with = "serialize_peer",
+            deserialize_with = "deserialize_peer"
+        )]
+        to: PeerId,
+    },
+    /// The record store filled past one of the capacity alert thresholds
+    CapacityThresholdCrossed {
+        /// The fraction of the store capacity crossed
+        fraction: f32,
+        /// The bytes of the stored record values
+        used_bytes: u64,
+        /// The bytes the record values could fill the store with at most
+        max_bytes: u64,
+    },
+    /// The record store dropped back below one of the capacity alert thresholds
+    CapacityThresholdCleared {
+        /// The fraction of the store capacity dropped back below
+        fraction: f32,
+        /// The bytes of the stored record values
+        used_bytes: u64,
+        /// The bytes the record values could fill the store with at most
+        max_bytes: u64,
+    },
+}
+
+fn serialize_peer<S: Serializer>(
+    peer_id: &PeerId,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    peer_id.to_bytes().serialize(serializer)
+}
+
+fn deserialize_peer<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<PeerId, D::Error> {
+    let bytes = Vec::<u8>::deserialize(deserializer)?;
+    PeerId::from_bytes(&bytes).map_err(serde::de::Error::custom)
+}
+
+fn serialize_publisher<S: Serializer>(
+    publisher: &Option<PeerId>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    publisher
+        .map(|peer_id| peer_id.to_bytes())
+        .serialize(serializer)
+}
+
+fn deserialize_publisher<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<PeerId>, D::Error> {
+    Option::<Vec<u8>>::deserialize(deserializer)?
+        .map(|bytes| PeerId::from_bytes(&bytes).map_err(serde::de::Error::custom))
+        .transpose()
 }
 
 impl NodeEvent {
@@ -96,4 +191,37 @@ impl NodeEvent {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
         rmp_serde::from_slice(bytes).map_err(|_| Error::NodeEventParsingFailed)
     }
+
+    /// The name of the kind of event, e.g. "ChunkStored". The names are kept stable, to be
+    /// dispatched on by the consumers of the exported events.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodeEvent::ConnectedToNetwork => "ConnectedToNetwork",
+            NodeEvent::ChunkStored(_) => "ChunkStored",
+            NodeEvent::RegisterCreated(_) => "RegisterCreated",
+            NodeEvent::RegisterEdited(_) => "RegisterEdited",
+            NodeEvent::SpendStored(_) => "SpendStored",
+            NodeEvent::PutAccepted { .. } => "PutAccepted",
+            NodeEvent::ChannelClosed => "ChannelClosed",
+            NodeEvent::GossipsubMsg { .. } => "GossipsubMsg",
+            NodeEvent::TransferNotif { .. } => "TransferNotif",
+            NodeEvent::TerminateNode => "TerminateNode",
+            NodeEvent::RoutingTableRefreshed => "RoutingTableRefreshed",
+            NodeEvent::ReplicationIn { .. } => "ReplicationIn",
+            NodeEvent::ReplicationOut { .. } => "ReplicationOut",
+            NodeEvent::CapacityThresholdCrossed { .. } => "CapacityThresholdCrossed",
+            NodeEvent::CapacityThresholdCleared { .. } => "CapacityThresholdCleared",
+        }
+    }
+
+    /// Convert NodeEvent to a single line JSON object, of the form
+    /// `{"type":"<kind>","event":<the serde serialised event>}`, without the trailing newline.
+    pub fn to_json_line(&self) -> Result<String> {
+        let event = serde_json::to_value(self).map_err(|_| Error::NodeEventParsingFailed)?;
+        let line = serde_json::json!({
+            "type": self.kind(),
+            "event": event,
+        });
+        serde_json::to_string(&line).map_err(|_| Error::NodeEventParsingFailed)
+    }
 }
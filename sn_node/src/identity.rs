@@ -0,0 +1,138 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::error::{Error, Result};
+use aes_gcm_siv::{
+    aead::{Aead, KeyInit},
+    Aes256GcmSiv, Nonce,
+};
+use argon2::Argon2;
+use libp2p::{identity::Keypair, PeerId};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+/// A node's keypair, encrypted with a key derived from a passphrase.
+#[derive(Serialize, Deserialize)]
+struct ExportedIdentity {
+    /// The PeerId of the keypair, in the clear, to tell which node the identity is of.
+    peer_id: Vec<u8>,
+    salt: [u8; 32],
+    nonce: [u8; 12],
+    /// The protobuf encoded keypair.
+    ciphertext: Vec<u8>,
+}
+
+/// Writes the keypair to `path`, encrypted with the passphrase.
+pub(crate) fn export_keypair(keypair: &Keypair, path: &Path, passphrase: &str) -> Result<()> {
+    let keypair_bytes = keypair
+        .to_protobuf_encoding()
+        .map_err(|err| Error::InvalidIdentity(err.to_string()))?;
+    let salt: [u8; 32] = rand::random();
+    let nonce: [u8; 12] = rand::random();
+    let ciphertext = passphrase_cipher(passphrase, &salt)?
+        .encrypt(Nonce::from_slice(&nonce), keypair_bytes.as_slice())
+        .map_err(|err| Error::InvalidIdentity(err.to_string()))?;
+
+    let exported = ExportedIdentity {
+        peer_id: keypair.public().to_peer_id().to_bytes(),
+        salt,
+        nonce,
+        ciphertext,
+    };
+    let bytes =
+        rmp_serde::to_vec(&exported).map_err(|err| Error::InvalidIdentity(err.to_string()))?;
+
+    let mut opt = OpenOptions::new();
+    let _ = opt.write(true).create(true).truncate(true);
+    // On Unix systems, make sure only the current user can read/write.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        let _ = opt.mode(0o600);
+    }
+    opt.open(path)?.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Reads the keypair exported to `path`, erroring with `Error::WrongIdentityPassphrase` if it
+/// can't be decrypted with the passphrase.
+pub(crate) fn import_keypair(path: &Path, passphrase: &str) -> Result<Keypair> {
+    let bytes = fs::read(path)?;
+    let exported: ExportedIdentity =
+        rmp_serde::from_slice(&bytes).map_err(|err| Error::InvalidIdentity(err.to_string()))?;
+    let peer_id = PeerId::from_bytes(&exported.peer_id)
+        .map_err(|err| Error::InvalidIdentity(err.to_string()))?;
+
+    // the ciphertext is authenticated, hence a wrong passphrase fails to decrypt it
+    let keypair_bytes = passphrase_cipher(passphrase, &exported.salt)?
+        .decrypt(
+            Nonce::from_slice(&exported.nonce),
+            exported.ciphertext.as_slice(),
+        )
+        .map_err(|_| Error::WrongIdentityPassphrase)?;
+    let keypair = Keypair::from_protobuf_encoding(&keypair_bytes)
+        .map_err(|err| Error::InvalidIdentity(err.to_string()))?;
+    if keypair.public().to_peer_id() != peer_id {
+        return Err(Error::InvalidIdentity(format!(
+            "the keypair is not the one of {peer_id:?}"
+        )));
+    }
+    Ok(keypair)
+}
+
+/// Derives the cipher the identity is encrypted with from the passphrase and salt.
+fn passphrase_cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256GcmSiv> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| Error::InvalidIdentity(err.to_string()))?;
+    Ok(Aes256GcmSiv::new(&key.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn exported_identity_is_imported_with_the_passphrase() -> Result<()> {
+        let temp_dir = TempDir::new().expect("Should be able to create a temp dir.");
+        let path = temp_dir.path().join("identity");
+        let keypair = Keypair::generate_ed25519();
+
+        export_keypair(&keypair, &path, "correct horse battery staple")?;
+        let imported = import_keypair(&path, "correct horse battery staple")?;
+        assert_eq!(imported.public(), keypair.public());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path)?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn exported_identity_is_not_imported_with_a_wrong_passphrase() -> Result<()> {
+        let temp_dir = TempDir::new().expect("Should be able to create a temp dir.");
+        let path = temp_dir.path().join("identity");
+        let keypair = Keypair::generate_ed25519();
+
+        export_keypair(&keypair, &path, "correct horse battery staple")?;
+        assert!(matches!(
+            import_keypair(&path, "wrong horse battery staple"),
+            Err(Error::WrongIdentityPassphrase)
+        ));
+        Ok(())
+    }
+}
@@ -29,6 +29,7 @@ extern crate tracing;
 
 mod error;
 mod event;
+mod identity;
 mod log_markers;
 #[cfg(feature = "open-metrics")]
 mod metrics;
@@ -41,23 +42,46 @@ mod spends;
 pub use self::{
     event::{NodeEvent, NodeEventsChannel, NodeEventsReceiver},
     log_markers::Marker,
-    node::{
-        NodeBuilder, NodeCmd, PERIODIC_REPLICATION_INTERVAL_MAX_S, ROYALTY_TRANSFER_NOTIF_TOPIC,
-    },
+    node::{NodeBuilder, NodeCmd, TransferNotifHandler, PERIODIC_REPLICATION_INTERVAL_MAX_S},
+    replication::DrainReport,
 };
+pub use sn_networking::{
+    AccessStats, CompressionAlgo, ConnStats, ConnectionLimits, DialBackoff, DiskRecordStoreBackend,
+    KBucketEntry, MemoryRecordStoreBackend, RecordAgeStats, RecordStoreBackend, ValidationOutcome,
+};
+pub use sn_protocol::messages::ROYALTY_TRANSFER_NOTIF_TOPIC;
 
 use crate::error::{Error, Result};
 use bls::PublicKey;
 use bytes::Bytes;
-use libp2p::PeerId;
+use libp2p::{
+    identity::Keypair,
+    kad::{KBucketDistance, Record, RecordKey},
+    multiaddr::Protocol,
+    Multiaddr, PeerId,
+};
+use serde::{Serialize, Serializer};
 use sn_networking::{Network, SwarmLocalState};
-use sn_protocol::{get_port_from_multiaddr, NetworkAddress};
-use sn_transfers::{HotWallet, NanoTokens};
+use sn_protocol::{
+    get_port_from_multiaddr,
+    messages::{Query, QueryResponse, Request, Response},
+    storage::ChunkAddress,
+    NetworkAddress, PrettyPrintRecordKey,
+};
+use sn_transfers::{HotWallet, NanoTokens, Transfer};
 use std::{
     collections::{BTreeMap, HashSet},
+    io::Write,
+    net::SocketAddr,
     path::PathBuf,
+    sync::Arc,
+    time::Duration,
 };
-use tokio::sync::broadcast;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc,
+};
+use xor_name::XorName;
 
 /// Once a node is started and running, the user obtains
 /// a `NodeRunning` object which can be used to interact with it.
@@ -66,6 +90,56 @@ pub struct RunningNode {
     network: Network,
     node_events_channel: NodeEventsChannel,
     node_cmds: broadcast::Sender<NodeCmd>,
+    initial_peers: Arc<Vec<Multiaddr>>,
+    keypair: Keypair,
+    config: NodeConfig,
+}
+
+/// The configuration a node was started with, resolved from its `NodeBuilder` settings and their
+/// defaults. Serializes to e.g. JSON, to be included in bug reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeConfig {
+    /// The dir the node persists its records, wallet and state in.
+    pub root_dir: PathBuf,
+    /// The addresses the node listens on. A port of 0 is picked by the OS when listening.
+    pub listen_addrs: Vec<SocketAddr>,
+    /// Whether the node runs in a local network, discovering its peers with mDNS.
+    pub local: bool,
+    /// The max number of records the node stores, bound by the capacity of its record store.
+    pub record_store_capacity: usize,
+    /// How many of the peers closest to a record it is replicated to.
+    pub replication_factor: usize,
+    /// The peers the node bootstraps from.
+    #[serde(serialize_with = "serialize_multiaddrs")]
+    pub bootstrap_peers: Vec<Multiaddr>,
+}
+
+fn serialize_multiaddrs<S: Serializer>(
+    addrs: &[Multiaddr],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_seq(addrs.iter().map(|addr| addr.to_string()))
+}
+
+/// Whether the node managed to join the network through its bootstrap peers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapHealth {
+    /// The bootstrap peers the node is connected to, or has added to its routing table.
+    pub connected: Vec<Multiaddr>,
+    /// The bootstrap peers the node couldn't reach. The ones whose address lacks a peer id can't
+    /// be checked, hence are deemed failed.
+    pub failed: Vec<Multiaddr>,
+    /// The number of peers in the routing table.
+    pub routing_table_size: usize,
+}
+
+impl BootstrapHealth {
+    /// Whether the node has joined the network, i.e. it reached a bootstrap peer or it knows
+    /// other peers anyway, e.g. it kept its peer id and routing table while its original
+    /// bootstrap peers are now down.
+    pub fn is_bootstrapped(&self) -> bool {
+        !self.connected.is_empty() || self.routing_table_size > 0
+    }
 }
 
 impl RunningNode {
@@ -86,18 +160,67 @@ impl RunningNode {
         self.network.root_dir_path.clone()
     }
 
+    /// Returns the configuration the node was started with.
+    pub fn effective_config(&self) -> NodeConfig {
+        self.config.clone()
+    }
+
     /// Returns the wallet balance of the node
     pub fn get_node_wallet_balance(&self) -> Result<NanoTokens> {
         let wallet = HotWallet::load_from(&self.network.root_dir_path)?;
         Ok(wallet.balance())
     }
 
+    /// Renders the node's current metrics to the OpenMetrics text exposition format.
+    /// This doesn't require the metrics server to be reachable, e.g. when it couldn't bind a port.
+    #[cfg(feature = "open-metrics")]
+    pub fn metrics_snapshot(&self) -> Result<String> {
+        let metrics = self.network.metrics_snapshot()?;
+        Ok(metrics)
+    }
+
     /// Returns a `SwarmLocalState` with some information obtained from swarm's local state.
     pub async fn get_swarm_local_state(&self) -> Result<SwarmLocalState> {
         let state = self.network.get_swarm_local_state().await?;
         Ok(state)
     }
 
+    /// Reports which of the bootstrap peers the node was started with it reached, so that the
+    /// startup tooling can fail fast rather than leave a misconfigured node idling.
+    pub async fn bootstrap_health(&self) -> Result<BootstrapHealth> {
+        let connected_peers: HashSet<PeerId> = self
+            .network
+            .get_swarm_local_state()
+            .await?
+            .connected_peers
+            .into_iter()
+            .collect();
+        let routing_table_peers: HashSet<PeerId> = self
+            .network
+            .get_kbuckets()
+            .await?
+            .into_values()
+            .flatten()
+            .collect();
+
+        let (connected, failed) = self.initial_peers.iter().cloned().partition(|addr| {
+            addr.iter()
+                .find_map(|protocol| match protocol {
+                    Protocol::P2p(peer_id) => Some(peer_id),
+                    _ => None,
+                })
+                .is_some_and(|peer_id| {
+                    connected_peers.contains(&peer_id) || routing_table_peers.contains(&peer_id)
+                })
+        });
+
+        Ok(BootstrapHealth {
+            connected,
+            failed,
+            routing_table_size: routing_table_peers.len(),
+        })
+    }
+
     /// Return the node's listening port
     pub async fn get_node_listening_port(&self) -> Result<u16> {
         let listen_addrs = self.network.get_swarm_local_state().await?.listeners;
@@ -109,11 +232,45 @@ impl RunningNode {
         Err(Error::FailedToGetNodePort)
     }
 
+    /// Returns the XOR distance between this node and the address.
+    /// Together with the close group, this tells whether the node is responsible for the address.
+    pub fn distance_to(&self, addr: &NetworkAddress) -> KBucketDistance {
+        NetworkAddress::from_peer(self.peer_id()).distance(addr)
+    }
+
+    /// Returns the ilog2 of the XOR distance between this node and the address, i.e. the index of
+    /// the kbucket the address falls into, as listed by `get_kbuckets`.
+    /// Returns `None` for the node's own address.
+    pub fn distance_ilog2_to(&self, addr: &NetworkAddress) -> Option<u32> {
+        self.distance_to(addr).ilog2()
+    }
+
     /// Returns the node events channel where to subscribe to receive `NodeEvent`s
     pub fn node_events_channel(&self) -> &NodeEventsChannel {
         &self.node_events_channel
     }
 
+    /// Subscribes to the node events channel and writes each event to `out` as a JSON line, as
+    /// formatted by `NodeEvent::to_json_line`, e.g. to pipe the events into a log aggregator.
+    ///
+    /// Runs until the channel is closed, or errors if writing to `out` fails. The events missed
+    /// when not keeping up with the channel are skipped, with a warning logged.
+    pub async fn stream_events_jsonl(&self, mut out: impl Write) -> Result<()> {
+        let mut events = self.node_events_channel.subscribe();
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    writeln!(out, "{}", event.to_json_line()?)?;
+                    out.flush()?;
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Skipped exporting {skipped} node events, as they were not read in time");
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+
     /// Returns the list of all the RecordKeys held by the node
     pub async fn get_all_record_addresses(&self) -> Result<HashSet<NetworkAddress>> {
         #[allow(clippy::mutable_key_type)] // for Bytes in NetworkAddress
@@ -127,6 +284,194 @@ impl RunningNode {
         Ok(addresses)
     }
 
+    /// Returns the addresses of the records held by the node that a client labelled with `label`.
+    /// Labels are best-effort, they are not part of the records and are lost on restart unless
+    /// checkpointed.
+    pub async fn record_addresses_with_label(
+        &self,
+        label: &str,
+    ) -> Result<HashSet<NetworkAddress>> {
+        let addresses = self.network.get_record_addresses_with_label(label).await?;
+        Ok(addresses)
+    }
+
+    /// Writes the node's identity keypair to `path`, encrypted with the passphrase, to be
+    /// imported with `NodeBuilder::import_identity`, e.g. to migrate the node to new hardware
+    /// keeping its `PeerId`.
+    pub fn export_identity(&self, path: PathBuf, passphrase: &str) -> Result<()> {
+        identity::export_keypair(&self.keypair, &path, passphrase)?;
+        info!("Exported the node's identity to {path:?}");
+        Ok(())
+    }
+
+    /// Writes every record held by the node to `out_dir`, one file per record named after the
+    /// hex encoded record key, e.g. to migrate the node's data to new hardware.
+    /// Returns the number of records exported.
+    pub async fn export_records(&self, out_dir: PathBuf) -> Result<usize> {
+        std::fs::create_dir_all(&out_dir)?;
+        let addresses = self.network.get_all_local_record_addresses().await?;
+        let mut exported = 0;
+        for addr in addresses.keys() {
+            let key = addr.to_record_key();
+            // the record might have been pruned since we listed the addresses
+            let Some(record) = self.network.get_local_record(&key).await? else {
+                debug!(
+                    "Record {:?} is no longer held, not exporting it",
+                    PrettyPrintRecordKey::from(&key)
+                );
+                continue;
+            };
+            std::fs::write(out_dir.join(hex::encode(key.as_ref())), &record.value)?;
+            exported += 1;
+        }
+        info!("Exported {exported} records to {out_dir:?}");
+        Ok(exported)
+    }
+
+    /// Restores records from a directory written by `export_records`, validating each against
+    /// its address. Records which fail validation, which are already held, or which this node
+    /// isn't responsible for are skipped. Returns the number of records imported.
+    pub async fn import_records(&self, in_dir: PathBuf) -> Result<usize> {
+        let mut records = vec![];
+        for entry in std::fs::read_dir(&in_dir)? {
+            let path = entry?.path();
+            let key = match path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(hex::decode)
+            {
+                Some(Ok(key)) => RecordKey::from(key),
+                _ => {
+                    warn!("Skipping {path:?}, its name is not a hex encoded record key");
+                    continue;
+                }
+            };
+            records.push(Record {
+                key,
+                value: std::fs::read(&path)?,
+                publisher: None,
+                expires: None,
+            });
+        }
+        info!("Importing {} records from {in_dir:?}", records.len());
+
+        let (sender, mut receiver) = mpsc::channel(1);
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::ImportRecords { records, sender })
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        receiver.recv().await.ok_or_else(|| {
+            Error::NodeCmdFailed("The node stopped before importing the records".to_string())
+        })
+    }
+
+    /// Reclaims the disk space used by the files of the record store that don't back any record
+    /// anymore, e.g. the ones left behind by a previous run. Returns the number of bytes reclaimed.
+    /// This is safe to run while the node is serving, the files are removed off thread and the
+    /// ones written within the last minute are left alone.
+    pub async fn compact_store(&self) -> Result<u64> {
+        let reclaimed = self.network.compact_record_store().await?;
+        Ok(reclaimed)
+    }
+
+    /// Returns how many records this node was asked to replicate but doesn't hold yet, either
+    /// still queued or being fetched. A steadily growing number means the node can't keep up
+    /// with the records it is responsible for.
+    pub async fn replication_queue_depth(&self) -> Result<usize> {
+        let depth = self.network.get_replication_queue_depth().await?;
+        Ok(depth)
+    }
+
+    /// Returns the addresses of the records this node is responsible for but doesn't hold, e.g.
+    /// as it joined recently or missed their replication.
+    ///
+    /// The close group of the node is asked for the records it holds, and those within the range
+    /// of the node are checked against its local store. Records still being replicated to the
+    /// node are listed too, until fetched.
+    pub async fn missing_responsible_records(&self) -> Result<Vec<NetworkAddress>> {
+        let our_address = NetworkAddress::from_peer(self.network.peer_id);
+        let close_peers = self
+            .network
+            .get_close_group_local_peers(&our_address)
+            .await?;
+
+        let request = Request::Query(Query::GetReplicationList {
+            requester: our_address,
+        });
+        let responses = futures::future::join_all(close_peers.into_iter().map(|peer| {
+            let network = self.network.clone();
+            let request = request.clone();
+            async move { (peer, network.send_request(request, peer).await) }
+        }))
+        .await;
+
+        let mut keys = Vec::new();
+        for (peer, response) in responses {
+            match response {
+                Ok(Response::Query(QueryResponse::GetReplicationList(Ok((
+                    _holder,
+                    peer_keys,
+                ))))) => keys.extend(peer_keys),
+                other => warn!("Could not get the replication list of {peer:?}: {other:?}"),
+            }
+        }
+
+        let missing = self.network.get_missing_responsible_records(keys).await?;
+        Ok(missing)
+    }
+
+    /// Returns when the oldest and newest records held by this node were stored, along with a
+    /// histogram of the ages of all of them, e.g. to check how long the data is retained for.
+    pub async fn record_age_stats(&self) -> Result<RecordAgeStats> {
+        let stats = self.network.get_record_age_stats().await?;
+        Ok(stats)
+    }
+
+    /// Returns how many times the record at the address has been served to Kademlia GET requests
+    /// from the network, and when it last was. The node's own reads of the record, e.g. to
+    /// replicate it, are not counted.
+    ///
+    /// The counts are kept in memory, hence start over when the node restarts. `None` is
+    /// returned for a record not served since then, or not held.
+    pub async fn record_access_stats(&self, addr: NetworkAddress) -> Result<Option<AccessStats>> {
+        let stats = self
+            .network
+            .get_record_access_stats(addr.to_record_key())
+            .await?;
+        Ok(stats)
+    }
+
+    /// Returns the store cost this node would currently quote for a new record.
+    /// The cost rises as the node's record store fills up.
+    pub async fn current_store_cost(&self) -> Result<NanoTokens> {
+        // a random address that we don't hold, as records we already hold are quoted at zero
+        let key = NetworkAddress::from_chunk_address(ChunkAddress::new(XorName::random(
+            &mut rand::thread_rng(),
+        )))
+        .to_record_key();
+        let cost = self.network.get_local_storecost(key).await?;
+        Ok(cost)
+    }
+
+    /// Returns the peers that are currently blocked.
+    pub async fn blocked_peers(&self) -> Result<HashSet<PeerId>> {
+        let blocked_peers = self.network.get_blocked_peers().await?;
+        Ok(blocked_peers)
+    }
+
+    /// Returns the dial failures, timeouts and protocol errors the node has encountered since it
+    /// started, or since the counters were last reset. Set `reset` to zero them once read.
+    pub async fn connection_stats(&self, reset: bool) -> Result<ConnStats> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::GetConnectionStats { reset, sender })
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        receiver.recv().await.ok_or_else(|| {
+            Error::NodeCmdFailed("The node failed to report its connection stats".to_string())
+        })
+    }
+
     /// Returns a map where each key is the ilog2 distance of that Kbucket and each value is a vector of peers in that
     /// bucket.
     pub async fn get_kbuckets(&self) -> Result<BTreeMap<u32, Vec<PeerId>>> {
@@ -134,6 +479,13 @@ impl RunningNode {
         Ok(kbuckets)
     }
 
+    /// Returns the entry of the peer in the node's RoutingTable, i.e. its bucket and the addresses
+    /// the node knows it by, or `None` if the node doesn't know the peer.
+    pub async fn knows_peer(&self, peer: PeerId) -> Result<Option<KBucketEntry>> {
+        let entry = self.network.get_kbucket_entry(peer).await?;
+        Ok(entry)
+    }
+
     /// Subscribe to given gossipsub topic
     pub fn subscribe_to_topic(&self, topic_id: String) {
         self.network.subscribe_to_topic(topic_id);
@@ -149,6 +501,13 @@ impl RunningNode {
         self.network.unsubscribe_from_topic(topic_id);
     }
 
+    /// Set whether the messages received on a given gossipsub topic are relayed to our peers.
+    /// While relaying is disabled the node keeps receiving the topic's messages, but no longer
+    /// forwards others' messages on it, e.g. to save bandwidth on a node only consuming them.
+    pub fn set_topic_relay(&self, topic_id: String, relay: bool) {
+        self.network.set_gossip_topic_relay(topic_id, relay);
+    }
+
     /// Publish a message on a given gossipsub topic
     pub fn publish_on_topic(&self, topic_id: String, msg: Bytes) {
         self.network.publish_on_topic(topic_id, msg);
@@ -164,4 +523,147 @@ impl RunningNode {
             .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
         Ok(())
     }
+
+    /// Drop the Transfer notifications which are not signed by the node that published them,
+    /// to not be fooled by notifications forged by malicious peers. The signed notifications
+    /// report their publisher in `NodeEvent::TransferNotif`. Defaults to accepting both, as
+    /// nodes only sign the notifications they publish if set up to.
+    pub fn require_signed_transfer_notifs(&self, strict: bool) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::TransferNotifsStrict(strict))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Register a callback invoked with each decoded Transfer notification matching the key set
+    /// with `transfer_notifs_filter`. No notifications are decoded, nor callbacks invoked, until
+    /// a filter key is set. Callbacks are run on the node's tasks, so they shall not block.
+    pub fn on_transfer_notification(
+        &self,
+        handler: impl Fn(Transfer) + Send + Sync + 'static,
+    ) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::TransferNotifHandler(Arc::new(handler)))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Disconnect the peer and refuse any further connection with it, e.g. when it is abusive.
+    /// The peer stays blocked across restarts, until `unblock_peer` is called.
+    /// A warning is logged if the peer was in our close group, as we then replicate to one peer less.
+    pub fn block_peer(&self, peer: PeerId) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::BlockPeer(peer))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Accept connections with a previously blocked peer again.
+    pub fn unblock_peer(&self, peer: PeerId) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::UnblockPeer(peer))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Exempt the record at the address from being pruned when the store is full, e.g. to
+    /// guarantee the availability of application-critical data on this node.
+    /// The record stays pinned across restarts, until `unpin_record` is called.
+    pub fn pin_record(&self, addr: NetworkAddress) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::PinRecord(addr))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Let a previously pinned record be pruned again when the store is full.
+    pub fn unpin_record(&self, addr: NetworkAddress) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::UnpinRecord(addr))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Cap how fast the records are sent to the peers replicating them from us, e.g. on a
+    /// metered connection. `None` lifts the cap. Serving the records to clients is not affected.
+    pub fn set_replication_rate_limit(&self, bytes_per_sec: Option<u64>) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::SetReplicationRateLimit(bytes_per_sec))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Drains the node ahead of decommissioning it: stops accepting new records and quoting store
+    /// costs, then replicates all the records held until a majority of their close group is
+    /// confirmed to hold them, or the timeout elapses.
+    ///
+    /// The node keeps serving its records throughout, and only accepts new ones again after a
+    /// restart. The returned report lists the records that couldn't be confirmed replicated.
+    pub async fn drain(&self, timeout: Duration) -> Result<DrainReport> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::StopAcceptingRecords)
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        replication::drain_records(&self.network, timeout).await
+    }
+
+    /// Trigger a Kademlia bootstrap query to refresh the RoutingTable.
+    /// Returns once the query has been requested, without waiting for it to complete.
+    /// A `NodeEvent::RoutingTableRefreshed` is broadcasted once the query has completed.
+    pub fn refresh_routing_table(&self) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::RefreshRoutingTable)
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Re-announce the node to its close group, e.g. after a connectivity blip, so traffic is
+    /// routed to it again without waiting for the periodic refresh. The node is re-advertised
+    /// through a Kademlia self lookup, and its close group is sent the list of records it holds.
+    ///
+    /// Returns `false`, having done nothing, if the node isn't bootstrapped yet.
+    pub async fn announce_presence(&self) -> Result<bool> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::AnnouncePresence(sender))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        receiver.recv().await.ok_or_else(|| {
+            Error::NodeCmdFailed("The node failed to announce its presence".to_string())
+        })
+    }
+
+    /// Persist the in-memory indices of the node, e.g. the record labels and ages, to its root dir, from
+    /// where they are loaded back on startup. Returns once they are written, e.g. to checkpoint
+    /// the node ahead of a planned restart.
+    pub async fn checkpoint(&self) -> Result<()> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::Checkpoint(sender))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        receiver
+            .recv()
+            .await
+            .ok_or_else(|| Error::NodeCmdFailed("The node failed to checkpoint".to_string()))?
+    }
+
+    /// Trigger a Kademlia query for a random address at the given ilog2 distance from us,
+    /// to discover peers for a sparsely populated bucket.
+    /// Returns once the query has been requested, without waiting for it to complete.
+    pub fn fill_bucket(&self, ilog2_distance: u32) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::FillBucket(ilog2_distance))
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
 }
@@ -9,44 +9,59 @@
 use super::{
     error::{Error, Result},
     event::NodeEventsChannel,
+    identity,
+    replication::{
+        ReplicationEvents, ReplicationRateLimiter, DEFAULT_MAX_REPLICATION_EVENTS_PER_SEC,
+    },
     Marker, NodeEvent,
 };
 #[cfg(feature = "open-metrics")]
 use crate::metrics::NodeMetrics;
-use crate::RunningNode;
-use bls::{PublicKey, PK_SIZE};
+use crate::{NodeConfig, RunningNode};
+use bls::PublicKey;
 use bytes::Bytes;
-use libp2p::{identity::Keypair, Multiaddr, PeerId};
+use libp2p::{
+    identity::Keypair,
+    kad::{Record, RecordKey},
+    Multiaddr, PeerId,
+};
 #[cfg(feature = "open-metrics")]
 use prometheus_client::registry::Registry;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use sn_networking::{
-    close_group_majority, Network, NetworkBuilder, NetworkEvent, SwarmDriver, CLOSE_GROUP_SIZE,
+    close_group_majority, CompressionAlgo, ConnStats, ConnectionLimits, DialBackoff,
+    GossipValidator, Network, NetworkBuilder, NetworkEvent, RecordStoreBackend, SwarmDriver,
+    ValidationOutcome, CLOSE_GROUP_SIZE, MAX_RECORDS_COUNT, REPLICATE_RANGE,
 };
 use sn_protocol::{
     error::Error as ProtocolError,
-    messages::{ChunkProof, CmdResponse, Query, QueryResponse, Request, Response},
+    messages::{
+        decode_transfer_notif, transfer_notif_key, ChunkProof, CmdOk, CmdResponse, Query,
+        QueryResponse, Request, Response, ROYALTY_TRANSFER_NOTIF_TOPIC,
+    },
     NetworkAddress, PrettyPrintRecordKey,
 };
-use sn_transfers::{CashNoteRedemption, HotWallet, MainPubkey, MainSecretKey, NanoTokens};
+use sn_transfers::{HotWallet, MainPubkey, MainSecretKey, NanoTokens, Transfer};
 use std::{
-    net::SocketAddr,
-    path::PathBuf,
+    collections::BTreeSet,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
     time::Duration,
 };
 use tokio::{
-    sync::{broadcast, mpsc::Receiver},
+    sync::{broadcast, mpsc, mpsc::Receiver},
     task::{spawn, JoinHandle},
 };
 
-/// Expected topic name where notifications of royalty transfers are sent on.
-/// The notification msg is expected to contain the serialised public key, followed by the
-/// serialised transfer info encrypted against the referenced public key.
-pub const ROYALTY_TRANSFER_NOTIF_TOPIC: &str = "ROYALTY_TRANSFER_NOTIFICATION";
+/// The file in the node's root dir where the blocked peers are kept, so they stay blocked across restarts.
+const BLOCKED_PEERS_FILENAME: &str = "blocked_peers";
+
+/// The file under the root dir persisting the records pinned by the user.
+const PINNED_RECORDS_FILENAME: &str = "pinned_records";
 
 /// Defines the percentage (ie 1/FORWARDER_CHOOSING_FACTOR th of all nodes) of nodes
 /// which will act as royalty_transfer_notify forwarder.
@@ -61,9 +76,22 @@ pub const PERIODIC_REPLICATION_INTERVAL_MAX_S: u64 = 45;
 pub struct NodeBuilder {
     keypair: Keypair,
     addr: SocketAddr,
+    listen_ips: Vec<IpAddr>,
     initial_peers: Vec<Multiaddr>,
     local: bool,
     root_dir: PathBuf,
+    dial_backoff: DialBackoff,
+    connection_limits: ConnectionLimits,
+    replication_factor: usize,
+    record_store: Option<Arc<dyn RecordStoreBackend>>,
+    record_compression: CompressionAlgo,
+    gossip_validators: Vec<(String, GossipValidator)>,
+    gossip_heartbeat_interval: Option<Duration>,
+    replication_events_sample_rate: u32,
+    replication_events_max_per_sec: u32,
+    capacity_alert_thresholds: Vec<f32>,
+    #[cfg(feature = "royalties-by-gossip")]
+    sign_transfer_notifs: bool,
     #[cfg(feature = "open-metrics")]
     metrics_server_port: u16,
 }
@@ -80,14 +108,131 @@ impl NodeBuilder {
         Self {
             keypair,
             addr,
+            listen_ips: vec![],
             initial_peers,
             local,
             root_dir,
+            dial_backoff: Default::default(),
+            connection_limits: Default::default(),
+            replication_factor: REPLICATE_RANGE,
+            record_store: None,
+            record_compression: CompressionAlgo::None,
+            gossip_validators: vec![],
+            gossip_heartbeat_interval: None,
+            replication_events_sample_rate: 1,
+            replication_events_max_per_sec: DEFAULT_MAX_REPLICATION_EVENTS_PER_SEC,
+            capacity_alert_thresholds: vec![],
+            #[cfg(feature = "royalties-by-gossip")]
+            sign_transfer_notifs: false,
             #[cfg(feature = "open-metrics")]
             metrics_server_port: 0,
         }
     }
 
+    /// Only listen on the given IP, on the port of the address the builder was instantiated
+    /// with, rather than on the IP of that address. Can be called several times to listen on
+    /// several IPs, e.g. to bind some of the interfaces of a multi-homed host only.
+    pub fn listen_ip(&mut self, ip: IpAddr) {
+        self.listen_ips.push(ip);
+    }
+
+    /// Run the node with the identity keypair exported by `RunningNode::export_identity`, hence
+    /// with the same `PeerId` as the node it was exported from. Errors without changing the
+    /// identity if the passphrase is not the one it was exported with.
+    ///
+    /// Note that the imported keypair is not persisted by the builder.
+    pub fn import_identity(&mut self, path: &Path, passphrase: &str) -> Result<()> {
+        self.keypair = identity::import_keypair(path, passphrase)?;
+        info!(
+            "Imported the identity of {:?} from {path:?}",
+            self.keypair.public().to_peer_id()
+        );
+        Ok(())
+    }
+
+    /// Set the backoff applied when redialing peers we have failed to connect to.
+    /// Defaults to no backoff, i.e. failed peers are redialed straight away.
+    pub fn dial_backoff(&mut self, dial_backoff: DialBackoff) {
+        self.dial_backoff = dial_backoff;
+    }
+
+    /// Cap the connections of the node, e.g. to not run out of file descriptors on a small host.
+    /// The connections beyond the limits are refused. Defaults to `ConnectionLimits::default()`,
+    /// which only caps the incoming connections, well above what a node needs.
+    pub fn connection_limits(&mut self, connection_limits: ConnectionLimits) {
+        self.connection_limits = connection_limits;
+    }
+
+    /// Set how many of the peers closest to a record it is replicated to. Defaults to
+    /// `REPLICATE_RANGE`, which small test networks may want to lower below their node count.
+    pub fn replication_factor(&mut self, replication_factor: usize) {
+        self.replication_factor = replication_factor;
+    }
+
+    /// Set where the node persists its records, e.g. an in-memory store for tests.
+    /// Defaults to storing them on disk under the root dir.
+    pub fn record_store(&mut self, record_store: Arc<dyn RecordStoreBackend>) {
+        self.record_store = Some(record_store);
+    }
+
+    /// Set how the records are compressed before being persisted, to fit more of them on disk.
+    /// The records are decompressed on read, so they are validated and served as they were
+    /// received. Defaults to no compression.
+    pub fn record_compression(&mut self, compression: CompressionAlgo) {
+        self.record_compression = compression;
+    }
+
+    /// Validate the gossip messages received on the topic, e.g. to reject malformed payloads,
+    /// before they are handled or forwarded to our peers.
+    /// The messages on topics without a validator are accepted.
+    pub fn gossip_validator(
+        &mut self,
+        topic: impl Into<String>,
+        validator: impl Fn(&[u8]) -> ValidationOutcome + Send + Sync + 'static,
+    ) {
+        self.gossip_validators
+            .push((topic.into(), GossipValidator::new(validator)));
+    }
+
+    /// Set the gossipsub heartbeat interval, i.e. how often the meshes are maintained and the
+    /// recent messages gossiped about. Faster heartbeats deliver the transfer notifications
+    /// sooner, at the cost of more traffic. Defaults to 5 seconds, and starting the node fails
+    /// if it's below `GOSSIP_HEARTBEAT_INTERVAL_MIN`.
+    pub fn gossip_heartbeat_interval(&mut self, interval: Duration) {
+        self.gossip_heartbeat_interval = Some(interval);
+    }
+
+    /// Only emit one in every `sample_rate` of the `NodeEvent::ReplicationIn`/`ReplicationOut`
+    /// events, so that heavy replication doesn't flood the events channel. Defaults to emitting
+    /// them all, up to the `replication_events_max_per_sec` cap.
+    pub fn replication_events_sample_rate(&mut self, sample_rate: u32) {
+        self.replication_events_sample_rate = sample_rate;
+    }
+
+    /// Never emit more than `max_per_sec` of the sampled `NodeEvent::ReplicationIn`/
+    /// `ReplicationOut` events per second, dropping the ones beyond. Defaults to 50, and zero
+    /// turns the events off.
+    pub fn replication_events_max_per_sec(&mut self, max_per_sec: u32) {
+        self.replication_events_max_per_sec = max_per_sec;
+    }
+
+    /// Emit a `NodeEvent::CapacityThresholdCrossed` when the record store fills past each of the
+    /// fractions of its capacity, e.g. `vec![0.8, 0.9, 0.95]`, and a
+    /// `NodeEvent::CapacityThresholdCleared` when it drops back below it, so that a supervising
+    /// process can add capacity or drain the node before the store is full.
+    /// Fractions outside of `(0, 1]` are ignored. Defaults to none.
+    pub fn capacity_alert_thresholds(&mut self, thresholds: Vec<f32>) {
+        self.capacity_alert_thresholds = thresholds;
+    }
+
+    /// Sign the transfer notifications the node publishes with its identity key, so that their
+    /// recipients can authenticate the node. Defaults to publishing them unsigned, which is what
+    /// nodes that don't verify the signatures understand.
+    #[cfg(feature = "royalties-by-gossip")]
+    pub fn sign_transfer_notifs(&mut self, sign: bool) {
+        self.sign_transfer_notifs = sign;
+    }
+
     #[cfg(feature = "open-metrics")]
     /// Set the port for the OpenMetrics server. Defaults to a random port if not set
     pub fn metrics_server_port(&mut self, port: u16) {
@@ -128,10 +273,51 @@ impl NodeBuilder {
             (metrics_registry, node_metrics)
         };
 
+        let blocked_peers = read_blocked_peers(&self.root_dir);
+        let pinned_records = read_pinned_records(&self.root_dir);
+        let keypair = self.keypair.clone();
+        let config = NodeConfig {
+            root_dir: self.root_dir.clone(),
+            listen_addrs: if self.listen_ips.is_empty() {
+                vec![self.addr]
+            } else {
+                self.listen_ips
+                    .iter()
+                    .map(|ip| SocketAddr::new(*ip, self.addr.port()))
+                    .collect()
+            },
+            local: self.local,
+            record_store_capacity: self
+                .record_store
+                .as_ref()
+                .and_then(|record_store| record_store.capacity())
+                .map_or(MAX_RECORDS_COUNT, |capacity| {
+                    capacity.min(MAX_RECORDS_COUNT)
+                }),
+            replication_factor: self.replication_factor.max(1),
+            bootstrap_peers: self.initial_peers.clone(),
+        };
         let mut network_builder = NetworkBuilder::new(self.keypair, self.local, self.root_dir);
 
         network_builder.enable_gossip();
         network_builder.listen_addr(self.addr);
+        for ip in self.listen_ips {
+            network_builder.listen_ip(ip);
+        }
+        network_builder.dial_backoff(self.dial_backoff);
+        network_builder.connection_limits(self.connection_limits);
+        network_builder.replication_factor(self.replication_factor);
+        if let Some(record_store) = self.record_store {
+            network_builder.record_store_backend(record_store);
+        }
+        network_builder.record_compression(self.record_compression);
+        network_builder.capacity_alert_thresholds(self.capacity_alert_thresholds);
+        if let Some(interval) = self.gossip_heartbeat_interval {
+            network_builder.gossip_heartbeat_interval(interval);
+        }
+        for (topic, validator) in self.gossip_validators {
+            network_builder.gossip_validator(topic, validator);
+        }
         #[cfg(feature = "open-metrics")]
         network_builder.metrics_registry(metrics_registry);
         #[cfg(feature = "open-metrics")]
@@ -141,13 +327,34 @@ impl NodeBuilder {
         let node_events_channel = NodeEventsChannel::default();
         let (node_cmds, _) = broadcast::channel(10);
 
+        for peer_id in &blocked_peers {
+            network.block_peer(*peer_id);
+        }
+        for addr in &pinned_records {
+            network.pin_record(addr.to_record_key());
+        }
+
+        let initial_peers = Arc::new(self.initial_peers);
         let node = Node {
             network: network.clone(),
             events_channel: node_events_channel.clone(),
             node_cmds: node_cmds.clone(),
-            initial_peers: Arc::new(self.initial_peers),
+            initial_peers: initial_peers.clone(),
             reward_address: Arc::new(reward_address),
             transfer_notifs_filter: None,
+            transfer_notifs_strict: false,
+            transfer_notif_handlers: vec![],
+            #[cfg(feature = "royalties-by-gossip")]
+            sign_transfer_notifs: self.sign_transfer_notifs,
+            blocked_peers,
+            pinned_records,
+            replication_rate_limiter: Default::default(),
+            replication_events: ReplicationEvents::new(
+                node_events_channel.clone(),
+                self.replication_events_sample_rate,
+                self.replication_events_max_per_sec,
+            ),
+            draining: Default::default(),
             #[cfg(feature = "open-metrics")]
             node_metrics,
         };
@@ -155,6 +362,9 @@ impl NodeBuilder {
             network,
             node_events_channel,
             node_cmds,
+            initial_peers,
+            keypair,
+            config,
         };
 
         // Run the node
@@ -179,11 +389,54 @@ impl NodeBuilder {
     }
 }
 
+/// A callback invoked with each decoded Transfer notification matching the filter key.
+pub type TransferNotifHandler = Arc<dyn Fn(Transfer) + Send + Sync>;
+
 /// Commands that can be sent by the user to the Node instance, e.g. to mutate some settings.
 #[derive(Clone)]
 pub enum NodeCmd {
     /// Set a PublicKey to start decoding and accepting Transfer notifications received over gossipsub.
     TransferNotifsFilter(Option<PublicKey>),
+    /// Register a callback for the decoded Transfer notifications matching the filter key.
+    TransferNotifHandler(TransferNotifHandler),
+    /// Drop the Transfer notifications which are not signed by their publisher.
+    TransferNotifsStrict(bool),
+    /// Trigger a Kademlia bootstrap query to refresh the RoutingTable.
+    RefreshRoutingTable,
+    /// Trigger a Kademlia query to discover peers for the bucket at the given ilog2 distance.
+    FillBucket(u32),
+    /// Re-advertise the node to its close group, sending back whether anything was done.
+    AnnouncePresence(mpsc::Sender<bool>),
+    /// Persist the in-memory indices of the node to the root dir, sending back the outcome.
+    Checkpoint(mpsc::Sender<Result<()>>),
+    /// Disconnect the peer and refuse any further connection with it, also after a restart.
+    BlockPeer(PeerId),
+    /// Accept connections with a previously blocked peer again.
+    UnblockPeer(PeerId),
+    /// Validate and store records restored from a backup, sending back how many were stored.
+    ImportRecords {
+        /// The records to import.
+        records: Vec<Record>,
+        /// Where the number of imported records is sent once done.
+        sender: mpsc::Sender<usize>,
+    },
+    /// Never prune the record, even when the store is full, also after a restart.
+    PinRecord(NetworkAddress),
+    /// Let a previously pinned record be pruned again.
+    UnpinRecord(NetworkAddress),
+    /// Cap the bytes per second of the records sent to the peers replicating them from us,
+    /// `None` meaning unlimited. Serving the records to clients is not affected.
+    SetReplicationRateLimit(Option<u64>),
+    /// Stop accepting new records, from clients and through replication, and stop quoting store
+    /// costs, ahead of the node being decommissioned. This lasts until the node is restarted.
+    StopAcceptingRecords,
+    /// Get the connection error counters of the node, optionally resetting them afterwards.
+    GetConnectionStats {
+        /// Whether to reset the counters once read.
+        reset: bool,
+        /// Where the counters are sent.
+        sender: mpsc::Sender<ConnStats>,
+    },
 }
 
 /// `Node` represents a single node in the distributed network. It handles
@@ -199,6 +452,22 @@ pub(crate) struct Node {
     initial_peers: Arc<Vec<Multiaddr>>,
     reward_address: Arc<MainPubkey>,
     transfer_notifs_filter: Option<PublicKey>,
+    // Whether the unsigned transfer notifications are dropped.
+    transfer_notifs_strict: bool,
+    transfer_notif_handlers: Vec<TransferNotifHandler>,
+    // Whether the transfer notifications we publish are signed.
+    #[cfg(feature = "royalties-by-gossip")]
+    pub(crate) sign_transfer_notifs: bool,
+    // Peers blocked by the user, persisted to the root dir.
+    blocked_peers: BTreeSet<PeerId>,
+    // Records pinned by the user, persisted to the root dir.
+    pinned_records: BTreeSet<NetworkAddress>,
+    // Caps the rate of the records served for replication.
+    replication_rate_limiter: ReplicationRateLimiter,
+    // Emits the sampled events of the records replicated in and out.
+    pub(crate) replication_events: ReplicationEvents,
+    // Set once the node is being drained, from then on it takes no new records.
+    draining: Arc<AtomicBool>,
     #[cfg(feature = "open-metrics")]
     pub(crate) node_metrics: NodeMetrics,
 }
@@ -289,6 +558,103 @@ impl Node {
                                 self.transfer_notifs_filter = filter;
                                 self.network.start_handle_gossip();
                             }
+                            Ok(NodeCmd::TransferNotifHandler(handler)) => {
+                                self.transfer_notif_handlers.push(handler);
+                            }
+                            Ok(NodeCmd::TransferNotifsStrict(strict)) => {
+                                self.transfer_notifs_strict = strict;
+                            }
+                            Ok(NodeCmd::RefreshRoutingTable) => {
+                                self.network.refresh_routing_table();
+                            }
+                            Ok(NodeCmd::FillBucket(ilog2_distance)) => {
+                                self.network.fill_bucket(ilog2_distance);
+                            }
+                            Ok(NodeCmd::AnnouncePresence(sender)) => {
+                                let network = self.network.clone();
+                                let _handle = spawn(async move {
+                                    match network.announce_presence().await {
+                                        Ok(announced) => {
+                                            if sender.send(announced).await.is_err() {
+                                                warn!("Announced our presence, but the requester is gone");
+                                            }
+                                        }
+                                        Err(err) => error!("Failed to announce our presence: {err:?}"),
+                                    }
+                                });
+                            }
+                            Ok(NodeCmd::Checkpoint(sender)) => {
+                                // the blocked peers and pinned records are persisted as they change,
+                                // rewriting them covers any write that failed back then
+                                write_blocked_peers(&self.network.root_dir_path, &self.blocked_peers);
+                                write_pinned_records(&self.network.root_dir_path, &self.pinned_records);
+                                let network = self.network.clone();
+                                let _handle = spawn(async move {
+                                    let result = network.checkpoint().await;
+                                    if let Err(err) = &result {
+                                        error!("Failed to checkpoint the node: {err:?}");
+                                    }
+                                    if sender.send(result.map_err(Error::from)).await.is_err() {
+                                        warn!("Checkpointed the node, but the requester is gone");
+                                    }
+                                });
+                            }
+                            Ok(NodeCmd::BlockPeer(peer_id)) => {
+                                self.network.block_peer(peer_id);
+                                if self.blocked_peers.insert(peer_id) {
+                                    write_blocked_peers(&self.network.root_dir_path, &self.blocked_peers);
+                                }
+                            }
+                            Ok(NodeCmd::UnblockPeer(peer_id)) => {
+                                self.network.unblock_peer(peer_id);
+                                if self.blocked_peers.remove(&peer_id) {
+                                    write_blocked_peers(&self.network.root_dir_path, &self.blocked_peers);
+                                }
+                            }
+                            Ok(NodeCmd::PinRecord(addr)) => {
+                                let addr = NetworkAddress::from_record_key(&addr.to_record_key());
+                                self.network.pin_record(addr.to_record_key());
+                                if self.pinned_records.insert(addr) {
+                                    write_pinned_records(&self.network.root_dir_path, &self.pinned_records);
+                                }
+                            }
+                            Ok(NodeCmd::UnpinRecord(addr)) => {
+                                let addr = NetworkAddress::from_record_key(&addr.to_record_key());
+                                self.network.unpin_record(addr.to_record_key());
+                                if self.pinned_records.remove(&addr) {
+                                    write_pinned_records(&self.network.root_dir_path, &self.pinned_records);
+                                }
+                            }
+                            Ok(NodeCmd::SetReplicationRateLimit(bytes_per_sec)) => {
+                                info!("Setting the replication rate limit to {bytes_per_sec:?} bytes per second");
+                                self.replication_rate_limiter.set_bytes_per_sec(bytes_per_sec);
+                            }
+                            Ok(NodeCmd::StopAcceptingRecords) => {
+                                info!("No longer accepting new records, the node is being drained");
+                                self.draining.store(true, Ordering::SeqCst);
+                            }
+                            Ok(NodeCmd::ImportRecords { records, sender }) => {
+                                let node = self.clone();
+                                let _handle = spawn(async move {
+                                    let imported = node.import_records(records).await;
+                                    if sender.send(imported).await.is_err() {
+                                        warn!("Imported {imported} records, but the requester is gone");
+                                    }
+                                });
+                            }
+                            Ok(NodeCmd::GetConnectionStats { reset, sender }) => {
+                                let network = self.network.clone();
+                                let _handle = spawn(async move {
+                                    match network.get_connection_stats(reset).await {
+                                        Ok(stats) => {
+                                            if sender.send(stats).await.is_err() {
+                                                warn!("Got the connection stats, but the requester is gone");
+                                            }
+                                        }
+                                        Err(err) => error!("Failed to get the connection stats: {err:?}"),
+                                    }
+                                });
+                            }
                             Err(err) => error!("When trying to read from the NodeCmds channel/receiver: {err:?}")
                         }
                     }
@@ -297,6 +663,52 @@ impl Node {
         });
     }
 
+    /// Validates and stores the records we are responsible for, returning how many were stored.
+    /// Records that fail validation, that we already hold, or that we are not responsible for are skipped.
+    async fn import_records(&self, records: Vec<Record>) -> usize {
+        let total = records.len();
+        let mut imported = 0;
+        for record in records {
+            let pretty_key = PrettyPrintRecordKey::from(&record.key).into_owned();
+            let addr = NetworkAddress::from_record_key(&record.key);
+            match self.is_responsible_for(&addr).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    debug!("Not importing {pretty_key:?}, we are not responsible for it");
+                    continue;
+                }
+                Err(err) => {
+                    warn!(
+                        "Not importing {pretty_key:?}, could not check our responsibility: {err}"
+                    );
+                    continue;
+                }
+            }
+
+            match self.store_prepaid_record(record).await {
+                Ok(CmdOk::StoredSuccessfully) => imported += 1,
+                Ok(CmdOk::DataAlreadyPresent) => {
+                    debug!("Not importing {pretty_key:?}, we already hold it");
+                }
+                Err(err) => warn!("Not importing {pretty_key:?}, it failed validation: {err}"),
+            }
+        }
+        info!("Imported {imported} of {total} records");
+        imported
+    }
+
+    /// Returns true if we are among the close group of the address, as far as our RT knows.
+    async fn is_responsible_for(&self, addr: &NetworkAddress) -> Result<bool> {
+        let close_peers = self.network.get_close_group_local_peers(addr).await?;
+        if close_peers.len() < CLOSE_GROUP_SIZE {
+            return Ok(true);
+        }
+        let our_distance = addr.distance(&NetworkAddress::from_peer(self.network.peer_id));
+        Ok(close_peers
+            .iter()
+            .any(|peer| addr.distance(&NetworkAddress::from_peer(*peer)) > our_distance))
+    }
+
     /// Calls Marker::log() to insert the marker into the log files.
     /// Also calls NodeMetrics::record() to record the metric if the `open-metrics` feature flag is enabled.
     pub(crate) fn record_metrics(&self, marker: Marker) {
@@ -368,20 +780,38 @@ impl Node {
             }
             NetworkEvent::KeysToFetchForReplication(keys) => {
                 event_header = "KeysToFetchForReplication";
-                info!("Going to fetch {:?} keys for replication", keys.len());
-                self.record_metrics(Marker::fetching_keys_for_replication(&keys));
+                if self.draining.load(Ordering::SeqCst) {
+                    debug!(
+                        "Not fetching {:?} keys for replication, the node is being drained",
+                        keys.len()
+                    );
+                } else {
+                    info!("Going to fetch {:?} keys for replication", keys.len());
+                    self.record_metrics(Marker::fetching_keys_for_replication(&keys));
 
-                if let Err(err) = self.fetch_replication_keys_without_wait(keys) {
-                    error!("Error while trying to fetch replicated data {err:?}");
+                    if let Err(err) = self.fetch_replication_keys_without_wait(keys) {
+                        error!("Error while trying to fetch replicated data {err:?}");
+                    }
                 }
             }
             NetworkEvent::QueryRequestReceived { query, channel } => {
                 event_header = "QueryRequestReceived";
                 let network = self.network.clone();
                 let payment_address = *self.reward_address;
+                let replication_rate_limiter = self.replication_rate_limiter.clone();
+                let replication_events = self.replication_events.clone();
+                let is_draining = self.draining.load(Ordering::SeqCst);
 
                 let _handle = spawn(async move {
-                    let res = Self::handle_query(&network, query, payment_address).await;
+                    let res = Self::handle_query(
+                        &network,
+                        query,
+                        payment_address,
+                        &replication_rate_limiter,
+                        &replication_events,
+                        is_draining,
+                    )
+                    .await;
                     trace!("Sending response {res:?}");
 
                     network.send_response(res, channel);
@@ -389,17 +819,24 @@ impl Node {
             }
             NetworkEvent::UnverifiedRecord(record) => {
                 event_header = "UnverifiedRecord";
-                // queries can be long running and require validation, so we spawn a task to handle them
-                let self_clone = self.clone();
-                let _handle = spawn(async move {
-                    let key = PrettyPrintRecordKey::from(&record.key).into_owned();
-                    match self_clone.validate_and_store_record(record).await {
-                        Ok(cmdok) => trace!("UnverifiedRecord {key} stored with {cmdok:?}."),
-                        Err(err) => {
-                            self_clone.record_metrics(Marker::RecordRejected(&key, &err));
+                if self.draining.load(Ordering::SeqCst) {
+                    debug!(
+                        "Not storing the record {:?}, the node is being drained",
+                        PrettyPrintRecordKey::from(&record.key)
+                    );
+                } else {
+                    // queries can be long running and require validation, so we spawn a task to handle them
+                    let self_clone = self.clone();
+                    let _handle = spawn(async move {
+                        let key = PrettyPrintRecordKey::from(&record.key).into_owned();
+                        match self_clone.validate_and_store_record(record).await {
+                            Ok(cmdok) => trace!("UnverifiedRecord {key} stored with {cmdok:?}."),
+                            Err(err) => {
+                                self_clone.record_metrics(Marker::RecordRejected(&key, &err));
+                            }
                         }
-                    }
-                });
+                    });
+                }
             }
             NetworkEvent::GossipsubMsgReceived { topic, msg }
             | NetworkEvent::GossipsubMsgPublished { topic, msg } => {
@@ -407,7 +844,9 @@ impl Node {
                 trace!("Received a gossip msg for the topic of {topic}");
                 let events_channel = self.events_channel.clone();
 
-                if events_channel.receiver_count() == 0 {
+                let has_notif_handlers = topic == ROYALTY_TRANSFER_NOTIF_TOPIC
+                    && !self.transfer_notif_handlers.is_empty();
+                if events_channel.receiver_count() == 0 && !has_notif_handlers {
                     trace!(
                         "Network handling statistics, Event {event_header:?} handled in {:?} : {event_string:?}",
                         start.elapsed()
@@ -418,9 +857,24 @@ impl Node {
                     // this is expected to be a notification of a transfer which we treat specially,
                     // and we try to decode it only if it's referring to a PK the user is interested in
                     if let Some(filter_pk) = self.transfer_notifs_filter {
+                        let handlers = self.transfer_notif_handlers.clone();
+                        let strict = self.transfer_notifs_strict;
                         let _handle = spawn(async move {
-                            match try_decode_transfer_notif(&msg, filter_pk) {
-                                Ok(Some(notif_event)) => events_channel.broadcast(notif_event),
+                            match try_decode_transfer_notif(&msg, filter_pk, strict) {
+                                Ok(Some(notif_event)) => {
+                                    if let NodeEvent::TransferNotif {
+                                        cashnote_redemptions,
+                                        ..
+                                    } = &notif_event
+                                    {
+                                        for handler in &handlers {
+                                            handler(Transfer::NetworkRoyalties(
+                                                cashnote_redemptions.clone(),
+                                            ));
+                                        }
+                                    }
+                                    events_channel.broadcast(notif_event)
+                                }
                                 Ok(None) => { /* transfer notif filered out */ }
                                 Err(err) => {
                                     warn!("GossipsubMsg matching the transfer notif. topic name, couldn't be decoded as such: {err:?}");
@@ -459,6 +913,35 @@ impl Node {
                     network.notify_node_status(peer_id, addrs, is_bad);
                 });
             }
+            NetworkEvent::RoutingTableRefreshed => {
+                event_header = "RoutingTableRefreshed";
+                self.events_channel
+                    .broadcast(NodeEvent::RoutingTableRefreshed);
+            }
+            NetworkEvent::CapacityThresholdCrossed {
+                threshold,
+                upward,
+                used_bytes,
+                max_bytes,
+            } => {
+                event_header = "CapacityThresholdCrossed";
+                let event = if upward {
+                    warn!("The record store is filled past {threshold} of its capacity");
+                    NodeEvent::CapacityThresholdCrossed {
+                        fraction: threshold,
+                        used_bytes,
+                        max_bytes,
+                    }
+                } else {
+                    info!("The record store dropped back below {threshold} of its capacity");
+                    NodeEvent::CapacityThresholdCleared {
+                        fraction: threshold,
+                        used_bytes,
+                        max_bytes,
+                    }
+                };
+                self.events_channel.broadcast(event);
+            }
         }
 
         trace!(
@@ -541,8 +1024,19 @@ impl Node {
         network: &Network,
         query: Query,
         payment_address: MainPubkey,
+        replication_rate_limiter: &ReplicationRateLimiter,
+        replication_events: &ReplicationEvents,
+        is_draining: bool,
     ) -> Response {
         let resp: QueryResponse = match query {
+            Query::GetStoreCost(address) if is_draining => {
+                trace!("Not quoting for {address:?}, the node is being drained");
+                QueryResponse::GetStoreCost {
+                    quote: Err(ProtocolError::GetStoreCostFailed),
+                    payment_address,
+                    peer_address: NetworkAddress::from_peer(network.peer_id),
+                }
+            }
             Query::GetStoreCost(address) => {
                 trace!("Got GetStoreCost request for {address:?}");
                 let record_key = address.to_record_key();
@@ -587,12 +1081,31 @@ impl Node {
 
                 if let Some(record_key) = record_key {
                     if let Ok(Some(record)) = network.get_local_record(&record_key).await {
+                        replication_rate_limiter.throttle(record.value.len()).await;
+                        if let Some(requester_id) = requester.as_peer_id() {
+                            replication_events.replicated_out(key.clone(), requester_id);
+                        }
                         result = Ok((our_address, Bytes::from(record.value)));
                     }
                 }
 
                 QueryResponse::GetReplicatedRecord(result)
             }
+            Query::GetRecordFromPeer(key) => {
+                trace!("Got GetRecordFromPeer regarding {key:?}");
+
+                let our_address = NetworkAddress::from_peer(network.peer_id);
+                let mut result = Err(ProtocolError::RecordNotFound {
+                    holder: Box::new(our_address.clone()),
+                    key: Box::new(key.clone()),
+                });
+
+                if let Ok(Some(record)) = network.get_local_record(&key.to_record_key()).await {
+                    result = Ok((our_address, Bytes::from(record.value)));
+                }
+
+                QueryResponse::GetRecordFromPeer(result)
+            }
             Query::GetChunkExistenceProof { key, nonce } => {
                 trace!("Got GetChunkExistenceProof for chunk {key:?}");
 
@@ -626,6 +1139,22 @@ impl Node {
                     is_in_trouble,
                 }
             }
+            Query::GetReplicationList { requester } => {
+                trace!("Got GetReplicationList from {requester:?}");
+
+                let result = match network.get_all_local_record_addresses().await {
+                    Ok(addresses) => Ok((
+                        NetworkAddress::from_peer(network.peer_id),
+                        addresses.into_iter().collect(),
+                    )),
+                    Err(err) => {
+                        warn!("Could not get the replication list for {requester:?}: {err:?}");
+                        Err(ProtocolError::GetReplicationListFailed)
+                    }
+                };
+
+                QueryResponse::GetReplicationList(result)
+            }
         };
         Response::Query(resp)
     }
@@ -649,20 +1178,128 @@ impl Node {
     }
 }
 
-fn try_decode_transfer_notif(msg: &[u8], filter: PublicKey) -> eyre::Result<Option<NodeEvent>> {
-    let mut key_bytes = [0u8; PK_SIZE];
-    key_bytes.copy_from_slice(
-        msg.get(0..PK_SIZE)
-            .ok_or_else(|| eyre::eyre!("msg doesn't have enough bytes"))?,
-    );
-    let key = PublicKey::from_bytes(key_bytes)?;
-    if key == filter {
-        let cashnote_redemptions: Vec<CashNoteRedemption> = rmp_serde::from_slice(&msg[PK_SIZE..])?;
-        Ok(Some(NodeEvent::TransferNotif {
-            key,
-            cashnote_redemptions,
-        }))
-    } else {
-        Ok(None)
+/// Encodes a notification of the transfer to the key, signing it with the node's identity key if
+/// a network is given.
+#[cfg(feature = "royalties-by-gossip")]
+pub(crate) fn encode_transfer_notif(
+    key: PublicKey,
+    cashnote_redemptions: Vec<sn_transfers::CashNoteRedemption>,
+    signer: Option<&Network>,
+) -> eyre::Result<Bytes> {
+    use sn_protocol::messages;
+
+    let unsigned = messages::encode_transfer_notif(&key, &cashnote_redemptions)?;
+    let Some(network) = signer else {
+        return Ok(unsigned);
+    };
+
+    let signature = network.sign(&unsigned)?;
+    Ok(messages::encode_signed_transfer_notif(
+        &key,
+        cashnote_redemptions,
+        &network.public_key(),
+        signature,
+    )?)
+}
+
+/// Decodes the notification if it's about the filter key, verifying its signature if it is signed.
+/// Unsigned notifications are dropped in strict mode, as are the ones with an invalid signature.
+fn try_decode_transfer_notif(
+    msg: &[u8],
+    filter: PublicKey,
+    strict: bool,
+) -> eyre::Result<Option<NodeEvent>> {
+    let key = transfer_notif_key(msg)?;
+    if key != filter {
+        return Ok(None);
+    }
+
+    let notif = match decode_transfer_notif(msg) {
+        Ok(notif) => notif,
+        Err(ProtocolError::TransferNotifInvalidSignature(publisher)) => {
+            warn!("Dropping a transfer notification for {key:?} with an invalid signature from {publisher}");
+            return Ok(None);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    if strict && notif.publisher.is_none() {
+        debug!("Dropping an unsigned transfer notification for {key:?}");
+        return Ok(None);
+    }
+
+    Ok(Some(NodeEvent::TransferNotif {
+        key,
+        cashnote_redemptions: notif.cashnote_redemptions,
+        publisher: notif.publisher,
+    }))
+}
+
+/// Reads the peers blocked in a previous run. A missing or unreadable file means no peer is blocked.
+fn read_blocked_peers(root_dir: &Path) -> BTreeSet<PeerId> {
+    read_persisted_set(root_dir, BLOCKED_PEERS_FILENAME, |line| {
+        line.parse::<PeerId>()
+    })
+}
+
+/// Persists the blocked peers, one per line, so that they stay blocked after a restart.
+fn write_blocked_peers(root_dir: &Path, blocked_peers: &BTreeSet<PeerId>) {
+    write_persisted_set(root_dir, BLOCKED_PEERS_FILENAME, blocked_peers, |peer_id| {
+        peer_id.to_string()
+    });
+}
+
+/// Reads the records pinned in a previous run. A missing or unreadable file means no record is pinned.
+fn read_pinned_records(root_dir: &Path) -> BTreeSet<NetworkAddress> {
+    read_persisted_set(root_dir, PINNED_RECORDS_FILENAME, |line| {
+        hex::decode(line).map(|bytes| NetworkAddress::from_record_key(&RecordKey::from(bytes)))
+    })
+}
+
+/// Persists the hex encoded keys of the pinned records, one per line, so that they stay pinned
+/// after a restart.
+fn write_pinned_records(root_dir: &Path, pinned_records: &BTreeSet<NetworkAddress>) {
+    write_persisted_set(root_dir, PINNED_RECORDS_FILENAME, pinned_records, |addr| {
+        hex::encode(addr.to_record_key())
+    });
+}
+
+/// Reads the set persisted by `write_persisted_set` to the file under the root dir, skipping the
+/// entries that fail to parse. A missing or unreadable file means an empty set.
+fn read_persisted_set<T: Ord, E: std::fmt::Display>(
+    root_dir: &Path,
+    filename: &str,
+    parse: impl Fn(&str) -> std::result::Result<T, E>,
+) -> BTreeSet<T> {
+    let path = root_dir.join(filename);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return BTreeSet::new();
+    };
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match parse(line.trim()) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                warn!("Ignoring invalid entry {line:?} in {path:?}: {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Persists the set to the file under the root dir, one formatted entry per line.
+fn write_persisted_set<T>(
+    root_dir: &Path,
+    filename: &str,
+    entries: &BTreeSet<T>,
+    format: impl Fn(&T) -> String,
+) {
+    let path = root_dir.join(filename);
+    let content: String = entries
+        .iter()
+        .map(|entry| format!("{}\n", format(entry)))
+        .collect();
+    if let Err(err) = std::fs::write(&path, content) {
+        error!("Failed to persist {path:?}: {err}");
     }
 }
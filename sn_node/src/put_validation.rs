@@ -7,17 +7,13 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 #[cfg(feature = "royalties-by-gossip")]
-use crate::node::ROYALTY_TRANSFER_NOTIF_TOPIC;
+use crate::node::encode_transfer_notif;
 use crate::{
     node::Node,
     spends::{aggregate_spends, check_parent_spends},
     Error, Marker, Result,
 };
-#[cfg(feature = "royalties-by-gossip")]
-use bytes::{BufMut, BytesMut};
 use libp2p::kad::{Record, RecordKey};
-#[cfg(feature = "royalties-by-gossip")]
-use serde::Serialize;
 use sn_networking::{get_singed_spends_from_record, Error as NetworkError, GetRecordError};
 use sn_protocol::{
     messages::CmdOk,
@@ -76,6 +72,7 @@ impl Node {
                 if store_chunk_result.is_ok() {
                     Marker::ValidPaidChunkPutFromClient(&PrettyPrintRecordKey::from(&record.key))
                         .log();
+                    self.notify_put_accepted(&store_chunk_result, &record_key, true, false);
                     self.replicate_valid_fresh_record(record_key, RecordType::Chunk);
                 }
 
@@ -93,6 +90,7 @@ impl Node {
                 let result = self.validate_spend_record(record).await;
                 if result.is_ok() {
                     Marker::ValidSpendPutFromClient(&PrettyPrintRecordKey::from(&record_key)).log();
+                    self.notify_put_accepted(&result, &record_key, false, false);
                     let content_hash = XorName::from_content(&value_to_hash);
                     self.replicate_valid_fresh_record(
                         record_key,
@@ -122,6 +120,7 @@ impl Node {
 
                 if result.is_ok() {
                     Marker::ValidPaidRegisterPutFromClient(&pretty_key).log();
+                    self.notify_put_accepted(&result, &key, false, false);
                     // we dont try and force replicaiton here as there's state to be kept in sync
                     // which we leave up to the client to enforce
                 }
@@ -148,19 +147,24 @@ impl Node {
                 // The payment shall get deposit to self even the register already presents.
                 // However, if the register already presents, the incoming one maybe for edit only.
                 // Hence the corresponding payment error shall not be thrown out.
-                if let Err(err) = self
+                let payment_verified = match self
                     .payment_for_us_exists_and_is_still_valid(&net_addr, payment)
                     .await
                 {
-                    if already_exists {
+                    Ok(()) => true,
+                    Err(err) if already_exists => {
                         trace!("Payment of the incoming exists register {pretty_key:?} having error {err:?}");
-                    } else {
+                        false
+                    }
+                    Err(err) => {
                         error!("Payment of the incoming non-exist register {pretty_key:?} having error {err:?}");
                         return Err(err);
                     }
-                }
+                };
 
-                self.validate_and_store_register(register, true).await
+                let result = self.validate_and_store_register(register, true).await;
+                self.notify_put_accepted(&result, &key, payment_verified, false);
+                result
             }
         }
     }
@@ -188,7 +192,8 @@ impl Node {
     pub(crate) async fn store_prepaid_record(&self, record: Record) -> Result<CmdOk> {
         trace!("Storing prepaid record {:?}", record.key);
         let record_header = RecordHeader::from_record(&record)?;
-        match record_header.kind {
+        let record_key = record.key.clone();
+        let result = match record_header.kind {
             // A separate flow handles payment for chunks and registers
             RecordKind::ChunkWithPayment | RecordKind::RegisterWithPayment => {
                 warn!("Prepaid record came with Payment, which should be handled in another flow");
@@ -199,7 +204,6 @@ impl Node {
             RecordKind::Chunk => {
                 let chunk = try_deserialize_record::<Chunk>(&record)?;
 
-                let record_key = record.key.clone();
                 let already_exists = self
                     .validate_key_and_existence(&chunk.network_address(), &record_key)
                     .await?;
@@ -228,6 +232,26 @@ impl Node {
                 }
                 self.validate_and_store_register(register, false).await
             }
+        };
+        self.notify_put_accepted(&result, &record_key, false, true);
+        result
+    }
+
+    /// Lets the subscribers know why a record was accepted, if it was stored.
+    fn notify_put_accepted(
+        &self,
+        result: &Result<CmdOk>,
+        key: &RecordKey,
+        payment_verified: bool,
+        is_replication: bool,
+    ) {
+        if let Ok(CmdOk::StoredSuccessfully) = result {
+            self.events_channel
+                .broadcast(crate::NodeEvent::PutAccepted {
+                    address: NetworkAddress::from_record_key(key),
+                    payment_verified,
+                    is_replication,
+                });
         }
     }
 
@@ -531,15 +555,9 @@ impl Node {
             // for the network royalties payment.
             let royalties_pk = *NETWORK_ROYALTIES_PK;
             trace!("Publishing a royalties transfer notification over gossipsub for record {pretty_key} and beneficiary {royalties_pk:?}");
-            let royalties_pk_bytes = royalties_pk.to_bytes();
-
-            let mut msg = BytesMut::with_capacity(royalties_pk_bytes.len());
-            msg.extend_from_slice(&royalties_pk_bytes);
-            let mut msg = msg.writer();
-            let mut serialiser = rmp_serde::Serializer::new(&mut msg);
-            match royalties_cash_notes_r.serialize(&mut serialiser) {
-                Ok(()) => {
-                    let msg = msg.into_inner().freeze();
+            let signer = self.sign_transfer_notifs.then_some(&self.network);
+            match encode_transfer_notif(royalties_pk.public_key(), royalties_cash_notes_r, signer) {
+                Ok(msg) => {
                     self.network.publish_on_topic(ROYALTY_TRANSFER_NOTIF_TOPIC.to_string(), msg);
                 }
                 Err(err) => warn!("Failed to serialise network royalties payment data to publish a notification over gossipsub for record {pretty_key}: {err:?}"),
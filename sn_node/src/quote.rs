@@ -9,10 +9,7 @@
 use crate::{node::Node, Error, Result};
 use sn_networking::Network;
 use sn_protocol::{error::Error as ProtocolError, NetworkAddress};
-use sn_transfers::{NanoTokens, PaymentQuote};
-
-/// The time in seconds that a quote is valid for
-const QUOTE_EXPIRATION_SECS: u64 = 3600;
+use sn_transfers::{NanoTokens, PaymentQuote, QUOTE_EXPIRATION_SECS};
 
 impl Node {
     pub(crate) fn create_quote_for_storecost(
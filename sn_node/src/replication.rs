@@ -6,21 +6,363 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{error::Result, node::Node};
+use crate::{error::Result, event::NodeEventsChannel, node::Node, NodeEvent};
+use futures::{stream, StreamExt};
 use libp2p::{
     kad::{Quorum, Record, RecordKey},
     PeerId,
 };
-use sn_networking::{sort_peers_by_address, GetRecordCfg, Network, REPLICATE_RANGE};
+use sn_networking::{sort_peers_by_address, GetRecordCfg, Network};
 use sn_protocol::{
-    messages::{Cmd, Query, QueryResponse, Request, Response},
-    storage::RecordType,
+    messages::{ChunkProof, Cmd, CmdOk, Query, QueryResponse, Request, Response},
+    storage::{try_deserialize_record, RecordHeader, RecordKind, RecordType},
     NetworkAddress, PrettyPrintRecordKey,
 };
-use tokio::task::{spawn, JoinHandle};
+use sn_registers::SignedRegister;
+use sn_transfers::SignedSpend;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{
+    task::{spawn, JoinHandle},
+    time::sleep,
+};
+
+/// How long to wait between two rounds of checking that the records being drained are replicated.
+const DRAIN_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The max number of records whose replication is checked at once while draining.
+const MAX_CONCURRENT_DRAIN_CHECKS: usize = 16;
+
+/// The default max number of `ReplicationIn`/`ReplicationOut` events emitted per second, whatever
+/// the sample rate. The events beyond it are dropped.
+pub(crate) const DEFAULT_MAX_REPLICATION_EVENTS_PER_SEC: u32 = 50;
+
+/// The longest the records served for replication are queued by the rate limit. A burst of
+/// requests beyond it is sent out without waiting any longer, rather than piling up delays.
+const MAX_REPLICATION_THROTTLE_BACKLOG: Duration = Duration::from_secs(10);
+
+/// The outcome of draining the records of a node ahead of decommissioning it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrainReport {
+    /// The number of records confirmed to be held by a majority of their close group.
+    pub confirmed: usize,
+    /// The records that couldn't be confirmed replicated before the timeout.
+    pub unconfirmed: Vec<NetworkAddress>,
+}
+
+/// Caps the rate at which we send out the records that peers replicate from us.
+/// Shared by all the clones of the `Node`, so that a new limit applies straight away.
+#[derive(Clone, Default)]
+pub(crate) struct ReplicationRateLimiter(Arc<Mutex<RateLimit>>);
+
+#[derive(Default)]
+struct RateLimit {
+    bytes_per_sec: Option<u64>,
+    // When the bytes allowed so far have all been sent.
+    next_send_at: Option<Instant>,
+}
+
+impl ReplicationRateLimiter {
+    /// Sets the max bytes per second sent out, `None` meaning unlimited.
+    /// A limit of zero is treated as one byte per second.
+    pub(crate) fn set_bytes_per_sec(&self, bytes_per_sec: Option<u64>) {
+        let Ok(mut limit) = self.0.lock() else {
+            error!("The replication rate limit lock is poisoned, the limit has not been set");
+            return;
+        };
+        limit.bytes_per_sec = bytes_per_sec.map(|rate| rate.max(1));
+        limit.next_send_at = None;
+    }
+
+    /// Waits until `bytes` more can be sent out within the limit, if any.
+    pub(crate) async fn throttle(&self, bytes: usize) {
+        let delay = self.reserve(bytes);
+        if !delay.is_zero() {
+            trace!("Throttling the replication of {bytes} bytes for {delay:?}");
+            sleep(delay).await;
+        }
+    }
+
+    /// Accounts for `bytes` more sent out, returning how long to wait before sending them.
+    /// The backlog of the sends is capped to `MAX_REPLICATION_THROTTLE_BACKLOG`.
+    fn reserve(&self, bytes: usize) -> Duration {
+        let Ok(mut limit) = self.0.lock() else {
+            return Duration::ZERO;
+        };
+        let Some(bytes_per_sec) = limit.bytes_per_sec else {
+            return Duration::ZERO;
+        };
+        let now = Instant::now();
+        let send_at = limit.next_send_at.map_or(now, |at| at.max(now));
+        let sent_at = send_at + Duration::from_secs_f64(bytes as f64 / bytes_per_sec as f64);
+        limit.next_send_at = Some(sent_at.min(now + MAX_REPLICATION_THROTTLE_BACKLOG));
+        send_at - now
+    }
+}
+
+/// Emits the `ReplicationIn`/`ReplicationOut` events, sampled and capped per second so that
+/// heavy replication doesn't flood the events channel.
+/// Shared by all the clones of the `Node`, so that the sampling spans all the replications.
+#[derive(Clone)]
+pub(crate) struct ReplicationEvents {
+    events_channel: NodeEventsChannel,
+    // Only one in every `sample_rate` events is emitted.
+    sample_rate: u32,
+    // The events sampled beyond this many in a second are dropped.
+    max_per_sec: u32,
+    sampling: Arc<Mutex<EventSampling>>,
+}
+
+struct EventSampling {
+    seen: u64,
+    window_start: Instant,
+    emitted_in_window: u32,
+}
+
+impl ReplicationEvents {
+    /// A sample rate of zero is treated as one, i.e. all the events are emitted, up to the cap
+    /// of `max_per_sec`.
+    pub(crate) fn new(
+        events_channel: NodeEventsChannel,
+        sample_rate: u32,
+        max_per_sec: u32,
+    ) -> Self {
+        Self {
+            events_channel,
+            sample_rate: sample_rate.max(1),
+            max_per_sec,
+            sampling: Arc::new(Mutex::new(EventSampling {
+                seen: 0,
+                window_start: Instant::now(),
+                emitted_in_window: 0,
+            })),
+        }
+    }
+
+    /// A record has been replicated in from `from`.
+    pub(crate) fn replicated_in(&self, address: NetworkAddress, from: PeerId) {
+        if self.should_emit() {
+            self.events_channel
+                .broadcast(NodeEvent::ReplicationIn { address, from });
+        }
+    }
+
+    /// A record has been replicated out to `to`.
+    pub(crate) fn replicated_out(&self, address: NetworkAddress, to: PeerId) {
+        if self.should_emit() {
+            self.events_channel
+                .broadcast(NodeEvent::ReplicationOut { address, to });
+        }
+    }
+
+    fn should_emit(&self) -> bool {
+        // nobody to tell, don't bother sampling
+        if self.events_channel.receiver_count() == 0 {
+            return false;
+        }
+        let Ok(mut sampling) = self.sampling.lock() else {
+            return false;
+        };
+        sampling.seen += 1;
+        if (sampling.seen - 1) % u64::from(self.sample_rate) != 0 {
+            return false;
+        }
+
+        let now = Instant::now();
+        if now.duration_since(sampling.window_start) >= Duration::from_secs(1) {
+            sampling.window_start = now;
+            sampling.emitted_in_window = 0;
+        }
+        if sampling.emitted_in_window >= self.max_per_sec {
+            return false;
+        }
+        sampling.emitted_in_window += 1;
+        true
+    }
+}
+
+/// Replicates all the records held to their close groups, until a majority of each group is
+/// confirmed to hold them, or the timeout elapses.
+///
+/// The peers are asked to fetch the records they are missing from us, over and over, hence we
+/// have to keep serving them throughout.
+pub(crate) async fn drain_records(network: &Network, timeout: Duration) -> Result<DrainReport> {
+    let deadline = Instant::now() + timeout;
+    #[allow(clippy::mutable_key_type)] // for the Bytes in NetworkAddress
+    let mut pending: BTreeMap<NetworkAddress, RecordType> = network
+        .get_all_local_record_addresses()
+        .await?
+        .into_iter()
+        .collect();
+    info!("Draining {} records within {timeout:?}", pending.len());
+
+    let mut report = DrainReport::default();
+    loop {
+        let confirmed: Vec<NetworkAddress> = stream::iter(pending.iter())
+            .map(|(addr, record_type)| async move {
+                is_replicated_elsewhere(network, addr, record_type)
+                    .await
+                    .then(|| addr.clone())
+            })
+            .buffer_unordered(MAX_CONCURRENT_DRAIN_CHECKS)
+            .filter_map(|addr| async move { addr })
+            .collect()
+            .await;
+        for addr in confirmed {
+            let _ = pending.remove(&addr);
+            report.confirmed += 1;
+        }
+
+        let now = Instant::now();
+        if pending.is_empty() || now >= deadline {
+            break;
+        }
+        debug!("{} records are still to be replicated", pending.len());
+        sleep(DRAIN_CHECK_INTERVAL.min(deadline - now)).await;
+    }
+
+    if !pending.is_empty() {
+        warn!(
+            "{} records couldn't be confirmed replicated within {timeout:?}",
+            pending.len()
+        );
+    }
+    report.unconfirmed = pending.into_keys().collect();
+    Ok(report)
+}
+
+/// Checks whether a majority of the close group of the record, other than us, hold it.
+/// If not, the peers missing it are asked to replicate it from us.
+///
+/// A chunk is checked through a proof of its content. Registers and spends are mutable and merged
+/// by their holders, so the copy of each peer is fetched to check it contains ours instead.
+async fn is_replicated_elsewhere(
+    network: &Network,
+    addr: &NetworkAddress,
+    record_type: &RecordType,
+) -> bool {
+    let record = match network.get_local_record(&addr.to_record_key()).await {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            // e.g. pruned since, it's no longer ours to hand over
+            debug!("Record {addr:?} is no longer held, skipping it");
+            return true;
+        }
+        Err(err) => {
+            error!("Failed to get the local record {addr:?} to drain it: {err:?}");
+            return false;
+        }
+    };
+    let close_peers = match network.get_closest_peers(addr, true).await {
+        Ok(peers) => peers,
+        Err(err) => {
+            warn!("Failed to get the close group of {addr:?} to drain it: {err:?}");
+            return false;
+        }
+    };
+
+    let (holders, missing): (Vec<PeerId>, Vec<PeerId>) = match record_type {
+        RecordType::Chunk => {
+            let nonce = rand::random();
+            let expected_proof = ChunkProof::new(&record.value, nonce);
+            let request = Request::Query(Query::GetChunkExistenceProof {
+                key: addr.clone(),
+                nonce,
+            });
+            let responses = network
+                .send_and_get_responses(&close_peers, &request, true)
+                .await;
+            close_peers.into_iter().partition(|peer_id| {
+                matches!(
+                    responses.get(peer_id),
+                    Some(Ok(Response::Query(QueryResponse::GetChunkExistenceProof(Ok(proof)))))
+                        if expected_proof.verify(proof)
+                )
+            })
+        }
+        RecordType::NonChunk(_) => {
+            let request = Request::Query(Query::GetRecordFromPeer(addr.clone()));
+            let responses = network
+                .send_and_get_responses(&close_peers, &request, true)
+                .await;
+            close_peers
+                .into_iter()
+                .partition(|peer_id| match responses.get(peer_id) {
+                    Some(Ok(Response::Query(QueryResponse::GetRecordFromPeer(Ok((_, value)))))) => {
+                        let theirs = Record::new(record.key.clone(), value.to_vec());
+                        holds_our_record(&record, &theirs)
+                    }
+                    _ => false,
+                })
+        }
+    };
+    if holders.len() >= network.close_group_majority() {
+        return true;
+    }
+
+    trace!(
+        "Record {addr:?} is held by {} peers only, asking {} more to replicate it",
+        holders.len(),
+        missing.len()
+    );
+    let our_address = NetworkAddress::from_peer(network.peer_id);
+    for peer_id in missing {
+        let request = Request::Cmd(Cmd::Replicate {
+            holder: our_address.clone(),
+            keys: vec![(addr.clone(), record_type.clone())],
+        });
+        network.send_req_ignore_reply(request, peer_id);
+    }
+    false
+}
+
+/// Whether a peer's copy of a record contains ours, i.e. the peer misses nothing we'd hand over.
+/// Registers and spends are compared by their content, as the copies may have been merged with
+/// others, e.g. holding more register ops, or the spends of a double spend.
+fn holds_our_record(ours: &Record, theirs: &Record) -> bool {
+    let (our_kind, their_kind) = match (
+        RecordHeader::from_record(ours),
+        RecordHeader::from_record(theirs),
+    ) {
+        (Ok(our_header), Ok(their_header)) => (our_header.kind, their_header.kind),
+        _ => return false,
+    };
+    if our_kind != their_kind {
+        return false;
+    }
+
+    match our_kind {
+        RecordKind::Register => {
+            match (
+                try_deserialize_record::<SignedRegister>(ours),
+                try_deserialize_record::<SignedRegister>(theirs),
+            ) {
+                // their copy has all our ops if merging ours into it doesn't add any
+                (Ok(ours), Ok(theirs)) => {
+                    let mut merged = theirs.clone();
+                    merged.merge(ours).is_ok() && merged == theirs
+                }
+                _ => false,
+            }
+        }
+        RecordKind::Spend => {
+            match (
+                try_deserialize_record::<Vec<SignedSpend>>(ours),
+                try_deserialize_record::<Vec<SignedSpend>>(theirs),
+            ) {
+                (Ok(ours), Ok(theirs)) => ours.iter().all(|spend| theirs.contains(spend)),
+                _ => false,
+            }
+        }
+        _ => ours.value == theirs.value,
+    }
+}
 
 impl Node {
-    /// Sends _all_ record keys every interval to all peers within the REPLICATE_RANGE.
+    /// Sends _all_ record keys every interval to all peers within the replication range.
     pub(crate) fn try_interval_replication(network: Network) {
         network.trigger_interval_replication()
     }
@@ -40,6 +382,7 @@ impl Node {
                     requester,
                     key: NetworkAddress::from_record_key(&key),
                 });
+                let mut fetched_from_holder = false;
                 let record_opt = if let Ok(resp) = node.network.send_request(req, holder).await {
                     match resp {
                         Response::Query(QueryResponse::GetReplicatedRecord(result)) => match result
@@ -60,6 +403,7 @@ impl Node {
                 };
 
                 let record = if let Some(record_content) = record_opt {
+                    fetched_from_holder = true;
                     Record::new(key, record_content.to_vec())
                 } else {
                     trace!(
@@ -77,7 +421,12 @@ impl Node {
                 trace!(
                     "Got Replication Record {pretty_key:?} from network, validating and storing it"
                 );
+                let address = NetworkAddress::from_record_key(&record.key);
                 let result = node.store_prepaid_record(record).await?;
+                // when fetched from the network, we don't know which peer the record came from
+                if fetched_from_holder && matches!(result, CmdOk::StoredSuccessfully) {
+                    node.replication_events.replicated_in(address, holder);
+                }
                 trace!(
                     "Completed storing Replication Record {pretty_key:?} from network, result: {result:?}"
                 );
@@ -98,7 +447,7 @@ impl Node {
         let network = self.network.clone();
 
         let _handle = spawn(async move {
-            let start = std::time::Instant::now();
+            let start = Instant::now();
             let pretty_key = PrettyPrintRecordKey::from(&paid_key);
 
             // first we wait until our own network store can return the record
@@ -128,7 +477,7 @@ impl Node {
                 }
 
                 retry_count += 1;
-                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                sleep(Duration::from_millis(100)).await;
             }
 
             trace!("Start replication of fresh record {pretty_key:?} from store");
@@ -150,7 +499,7 @@ impl Node {
             let sorted_based_on_addr = match sort_peers_by_address(
                 &closest_k_peers,
                 &data_addr,
-                REPLICATE_RANGE,
+                network.replication_range(),
             ) {
                 Ok(result) => result,
                 Err(err) => {
@@ -182,3 +531,111 @@ impl Node {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls::SecretKey;
+    use bytes::Bytes;
+    use sn_protocol::storage::{try_serialize_record, Chunk};
+    use sn_registers::{Permissions, Register};
+    use std::collections::BTreeSet;
+    use xor_name::XorName;
+
+    #[test]
+    fn chunks_are_held_with_the_same_content() -> eyre::Result<()> {
+        let record_of = |content: &'static [u8]| -> eyre::Result<Record> {
+            let chunk = Chunk::new(Bytes::from_static(content));
+            let value = try_serialize_record(&chunk, RecordKind::Chunk)?;
+            Ok(Record::new(
+                NetworkAddress::from_chunk_address(*chunk.address()).to_record_key(),
+                value.to_vec(),
+            ))
+        };
+
+        let ours = record_of(b"chunk")?;
+        assert!(holds_our_record(&ours, &ours.clone()));
+        assert!(!holds_our_record(&ours, &record_of(b"other chunk")?));
+        Ok(())
+    }
+
+    #[test]
+    fn registers_are_held_with_all_our_ops() -> eyre::Result<()> {
+        let owner = SecretKey::random();
+        let mut register = Register::new(
+            owner.public_key(),
+            XorName::random(&mut rand::thread_rng()),
+            Permissions::default(),
+        );
+        let created = register.clone().into_signed(&owner)?;
+        let mut written = created.clone();
+        let (_, op) = register.write(b"entry".to_vec(), &BTreeSet::new(), &owner)?;
+        written.add_op(op)?;
+
+        let key = NetworkAddress::from_register_address(*created.address()).to_record_key();
+        let record_of = |register: &SignedRegister| -> eyre::Result<Record> {
+            let value = try_serialize_record(register, RecordKind::Register)?;
+            Ok(Record::new(key.clone(), value.to_vec()))
+        };
+
+        // a peer holding more ops than us has all of ours, though its copy differs
+        assert!(holds_our_record(
+            &record_of(&created)?,
+            &record_of(&written)?
+        ));
+        assert!(!holds_our_record(
+            &record_of(&written)?,
+            &record_of(&created)?
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn replication_is_throttled_to_the_rate_limit() {
+        let limiter = ReplicationRateLimiter::default();
+        // unlimited by default
+        assert!(limiter.reserve(1_000_000).is_zero());
+        assert!(limiter.reserve(1_000_000).is_zero());
+
+        limiter.set_bytes_per_sec(Some(1_000));
+        // the first send goes out straight away, the next once it is sent
+        assert!(limiter.reserve(500).is_zero());
+        let delay = limiter.reserve(500);
+        assert!(delay > Duration::from_millis(400) && delay <= Duration::from_millis(500));
+
+        // the backlog is capped, however much is queued
+        let _ = limiter.reserve(1_000_000);
+        assert!(limiter.reserve(1) <= MAX_REPLICATION_THROTTLE_BACKLOG);
+
+        limiter.set_bytes_per_sec(None);
+        assert!(limiter.reserve(1_000_000).is_zero());
+    }
+
+    #[test]
+    fn replication_events_are_sampled() {
+        let events_channel = NodeEventsChannel::default();
+        let events = ReplicationEvents::new(events_channel.clone(), 3, u32::MAX);
+        // nothing is emitted while nobody is listening
+        assert!(!events.should_emit());
+
+        let _receiver = events_channel.subscribe();
+        let emitted: Vec<bool> = (0..7).map(|_| events.should_emit()).collect();
+        assert_eq!(emitted, vec![true, false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn replication_events_are_capped_per_second() {
+        let events_channel = NodeEventsChannel::default();
+        let _receiver = events_channel.subscribe();
+        let events = ReplicationEvents::new(events_channel, 1, 5);
+
+        let emitted = (0..20).filter(|_| events.should_emit()).count();
+        assert_eq!(emitted, 5);
+
+        // a new window lets more events through
+        if let Ok(mut sampling) = events.sampling.lock() {
+            sampling.window_start -= Duration::from_secs(1);
+        }
+        assert!(events.should_emit());
+    }
+}
@@ -17,7 +17,10 @@ use sn_protocol::{
     safenode_proto::{NodeInfoRequest, RestartRequest},
     test_utils::DeploymentInventory,
 };
-use sn_transfers::{create_faucet_wallet, HotWallet, NanoTokens, Transfer};
+use sn_transfers::{
+    create_faucet_wallet, rng, CashNote, DerivationIndex, Hash, HotWallet, MainPubkey, NanoTokens,
+    OfflineTransfer, Transfer,
+};
 use std::{net::SocketAddr, path::Path};
 use tokio::{
     sync::Mutex,
@@ -134,6 +137,57 @@ pub async fn add_funds_to_wallet(client: &Client, to_wallet_dir: &Path) -> Resul
     }
 }
 
+/// Spends the same CashNotes of the wallet twice, sending a third of its balance to the recipient
+/// and to the wallet itself, then returns the CashNotes created for each.
+///
+/// Both spends are sent to the network, which accepts them, so that the double spend is only
+/// detected when verifying the created CashNotes.
+pub async fn create_double_spend(
+    client: &Client,
+    wallet: &mut HotWallet,
+    recipient: MainPubkey,
+) -> Result<(CashNote, CashNote)> {
+    let amount = NanoTokens::from(wallet.balance().as_nano() / 3);
+    let change_to = wallet.address();
+    let (cash_notes, exclusive_access) = wallet.available_cash_notes()?;
+
+    let mut rng = rng::thread_rng();
+    let reason_hash = Hash::default();
+    let to_recipient = OfflineTransfer::new(
+        cash_notes.clone(),
+        vec![(amount, recipient, DerivationIndex::random(&mut rng))],
+        change_to,
+        reason_hash,
+    )?;
+    let to_self = OfflineTransfer::new(
+        cash_notes,
+        vec![(amount, change_to, DerivationIndex::random(&mut rng))],
+        change_to,
+        reason_hash,
+    )?;
+
+    info!("Sending both spends of the same CashNotes to the network...");
+    client
+        .send_spends(to_recipient.all_spend_requests.iter(), false)
+        .await?;
+    client
+        .send_spends(to_self.all_spend_requests.iter(), false)
+        .await?;
+    std::mem::drop(exclusive_access);
+
+    let first = to_recipient
+        .created_cash_notes
+        .into_iter()
+        .next()
+        .ok_or_eyre("No CashNote created for the recipient")?;
+    let second = to_self
+        .created_cash_notes
+        .into_iter()
+        .next()
+        .ok_or_eyre("No CashNote created for the wallet")?;
+    Ok((first, second))
+}
+
 /// Create a client and fund the wallet.
 /// If SN_INVENTORY flag is passed, the wallet is funded by fetching it from the faucet
 /// Else create a genesis wallet and transfer funds from there.
@@ -357,8 +411,8 @@ impl Droplet {
                     return Err(err.into());
                 }
             };
-            let cashnotes = match client.receive(&transfer, &local_wallet).await {
-                Ok(cashnotes) => cashnotes,
+            let (cashnotes, _memo) = match client.receive(&transfer, &local_wallet).await {
+                Ok(received) => received,
                 Err(err) => {
                     println!("Failed to verify and redeem transfer: {err:?}");
                     error!("Failed to verify and redeem transfer: {err:?}");
@@ -11,22 +11,24 @@ pub mod client;
 
 use self::client::{Droplet, NonDroplet};
 use bytes::Bytes;
-use eyre::{bail, eyre, OptionExt, Result};
+use eyre::{eyre, OptionExt, Result};
 use itertools::Either;
-use libp2p::PeerId;
+use libp2p::{kad::RecordKey, PeerId};
 use rand::{
     distributions::{Distribution, Standard},
     Rng,
 };
 use self_encryption::MIN_ENCRYPTABLE_BYTES;
-use sn_client::{Client, FilesApi};
+use sn_client::{networking::CLOSE_GROUP_SIZE, Client, FilesApi};
 use sn_protocol::{
     node_registry::{get_local_node_registry_path, NodeRegistry},
     safenode_manager_proto::safe_node_manager_client::SafeNodeManagerClient,
-    safenode_proto::{safe_node_client::SafeNodeClient, NodeInfoRequest},
+    safenode_proto::{safe_node_client::SafeNodeClient, NodeInfoRequest, RecordAddressesRequest},
     storage::ChunkAddress,
     test_utils::DeploymentInventory,
+    NetworkAddress,
 };
+use sn_transfers::{SignedSpend, SpendAddress};
 use std::{
     fs::File,
     io::Write,
@@ -69,45 +71,113 @@ pub fn random_content(
     ))
 }
 
+const RPC_CONNECTION_ATTEMPTS: usize = 10;
+
+/// The reason we could not connect to a RPC endpoint, from the last of the attempts.
+#[derive(Debug, thiserror::Error)]
+pub enum RpcConnectionError {
+    #[error("Connection to {endpoint} was refused, the node is likely not up: {source}")]
+    ConnectionRefused {
+        endpoint: String,
+        source: tonic::transport::Error,
+    },
+    #[error(
+        "TLS/handshake with {endpoint} failed, the RPC endpoint might be misconfigured: {source}"
+    )]
+    Handshake {
+        endpoint: String,
+        source: tonic::transport::Error,
+    },
+    #[error("Timed out while connecting to {endpoint}: {source}")]
+    Timeout {
+        endpoint: String,
+        source: tonic::transport::Error,
+    },
+    #[error("Failed to connect to {endpoint}: {source}")]
+    Other {
+        endpoint: String,
+        source: tonic::transport::Error,
+    },
+}
+
+impl RpcConnectionError {
+    fn new(endpoint: String, source: tonic::transport::Error) -> Self {
+        // tonic only exposes an opaque "transport error", the actual cause is down its source chain
+        let mut is_refused = false;
+        let mut is_timeout = false;
+        let mut is_handshake = false;
+        let mut cause: Option<&(dyn std::error::Error + 'static)> = Some(&source);
+        while let Some(err) = cause {
+            if let Some(io_err) = err.downcast_ref::<std::io::Error>() {
+                match io_err.kind() {
+                    std::io::ErrorKind::ConnectionRefused => is_refused = true,
+                    std::io::ErrorKind::TimedOut => is_timeout = true,
+                    _ => {}
+                }
+            }
+            let msg = err.to_string().to_lowercase();
+            if msg.contains("tls") || msg.contains("handshake") || msg.contains("certificate") {
+                is_handshake = true;
+            }
+            if msg.contains("timed out") || msg.contains("deadline") {
+                is_timeout = true;
+            }
+            cause = err.source();
+        }
+
+        if is_refused {
+            Self::ConnectionRefused { endpoint, source }
+        } else if is_handshake {
+            Self::Handshake { endpoint, source }
+        } else if is_timeout {
+            Self::Timeout { endpoint, source }
+        } else {
+            Self::Other { endpoint, source }
+        }
+    }
+}
+
 // Connect to a RPC socket addr with retry
 pub async fn get_safenode_rpc_client(
     socket_addr: SocketAddr,
-) -> Result<SafeNodeClient<tonic::transport::Channel>> {
+) -> Result<SafeNodeClient<tonic::transport::Channel>, RpcConnectionError> {
     // get the new PeerId for the current NodeIndex
     let endpoint = format!("https://{socket_addr}");
     let mut attempts = 0;
     loop {
-        if let Ok(rpc_client) = SafeNodeClient::connect(endpoint.clone()).await {
-            break Ok(rpc_client);
-        }
+        let err = match SafeNodeClient::connect(endpoint.clone()).await {
+            Ok(rpc_client) => break Ok(rpc_client),
+            Err(err) => RpcConnectionError::new(endpoint.clone(), err),
+        };
         attempts += 1;
-        println!("Could not connect to rpc {endpoint:?}. Attempts: {attempts:?}/10");
-        error!("Could not connect to rpc {endpoint:?}. Attempts: {attempts:?}/10");
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        if attempts >= 10 {
-            bail!("Failed to connect to {endpoint:?} even after 10 retries");
+        println!("Could not connect to rpc {endpoint:?}. Attempts: {attempts:?}/{RPC_CONNECTION_ATTEMPTS}: {err}");
+        error!("Could not connect to rpc {endpoint:?}. Attempts: {attempts:?}/{RPC_CONNECTION_ATTEMPTS}: {err:?}");
+        if attempts >= RPC_CONNECTION_ATTEMPTS {
+            break Err(err);
         }
+        tokio::time::sleep(Duration::from_secs(1)).await;
     }
 }
 
 // Connect to a RPC socket addr with retry
 pub async fn get_safenode_manager_rpc_client(
     socket_addr: SocketAddr,
-) -> Result<SafeNodeManagerClient<tonic::transport::Channel>> {
+) -> Result<SafeNodeManagerClient<tonic::transport::Channel>, RpcConnectionError> {
     // get the new PeerId for the current NodeIndex
     let endpoint = format!("https://{socket_addr}");
     let mut attempts = 0;
     loop {
-        if let Ok(rpc_client) = SafeNodeManagerClient::connect(endpoint.clone()).await {
-            break Ok(rpc_client);
-        }
+        let err = match SafeNodeManagerClient::connect(endpoint.clone()).await {
+            Ok(rpc_client) => break Ok(rpc_client),
+            Err(err) => RpcConnectionError::new(endpoint.clone(), err),
+        };
         attempts += 1;
-        println!("Could not connect to rpc {endpoint:?}. Attempts: {attempts:?}/10");
-        error!("Could not connect to rpc {endpoint:?}. Attempts: {attempts:?}/10");
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        if attempts >= 10 {
-            bail!("Failed to connect to {endpoint:?} even after 10 retries");
+        println!("Could not connect to rpc {endpoint:?}. Attempts: {attempts:?}/{RPC_CONNECTION_ATTEMPTS}: {err}");
+        error!("Could not connect to rpc {endpoint:?}. Attempts: {attempts:?}/{RPC_CONNECTION_ATTEMPTS}: {err:?}");
+        if attempts >= RPC_CONNECTION_ATTEMPTS {
+            break Err(err);
         }
+        tokio::time::sleep(Duration::from_secs(1)).await;
     }
 }
 
@@ -132,6 +202,106 @@ pub async fn get_all_peer_ids(node_rpc_addresses: &Vec<SocketAddr>) -> Result<Ve
     Ok(all_peers)
 }
 
+// How often the nodes are polled while waiting for them to converge
+const CONVERGENCE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Waits until both nodes hold the record at `addr`, erroring out if they don't within `timeout`.
+pub async fn assert_records_converged(
+    rpc_a: SocketAddr,
+    rpc_b: SocketAddr,
+    addr: NetworkAddress,
+    timeout: Duration,
+) -> Result<()> {
+    let key = addr.to_record_key();
+    let started = std::time::Instant::now();
+    loop {
+        let held_by_a = node_holds_record(rpc_a, &key).await?;
+        let held_by_b = node_holds_record(rpc_b, &key).await?;
+        if held_by_a && held_by_b {
+            debug!("Nodes {rpc_a} and {rpc_b} have converged on {addr:?}");
+            return Ok(());
+        }
+
+        if started.elapsed() >= timeout {
+            let missing: Vec<_> = [(rpc_a, held_by_a), (rpc_b, held_by_b)]
+                .into_iter()
+                .filter(|(_, held)| !held)
+                .map(|(rpc, _)| rpc.to_string())
+                .collect();
+            return Err(eyre!(
+                "Nodes {rpc_a} and {rpc_b} did not converge on {addr:?} within {timeout:?}, missing from: {}",
+                missing.join(", ")
+            ));
+        }
+        tokio::time::sleep(CONVERGENCE_POLL_INTERVAL).await;
+    }
+}
+
+// Returns true if the node at the RPC addr currently holds the record
+async fn node_holds_record(rpc_addr: SocketAddr, key: &RecordKey) -> Result<bool> {
+    let mut rpc_client = get_safenode_rpc_client(rpc_addr).await?;
+    let response = rpc_client
+        .record_addresses(Request::new(RecordAddressesRequest {}))
+        .await?;
+    Ok(response
+        .get_ref()
+        .addresses
+        .iter()
+        .any(|bytes| RecordKey::from(bytes.clone()) == *key))
+}
+
+/// Checks that every node responsible for the spend address, i.e. the `CLOSE_GROUP_SIZE` nodes
+/// closest to it, holds the spend, and that all of them return the very same `SignedSpend`.
+pub async fn assert_spend_consistent(
+    rpc_addrs: &[SocketAddr],
+    spend_addr: SpendAddress,
+) -> Result<()> {
+    let addr = NetworkAddress::from_spend_address(spend_addr);
+    let key = addr.to_record_key();
+
+    let mut nodes = Vec::new();
+    for rpc_addr in rpc_addrs {
+        let mut rpc_client = get_safenode_rpc_client(*rpc_addr).await?;
+        let response = rpc_client
+            .node_info(Request::new(NodeInfoRequest {}))
+            .await?;
+        let peer_id = PeerId::from_bytes(&response.get_ref().peer_id)?;
+        let holds_spend = node_holds_record(*rpc_addr, &key).await?;
+        nodes.push((*rpc_addr, peer_id, holds_spend));
+    }
+    nodes.sort_by_key(|(_, peer_id, _)| NetworkAddress::from_peer(*peer_id).distance(&addr));
+    nodes.truncate(CLOSE_GROUP_SIZE);
+
+    let missing: Vec<_> = nodes
+        .iter()
+        .filter(|(_, _, holds_spend)| !holds_spend)
+        .map(|(rpc_addr, peer_id, _)| format!("{peer_id:?} ({rpc_addr})"))
+        .collect();
+    if !missing.is_empty() {
+        return Err(eyre!(
+            "The spend at {spend_addr:?} is missing from the responsible nodes: {}",
+            missing.join(", ")
+        ));
+    }
+
+    let client = client::get_gossip_client().await;
+    let mut first_spend: Option<(PeerId, SignedSpend)> = None;
+    for (_, peer_id, _) in nodes {
+        let spend = client.get_spend_from_peer(spend_addr, peer_id).await?;
+        match &first_spend {
+            Some((first_peer, first)) if *first != spend => {
+                return Err(eyre!(
+                    "{peer_id:?} holds a different spend at {spend_addr:?} than {first_peer:?}: {spend:?} vs {first:?}"
+                ));
+            }
+            Some(_) => {}
+            None => first_spend = Some((peer_id, spend)),
+        }
+    }
+    debug!("The responsible nodes hold the same spend at {spend_addr:?}");
+    Ok(())
+}
+
 /// A struct to facilitate restart of droplet/local nodes
 pub struct NodeRestart {
     // Deployment inventory is used incase of Droplet nodes and NodeRegistry incase of NonDroplet nodes.
@@ -13,12 +13,13 @@ use libp2p_identity::PeerId;
 use semver::Version;
 use sn_node_manager::{
     config::*,
-    daemon_control,
+    daemon_control::{self, DaemonEndpoint},
     faucet_control::{add_faucet, start_faucet, stop_faucet, AddFaucetServiceOptions},
     helpers::download_and_extract_release,
     local::{kill_network, run_network, LocalNetworkOptions},
     node_control::{
-        add, remove, start, status, stop, upgrade, AddServiceOptions, UpgradeOptions, UpgradeResult,
+        add, reconcile, remove, start, status, stop, update_bootstrap_peers, upgrade,
+        AddServiceOptions, UpgradeOptions, UpgradeResult,
     },
     service::{NodeServiceManager, ServiceControl},
     VerbosityLevel,
@@ -29,7 +30,7 @@ use sn_protocol::node_registry::{get_local_node_registry_path, NodeRegistry};
 use sn_releases::{ReleaseType, SafeReleaseRepositoryInterface};
 use sn_transfers::get_faucet_data_dir;
 use std::{
-    net::Ipv4Addr,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
     path::PathBuf,
     process::{Command, Stdio},
     str::FromStr,
@@ -125,6 +126,14 @@ pub enum SubCmd {
         /// The version of safenode
         #[clap(long)]
         version: Option<String>,
+        /// Additional arguments to pass through to safenode, following a `--`.
+        ///
+        /// They are kept with the node and re-applied whenever its service is reinstalled, e.g.
+        /// on an upgrade.
+        ///
+        /// Example: safenode-manager add -- --max-records 4096
+        #[clap(last = true)]
+        safenode_args: Vec<String>,
     },
     #[clap(subcommand)]
     Daemon(DaemonSubCmd),
@@ -251,6 +260,17 @@ pub enum SubCmd {
         #[clap(long)]
         skip_validation: bool,
     },
+    /// Replace the bootstrap peers of the safenode services.
+    ///
+    /// Each service is stopped, reinstalled with the new peers, then started again if it was
+    /// running. The node registry is saved once all the services are updated.
+    ///
+    /// This command must run as the root/administrative user.
+    #[clap(name = "set-bootstrap-peers")]
+    SetBootstrapPeers {
+        #[command(flatten)]
+        peers: PeersArgs,
+    },
     /// Start a safenode service.
     ///
     /// If no peer ID(s) or service name(s) are supplied, all services will be started.
@@ -410,6 +430,7 @@ async fn main() -> Result<()> {
             url,
             user,
             version,
+            safenode_args,
         } => {
             if !is_running_as_root() {
                 return Err(eyre!("The add command must run as the root user"));
@@ -447,6 +468,7 @@ async fn main() -> Result<()> {
                 bootstrap_peers: get_peers_from_args(peers).await?,
                 node_port: port,
                 rpc_address,
+                safenode_args,
                 safenode_bin_path: safenode_download_path,
                 safenode_dir_path: service_data_dir_path.clone(),
                 service_data_dir_path,
@@ -466,6 +488,7 @@ async fn main() -> Result<()> {
         SubCmd::Daemon(DaemonSubCmd::Add {
             address,
             port,
+            socket_path,
             path,
         }) => {
             if !is_running_as_root() {
@@ -481,7 +504,11 @@ async fn main() -> Result<()> {
             let mut node_registry = NodeRegistry::load(&get_node_registry_path()?)?;
 
             let service_manager = NodeServiceManager {};
-            daemon_control::add_daemon(address, port, path, &mut node_registry, &service_manager)?;
+            let endpoint = match socket_path {
+                Some(socket_path) => DaemonEndpoint::Unix(socket_path),
+                None => DaemonEndpoint::Tcp(SocketAddr::new(IpAddr::V4(address), port)),
+            };
+            daemon_control::add_daemon(endpoint, path, &mut node_registry, &service_manager)?;
 
             Ok(())
         }
@@ -804,6 +831,55 @@ async fn main() -> Result<()> {
 
             Ok(())
         }
+        SubCmd::SetBootstrapPeers { peers } => {
+            if !is_running_as_root() {
+                return Err(eyre!(
+                    "The set-bootstrap-peers command must run as the root user"
+                ));
+            }
+
+            if verbosity != VerbosityLevel::Minimal {
+                println!("=================================================");
+                println!("            Set Bootstrap Peers                  ");
+                println!("=================================================");
+            }
+
+            let bootstrap_peers = get_peers_from_args(peers).await?;
+            let mut node_registry = NodeRegistry::load(&get_node_registry_path()?)?;
+            node_registry.set_bootstrap_peers(bootstrap_peers.clone());
+
+            let mut failed_services = Vec::new();
+            for node in node_registry.nodes.iter_mut() {
+                let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
+                if let Err(e) = update_bootstrap_peers(
+                    node,
+                    bootstrap_peers.clone(),
+                    &NodeServiceManager {},
+                    &rpc_client,
+                )
+                .await
+                {
+                    failed_services.push((node.service_name.clone(), e.to_string()));
+                }
+            }
+            node_registry.save()?;
+
+            if !failed_services.is_empty() {
+                println!(
+                    "Failed to update the bootstrap peers of {} service(s):",
+                    failed_services.len()
+                );
+                for failed in failed_services.iter() {
+                    println!("{} {}: {}", "✕".red(), failed.0, failed.1);
+                }
+                return Err(
+                    eyre!("Failed to update the bootstrap peers of one or more services")
+                        .suggestion("Run the command again to retry the failed services."),
+                );
+            }
+
+            Ok(())
+        }
         SubCmd::Start {
             peer_id,
             service_name,
@@ -913,6 +989,34 @@ async fn main() -> Result<()> {
                     println!("                Safenode Services                ");
                     println!("=================================================");
                 }
+                // The registry can be stale, e.g. after a reboot, so it's corrected first. If that
+                // fails, the last known state is shown rather than none at all.
+                let mut reconciled_registry = node_registry.clone();
+                match reconcile(&mut reconciled_registry, &NodeServiceManager {}) {
+                    Ok(report) => {
+                        node_registry = reconciled_registry;
+                        if !json {
+                            for fixed in &report.fixed {
+                                println!(
+                                    "Corrected {}: {:?} -> {:?}",
+                                    fixed.service_name, fixed.previous_status, fixed.status
+                                );
+                            }
+                            for service_name in &report.untracked_services {
+                                println!(
+                                    "{} The {service_name} service is installed, but not in the node registry",
+                                    "Warning:".yellow()
+                                );
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!(
+                            "{} Could not check the node services, the status shown is the last known one: {err}",
+                            "Warning:".yellow()
+                        );
+                    }
+                }
                 status(
                     &mut node_registry,
                     &NodeServiceManager {},
@@ -1052,15 +1156,15 @@ async fn main() -> Result<()> {
                     .ok_or_else(|| eyre!("No service named '{name}'"))?;
 
                 let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
-                // use the passed in env variable or re-use the one that we supplied during 'add()'
-                let env_variables = if provided_env_variable.is_some() {
-                    &provided_env_variable
-                } else {
-                    &node_registry.environment_variables
-                };
+                // use the passed in env variable or re-use the one the node was added with, falling
+                // back to the registry wide one for nodes added before it was kept per node
+                let env_variables = provided_env_variable
+                    .clone()
+                    .or_else(|| node.env_variables.clone())
+                    .or_else(|| node_registry.environment_variables.clone());
                 let options = UpgradeOptions {
                     bootstrap_peers: node_registry.bootstrap_peers.clone(),
-                    env_variables: env_variables.clone(),
+                    env_variables,
                     force,
                     start_node: !do_not_start,
                     target_safenode_path: upgrade_bin_path.clone(),
@@ -1092,15 +1196,15 @@ async fn main() -> Result<()> {
                     })?;
 
                 let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
-                // use the passed in env variable or re-use the one that we supplied during 'add()'
-                let env_variables = if provided_env_variable.is_some() {
-                    &provided_env_variable
-                } else {
-                    &node_registry.environment_variables
-                };
+                // use the passed in env variable or re-use the one the node was added with, falling
+                // back to the registry wide one for nodes added before it was kept per node
+                let env_variables = provided_env_variable
+                    .clone()
+                    .or_else(|| node.env_variables.clone())
+                    .or_else(|| node_registry.environment_variables.clone());
                 let options = UpgradeOptions {
                     bootstrap_peers: node_registry.bootstrap_peers.clone(),
-                    env_variables: env_variables.clone(),
+                    env_variables,
                     force,
                     start_node: !do_not_start,
                     target_safenode_path: upgrade_bin_path.clone(),
@@ -1121,15 +1225,15 @@ async fn main() -> Result<()> {
             } else {
                 for node in node_registry.nodes.iter_mut() {
                     let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
-                    // use the passed in env variable or re-use the one that we supplied during 'add()'
-                    let env_variables = if provided_env_variable.is_some() {
-                        &provided_env_variable
-                    } else {
-                        &node_registry.environment_variables
-                    };
+                    // use the passed in env variable or re-use the one the node was added with, falling
+                    // back to the registry wide one for nodes added before it was kept per node
+                    let env_variables = provided_env_variable
+                        .clone()
+                        .or_else(|| node.env_variables.clone())
+                        .or_else(|| node_registry.environment_variables.clone());
                     let options = UpgradeOptions {
                         bootstrap_peers: node_registry.bootstrap_peers.clone(),
-                        env_variables: env_variables.clone(),
+                        env_variables,
                         force,
                         start_node: !do_not_start,
                         target_safenode_path: upgrade_bin_path.clone(),
@@ -1197,6 +1301,11 @@ pub enum DaemonSubCmd {
         /// Specify a port for the daemon to listen for RPCs. It defaults to 12500 if not set.
         #[clap(long, default_value_t = 12500)]
         port: u16,
+        /// Specify a Unix domain socket for the daemon to listen on, instead of a TCP port.
+        ///
+        /// Only local tools can then talk to the daemon, and no port needs to be free.
+        #[clap(long, conflicts_with_all = ["address", "port"])]
+        socket_path: Option<PathBuf>,
         /// Daemon Path
         // todo: provide url/version.
         #[clap(long)]
@@ -18,17 +18,22 @@ use libp2p_identity::PeerId;
 use sn_node_manager::{
     config::get_node_registry_path,
     daemon_control::{self, DAEMON_DEFAULT_PORT},
+    node_control,
     service::NodeServiceManager,
 };
 use sn_node_rpc_client::RpcClient;
 use sn_protocol::{
-    node_registry::NodeRegistry,
+    node_registry::{NodeRegistry, NodeStatus},
     safenode_manager_proto::{
         safe_node_manager_server::{SafeNodeManager, SafeNodeManagerServer},
-        NodeServiceRestartRequest, NodeServiceRestartResponse,
+        GetNodeServicesRequest, GetNodeServicesResponse, NodeServiceInfo,
+        NodeServiceRestartRequest, NodeServiceRestartResponse, NodeServiceStatus,
     },
 };
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+};
 use tonic::{transport::Server, Code, Request, Response, Status};
 
 #[derive(Parser, Debug)]
@@ -42,6 +47,9 @@ struct Args {
     /// If not set, the daemon listens locally for commands.
     #[clap(long, default_value_t = Ipv4Addr::new(127, 0, 0, 1))]
     address: Ipv4Addr,
+    /// Specify a Unix domain socket for the daemon to listen on, instead of a TCP port.
+    #[clap(long, conflicts_with_all = ["address", "port"])]
+    socket_path: Option<PathBuf>,
 }
 
 struct SafeNodeManagerDaemon {}
@@ -80,6 +88,35 @@ impl SafeNodeManager for SafeNodeManagerDaemon {
 
         Ok(Response::new(NodeServiceRestartResponse {}))
     }
+
+    async fn get_node_services(
+        &self,
+        request: Request<GetNodeServicesRequest>,
+    ) -> Result<Response<GetNodeServicesResponse>, Status> {
+        info!("RPC request received {:?}", request.get_ref());
+
+        let node_registry_path = get_node_registry_path().map_err(|err| {
+            Status::new(
+                Code::Internal,
+                format!("Could not obtain node registry path {err}"),
+            )
+        })?;
+        let node_registry = NodeRegistry::load(&node_registry_path).map_err(|err| {
+            Status::new(
+                Code::Internal,
+                format!("Could not open local node registry with {err}"),
+            )
+        })?;
+
+        let nodes = Self::node_services_handler(node_registry).map_err(|err| {
+            Status::new(
+                Code::Internal,
+                format!("Failed to get the node services: {err}"),
+            )
+        })?;
+
+        Ok(Response::new(GetNodeServicesResponse { nodes }))
+    }
 }
 
 impl SafeNodeManagerDaemon {
@@ -111,6 +148,29 @@ impl SafeNodeManagerDaemon {
 
         res
     }
+
+    fn node_services_handler(mut node_registry: NodeRegistry) -> Result<Vec<NodeServiceInfo>> {
+        // the registry could be stale, e.g. if a node crashed since it was last updated.
+        let _ = node_control::reconcile(&mut node_registry, &NodeServiceManager {})?;
+        node_registry.save()?;
+
+        let nodes = node_registry
+            .nodes
+            .iter()
+            .map(|node| NodeServiceInfo {
+                peer_id: node.peer_id.map(|id| id.to_bytes()).unwrap_or_default(),
+                service_name: node.service_name.clone(),
+                status: match node.status {
+                    NodeStatus::Added => NodeServiceStatus::Added,
+                    NodeStatus::Running => NodeServiceStatus::Running,
+                    NodeStatus::Stopped => NodeServiceStatus::Stopped,
+                    NodeStatus::Removed => NodeServiceStatus::Removed,
+                } as i32,
+                rpc_socket_addr: node.rpc_socket_addr.to_string(),
+            })
+            .collect();
+        Ok(nodes)
+    }
 }
 
 // The SafeNodeManager trait returns `Status` as its error. So the actual logic is here and we can easily map the errors
@@ -124,11 +184,35 @@ async fn main() -> Result<()> {
     let service = SafeNodeManagerDaemon {};
 
     // adding our service to our server.
-    if let Err(err) = Server::builder()
-        .add_service(SafeNodeManagerServer::new(service))
-        .serve(SocketAddr::new(IpAddr::V4(args.address), args.port))
-        .await
-    {
+    let router = Server::builder().add_service(SafeNodeManagerServer::new(service));
+    let res = match args.socket_path {
+        #[cfg(unix)]
+        Some(socket_path) => {
+            use tokio_stream::{wrappers::UnixListenerStream, StreamExt};
+
+            // a socket file left behind by a previous run would prevent us from binding
+            if socket_path.exists() {
+                std::fs::remove_file(&socket_path)?;
+            }
+            let listener = tokio::net::UnixListener::bind(&socket_path)?;
+            println!("Listening for RPCs on {socket_path:?}");
+            let incoming =
+                UnixListenerStream::new(listener).map(|stream| stream.map(unix_socket::UnixStream));
+            router.serve_with_incoming(incoming).await
+        }
+        #[cfg(not(unix))]
+        Some(socket_path) => {
+            return Err(color_eyre::eyre::eyre!(
+                "Cannot listen on {socket_path:?}: Unix domain sockets are not supported on this platform"
+            ));
+        }
+        None => {
+            router
+                .serve(SocketAddr::new(IpAddr::V4(args.address), args.port))
+                .await
+        }
+    };
+    if let Err(err) = res {
         error!("Safenode Manager Daemon failed to start: {err:?}");
         println!("Safenode Manager Daemon failed to start: {err:?}");
         return Err(err.into());
@@ -136,3 +220,52 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(unix)]
+mod unix_socket {
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+    use tonic::transport::server::Connected;
+
+    /// A Unix domain socket connection, which tonic can serve RPCs over.
+    #[derive(Debug)]
+    pub struct UnixStream(pub tokio::net::UnixStream);
+
+    impl Connected for UnixStream {
+        type ConnectInfo = ();
+
+        fn connect_info(&self) -> Self::ConnectInfo {}
+    }
+
+    impl AsyncRead for UnixStream {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for UnixStream {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.0).poll_write(cx, buf)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_shutdown(cx)
+        }
+    }
+}
@@ -15,22 +15,80 @@ use colored::Colorize;
 use libp2p::PeerId;
 use service_manager::{ServiceInstallCtx, ServiceLabel};
 use sn_node_rpc_client::RpcActions;
-use sn_protocol::node_registry::{Daemon, Node, NodeRegistry, NodeStatus};
-use std::{
-    ffi::OsString,
-    net::{IpAddr, Ipv4Addr, SocketAddr},
-    path::PathBuf,
+use sn_protocol::{
+    node_registry::{Daemon, Node, NodeRegistry, NodeStatus},
+    safenode_manager_proto::{
+        safe_node_manager_client::SafeNodeManagerClient, GetNodeServicesRequest, NodeServiceInfo,
+        NodeServiceStatus,
+    },
 };
+use std::{ffi::OsString, net::SocketAddr, path::PathBuf};
+use tonic::transport::Channel;
 
 pub const DAEMON_DEFAULT_PORT: u16 = 12500;
 const DAEMON_SERVICE_NAME: &str = "safenodemand";
 
+/// Where the daemon listens for RPCs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DaemonEndpoint {
+    /// A TCP socket, which allows the nodes to be managed remotely.
+    Tcp(SocketAddr),
+    /// A Unix domain socket, which only local tools can talk to.
+    Unix(PathBuf),
+}
+
+impl DaemonEndpoint {
+    /// Returns the endpoint recorded for the daemon in the node registry, if any.
+    pub fn from_daemon(daemon: &Daemon) -> Option<Self> {
+        match (&daemon.socket_path, daemon.endpoint) {
+            (Some(socket_path), _) => Some(Self::Unix(socket_path.clone())),
+            (None, Some(endpoint)) => Some(Self::Tcp(endpoint)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// A node managed by the daemon, as the daemon sees it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManagedNodeInfo {
+    /// The peer id of the node, if it has been started.
+    pub peer_id: Option<PeerId>,
+    pub service_name: String,
+    /// The status of the node's service, checked against the service manager by the daemon.
+    pub status: NodeStatus,
+    /// The endpoint of the node's RPC service.
+    pub rpc_socket_addr: SocketAddr,
+}
+
+impl TryFrom<NodeServiceInfo> for ManagedNodeInfo {
+    type Error = color_eyre::eyre::Error;
+
+    fn try_from(info: NodeServiceInfo) -> Result<Self> {
+        let peer_id = if info.peer_id.is_empty() {
+            None
+        } else {
+            Some(PeerId::from_bytes(&info.peer_id)?)
+        };
+        let status = match info.status() {
+            NodeServiceStatus::Added => NodeStatus::Added,
+            NodeServiceStatus::Running => NodeStatus::Running,
+            NodeServiceStatus::Stopped => NodeStatus::Stopped,
+            NodeServiceStatus::Removed => NodeStatus::Removed,
+        };
+        Ok(Self {
+            peer_id,
+            service_name: info.service_name,
+            status,
+            rpc_socket_addr: info.rpc_socket_addr.parse()?,
+        })
+    }
+}
+
 /// Install the daemon as a service.
 ///
 /// This only defines the service; it does not start it.
 pub fn add_daemon(
-    address: Ipv4Addr,
-    port: u16,
+    endpoint: DaemonEndpoint,
     daemon_path: PathBuf,
     node_registry: &mut NodeRegistry,
     service_control: &dyn ServiceControl,
@@ -48,12 +106,18 @@ pub fn add_daemon(
     let install_ctx = ServiceInstallCtx {
         label: service_name.clone(),
         program: daemon_path.clone(),
-        args: vec![
-            OsString::from("--port"),
-            OsString::from(port.to_string()),
-            OsString::from("--address"),
-            OsString::from(address.to_string()),
-        ],
+        args: match &endpoint {
+            DaemonEndpoint::Tcp(socket_addr) => vec![
+                OsString::from("--port"),
+                OsString::from(socket_addr.port().to_string()),
+                OsString::from("--address"),
+                OsString::from(socket_addr.ip().to_string()),
+            ],
+            DaemonEndpoint::Unix(socket_path) => vec![
+                OsString::from("--socket-path"),
+                OsString::from(socket_path.as_os_str()),
+            ],
+        },
         contents: None,
         username: None,
         working_directory: None,
@@ -64,7 +128,14 @@ pub fn add_daemon(
         Ok(()) => {
             let daemon = Daemon {
                 daemon_path,
-                endpoint: Some(SocketAddr::new(IpAddr::V4(address), port)),
+                endpoint: match &endpoint {
+                    DaemonEndpoint::Tcp(socket_addr) => Some(*socket_addr),
+                    DaemonEndpoint::Unix(_) => None,
+                },
+                socket_path: match endpoint {
+                    DaemonEndpoint::Tcp(_) => None,
+                    DaemonEndpoint::Unix(socket_path) => Some(socket_path),
+                },
                 pid: None,
                 service_name: DAEMON_SERVICE_NAME.to_string(),
                 status: NodeStatus::Added,
@@ -107,7 +178,7 @@ pub fn start_daemon(
     println!("{} Started faucet service", "✓".green());
     if verbosity != VerbosityLevel::Minimal {
         println!("  - PID: {}", pid);
-        println!("  - Endpoint: {:?}", daemon.endpoint);
+        println!("  - Endpoint: {:?}", DaemonEndpoint::from_daemon(daemon));
     }
 
     Ok(())
@@ -152,6 +223,56 @@ pub fn stop_daemon(daemon: &mut Daemon, service_control: &dyn ServiceControl) ->
     }
 }
 
+/// Connect to the daemon's RPC service, over TCP or a Unix domain socket.
+pub async fn connect_to_daemon(
+    endpoint: &DaemonEndpoint,
+) -> Result<SafeNodeManagerClient<Channel>> {
+    match endpoint {
+        DaemonEndpoint::Tcp(socket_addr) => {
+            Ok(SafeNodeManagerClient::connect(format!("https://{socket_addr}")).await?)
+        }
+        DaemonEndpoint::Unix(socket_path) => connect_to_unix_socket(socket_path.clone()).await,
+    }
+}
+
+/// Get the nodes managed by the daemon listening on the endpoint, with their current status and
+/// RPC endpoint, without having to read the node registry on the daemon's machine.
+pub async fn get_daemon_managed_nodes(
+    daemon_endpoint: &DaemonEndpoint,
+) -> Result<Vec<ManagedNodeInfo>> {
+    let mut client = connect_to_daemon(daemon_endpoint).await?;
+    let response = client
+        .get_node_services(tonic::Request::new(GetNodeServicesRequest {}))
+        .await
+        .map_err(|err| {
+            eyre!("Failed to get the nodes from the daemon at {daemon_endpoint:?}: {err}")
+        })?;
+    response
+        .into_inner()
+        .nodes
+        .into_iter()
+        .map(ManagedNodeInfo::try_from)
+        .collect()
+}
+
+#[cfg(unix)]
+async fn connect_to_unix_socket(socket_path: PathBuf) -> Result<SafeNodeManagerClient<Channel>> {
+    // the uri is only required to build the endpoint, the connection goes through the socket
+    let channel = tonic::transport::Endpoint::from_static("http://[::]:12500")
+        .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
+            tokio::net::UnixStream::connect(socket_path.clone())
+        }))
+        .await?;
+    Ok(SafeNodeManagerClient::new(channel))
+}
+
+#[cfg(not(unix))]
+async fn connect_to_unix_socket(socket_path: PathBuf) -> Result<SafeNodeManagerClient<Channel>> {
+    Err(eyre!(
+        "Cannot connect to {socket_path:?}: Unix domain sockets are not supported on this platform"
+    ))
+}
+
 pub async fn restart_node_service(
     node_registry: &mut NodeRegistry,
     peer_id: PeerId,
@@ -174,6 +295,13 @@ pub async fn restart_node_service(
             )
         })?;
 
+    // re-apply the env the node was installed with, falling back to the registry wide env for
+    // nodes that were added before it was being tracked per node.
+    let env_variables = current_node
+        .env_variables
+        .clone()
+        .or_else(|| node_registry.environment_variables.clone());
+
     if retain_peer_id {
         // reuse the same port and root dir to retain peer id.
         service_control
@@ -195,7 +323,8 @@ pub async fn restart_node_service(
             log_dir_path: current_node.log_dir_path.clone(),
             safenode_path: current_node.safenode_path.clone(),
             service_user: current_node.user.clone(),
-            env_variables: node_registry.environment_variables.clone(),
+            env_variables: env_variables.clone(),
+            safenode_args: current_node.safenode_args.clone(),
         }
         .build()?;
         service_control.install(install_ctx).map_err(|err| {
@@ -289,30 +418,23 @@ pub async fn restart_node_service(
             log_dir_path: log_dir_path.clone(),
             safenode_path: safenode_path.clone(),
             service_user: current_node.user.clone(),
-            env_variables: node_registry.environment_variables.clone(),
+            env_variables: env_variables.clone(),
+            safenode_args: current_node.safenode_args.clone(),
         }
         .build()?;
         service_control.install(install_ctx).map_err(|err| {
             eyre!("Error while installing node {new_service_name:?} with: {err:?}",)
         })?;
 
-        let mut node = Node {
-            genesis: current_node.genesis,
-            local: current_node.local,
-            service_name: new_service_name.clone(),
-            user: current_node.user.clone(),
-            number: new_node_number as u16,
-            rpc_socket_addr: current_node.rpc_socket_addr,
-            version: current_node.version.clone(),
-            status: NodeStatus::Added,
-            listen_addr: None,
-            pid: None,
-            peer_id: None,
+        let mut node = replacement_node(
+            current_node,
+            new_node_number as u16,
+            new_service_name.clone(),
             log_dir_path,
             data_dir_path,
             safenode_path,
-            connected_peers: None,
-        };
+            env_variables,
+        );
         node_control::start(
             &mut node,
             service_control,
@@ -330,3 +452,185 @@ pub async fn restart_node_service(
 
     Ok(())
 }
+
+/// The registry entry of a new node instance replacing `current_node`, under a new service.
+/// The restart history of the replaced node is carried over, as it is the same node restarted
+/// from the point of view of the user, along with the arguments its service was installed with.
+fn replacement_node(
+    current_node: &Node,
+    number: u16,
+    service_name: String,
+    log_dir_path: PathBuf,
+    data_dir_path: PathBuf,
+    safenode_path: PathBuf,
+    env_variables: Option<Vec<(String, String)>>,
+) -> Node {
+    Node {
+        genesis: current_node.genesis,
+        local: current_node.local,
+        service_name,
+        user: current_node.user.clone(),
+        number,
+        rpc_socket_addr: current_node.rpc_socket_addr,
+        version: current_node.version.clone(),
+        status: NodeStatus::Added,
+        listen_addr: None,
+        pid: None,
+        peer_id: None,
+        log_dir_path,
+        data_dir_path,
+        safenode_path,
+        connected_peers: None,
+        restart_count: current_node.restart_count,
+        last_started: current_node.last_started,
+        env_variables,
+        safenode_args: current_node.safenode_args.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::MockServiceControl;
+    use assert_fs::prelude::*;
+    use libp2p::Multiaddr;
+    use mockall::predicate::*;
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        str::FromStr,
+        time::{Duration, SystemTime},
+    };
+
+    #[test]
+    fn add_daemon_should_install_the_daemon_on_a_unix_socket() -> Result<()> {
+        let tmp_data_dir = assert_fs::TempDir::new()?;
+        let node_reg_path = tmp_data_dir.child("node_reg.json");
+        let socket_path = tmp_data_dir.child("safenodemand.sock").to_path_buf();
+        let daemon_path = PathBuf::from("/usr/local/bin/safenodemand");
+        let mut node_registry = NodeRegistry {
+            faucet: None,
+            save_path: node_reg_path.to_path_buf(),
+            nodes: vec![],
+            bootstrap_peers: vec![],
+            environment_variables: None,
+            daemon: None,
+        };
+
+        let mut mock_service_control = MockServiceControl::new();
+        mock_service_control
+            .expect_stop()
+            .with(eq(DAEMON_SERVICE_NAME))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_service_control
+            .expect_uninstall()
+            .with(eq(DAEMON_SERVICE_NAME))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_service_control
+            .expect_install()
+            .with(eq(ServiceInstallCtx {
+                label: DAEMON_SERVICE_NAME.parse()?,
+                program: daemon_path.clone(),
+                args: vec![
+                    OsString::from("--socket-path"),
+                    OsString::from(socket_path.as_os_str()),
+                ],
+                contents: None,
+                username: None,
+                working_directory: None,
+                environment: None,
+            }))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        add_daemon(
+            DaemonEndpoint::Unix(socket_path.clone()),
+            daemon_path,
+            &mut node_registry,
+            &mock_service_control,
+        )?;
+
+        let daemon = node_registry
+            .daemon
+            .ok_or_eyre("The daemon was not added")?;
+        assert_eq!(daemon.endpoint, None);
+        assert_eq!(daemon.socket_path, Some(socket_path.clone()));
+        assert_eq!(
+            DaemonEndpoint::from_daemon(&daemon),
+            Some(DaemonEndpoint::Unix(socket_path))
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn connect_to_daemon_should_connect_over_a_unix_socket() -> Result<()> {
+        let tmp_data_dir = assert_fs::TempDir::new()?;
+        let socket_path = tmp_data_dir.child("safenodemand.sock").to_path_buf();
+        let listener = tokio::net::UnixListener::bind(&socket_path)?;
+        let accepted = tokio::spawn(async move { listener.accept().await.map(|_| ()) });
+
+        let _client = connect_to_daemon(&DaemonEndpoint::Unix(socket_path)).await?;
+        accepted.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn connect_to_daemon_should_fail_without_a_listener_on_the_socket() {
+        let tmp_data_dir = assert_fs::TempDir::new().expect("Should be able to create a temp dir.");
+        let socket_path = tmp_data_dir.child("safenodemand.sock").to_path_buf();
+        assert!(connect_to_daemon(&DaemonEndpoint::Unix(socket_path))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn replacement_node_should_carry_over_the_restart_history() -> Result<()> {
+        let last_started = SystemTime::now() - Duration::from_secs(60);
+        let current_node = Node {
+            genesis: false,
+            local: false,
+            version: "0.98.1".to_string(),
+            service_name: "safenode1".to_string(),
+            user: "safe".to_string(),
+            number: 1,
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            status: NodeStatus::Running,
+            pid: Some(1000),
+            listen_addr: Some(vec![Multiaddr::from_str("/ip4/127.0.0.1/tcp/12000")?]),
+            peer_id: Some(PeerId::from_str(
+                "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
+            )?),
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+            connected_peers: None,
+            restart_count: 3,
+            last_started: Some(last_started),
+            env_variables: None,
+            safenode_args: vec!["--max-records".to_string(), "4096".to_string()],
+        };
+
+        let node = replacement_node(
+            &current_node,
+            2,
+            "safenode2".to_string(),
+            PathBuf::from("/var/log/safenode/safenode2"),
+            PathBuf::from("/var/safenode-manager/services/safenode2"),
+            PathBuf::from("/var/safenode-manager/services/safenode2/safenode"),
+            None,
+        );
+
+        assert_eq!(node.service_name, "safenode2");
+        assert_eq!(node.number, 2);
+        assert_eq!(node.status, NodeStatus::Added);
+        assert_eq!(node.pid, None);
+        assert_eq!(node.peer_id, None);
+        assert_eq!(node.listen_addr, None);
+        assert_eq!(node.restart_count, 3);
+        assert_eq!(node.last_started, Some(last_started));
+        assert_eq!(node.safenode_args, current_node.safenode_args);
+        Ok(())
+    }
+}
@@ -23,6 +23,7 @@ use std::{
     path::PathBuf,
     process::{Command, Stdio},
     str::FromStr,
+    time::SystemTime,
 };
 use sysinfo::{Pid, ProcessExt, System, SystemExt};
 
@@ -319,6 +320,10 @@ pub async fn run_node(
         log_dir_path: node_info.log_path,
         data_dir_path: node_info.data_path,
         safenode_path: launcher.get_safenode_path(),
+        restart_count: 0,
+        last_started: Some(SystemTime::now()),
+        env_variables: None,
+        safenode_args: vec![],
     })
 }
 
@@ -29,6 +29,7 @@ pub struct InstallNodeServiceCtxBuilder {
     pub safenode_path: PathBuf,
     pub service_user: String,
     pub env_variables: Option<Vec<(String, String)>>,
+    pub safenode_args: Vec<String>,
 }
 
 impl InstallNodeServiceCtxBuilder {
@@ -64,6 +65,7 @@ impl InstallNodeServiceCtxBuilder {
             args.push(OsString::from("--peer"));
             args.push(OsString::from(peers_str));
         }
+        args.extend(self.safenode_args.into_iter().map(OsString::from));
 
         let mut service_ctx = ServiceInstallCtx {
             label: label.clone(),
@@ -126,6 +128,7 @@ pub struct AddServiceOptions {
     pub local: bool,
     pub node_port: Option<u16>,
     pub rpc_address: Option<Ipv4Addr>,
+    pub safenode_args: Vec<String>,
     pub safenode_bin_path: PathBuf,
     pub safenode_dir_path: PathBuf,
     pub service_data_dir_path: PathBuf,
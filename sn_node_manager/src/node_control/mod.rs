@@ -12,17 +12,46 @@ mod tests;
 
 pub use config::{AddServiceOptions, InstallNodeServiceCtxBuilder, UpgradeOptions};
 
-use crate::{config::create_owned_dir, service::ServiceControl, VerbosityLevel};
+use crate::{
+    config::create_owned_dir,
+    service::{ServiceControl, ServiceStatus},
+    VerbosityLevel,
+};
 use color_eyre::{
     eyre::{eyre, OptionExt},
     Help, Result,
 };
 use colored::Colorize;
-use libp2p::multiaddr::Protocol;
+use libp2p::{multiaddr::Protocol, Multiaddr};
 use semver::Version;
 use sn_node_rpc_client::{RpcActions, RpcClient};
 use sn_protocol::node_registry::{Node, NodeRegistry, NodeStatus};
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::SystemTime,
+};
+
+/// The names of the node services are this prefix followed by the number of the node.
+const NODE_SERVICE_NAME_PREFIX: &str = "safenode";
+
+/// A node whose registry entry `reconcile` corrected to match its service.
+#[derive(Clone, Debug)]
+pub struct ReconciledNode {
+    pub service_name: String,
+    pub previous_status: NodeStatus,
+    pub status: NodeStatus,
+    pub previous_pid: Option<u32>,
+    pub pid: Option<u32>,
+}
+
+/// The discrepancies between the registry and the services fixed by `reconcile`.
+#[derive(Clone, Debug, Default)]
+pub struct ReconcileReport {
+    pub fixed: Vec<ReconciledNode>,
+    /// The node services installed on the machine that no node of the registry refers to, e.g.
+    /// after the registry was lost. They are left untouched.
+    pub untracked_services: Vec<String>,
+}
 
 pub enum UpgradeResult {
     Forced(String, String),
@@ -115,7 +144,7 @@ pub async fn add(
             SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), rpc_free_port)
         };
 
-        let service_name = format!("safenode{node_number}");
+        let service_name = format!("{NODE_SERVICE_NAME_PREFIX}{node_number}");
         let service_data_dir_path = options.service_data_dir_path.join(service_name.clone());
         let service_safenode_path = service_data_dir_path.join(safenode_file_name.clone());
         let service_log_dir_path = options.service_log_dir_path.join(service_name.clone());
@@ -137,6 +166,7 @@ pub async fn add(
             name: service_name.clone(),
             node_port: options.node_port,
             rpc_socket_addr,
+            safenode_args: options.safenode_args.clone(),
             safenode_path: service_safenode_path.clone(),
             service_user: options.user.clone(),
         }
@@ -168,6 +198,10 @@ pub async fn add(
                     data_dir_path: service_data_dir_path.clone(),
                     safenode_path: service_safenode_path,
                     connected_peers: None,
+                    restart_count: 0,
+                    last_started: None,
+                    env_variables: options.env_variables.clone(),
+                    safenode_args: options.safenode_args.clone(),
                 });
                 // We save the node registry for each service because it's possible any number of
                 // services could fail to be added.
@@ -247,6 +281,10 @@ pub async fn start(
     node.pid = Some(node_info.pid);
     node.peer_id = Some(node_info.peer_id);
     node.status = NodeStatus::Running;
+    if node.last_started.is_some() {
+        node.restart_count += 1;
+    }
+    node.last_started = Some(SystemTime::now());
 
     println!("{} Started {} service", "✓".green(), node.service_name);
     if verbosity != VerbosityLevel::Minimal {
@@ -384,6 +422,7 @@ pub async fn status(
                     .as_ref()
                     .map_or("-".to_string(), |p| p.len().to_string())
             );
+            println!("Restarts: {}", node.restart_count);
             println!();
         }
     } else {
@@ -424,6 +463,81 @@ pub async fn status(
     Ok(())
 }
 
+/// Update the status and PID of the nodes in the registry to match their services, as reported
+/// by the OS service manager, e.g. after a reboot left the registry with stale data.
+///
+/// A node whose service is running keeps its PID if the service manager doesn't report one and
+/// the process is still alive. Removed nodes are left alone.
+///
+/// The node services installed without a node in the registry, or only a removed one, are
+/// reported as untracked.
+pub fn reconcile(
+    node_registry: &mut NodeRegistry,
+    service_control: &dyn ServiceControl,
+) -> Result<ReconcileReport> {
+    let mut report = ReconcileReport::default();
+    for node in &mut node_registry.nodes {
+        if node.status == NodeStatus::Removed {
+            continue;
+        }
+
+        let previous_status = node.status.clone();
+        let previous_pid = node.pid;
+        match service_control.status(&node.service_name)? {
+            ServiceStatus::Running { pid } => {
+                node.status = NodeStatus::Running;
+                node.pid = pid.or_else(|| {
+                    node.pid
+                        .filter(|pid| service_control.is_service_process_running(*pid))
+                });
+            }
+            ServiceStatus::Stopped => {
+                // A node that has never been started is not considered stopped.
+                if node.status != NodeStatus::Added {
+                    node.status = NodeStatus::Stopped;
+                }
+                node.pid = None;
+                node.connected_peers = None;
+            }
+            ServiceStatus::NotFound => {
+                node.status = NodeStatus::Removed;
+                node.pid = None;
+                node.connected_peers = None;
+            }
+        }
+
+        if node.status != previous_status || node.pid != previous_pid {
+            report.fixed.push(ReconciledNode {
+                service_name: node.service_name.clone(),
+                previous_status,
+                status: node.status.clone(),
+                previous_pid,
+                pid: node.pid,
+            });
+        }
+    }
+
+    report.untracked_services = service_control
+        .list_services(NODE_SERVICE_NAME_PREFIX)?
+        .into_iter()
+        .filter(|service_name| is_node_service_name(service_name))
+        .filter(|service_name| {
+            !node_registry.nodes.iter().any(|node| {
+                node.service_name == *service_name && node.status != NodeStatus::Removed
+            })
+        })
+        .collect();
+    Ok(report)
+}
+
+/// Whether the service is the one of a node, rather than e.g. the daemon's, which shares the
+/// prefix of the node services.
+fn is_node_service_name(service_name: &str) -> bool {
+    service_name
+        .strip_prefix(NODE_SERVICE_NAME_PREFIX)
+        .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
 pub async fn remove(
     node: &mut Node,
     service_control: &dyn ServiceControl,
@@ -452,8 +566,7 @@ pub async fn remove(
     service_control.uninstall(&node.service_name)?;
 
     if !keep_directories {
-        std::fs::remove_dir_all(&node.data_dir_path)?;
-        std::fs::remove_dir_all(&node.log_dir_path)?;
+        remove_node_directories(node)?;
     }
 
     node.status = NodeStatus::Removed;
@@ -463,6 +576,48 @@ pub async fn remove(
     Ok(())
 }
 
+/// Undo the addition of a node: uninstall its service, optionally delete its data and log
+/// directories, then drop it from the registry altogether, rather than keeping it as `Removed`.
+///
+/// This also covers a node that was added but never started, whose directories may not have
+/// been populated, or may have been deleted already. A node previously removed with its
+/// directories kept is dropped from the registry without touching its service again.
+pub async fn remove_node(
+    node_registry: &mut NodeRegistry,
+    service_name: &str,
+    service_control: &dyn ServiceControl,
+    delete_data: bool,
+) -> Result<()> {
+    let index = node_registry
+        .nodes
+        .iter()
+        .position(|node| node.service_name == service_name)
+        .ok_or_else(|| eyre!("No service named '{service_name}'"))?;
+
+    let node = &mut node_registry.nodes[index];
+    if node.status == NodeStatus::Removed {
+        if delete_data {
+            remove_node_directories(node)?;
+        }
+    } else {
+        remove(node, service_control, !delete_data).await?;
+    }
+
+    let _ = node_registry.nodes.remove(index);
+    Ok(())
+}
+
+/// Deletes the data and log directories of the node, ignoring the ones that don't exist.
+fn remove_node_directories(node: &Node) -> Result<()> {
+    for dir in [&node.data_dir_path, &node.log_dir_path] {
+        match std::fs::remove_dir_all(dir) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 pub async fn upgrade(
     options: UpgradeOptions,
     node: &mut Node,
@@ -479,8 +634,10 @@ pub async fn upgrade(
     stop(node, service_control).await?;
     std::fs::copy(options.target_safenode_path, &node.safenode_path)?;
 
-    // Install the service again to make sure we re-use the same node port.
+    // Install the service again to make sure we re-use the same node port and the arguments it
+    // was added with. The env is only replaced if a new one is provided.
     // Windows requires that the service be uninstalled first.
+    let env_variables = options.env_variables.or_else(|| node.env_variables.clone());
     service_control.uninstall(&node.service_name.clone())?;
     let install_ctx = InstallNodeServiceCtxBuilder {
         local: node.local,
@@ -493,10 +650,12 @@ pub async fn upgrade(
         log_dir_path: node.log_dir_path.clone(),
         safenode_path: node.safenode_path.clone(),
         service_user: node.user.clone(),
-        env_variables: options.env_variables.clone(),
+        env_variables: env_variables.clone(),
+        safenode_args: node.safenode_args.clone(),
     }
     .build()?;
     service_control.install(install_ctx)?;
+    node.env_variables = env_variables;
 
     if options.start_node {
         start(node, service_control, rpc_client, VerbosityLevel::Normal).await?;
@@ -515,6 +674,60 @@ pub async fn upgrade(
     }
 }
 
+/// Reinstall the service of a node with a new set of bootstrap peers.
+///
+/// A running node is stopped before its service is reinstalled, then started again, so it uses
+/// the new peers right away. A stopped node uses them the next time it's started. Removed nodes
+/// are left alone.
+///
+/// The node is updated in place; the caller is expected to save the registry afterwards.
+pub async fn update_bootstrap_peers(
+    node: &mut Node,
+    bootstrap_peers: Vec<Multiaddr>,
+    service_control: &dyn ServiceControl,
+    rpc_client: &dyn RpcActions,
+) -> Result<()> {
+    if node.status == NodeStatus::Removed {
+        return Ok(());
+    }
+
+    let was_running = node.status == NodeStatus::Running;
+    if was_running {
+        stop(node, service_control).await?;
+    }
+
+    // Install the service again to make sure we re-use the same node port.
+    // Windows requires that the service be uninstalled first.
+    service_control.uninstall(&node.service_name.clone())?;
+    let install_ctx = InstallNodeServiceCtxBuilder {
+        local: node.local,
+        data_dir_path: node.data_dir_path.clone(),
+        genesis: node.genesis,
+        name: node.service_name.clone(),
+        node_port: node.get_safenode_port(),
+        bootstrap_peers,
+        rpc_socket_addr: node.rpc_socket_addr,
+        log_dir_path: node.log_dir_path.clone(),
+        safenode_path: node.safenode_path.clone(),
+        service_user: node.user.clone(),
+        env_variables: node.env_variables.clone(),
+        safenode_args: node.safenode_args.clone(),
+    }
+    .build()?;
+    service_control.install(install_ctx)?;
+
+    if was_running {
+        start(node, service_control, rpc_client, VerbosityLevel::Normal).await?;
+    }
+
+    println!(
+        "{} Updated the bootstrap peers of {}",
+        "✓".green(),
+        node.service_name
+    );
+    Ok(())
+}
+
 fn format_status(status: &NodeStatus) -> String {
     match status {
         NodeStatus::Running => "RUNNING".green().to_string(),
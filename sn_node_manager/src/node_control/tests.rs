@@ -9,10 +9,11 @@
 use crate::{
     node_control::{
         add,
-        config::{AddServiceOptions, InstallNodeServiceCtxBuilder},
-        remove, start, stop,
+        config::{AddServiceOptions, InstallNodeServiceCtxBuilder, UpgradeOptions},
+        reconcile, remove, remove_node, start, stop, update_bootstrap_peers, upgrade,
+        UpgradeResult,
     },
-    service::MockServiceControl,
+    service::{MockServiceControl, ServiceStatus},
     VerbosityLevel,
 };
 use assert_fs::prelude::*;
@@ -23,6 +24,7 @@ use libp2p::Multiaddr;
 use libp2p_identity::PeerId;
 use mockall::{mock, predicate::*, Sequence};
 use predicates::prelude::*;
+use semver::Version;
 use sn_node_rpc_client::{NetworkInfo, NodeInfo, RecordAddress, Result as RpcResult, RpcActions};
 use sn_protocol::node_registry::{Node, NodeRegistry, NodeStatus};
 use sn_releases::{
@@ -120,6 +122,7 @@ async fn add_genesis_node_should_use_latest_version_and_add_one_service() -> Res
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         bootstrap_peers: vec![],
         env_variables: None,
+        safenode_args: vec![],
     }
     .build()?;
     mock_service_control
@@ -145,6 +148,7 @@ async fn add_genesis_node_should_use_latest_version_and_add_one_service() -> Res
             user: get_username(),
             version: latest_version.to_string(),
             env_variables: None,
+            safenode_args: vec![],
         },
         &mut node_registry,
         &mock_service_control,
@@ -208,6 +212,10 @@ async fn add_genesis_node_should_return_an_error_if_there_is_already_a_genesis_n
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
             connected_peers: None,
+            restart_count: 0,
+            last_started: None,
+            env_variables: None,
+            safenode_args: vec![],
         }],
         bootstrap_peers: vec![],
         environment_variables: None,
@@ -240,6 +248,7 @@ async fn add_genesis_node_should_return_an_error_if_there_is_already_a_genesis_n
             user: get_username(),
             version: latest_version.to_string(),
             env_variables: None,
+            safenode_args: vec![],
         },
         &mut node_registry,
         &mock_service_control,
@@ -298,6 +307,7 @@ async fn add_genesis_node_should_return_an_error_if_count_is_greater_than_1() ->
             user: get_username(),
             version: latest_version.to_string(),
             env_variables: None,
+            safenode_args: vec![],
         },
         &mut node_registry,
         &mock_service_control,
@@ -362,6 +372,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         bootstrap_peers: vec![],
         env_variables: None,
+        safenode_args: vec![],
     }
     .build()?;
 
@@ -393,6 +404,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
         data_dir_path: node_data_dir.to_path_buf().join("safenode2"),
         bootstrap_peers: vec![],
         env_variables: None,
+        safenode_args: vec![],
     }
     .build()?;
 
@@ -424,6 +436,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
         data_dir_path: node_data_dir.to_path_buf().join("safenode3"),
         bootstrap_peers: vec![],
         env_variables: None,
+        safenode_args: vec![],
     }
     .build()?;
 
@@ -450,6 +463,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
             user: get_username(),
             version: latest_version.to_string(),
             env_variables: None,
+            safenode_args: vec![],
         },
         &mut node_registry,
         &mock_service_control,
@@ -563,6 +577,7 @@ async fn add_node_should_update_the_bootstrap_peers_inside_node_registry() -> Re
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         bootstrap_peers: new_peers.clone(),
         env_variables: None,
+        safenode_args: vec![],
     }
     .build()?;
     mock_service_control
@@ -588,6 +603,7 @@ async fn add_node_should_update_the_bootstrap_peers_inside_node_registry() -> Re
             user: get_username(),
             version: latest_version.to_string(),
             env_variables: None,
+            safenode_args: vec![],
         },
         &mut node_registry,
         &mock_service_control,
@@ -675,6 +691,7 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         bootstrap_peers: vec![],
         env_variables: env_variables.clone(),
+        safenode_args: vec![],
     }
     .build()?;
     mock_service_control
@@ -700,6 +717,7 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
             user: get_username(),
             version: latest_version.to_string(),
             env_variables: env_variables.clone(),
+            safenode_args: vec![],
         },
         &mut node_registry,
         &mock_service_control,
@@ -714,6 +732,7 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
     assert_eq!(node_registry.environment_variables, env_variables);
 
     assert_eq!(node_registry.nodes.len(), 1);
+    assert_eq!(node_registry.nodes[0].env_variables, env_variables);
     assert_eq!(node_registry.nodes[0].version, latest_version);
     assert_eq!(node_registry.nodes[0].service_name, "safenode1");
     assert_eq!(node_registry.nodes[0].user, get_username());
@@ -762,6 +781,10 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
             connected_peers: None,
+            restart_count: 0,
+            last_started: None,
+            env_variables: None,
+            safenode_args: vec![],
         }],
         bootstrap_peers: vec![],
         environment_variables: None,
@@ -796,6 +819,7 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
         data_dir_path: node_data_dir.to_path_buf().join("safenode2"),
         bootstrap_peers: vec![],
         env_variables: None,
+        safenode_args: vec![],
     }
     .build()?;
 
@@ -822,6 +846,7 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
             user: get_username(),
             version: latest_version.to_string(),
             env_variables: None,
+            safenode_args: vec![],
         },
         &mut node_registry,
         &mock_service_control,
@@ -899,6 +924,7 @@ async fn add_node_should_use_custom_ports_for_one_service() -> Result<()> {
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         bootstrap_peers: vec![],
         env_variables: None,
+        safenode_args: vec![],
     }
     .build()?;
 
@@ -925,6 +951,7 @@ async fn add_node_should_use_custom_ports_for_one_service() -> Result<()> {
             user: get_username(),
             version: latest_version.to_string(),
             env_variables: None,
+            safenode_args: vec![],
         },
         &mut node_registry,
         &mock_service_control,
@@ -997,6 +1024,7 @@ async fn add_node_should_return_error_if_custom_port_is_used_and_more_than_one_s
             user: get_username(),
             version: latest_version.to_string(),
             env_variables: None,
+            safenode_args: vec![],
         },
         &mut node_registry,
         &MockServiceControl::new(),
@@ -1084,6 +1112,10 @@ async fn start_should_start_a_newly_installed_service() -> Result<()> {
         data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
         safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
         connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
     start(
         &mut node,
@@ -1101,6 +1133,8 @@ async fn start_should_start_a_newly_installed_service() -> Result<()> {
         )?)
     );
     assert_matches!(node.status, NodeStatus::Running);
+    assert_eq!(node.restart_count, 0);
+    assert!(node.last_started.is_some());
 
     Ok(())
 }
@@ -1158,6 +1192,10 @@ async fn start_should_start_a_stopped_service() -> Result<()> {
         data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
         safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
         connected_peers: None,
+        restart_count: 0,
+        last_started: Some(std::time::UNIX_EPOCH),
+        env_variables: None,
+        safenode_args: vec![],
     };
     start(
         &mut node,
@@ -1169,6 +1207,8 @@ async fn start_should_start_a_stopped_service() -> Result<()> {
 
     assert_matches!(node.status, NodeStatus::Running);
     assert_eq!(node.pid, Some(1001));
+    assert_eq!(node.restart_count, 1);
+    assert!(node.last_started > Some(std::time::UNIX_EPOCH));
     assert_eq!(
         node.peer_id,
         Some(PeerId::from_str(
@@ -1223,6 +1263,10 @@ async fn start_should_not_attempt_to_start_a_running_service() -> Result<()> {
         data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
         safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
         connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
     start(
         &mut node,
@@ -1279,6 +1323,10 @@ async fn start_should_start_a_service_marked_as_running_but_had_since_stopped()
         data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
         safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
         connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
     start(
         &mut node,
@@ -1329,6 +1377,10 @@ async fn stop_should_stop_a_running_service() -> Result<()> {
         connected_peers: Some(vec![PeerId::from_str(
             "12D3KooWKbV9vUmZQdHmTwrQqHrqAQpM7GUWHJXeK1xLeh2LVpuc",
         )?]),
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
     stop(&mut node, &mock_service_control).await?;
 
@@ -1366,6 +1418,10 @@ async fn stop_should_not_return_error_for_attempt_to_stop_installed_service() ->
         data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
         safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
         connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
 
     let result = stop(&mut node, &mock_service_control).await;
@@ -1399,6 +1455,10 @@ async fn stop_should_return_ok_when_attempting_to_stop_service_that_was_already_
         data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
         safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
         connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
 
     stop(&mut node, &mock_service_control).await?;
@@ -1442,6 +1502,10 @@ async fn remove_should_remove_an_added_node() -> Result<()> {
         data_dir_path: data_dir.to_path_buf(),
         safenode_path: safenode_bin.to_path_buf(),
         connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
 
     remove(&mut node, &mock_service_control, false).await?;
@@ -1480,6 +1544,10 @@ async fn remove_should_return_an_error_if_attempting_to_remove_a_running_node()
         data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
         safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
         connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
 
     let result = remove(&mut node, &mock_service_control, false).await;
@@ -1527,6 +1595,10 @@ async fn remove_should_return_an_error_for_a_node_that_was_marked_running_but_wa
         data_dir_path: data_dir.to_path_buf(),
         safenode_path: safenode_bin.to_path_buf(),
         connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
 
     let result = remove(&mut node, &mock_service_control, false).await;
@@ -1577,6 +1649,10 @@ async fn remove_should_remove_an_added_node_and_keep_directories() -> Result<()>
         data_dir_path: data_dir.to_path_buf(),
         safenode_path: safenode_bin.to_path_buf(),
         connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
     };
 
     remove(&mut node, &mock_service_control, true).await?;
@@ -1590,3 +1666,498 @@ async fn remove_should_remove_an_added_node_and_keep_directories() -> Result<()>
 
     Ok(())
 }
+
+#[tokio::test]
+async fn remove_node_should_drop_a_never_started_node_from_the_registry() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = temp_dir.child("node_reg.json");
+    // the log directory of a node that never ran may be missing
+    let log_dir = temp_dir.child("safenode1-logs");
+    let data_dir = temp_dir.child("safenode1-data");
+    data_dir.create_dir_all()?;
+    let safenode_bin = data_dir.child("safenode");
+    safenode_bin.write_binary(b"fake safenode binary")?;
+
+    let mut mock_service_control = MockServiceControl::new();
+    mock_service_control
+        .expect_uninstall()
+        .with(eq("safenode1"))
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let mut node_registry = NodeRegistry {
+        faucet: None,
+        save_path: node_reg_path.to_path_buf(),
+        nodes: vec![Node {
+            genesis: false,
+            local: false,
+            version: "0.98.1".to_string(),
+            service_name: "safenode1".to_string(),
+            user: "safe".to_string(),
+            number: 1,
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            status: NodeStatus::Added,
+            pid: None,
+            peer_id: None,
+            listen_addr: None,
+            log_dir_path: log_dir.to_path_buf(),
+            data_dir_path: data_dir.to_path_buf(),
+            safenode_path: safenode_bin.to_path_buf(),
+            connected_peers: None,
+            restart_count: 0,
+            last_started: None,
+            env_variables: None,
+            safenode_args: vec![],
+        }],
+        bootstrap_peers: vec![],
+        environment_variables: None,
+        daemon: None,
+    };
+
+    remove_node(&mut node_registry, "safenode1", &mock_service_control, true).await?;
+
+    assert!(node_registry.nodes.is_empty());
+    data_dir.assert(predicate::path::missing());
+
+    Ok(())
+}
+
+fn reconcile_test_node(service_name: &str, status: NodeStatus, pid: Option<u32>) -> Node {
+    Node {
+        genesis: false,
+        local: false,
+        version: "0.98.1".to_string(),
+        service_name: service_name.to_string(),
+        user: "safe".to_string(),
+        number: 1,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+        status,
+        pid,
+        peer_id: None,
+        listen_addr: None,
+        log_dir_path: PathBuf::from(format!("/var/log/safenode/{service_name}")),
+        data_dir_path: PathBuf::from(format!("/var/safenode-manager/services/{service_name}")),
+        safenode_path: PathBuf::from(format!(
+            "/var/safenode-manager/services/{service_name}/safenode"
+        )),
+        connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
+    }
+}
+
+#[test]
+fn reconcile_should_update_stale_statuses_and_pids() -> Result<()> {
+    let mut mock_service_control = MockServiceControl::new();
+    mock_service_control
+        .expect_status()
+        .with(eq("safenode1"))
+        .times(1)
+        .returning(|_| Ok(ServiceStatus::Stopped));
+    mock_service_control
+        .expect_status()
+        .with(eq("safenode2"))
+        .times(1)
+        .returning(|_| Ok(ServiceStatus::Running { pid: Some(2001) }));
+    mock_service_control
+        .expect_status()
+        .with(eq("safenode3"))
+        .times(1)
+        .returning(|_| Ok(ServiceStatus::NotFound));
+    mock_service_control
+        .expect_list_services()
+        .with(eq("safenode"))
+        .times(1)
+        .returning(|_| Ok(vec!["safenode1".to_string(), "safenode2".to_string()]));
+
+    let mut node_registry = NodeRegistry {
+        bootstrap_peers: vec![],
+        daemon: None,
+        environment_variables: None,
+        faucet: None,
+        nodes: vec![
+            reconcile_test_node("safenode1", NodeStatus::Running, Some(1000)),
+            reconcile_test_node("safenode2", NodeStatus::Running, Some(1001)),
+            reconcile_test_node("safenode3", NodeStatus::Stopped, None),
+        ],
+        save_path: PathBuf::from("/tmp/node_registry.json"),
+    };
+
+    let report = reconcile(&mut node_registry, &mock_service_control)?;
+
+    assert_eq!(report.fixed.len(), 3);
+    assert_matches!(node_registry.nodes[0].status, NodeStatus::Stopped);
+    assert_eq!(node_registry.nodes[0].pid, None);
+    assert_matches!(node_registry.nodes[1].status, NodeStatus::Running);
+    assert_eq!(node_registry.nodes[1].pid, Some(2001));
+    assert_matches!(node_registry.nodes[2].status, NodeStatus::Removed);
+    assert!(report.untracked_services.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn reconcile_should_not_report_nodes_matching_their_services() -> Result<()> {
+    let mut mock_service_control = MockServiceControl::new();
+    mock_service_control
+        .expect_status()
+        .with(eq("safenode1"))
+        .times(1)
+        .returning(|_| Ok(ServiceStatus::Running { pid: None }));
+    mock_service_control
+        .expect_is_service_process_running()
+        .with(eq(1000))
+        .times(1)
+        .returning(|_| true);
+    mock_service_control
+        .expect_status()
+        .with(eq("safenode2"))
+        .times(1)
+        .returning(|_| Ok(ServiceStatus::Stopped));
+    mock_service_control
+        .expect_list_services()
+        .with(eq("safenode"))
+        .times(1)
+        .returning(|_| Ok(vec!["safenode1".to_string(), "safenode2".to_string()]));
+
+    let mut node_registry = NodeRegistry {
+        bootstrap_peers: vec![],
+        daemon: None,
+        environment_variables: None,
+        faucet: None,
+        nodes: vec![
+            reconcile_test_node("safenode1", NodeStatus::Running, Some(1000)),
+            reconcile_test_node("safenode2", NodeStatus::Added, None),
+            reconcile_test_node("safenode3", NodeStatus::Removed, None),
+        ],
+        save_path: PathBuf::from("/tmp/node_registry.json"),
+    };
+
+    let report = reconcile(&mut node_registry, &mock_service_control)?;
+
+    assert!(report.fixed.is_empty());
+    assert!(report.untracked_services.is_empty());
+    assert_eq!(node_registry.nodes[0].pid, Some(1000));
+    assert_matches!(node_registry.nodes[1].status, NodeStatus::Added);
+
+    Ok(())
+}
+
+#[test]
+fn reconcile_should_report_the_node_services_missing_from_the_registry() -> Result<()> {
+    let mut mock_service_control = MockServiceControl::new();
+    mock_service_control
+        .expect_status()
+        .with(eq("safenode1"))
+        .times(1)
+        .returning(|_| Ok(ServiceStatus::Stopped));
+    mock_service_control
+        .expect_list_services()
+        .with(eq("safenode"))
+        .times(1)
+        .returning(|_| {
+            Ok(vec![
+                "safenode1".to_string(),
+                "safenode2".to_string(),
+                "safenode3".to_string(),
+                "safenodemand".to_string(),
+            ])
+        });
+
+    let mut node_registry = NodeRegistry {
+        bootstrap_peers: vec![],
+        daemon: None,
+        environment_variables: None,
+        faucet: None,
+        nodes: vec![
+            reconcile_test_node("safenode1", NodeStatus::Stopped, None),
+            reconcile_test_node("safenode2", NodeStatus::Removed, None),
+        ],
+        save_path: PathBuf::from("/tmp/node_registry.json"),
+    };
+
+    let report = reconcile(&mut node_registry, &mock_service_control)?;
+
+    assert!(report.fixed.is_empty());
+    // the daemon's service is not a node service
+    assert_eq!(
+        report.untracked_services,
+        vec!["safenode2".to_string(), "safenode3".to_string()]
+    );
+    assert_eq!(node_registry.nodes.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn update_bootstrap_peers_should_stop_reinstall_and_restart_a_running_service() -> Result<()>
+{
+    let new_peers = vec![Multiaddr::from_str(
+        "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWKbV9vUmZQdHmTwrQqHrqAQpM7GUWHJXeK1xLeh2LVpuc",
+    )?];
+
+    let mut mock_service_control = MockServiceControl::new();
+    let mut mock_rpc_client = MockRpcClient::new();
+    let mut seq = Sequence::new();
+    mock_service_control
+        .expect_is_service_process_running()
+        .with(eq(1000))
+        .times(1)
+        .returning(|_| true)
+        .in_sequence(&mut seq);
+    mock_service_control
+        .expect_stop()
+        .with(eq("safenode1"))
+        .times(1)
+        .returning(|_| Ok(()))
+        .in_sequence(&mut seq);
+    mock_service_control
+        .expect_uninstall()
+        .with(eq("safenode1"))
+        .times(1)
+        .returning(|_| Ok(()))
+        .in_sequence(&mut seq);
+    let install_ctx = InstallNodeServiceCtxBuilder {
+        local: false,
+        genesis: false,
+        name: "safenode1".to_string(),
+        safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+        node_port: None,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+        service_user: "safe".to_string(),
+        log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+        data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+        bootstrap_peers: new_peers.clone(),
+        env_variables: None,
+        safenode_args: vec![],
+    }
+    .build()?;
+    mock_service_control
+        .expect_install()
+        .with(eq(install_ctx))
+        .times(1)
+        .returning(|_| Ok(()))
+        .in_sequence(&mut seq);
+    mock_service_control
+        .expect_start()
+        .with(eq("safenode1"))
+        .times(1)
+        .returning(|_| Ok(()))
+        .in_sequence(&mut seq);
+    mock_service_control
+        .expect_wait()
+        .with(eq(3000))
+        .times(1)
+        .returning(|_| ())
+        .in_sequence(&mut seq);
+    mock_rpc_client.expect_node_info().times(1).returning(|| {
+        Ok(NodeInfo {
+            pid: 1001,
+            peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+            data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            log_path: PathBuf::from("/var/log/safenode/safenode1"),
+            version: "0.98.1".to_string(),
+            uptime: std::time::Duration::from_secs(1),
+        })
+    });
+    mock_rpc_client
+        .expect_network_info()
+        .times(1)
+        .returning(|| {
+            Ok(NetworkInfo {
+                connected_peers: Vec::new(),
+                listeners: Vec::new(),
+            })
+        });
+
+    let mut node = Node {
+        genesis: false,
+        local: false,
+        version: "0.98.1".to_string(),
+        service_name: "safenode1".to_string(),
+        user: "safe".to_string(),
+        number: 1,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+        status: NodeStatus::Running,
+        pid: Some(1000),
+        listen_addr: None,
+        peer_id: None,
+        log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+        data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+        safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+        connected_peers: None,
+        restart_count: 0,
+        last_started: Some(std::time::UNIX_EPOCH),
+        env_variables: None,
+        safenode_args: vec![],
+    };
+
+    update_bootstrap_peers(
+        &mut node,
+        new_peers,
+        &mock_service_control,
+        &mock_rpc_client,
+    )
+    .await?;
+
+    assert_matches!(node.status, NodeStatus::Running);
+    assert_eq!(node.pid, Some(1001));
+    assert_eq!(node.restart_count, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn update_bootstrap_peers_should_reinstall_a_stopped_service_without_starting_it(
+) -> Result<()> {
+    let new_peers = vec![Multiaddr::from_str(
+        "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWKbV9vUmZQdHmTwrQqHrqAQpM7GUWHJXeK1xLeh2LVpuc",
+    )?];
+
+    let mut mock_service_control = MockServiceControl::new();
+    let mock_rpc_client = MockRpcClient::new();
+    let mut seq = Sequence::new();
+    mock_service_control
+        .expect_uninstall()
+        .with(eq("safenode1"))
+        .times(1)
+        .returning(|_| Ok(()))
+        .in_sequence(&mut seq);
+    mock_service_control
+        .expect_install()
+        .times(1)
+        .returning(|_| Ok(()))
+        .in_sequence(&mut seq);
+    mock_service_control.expect_stop().times(0);
+    mock_service_control.expect_start().times(0);
+
+    let mut node = Node {
+        genesis: false,
+        local: false,
+        version: "0.98.1".to_string(),
+        service_name: "safenode1".to_string(),
+        user: "safe".to_string(),
+        number: 1,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+        status: NodeStatus::Stopped,
+        pid: None,
+        listen_addr: None,
+        peer_id: None,
+        log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+        data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+        safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+        connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: None,
+        safenode_args: vec![],
+    };
+
+    update_bootstrap_peers(
+        &mut node,
+        new_peers,
+        &mock_service_control,
+        &mock_rpc_client,
+    )
+    .await?;
+
+    assert_matches!(node.status, NodeStatus::Stopped);
+    assert_eq!(node.pid, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn upgrade_should_reinstall_the_service_with_the_args_and_env_it_was_added_with() -> Result<()>
+{
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let target_safenode = tmp_data_dir.child("safenode-upgrade");
+    target_safenode.write_binary(b"fake safenode v0.98.2")?;
+    let node_data_dir = tmp_data_dir.child("safenode1");
+    node_data_dir.create_dir_all()?;
+    let node_safenode = node_data_dir.child("safenode");
+    node_safenode.write_binary(b"fake safenode v0.98.1")?;
+
+    let safenode_args = vec!["--max-records".to_string(), "4096".to_string()];
+    let env_variables = Some(vec![("SN_LOG".to_owned(), "all".to_owned())]);
+
+    let mut mock_service_control = MockServiceControl::new();
+    let mock_rpc_client = MockRpcClient::new();
+    let mut seq = Sequence::new();
+    mock_service_control
+        .expect_uninstall()
+        .with(eq("safenode1"))
+        .times(1)
+        .returning(|_| Ok(()))
+        .in_sequence(&mut seq);
+    let install_ctx = InstallNodeServiceCtxBuilder {
+        local: false,
+        genesis: false,
+        name: "safenode1".to_string(),
+        safenode_path: node_safenode.to_path_buf(),
+        node_port: None,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+        service_user: "safe".to_string(),
+        log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+        data_dir_path: node_data_dir.to_path_buf(),
+        bootstrap_peers: vec![],
+        env_variables: env_variables.clone(),
+        safenode_args: safenode_args.clone(),
+    }
+    .build()?;
+    mock_service_control
+        .expect_install()
+        .with(eq(install_ctx))
+        .times(1)
+        .returning(|_| Ok(()))
+        .in_sequence(&mut seq);
+    mock_service_control.expect_start().times(0);
+
+    let mut node = Node {
+        genesis: false,
+        local: false,
+        version: "0.98.1".to_string(),
+        service_name: "safenode1".to_string(),
+        user: "safe".to_string(),
+        number: 1,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+        status: NodeStatus::Stopped,
+        pid: None,
+        listen_addr: None,
+        peer_id: None,
+        log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+        data_dir_path: node_data_dir.to_path_buf(),
+        safenode_path: node_safenode.to_path_buf(),
+        connected_peers: None,
+        restart_count: 0,
+        last_started: None,
+        env_variables: env_variables.clone(),
+        safenode_args: safenode_args.clone(),
+    };
+
+    let result = upgrade(
+        UpgradeOptions {
+            bootstrap_peers: vec![],
+            // no new env is provided, so the one the node was added with is kept
+            env_variables: None,
+            force: false,
+            start_node: false,
+            target_safenode_path: target_safenode.to_path_buf(),
+            target_version: Version::parse("0.98.2")?,
+        },
+        &mut node,
+        &mock_service_control,
+        &mock_rpc_client,
+    )
+    .await?;
+
+    assert_matches!(result, UpgradeResult::Upgraded(_, _));
+    assert_eq!(node.version, "0.98.2");
+    assert_eq!(node.env_variables, env_variables);
+    assert_eq!(node.safenode_args, safenode_args);
+    node_safenode.assert("fake safenode v0.98.2");
+
+    Ok(())
+}
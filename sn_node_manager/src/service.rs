@@ -16,6 +16,17 @@ use service_manager::{
 use std::net::{SocketAddr, TcpListener};
 use sysinfo::{Pid, ProcessExt, System, SystemExt};
 
+/// The state of a service, as reported by the OS service manager.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServiceStatus {
+    /// The service is running, with the PID of its process if it could be determined.
+    Running { pid: Option<u32> },
+    /// The service is installed but not running.
+    Stopped,
+    /// There is no service with that name.
+    NotFound,
+}
+
 /// A thin wrapper around the `service_manager::ServiceManager`, which makes our own testing
 /// easier.
 ///
@@ -30,7 +41,9 @@ pub trait ServiceControl: Sync {
     fn install(&self, install_ctx: ServiceInstallCtx) -> Result<()>;
     fn get_process_pid(&self, name: &str) -> Result<u32>;
     fn is_service_process_running(&self, pid: u32) -> bool;
+    fn list_services(&self, prefix: &str) -> Result<Vec<String>>;
     fn start(&self, service_name: &str) -> Result<()>;
+    fn status(&self, service_name: &str) -> Result<ServiceStatus>;
     fn stop(&self, service_name: &str) -> Result<()>;
     fn uninstall(&self, service_name: &str) -> Result<()>;
     fn wait(&self, delay: u64);
@@ -157,6 +170,59 @@ impl ServiceControl for NodeServiceManager {
         Err(eyre!("Could not find process named {name}"))
     }
 
+    #[cfg(target_os = "linux")]
+    fn list_services(&self, prefix: &str) -> Result<Vec<String>> {
+        use color_eyre::eyre::eyre;
+        use std::process::Command;
+        use std::str;
+
+        let output = Command::new("systemctl")
+            .arg("list-unit-files")
+            .arg("--type=service")
+            .arg("--no-legend")
+            .arg("--no-pager")
+            .output()?;
+        if !output.status.success() {
+            return Err(eyre!("Failed to list the installed services"));
+        }
+        let output_str = str::from_utf8(&output.stdout)?;
+        Ok(parse_systemctl_services(output_str, prefix))
+    }
+
+    #[cfg(target_os = "macos")]
+    fn list_services(&self, prefix: &str) -> Result<Vec<String>> {
+        use color_eyre::eyre::eyre;
+        use std::process::Command;
+        use std::str;
+
+        let output = Command::new("launchctl").arg("list").output()?;
+        if !output.status.success() {
+            return Err(eyre!("Failed to list the installed services"));
+        }
+        let output_str = str::from_utf8(&output.stdout)?;
+        Ok(parse_launchctl_services(output_str, prefix))
+    }
+
+    #[cfg(target_os = "windows")]
+    fn list_services(&self, prefix: &str) -> Result<Vec<String>> {
+        use color_eyre::eyre::eyre;
+        use std::process::Command;
+        use std::str;
+
+        let output = Command::new("sc")
+            .arg("query")
+            .arg("type=")
+            .arg("service")
+            .arg("state=")
+            .arg("all")
+            .output()?;
+        if !output.status.success() {
+            return Err(eyre!("Failed to list the installed services"));
+        }
+        let output_str = str::from_utf8(&output.stdout)?;
+        Ok(parse_sc_services(output_str, prefix))
+    }
+
     fn install(&self, install_ctx: ServiceInstallCtx) -> Result<()> {
         let manager = <dyn ServiceManager>::native()?;
         manager.install(install_ctx)?;
@@ -170,6 +236,102 @@ impl ServiceControl for NodeServiceManager {
         Ok(())
     }
 
+    #[cfg(target_os = "linux")]
+    fn status(&self, service_name: &str) -> Result<ServiceStatus> {
+        use color_eyre::eyre::eyre;
+        use std::process::Command;
+        use std::str;
+
+        let output = Command::new("systemctl")
+            .arg("show")
+            .arg(format!("{service_name}.service"))
+            .arg("--property=LoadState,ActiveState,MainPID")
+            .output()?;
+        if !output.status.success() {
+            return Err(eyre!(
+                "Failed to get the status of the {service_name} service"
+            ));
+        }
+        let output_str = str::from_utf8(&output.stdout)?;
+        let property = |name: &str| {
+            output_str
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+                .unwrap_or_default()
+        };
+        if property("LoadState") == "not-found" {
+            return Ok(ServiceStatus::NotFound);
+        }
+        match property("ActiveState") {
+            "active" | "activating" | "reloading" => {
+                let pid = property("MainPID")
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|pid| *pid != 0);
+                Ok(ServiceStatus::Running { pid })
+            }
+            _ => Ok(ServiceStatus::Stopped),
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn status(&self, service_name: &str) -> Result<ServiceStatus> {
+        use std::process::Command;
+        use std::str;
+
+        // The service is not loaded if it can't be listed.
+        let output = Command::new("launchctl")
+            .arg("list")
+            .arg(service_name)
+            .output()?;
+        if !output.status.success() {
+            return Ok(ServiceStatus::NotFound);
+        }
+        // A `"PID" = <pid>;` line is only listed while the service is running.
+        let output_str = str::from_utf8(&output.stdout)?;
+        let pid = output_str.lines().find_map(|line| {
+            line.trim()
+                .strip_prefix("\"PID\" = ")?
+                .trim_end_matches(';')
+                .parse::<u32>()
+                .ok()
+        });
+        match pid {
+            Some(pid) => Ok(ServiceStatus::Running { pid: Some(pid) }),
+            None => Ok(ServiceStatus::Stopped),
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn status(&self, service_name: &str) -> Result<ServiceStatus> {
+        use std::process::Command;
+        use std::str;
+
+        // The query fails if the service does not exist.
+        let output = Command::new("sc")
+            .arg("queryex")
+            .arg(service_name)
+            .output()?;
+        if !output.status.success() {
+            return Ok(ServiceStatus::NotFound);
+        }
+        let output_str = str::from_utf8(&output.stdout)?;
+        let field = |name: &str| {
+            output_str.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == name).then(|| value.trim())
+            })
+        };
+        if field("STATE").is_some_and(|state| state.ends_with("RUNNING")) {
+            let pid = field("PID")
+                .and_then(|pid| pid.parse::<u32>().ok())
+                .filter(|pid| *pid != 0);
+            Ok(ServiceStatus::Running { pid })
+        } else {
+            Ok(ServiceStatus::Stopped)
+        }
+    }
+
     fn stop(&self, service_name: &str) -> Result<()> {
         let label: ServiceLabel = service_name.parse()?;
         let manager = <dyn ServiceManager>::native()?;
@@ -191,3 +353,111 @@ impl ServiceControl for NodeServiceManager {
         std::thread::sleep(std::time::Duration::from_millis(delay));
     }
 }
+
+/// Parses the names of the services starting with `prefix` from the output of
+/// `systemctl list-unit-files --type=service --no-legend`, where each line starts with the unit.
+#[cfg(any(target_os = "linux", test))]
+fn parse_systemctl_services(output: &str, prefix: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter_map(|unit| unit.strip_suffix(".service"))
+        .filter(|name| name.starts_with(prefix))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Parses the labels of the services starting with `prefix` from the output of `launchctl list`,
+/// where each line is the PID, the last exit status and the label of the service.
+#[cfg(any(target_os = "macos", test))]
+fn parse_launchctl_services(output: &str, prefix: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(2))
+        .filter(|label| label.starts_with(prefix))
+        .map(|label| label.to_string())
+        .collect()
+}
+
+/// Parses the names of the services starting with `prefix` from the output of `sc query`, where
+/// each service is a block of fields, one of which is `SERVICE_NAME: <name>`.
+#[cfg(any(target_os = "windows", test))]
+fn parse_sc_services(output: &str, prefix: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("SERVICE_NAME:"))
+        .map(|name| name.trim())
+        .filter(|name| name.starts_with(prefix))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn systemctl_services_with_the_prefix_should_be_listed() {
+        let output = "\
+safenode1.service                          enabled  enabled
+safenode2.service                          disabled enabled
+safenodemand.service                       enabled  enabled
+ssh.service                                enabled  enabled
+systemd-journald.service                   static   -
+safenode3.socket                           enabled  enabled
+";
+        assert_eq!(
+            parse_systemctl_services(output, "safenode"),
+            vec!["safenode1", "safenode2", "safenodemand"]
+        );
+        assert_eq!(
+            parse_systemctl_services(output, "safenode1"),
+            vec!["safenode1"]
+        );
+        assert!(parse_systemctl_services("", "safenode").is_empty());
+    }
+
+    #[test]
+    fn launchctl_services_with_the_prefix_should_be_listed() {
+        let output = "\
+PID\tStatus\tLabel
+-\t0\tcom.apple.SafariHistoryServiceAgent
+1234\t0\tsafenode1
+-\t78\tsafenode2
+-\t0\tcom.apple.progressd
+";
+        assert_eq!(
+            parse_launchctl_services(output, "safenode"),
+            vec!["safenode1", "safenode2"]
+        );
+        assert!(parse_launchctl_services(output, "safenodemand").is_empty());
+    }
+
+    #[test]
+    fn sc_services_with_the_prefix_should_be_listed() {
+        let output = "\
+SERVICE_NAME: safenode1
+DISPLAY_NAME: safenode1
+        TYPE               : 10  WIN32_OWN_PROCESS
+        STATE              : 4  RUNNING
+                                (STOPPABLE, NOT_PAUSABLE, ACCEPTS_SHUTDOWN)
+        WIN32_EXIT_CODE    : 0  (0x0)
+
+SERVICE_NAME: safenode2
+DISPLAY_NAME: safenode2
+        TYPE               : 10  WIN32_OWN_PROCESS
+        STATE              : 1  STOPPED
+        WIN32_EXIT_CODE    : 0  (0x0)
+
+SERVICE_NAME: Spooler
+DISPLAY_NAME: Print Spooler
+        TYPE               : 110  WIN32_OWN_PROCESS (interactive)
+        STATE              : 4  RUNNING
+";
+        assert_eq!(
+            parse_sc_services(output, "safenode"),
+            vec!["safenode1", "safenode2"]
+        );
+        assert!(parse_sc_services(output, "Print").is_empty());
+    }
+}
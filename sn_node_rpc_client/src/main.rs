@@ -268,11 +268,15 @@ pub async fn transfers_events(
             Ok(NodeEvent::TransferNotif {
                 key,
                 cashnote_redemptions,
+                publisher,
             }) => {
                 println!(
                     "New transfer notification received for {key:?}, containing {} CashNoteRedemption/s.",
                     cashnote_redemptions.len()
                 );
+                if let Some(publisher) = publisher {
+                    println!("The notification was signed by {publisher:?}");
+                }
 
                 match client
                     .verify_cash_notes_redemptions(main_pk, &cashnote_redemptions)
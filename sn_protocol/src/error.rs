@@ -52,6 +52,16 @@ pub enum Error {
         /// Key of the missing record
         key: Box<NetworkAddress>,
     },
+    /// Record not found with the queried peer.
+    #[error("Peer {holder:?} does not hold Record {key:?}")]
+    RecordNotFound {
+        /// Holder that being contacted
+        holder: Box<NetworkAddress>,
+        /// Key of the missing record
+        key: Box<NetworkAddress>,
+    },
+    #[error("There was an error getting the record addresses from the RecordStore")]
+    GetReplicationListFailed,
 
     // ---------- record errors
     // Could not Serialize/Deserialize RecordHeader from Record
@@ -63,4 +73,10 @@ pub enum Error {
     // The record already exists at this node
     #[error("The record already exists, so do not charge for it: {0:?}")]
     RecordExists(PrettyPrintRecordKey<'static>),
+
+    // ---------- transfer notification errors
+    #[error("Could not encode/decode the transfer notification: {0}")]
+    TransferNotifParsingFailed(String),
+    #[error("The transfer notification published by {0} has an invalid signature")]
+    TransferNotifInvalidSignature(String),
 }
@@ -13,14 +13,19 @@ mod node_id;
 mod query;
 mod register;
 mod response;
+mod transfer_notif;
 
 pub use self::{
     chunk_proof::{ChunkProof, Nonce},
-    cmd::{Cmd, Hash},
+    cmd::{Cmd, Hash, MAX_RECORD_LABEL_LEN},
     node_id::NodeId,
     query::Query,
     register::RegisterCmd,
     response::{CmdOk, CmdResponse, QueryResponse},
+    transfer_notif::{
+        decode_transfer_notif, encode_signed_transfer_notif, encode_transfer_notif,
+        transfer_notif_key, TransferNotif, ROYALTY_TRANSFER_NOTIF_TOPIC,
+    },
 };
 
 use super::NetworkAddress;
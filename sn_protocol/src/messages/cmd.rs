@@ -12,6 +12,9 @@ use serde::{Deserialize, Serialize};
 // TODO: remove this dependency and define these types herein.
 pub use sn_transfers::Hash;
 
+/// The max length, in bytes, of the label a client can attach to a record.
+pub const MAX_RECORD_LABEL_LEN: usize = 64;
+
 /// Data and CashNote cmds - recording spends or creating, updating, and removing data.
 ///
 /// See the [`protocol`] module documentation for more details of the types supported by the Safe
@@ -30,6 +33,14 @@ pub enum Cmd {
         /// Keys of copy that shall be replicated.
         keys: Vec<(NetworkAddress, RecordType)>,
     },
+    /// Best-effort request for the holders of a record to index it under a client supplied label,
+    /// so that it can later be listed by that label. The label is not part of the record itself.
+    LabelRecord {
+        /// Address of the labelled record.
+        address: NetworkAddress,
+        /// Label of at most [`MAX_RECORD_LABEL_LEN`] bytes.
+        label: String,
+    },
 }
 
 impl std::fmt::Debug for Cmd {
@@ -43,6 +54,11 @@ impl std::fmt::Debug for Cmd {
                     .field("first_ten_keys", &first_ten_keys)
                     .finish()
             }
+            Cmd::LabelRecord { address, label } => f
+                .debug_struct("Cmd::LabelRecord")
+                .field("address", address)
+                .field("label", label)
+                .finish(),
         }
     }
 }
@@ -52,6 +68,7 @@ impl Cmd {
     pub fn dst(&self) -> NetworkAddress {
         match self {
             Cmd::Replicate { holder, .. } => holder.clone(),
+            Cmd::LabelRecord { address, .. } => address.clone(),
         }
     }
 }
@@ -67,6 +84,9 @@ impl std::fmt::Display for Cmd {
                     keys.len()
                 )
             }
+            Cmd::LabelRecord { address, label } => {
+                write!(f, "Cmd::LabelRecord({address:?} labelled {label:?})")
+            }
         }
     }
 }
@@ -31,6 +31,14 @@ pub enum Query {
         /// Key of the record to be fetched
         key: NetworkAddress,
     },
+    /// Retrieve a specific record from a specific peer, for a client to check the copy held by
+    /// that peer. Unlike [`GetReplicatedRecord`](Query::GetReplicatedRecord), it is not treated
+    /// as replication by the peer.
+    ///
+    /// This should eventually lead to a [`GetRecordFromPeer`] response.
+    ///
+    /// [`GetRecordFromPeer`]: super::QueryResponse::GetRecordFromPeer
+    GetRecordFromPeer(NetworkAddress),
     /// Get the proof that the chunk with the given NetworkAddress exists with the requested node.
     GetChunkExistenceProof {
         /// The Address of the chunk that we are trying to verify.
@@ -40,6 +48,16 @@ pub enum Query {
     },
     /// Queries close_group peers whether the target peer is a bad_node
     CheckNodeInProblem(NetworkAddress),
+    /// Retrieve the addresses of all the records a specific peer holds, e.g. for the requester to
+    /// find the records it is responsible for but doesn't hold.
+    ///
+    /// This should eventually lead to a [`GetReplicationList`] response.
+    ///
+    /// [`GetReplicationList`]: super::QueryResponse::GetReplicationList
+    GetReplicationList {
+        /// Sender of the query
+        requester: NetworkAddress,
+    },
 }
 
 impl Query {
@@ -51,6 +69,9 @@ impl Query {
             // and the destination shall be decided by the requester already.
             Query::GetReplicatedRecord { key, .. } => key.clone(),
             Query::GetChunkExistenceProof { key, .. } => key.clone(),
+            Query::GetRecordFromPeer(key) => key.clone(),
+            // Also a `one-to-one` message, sent to peers close to the requester.
+            Query::GetReplicationList { requester } => requester.clone(),
         }
     }
 }
@@ -67,9 +88,15 @@ impl std::fmt::Display for Query {
             Query::GetChunkExistenceProof { key, nonce } => {
                 write!(f, "Query::GetChunkExistenceProof({key:?} {nonce:?})")
             }
+            Query::GetRecordFromPeer(key) => {
+                write!(f, "Query::GetRecordFromPeer({key:?})")
+            }
             Query::CheckNodeInProblem(address) => {
                 write!(f, "Query::CheckNodeInProblem({address:?})")
             }
+            Query::GetReplicationList { requester } => {
+                write!(f, "Query::GetReplicationList({requester:?})")
+            }
         }
     }
 }
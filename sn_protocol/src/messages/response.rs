@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{error::Result, NetworkAddress};
+use crate::{error::Result, storage::RecordType, NetworkAddress};
 
 use super::ChunkProof;
 use bytes::Bytes;
@@ -46,12 +46,24 @@ pub enum QueryResponse {
     ///
     /// [`GetReplicatedRecord`]: crate::messages::Query::GetReplicatedRecord
     GetReplicatedRecord(Result<(NetworkAddress, Bytes)>),
+    // ===== RecordFromPeer =====
+    //
+    /// Response to [`GetRecordFromPeer`]
+    ///
+    /// [`GetRecordFromPeer`]: crate::messages::Query::GetRecordFromPeer
+    GetRecordFromPeer(Result<(NetworkAddress, Bytes)>),
     // ===== ReplicatedRecord =====
     //
     /// Response to [`GetChunkExistenceProof`]
     ///
     /// [`GetChunkExistenceProof`]: crate::messages::Query::GetChunkExistenceProof
     GetChunkExistenceProof(Result<ChunkProof>),
+    // ===== ReplicationList =====
+    //
+    /// Response to [`GetReplicationList`], with the holder and the addresses of its records
+    ///
+    /// [`GetReplicationList`]: crate::messages::Query::GetReplicationList
+    GetReplicationList(Result<(NetworkAddress, Vec<(NetworkAddress, RecordType)>)>),
 }
 
 // Debug implementation for QueryResponse, to avoid printing Vec<u8>
@@ -91,9 +103,34 @@ impl Debug for QueryResponse {
                     write!(f, "GetReplicatedRecord(Err({err:?}))")
                 }
             },
+            QueryResponse::GetRecordFromPeer(result) => match result {
+                Ok((holder, data)) => {
+                    write!(
+                        f,
+                        "GetRecordFromPeer(Ok((holder: {:?}, datalen: {:?})))",
+                        holder,
+                        data.len()
+                    )
+                }
+                Err(err) => {
+                    write!(f, "GetRecordFromPeer(Err({err:?}))")
+                }
+            },
             QueryResponse::GetChunkExistenceProof(proof) => {
                 write!(f, "GetChunkExistenceProof(proof: {proof:?})")
             }
+            QueryResponse::GetReplicationList(result) => match result {
+                Ok((holder, keys)) => {
+                    write!(
+                        f,
+                        "GetReplicationList(Ok((holder: {holder:?}, keys_len: {:?})))",
+                        keys.len()
+                    )
+                }
+                Err(err) => {
+                    write!(f, "GetReplicationList(Err({err:?}))")
+                }
+            },
         }
     }
 }
@@ -106,6 +143,11 @@ pub enum CmdResponse {
     //
     /// Response to replication cmd
     Replicate(Result<()>),
+    //
+    // ===== Labels =====
+    //
+    /// Response to record labelling cmd
+    LabelRecord(Result<()>),
 }
 
 /// The Ok variant of a CmdResponse
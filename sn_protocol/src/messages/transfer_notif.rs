@@ -0,0 +1,169 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::error::{Error, Result};
+use bls::{PublicKey, PK_SIZE};
+use bytes::Bytes;
+use libp2p::{identity, PeerId};
+use serde::{Deserialize, Serialize};
+use sn_transfers::CashNoteRedemption;
+
+/// The gossipsub topic nodes publish their royalty transfer notifications on.
+pub const ROYALTY_TRANSFER_NOTIF_TOPIC: &str = "ROYALTY_TRANSFER_NOTIFICATION";
+
+/// Follows the public key of a signed transfer notification, in place of the serialised transfer
+/// info. It is a byte msgpack never uses, so it can't be mistaken for an unsigned notification.
+const SIGNED_TRANSFER_NOTIF_MARKER: u8 = 0xc1;
+
+/// A notification of a transfer to a key, as published over gossipsub.
+///
+/// An unsigned notification is the key followed by the serialised CashNoteRedemptions. A signed
+/// one is the key, `SIGNED_TRANSFER_NOTIF_MARKER`, then the serialised `SignedTransferNotif`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferNotif {
+    /// The key the transfer is to.
+    pub key: PublicKey,
+    /// The CashNoteRedemptions of the transfer.
+    pub cashnote_redemptions: Vec<CashNoteRedemption>,
+    /// The node that signed the notification, if it is signed.
+    pub publisher: Option<PeerId>,
+}
+
+/// The payload of a signed transfer notification.
+#[derive(Serialize, Deserialize)]
+struct SignedTransferNotif {
+    cashnote_redemptions: Vec<CashNoteRedemption>,
+    /// The protobuf encoded identity key of the node that published the notification.
+    publisher: Vec<u8>,
+    /// The publisher's signature of the unsigned notification.
+    signature: Vec<u8>,
+}
+
+/// Encodes an unsigned notification of the transfer to the key. These are also the bytes a
+/// publisher signs, to be passed to `encode_signed_transfer_notif`.
+pub fn encode_transfer_notif(
+    key: &PublicKey,
+    cashnote_redemptions: &[CashNoteRedemption],
+) -> Result<Bytes> {
+    let mut msg = key.to_bytes().to_vec();
+    msg.extend(
+        rmp_serde::to_vec(cashnote_redemptions)
+            .map_err(|err| Error::TransferNotifParsingFailed(err.to_string()))?,
+    );
+    Ok(Bytes::from(msg))
+}
+
+/// Encodes a notification of the transfer to the key, signed by the publisher over the bytes of
+/// the unsigned notification.
+pub fn encode_signed_transfer_notif(
+    key: &PublicKey,
+    cashnote_redemptions: Vec<CashNoteRedemption>,
+    publisher: &identity::PublicKey,
+    signature: Vec<u8>,
+) -> Result<Bytes> {
+    let signed = SignedTransferNotif {
+        cashnote_redemptions,
+        publisher: publisher.encode_protobuf(),
+        signature,
+    };
+    let mut msg = key.to_bytes().to_vec();
+    msg.push(SIGNED_TRANSFER_NOTIF_MARKER);
+    msg.extend(
+        rmp_serde::to_vec(&signed)
+            .map_err(|err| Error::TransferNotifParsingFailed(err.to_string()))?,
+    );
+    Ok(Bytes::from(msg))
+}
+
+/// Reads the key a notification is about, without decoding the rest of it.
+pub fn transfer_notif_key(msg: &[u8]) -> Result<PublicKey> {
+    let key_bytes: [u8; PK_SIZE] = msg
+        .get(0..PK_SIZE)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::TransferNotifParsingFailed("msg doesn't have enough bytes".into()))?;
+    PublicKey::from_bytes(key_bytes)
+        .map_err(|err| Error::TransferNotifParsingFailed(format!("invalid key: {err}")))
+}
+
+/// Decodes a signed or unsigned notification, verifying the signature of a signed one.
+pub fn decode_transfer_notif(msg: &[u8]) -> Result<TransferNotif> {
+    let key = transfer_notif_key(msg)?;
+    let parsing_failed = |err: rmp_serde::decode::Error| {
+        Error::TransferNotifParsingFailed(format!("invalid cashnote redemptions: {err}"))
+    };
+
+    match msg[PK_SIZE..].split_first() {
+        Some((&SIGNED_TRANSFER_NOTIF_MARKER, payload)) => {
+            let signed: SignedTransferNotif =
+                rmp_serde::from_slice(payload).map_err(parsing_failed)?;
+            let publisher_key = identity::PublicKey::try_decode_protobuf(&signed.publisher)
+                .map_err(|err| {
+                    Error::TransferNotifParsingFailed(format!("invalid publisher: {err}"))
+                })?;
+            let unsigned = encode_transfer_notif(&key, &signed.cashnote_redemptions)?;
+            let publisher = PeerId::from(publisher_key.clone());
+            if !publisher_key.verify(&unsigned, &signed.signature) {
+                return Err(Error::TransferNotifInvalidSignature(publisher.to_string()));
+            }
+            Ok(TransferNotif {
+                key,
+                cashnote_redemptions: signed.cashnote_redemptions,
+                publisher: Some(publisher),
+            })
+        }
+        _ => Ok(TransferNotif {
+            key,
+            cashnote_redemptions: rmp_serde::from_slice(&msg[PK_SIZE..]).map_err(parsing_failed)?,
+            publisher: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls::SecretKey;
+
+    #[test]
+    fn unsigned_transfer_notif_round_trip() -> Result<()> {
+        let key = SecretKey::random().public_key();
+        let msg = encode_transfer_notif(&key, &[])?;
+
+        let notif = decode_transfer_notif(&msg)?;
+        assert_eq!(notif.key, key);
+        assert!(notif.cashnote_redemptions.is_empty());
+        assert_eq!(notif.publisher, None);
+
+        assert!(decode_transfer_notif(&msg[..PK_SIZE - 1]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn signed_transfer_notif_round_trip() -> Result<()> {
+        let key = SecretKey::random().public_key();
+        let keypair = identity::Keypair::generate_ed25519();
+        let unsigned = encode_transfer_notif(&key, &[])?;
+        let signature = keypair
+            .sign(&unsigned)
+            .map_err(|err| Error::TransferNotifParsingFailed(err.to_string()))?;
+
+        let msg = encode_signed_transfer_notif(&key, vec![], &keypair.public(), signature.clone())?;
+        let notif = decode_transfer_notif(&msg)?;
+        assert_eq!(notif.key, key);
+        assert_eq!(notif.publisher, Some(keypair.public().to_peer_id()));
+
+        // the signature doesn't verify against the key of another node
+        let impostor = identity::Keypair::generate_ed25519();
+        let msg = encode_signed_transfer_notif(&key, vec![], &impostor.public(), signature)?;
+        assert!(matches!(
+            decode_transfer_notif(&msg),
+            Err(Error::TransferNotifInvalidSignature(_))
+        ));
+        Ok(())
+    }
+}
@@ -15,6 +15,7 @@ use std::{
     net::SocketAddr,
     path::{Path, PathBuf},
     str::FromStr,
+    time::SystemTime,
 };
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -123,6 +124,19 @@ pub struct Node {
         deserialize_with = "deserialize_connected_peers"
     )]
     pub connected_peers: Option<Vec<PeerId>>,
+    /// The number of times the node has been started again after its first start.
+    #[serde(default)]
+    pub restart_count: u32,
+    /// The last time the node was started.
+    #[serde(default)]
+    pub last_started: Option<SystemTime>,
+    /// The environment variables the node's service was installed with.
+    #[serde(default)]
+    pub env_variables: Option<Vec<(String, String)>>,
+    /// The additional arguments the node's service was installed with, passed through to
+    /// safenode.
+    #[serde(default)]
+    pub safenode_args: Vec<String>,
 }
 
 impl Node {
@@ -144,6 +158,9 @@ impl Node {
 pub struct Daemon {
     pub daemon_path: PathBuf,
     pub endpoint: Option<SocketAddr>,
+    /// The Unix domain socket the daemon listens on, when it's not listening on a TCP endpoint.
+    #[serde(default)]
+    pub socket_path: Option<PathBuf>,
     pub pid: Option<u32>,
     pub service_name: String,
     pub status: NodeStatus,
@@ -172,6 +189,13 @@ impl NodeRegistry {
         Ok(())
     }
 
+    /// Replace the bootstrap peers used when (re)installing the node services.
+    ///
+    /// The services already installed are not affected until they are reinstalled.
+    pub fn set_bootstrap_peers(&mut self, peers: Vec<Multiaddr>) {
+        self.bootstrap_peers = peers;
+    }
+
     pub fn load(path: &Path) -> Result<Self> {
         if !path.exists() {
             return Ok(NodeRegistry {
@@ -22,7 +22,7 @@ pub use address::SpendAddress;
 pub use builder::UnsignedTransfer;
 pub use cashnote::CashNote;
 pub use nano::NanoTokens;
-pub use reason_hash::Hash;
+pub use reason_hash::{Hash, MAX_MEMO_LEN};
 pub use signed_spend::{SignedSpend, Spend};
 pub use transaction::Transaction;
 pub use unique_keys::{DerivationIndex, DerivedSecretKey, MainPubkey, MainSecretKey, UniquePubkey};
@@ -11,6 +11,9 @@ use std::{fmt, str::FromStr};
 
 use crate::Error;
 
+/// The maximum length in bytes of a memo that can be carried as a Spend Reason.
+pub const MAX_MEMO_LEN: usize = 32;
+
 /// sha3 256 hash used for Spend Reasons, Transaction hashes, anything hash related in this crate
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
 pub struct Hash([u8; 32]);
@@ -27,6 +30,28 @@ impl Hash {
         &self.0
     }
 
+    /// Embeds an opaque memo (eg. an invoice reference) to be used as a Spend Reason.
+    /// The memo is zero padded to 32 bytes, hence any trailing zero bytes are not preserved.
+    pub fn from_memo(memo: &[u8]) -> Result<Self, Error> {
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(Error::MemoTooLong(memo.len()));
+        }
+        let mut h = Self::default();
+        h.0[..memo.len()].copy_from_slice(memo);
+        Ok(h)
+    }
+
+    /// The memo embedded in this Spend Reason, with the zero padding stripped.
+    /// A default (zeroed) reason gives an empty memo.
+    pub fn memo(&self) -> Vec<u8> {
+        let len = self
+            .0
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |pos| pos + 1);
+        self.0[..len].to_vec()
+    }
+
     /// Deserializes a `Hash` represented as a hex string to a `Hash`.
     pub fn from_hex(hex: &str) -> Result<Self, Error> {
         let mut h = Self::default();
@@ -95,6 +120,20 @@ mod tests {
         assert_eq!(hash.slice(), expected);
     }
 
+    #[test]
+    fn memo() {
+        let memo = b"invoice-2024-0042";
+        let reason = Hash::from_memo(memo).expect("memo to fit");
+        assert_eq!(reason.memo(), memo.to_vec());
+
+        assert!(Hash::default().memo().is_empty());
+        assert!(Hash::from_memo(&[1; MAX_MEMO_LEN]).is_ok());
+        assert_eq!(
+            Hash::from_memo(&[1; MAX_MEMO_LEN + 1]),
+            Err(Error::MemoTooLong(MAX_MEMO_LEN + 1))
+        );
+    }
+
     #[test]
     fn hex_encoding() {
         let data = b"hello world";
@@ -65,6 +65,9 @@ pub enum Error {
     SpendsDoNotMatchInputs,
     #[error("Overflow occurred while adding values")]
     NumericOverflow,
+    /// The memo exceeds the size that can be carried in a Spend Reason.
+    #[error("Memo of {0} bytes exceeds the max of {max} bytes", max = crate::MAX_MEMO_LEN)]
+    MemoTooLong(usize),
 
     /// Not enough balance to perform a transaction
     #[error("Not enough balance, {0} available, {1} required")]
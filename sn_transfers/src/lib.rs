@@ -20,10 +20,10 @@ pub(crate) use cashnotes::{Input, TransactionBuilder};
 /// Types used in the public API
 pub use cashnotes::{
     CashNote, DerivationIndex, DerivedSecretKey, Hash, MainPubkey, MainSecretKey, NanoTokens,
-    SignedSpend, Spend, SpendAddress, Transaction, UniquePubkey, UnsignedTransfer,
+    SignedSpend, Spend, SpendAddress, Transaction, UniquePubkey, UnsignedTransfer, MAX_MEMO_LEN,
 };
 pub use error::{Error, Result};
-pub use transfers::{CashNoteRedemption, OfflineTransfer, Transfer};
+pub use transfers::{CashNoteRedemption, OfflineTransfer, Transfer, TransferInfo};
 
 /// Utilities exposed
 pub use genesis::{
@@ -33,7 +33,8 @@ pub use genesis::{
 };
 pub use wallet::bls_secret_from_hex;
 pub use wallet::{
-    Error as WalletError, HotWallet, Payment, PaymentQuote, Result as WalletResult, WatchOnlyWallet,
+    verify_wallet_dir, ActivitySummary, Error as WalletError, HotWallet, Payment, PaymentQuote,
+    Result as WalletResult, WalletIntegrityReport, WatchOnlyWallet, QUOTE_EXPIRATION_SECS,
 };
 
 // re-export crates used in our public API
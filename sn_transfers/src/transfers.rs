@@ -31,4 +31,4 @@ mod offline_transfer;
 mod transfer;
 
 pub use offline_transfer::{create_unsigned_transfer, CashNotesAndSecretKey, OfflineTransfer};
-pub use transfer::{CashNoteRedemption, Transfer};
+pub use transfer::{CashNoteRedemption, Transfer, TransferInfo};
@@ -6,7 +6,10 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{CashNote, Ciphertext, DerivationIndex, MainPubkey, MainSecretKey, SpendAddress};
+use crate::{
+    CashNote, Ciphertext, DerivationIndex, MainPubkey, MainSecretKey, SpendAddress, UniquePubkey,
+    NETWORK_ROYALTIES_PK,
+};
 
 use rayon::iter::ParallelIterator;
 use rayon::prelude::IntoParallelRefIterator;
@@ -28,6 +31,24 @@ pub enum Transfer {
     NetworkRoyalties(Vec<CashNoteRedemption>),
 }
 
+/// What can be learnt about a `Transfer` from its own contents, without any key nor network access.
+///
+/// The value of a transfer is not part of it, it's held by the parent spends on the network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferInfo {
+    /// Whether the CashNoteRedemptions are encrypted, in which case only the recipient can learn
+    /// anything more than their number.
+    pub encrypted: bool,
+    /// The number of CashNoteRedemptions in the transfer.
+    pub redemptions_count: usize,
+    /// The addresses of the parent spends of the CashNoteRedemptions, if not encrypted.
+    pub parent_spends: Vec<SpendAddress>,
+    /// The main key of the recipient, if not encrypted.
+    pub recipient: Option<MainPubkey>,
+    /// The unique keys of the CashNotes to be redeemed, if not encrypted.
+    pub unique_pubkeys: Vec<UniquePubkey>,
+}
+
 impl std::fmt::Debug for Transfer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -104,6 +125,30 @@ impl Transfer {
         }
     }
 
+    /// Inspects the transfer offline, e.g. to check who it is for before attempting to redeem it.
+    pub fn inspect(&self) -> TransferInfo {
+        match self {
+            Self::Encrypted(cyphers) => TransferInfo {
+                encrypted: true,
+                redemptions_count: cyphers.len(),
+                parent_spends: vec![],
+                recipient: None,
+                unique_pubkeys: vec![],
+            },
+            // the royalties are always paid to the network royalties key
+            Self::NetworkRoyalties(cnrs) => TransferInfo {
+                encrypted: false,
+                redemptions_count: cnrs.len(),
+                parent_spends: cnrs.iter().map(|cnr| cnr.parent_spend).collect(),
+                recipient: Some(*NETWORK_ROYALTIES_PK),
+                unique_pubkeys: cnrs
+                    .iter()
+                    .map(|cnr| NETWORK_ROYALTIES_PK.new_unique_pubkey(&cnr.derivation_index))
+                    .collect(),
+            },
+        }
+    }
+
     /// Deserializes a `Transfer` represented as a hex string to a `Transfer`.
     pub fn from_hex(hex: &str) -> Result<Self> {
         let mut bytes = hex::decode(hex).map_err(|_| Error::TransferDeserializationFailed)?;
@@ -226,4 +271,32 @@ mod tests {
 
         assert_eq!(cashnote_redemptions, vec![cashnote_redemption]);
     }
+
+    #[test]
+    fn test_transfer_inspection() {
+        let rng = &mut bls::rand::thread_rng();
+        let cashnote_redemption = CashNoteRedemption::new(
+            DerivationIndex([42; 32]),
+            SpendAddress::new(XorName::random(rng)),
+        );
+        let pk = MainSecretKey::random().main_pubkey();
+
+        let encrypted = Transfer::create(vec![cashnote_redemption.clone()], pk).unwrap();
+        let info = encrypted.inspect();
+        assert!(info.encrypted);
+        assert_eq!(info.redemptions_count, 1);
+        assert!(info.parent_spends.is_empty());
+        assert_eq!(info.recipient, None);
+
+        let royalties = Transfer::NetworkRoyalties(vec![cashnote_redemption.clone()]);
+        let info = royalties.inspect();
+        assert!(!info.encrypted);
+        assert_eq!(info.redemptions_count, 1);
+        assert_eq!(info.parent_spends, vec![cashnote_redemption.parent_spend]);
+        assert_eq!(info.recipient, Some(*NETWORK_ROYALTIES_PK));
+        assert_eq!(
+            info.unique_pubkeys,
+            vec![NETWORK_ROYALTIES_PK.new_unique_pubkey(&cashnote_redemption.derivation_index)]
+        );
+    }
 }
@@ -55,28 +55,45 @@
 mod data_payments;
 mod error;
 mod hot_wallet;
+mod integrity;
 mod keys;
 mod wallet_file;
 mod watch_only;
 
-use crate::{NanoTokens, UniquePubkey};
+use crate::{DerivationIndex, MainPubkey, NanoTokens, UniquePubkey};
 use wallet_file::wallet_file_name;
 
 pub use self::{
-    data_payments::{Payment, PaymentQuote},
+    data_payments::{Payment, PaymentQuote, QUOTE_EXPIRATION_SECS},
     error::{Error, Result},
     hot_wallet::HotWallet,
+    integrity::{verify_wallet_dir, WalletIntegrityReport},
     keys::bls_secret_from_hex,
     watch_only::WatchOnlyWallet,
 };
 pub(crate) use keys::store_new_keypair;
 
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fs, path::Path};
+use std::{collections::BTreeMap, fs, path::Path, time::SystemTime};
+
+/// A summary of the activity of a wallet, as recorded in its wallet dir.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ActivitySummary {
+    /// Number of transactions in which the wallet spent some of its cash notes.
+    pub total_sends: usize,
+    /// Number of transactions in which the wallet received cash notes from others.
+    pub total_receives: usize,
+    /// When the wallet last received or spent cash notes, if ever.
+    pub last_activity: Option<SystemTime>,
+}
 
 #[derive(Default, Serialize, Deserialize)]
 pub(super) struct KeyLessWallet {
     available_cash_notes: BTreeMap<UniquePubkey, NanoTokens>,
+    /// The addresses handed out to receive payments, besides the main one, with the index
+    /// their key is derived from the main key with.
+    #[serde(default)]
+    receive_addresses: BTreeMap<MainPubkey, DerivationIndex>,
 }
 
 impl KeyLessWallet {
@@ -51,6 +51,9 @@ impl PaymentDetails {
 /// A generic type for signatures
 pub type QuoteSignature = Vec<u8>;
 
+/// The time in seconds that a quote is valid for, past which nodes reject the payments made with it.
+pub const QUOTE_EXPIRATION_SECS: u64 = 3600;
+
 /// A payment quote to store data given by a node to a client
 /// Note that the PaymentQuote is a contract between the node and itself to make sure the clients aren’t mispaying.
 /// It is NOT a contract between the client and the node.
@@ -67,6 +67,12 @@ pub enum Error {
     /// No cached payment found for address
     #[error("No ongoing payment found for address {0:?}")]
     NoPaymentForAddress(XorName),
+    /// A transfer output of zero tokens was requested
+    #[error("Transfer amount must be more than zero")]
+    ZeroAmount,
+    /// Another send is in progress on the wallet
+    #[error("Another send is already in progress on this wallet, try again once it has completed")]
+    SendInProgress,
 
     /// Transfer error
     #[error("Transfer error: {0}")]
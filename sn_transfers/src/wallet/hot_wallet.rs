@@ -9,12 +9,12 @@ use super::{
     data_payments::{PaymentDetails, PaymentQuote},
     keys::{get_main_key, store_new_keypair},
     wallet_file::{
-        get_unconfirmed_spend_requests, load_created_cash_note, remove_cash_notes,
-        remove_unconfirmed_spend_requests, store_created_cash_notes,
-        store_unconfirmed_spend_requests,
+        append_transfer_history, get_unconfirmed_spend_requests, load_created_cash_note,
+        load_transfer_history, remove_cash_notes, remove_unconfirmed_spend_requests,
+        store_created_cash_notes, store_unconfirmed_spend_requests, TransferDirection,
     },
     watch_only::WatchOnlyWallet,
-    Error, Result,
+    ActivitySummary, Error, Result,
 };
 
 use crate::{
@@ -39,6 +39,10 @@ const WALLET_DIR_NAME: &str = "wallet";
 /// A locked file handle, that when dropped releases the lock.
 pub type WalletExclusiveAccess = File;
 
+/// A locked file handle marking a send as in progress on the wallet, that when dropped
+/// allows the next send to go ahead.
+pub type WalletSendGuard = File;
+
 /// A hot-wallet.
 pub struct HotWallet {
     /// The secret key with which we can access
@@ -52,12 +56,6 @@ pub struct HotWallet {
 }
 
 impl HotWallet {
-    /// Stores the wallet to disk.
-    /// This requires having exclusive access to the wallet to prevent concurrent processes from writing to it
-    fn store(&self, exclusive_access: WalletExclusiveAccess) -> Result<()> {
-        self.watchonly_wallet.store(exclusive_access)
-    }
-
     /// reloads the wallet from disk.
     fn reload(&mut self) -> Result<()> {
         // placeholder random MainSecretKey to take it out
@@ -76,6 +74,14 @@ impl HotWallet {
         self.watchonly_wallet.lock()
     }
 
+    /// Marks a send as in progress on the wallet, for as long as the returned guard is held.
+    /// Sending stages spends in `unconfirmed_spend_requests` and clears them once confirmed, so
+    /// two sends overlapping on the same wallet dir could clear each other's pending spends.
+    /// Returns `Error::SendInProgress` if another send, from this or any other process, holds it.
+    pub fn try_lock_sends(&self) -> Result<WalletSendGuard> {
+        self.watchonly_wallet.try_lock_sends()
+    }
+
     /// Stores the given cash_notes to the `created cash_notes dir` in the wallet dir.
     /// These can then be sent to the recipients out of band, over any channel preferred.
     pub fn store_cash_notes_to_disk<'a, T>(&self, cash_notes: T) -> Result<()>
@@ -247,7 +253,7 @@ impl HotWallet {
         spends
             .into_iter()
             .map(|(spend, dindex)| {
-                let derived_sk = self.key.derive_key(&dindex);
+                let derived_sk = self.derive_spend_key(&spend.unique_pubkey, &dindex);
                 let derived_key_sig = derived_sk.sign(&spend.to_bytes());
                 SignedSpend {
                     spend,
@@ -257,6 +263,52 @@ impl HotWallet {
             .collect()
     }
 
+    /// Returns the CashNotes this wallet currently believes it holds, as loaded from disk.
+    /// Unlike `available_cash_notes` this does not lock the wallet.
+    pub fn held_cash_notes(&self) -> Vec<CashNote> {
+        let wallet_dir = self.watchonly_wallet.wallet_dir();
+        self.watchonly_wallet
+            .available_cash_notes()
+            .keys()
+            .filter_map(|id| {
+                let cash_note = load_created_cash_note(id, wallet_dir);
+                if cash_note.is_none() {
+                    warn!("Skipping CashNote {id:?} because we don't have it");
+                }
+                cash_note
+            })
+            .collect()
+    }
+
+    /// Summarises the activity of the wallet from the transfer history stored in its dir.
+    ///
+    /// Every transaction spending our cash_notes is recorded as a send when it is made, and every
+    /// transaction we received cash_notes from as a receive when they are deposited. The change
+    /// of our own sends is not counted as a receive.
+    pub fn activity_summary(&self) -> Result<ActivitySummary> {
+        let history = load_transfer_history(self.watchonly_wallet.wallet_dir())?;
+
+        // a transaction can be recorded more than once, e.g. when its cash_notes are redeposited
+        let mut transactions = HashSet::new();
+        let mut last_activity = None;
+        for (direction, tx_hash, recorded_at) in history {
+            let _ = transactions.insert((direction, tx_hash));
+            last_activity = last_activity.max(Some(recorded_at));
+        }
+        let count = |direction| {
+            transactions
+                .iter()
+                .filter(|(recorded, _)| *recorded == direction)
+                .count()
+        };
+
+        Ok(ActivitySummary {
+            total_sends: count(TransferDirection::Send),
+            total_receives: count(TransferDirection::Receive),
+            last_activity,
+        })
+    }
+
     /// Returns all available cash_notes and an exclusive access to the wallet so no concurrent processes can
     /// get available cash_notes while we're modifying the wallet
     /// once the updated wallet is stored to disk it is safe to drop the WalletExclusiveAccess
@@ -275,7 +327,8 @@ impl HotWallet {
         for (id, _token) in self.watchonly_wallet.available_cash_notes().iter() {
             let held_cash_note = load_created_cash_note(id, &wallet_dir);
             if let Some(cash_note) = held_cash_note {
-                if let Ok(derived_key) = cash_note.derived_key(&self.key) {
+                if let Some(key) = self.receive_key_for(cash_note.main_pubkey()) {
+                    let derived_key = key.derive_key(&cash_note.derivation_index());
                     available_cash_notes.push((cash_note.clone(), Some(derived_key)));
                 } else {
                     warn!(
@@ -316,12 +369,17 @@ impl HotWallet {
             .build_unsigned_transaction(to, reason_hash)
     }
 
-    /// Make a transfer and return all created cash_notes
+    /// Make a transfer and return all created cash_notes.
+    /// Errors with `Error::ZeroAmount` if any of the outputs is of zero tokens.
     pub fn local_send(
         &mut self,
         to: Vec<(NanoTokens, MainPubkey)>,
         reason_hash: Option<Hash>,
     ) -> Result<Vec<CashNote>> {
+        if to.iter().any(|(amount, _)| amount.is_zero()) {
+            return Err(Error::ZeroAmount);
+        }
+
         let mut rng = &mut rand::rngs::OsRng;
         // create a unique key for each output
         let to_unique_keys: Vec<_> = to
@@ -383,6 +441,38 @@ impl HotWallet {
     pub fn local_send_storage_payment(
         &mut self,
         price_map: &BTreeMap<XorName, (MainPubkey, PaymentQuote, Vec<u8>)>,
+    ) -> Result<(NanoTokens, NanoTokens)> {
+        self.local_send_storage_payment_from(price_map, None, false)
+    }
+
+    /// Runs the same checks and builds the same transfer as `local_send_storage_payment`,
+    /// but leaves the wallet untouched, i.e. nothing is spent nor cached.
+    /// Returns the amount that would be paid for storage, including the network royalties fee.
+    pub fn local_send_storage_payment_dry_run(
+        &mut self,
+        price_map: &BTreeMap<XorName, (MainPubkey, PaymentQuote, Vec<u8>)>,
+    ) -> Result<(NanoTokens, NanoTokens)> {
+        self.local_send_storage_payment_from(price_map, None, true)
+    }
+
+    /// Performs a payment for each content address, funded only by the provided input cash_notes.
+    /// Includes payment of network royalties.
+    /// Errors before any transfer is created if an input is not spendable by this wallet,
+    /// or if the inputs don't cover the total cost.
+    /// Returns the amount paid for storage, including the network royalties fee paid.
+    pub fn local_send_storage_payment_with_inputs(
+        &mut self,
+        price_map: &BTreeMap<XorName, (MainPubkey, PaymentQuote, Vec<u8>)>,
+        inputs: &[CashNote],
+    ) -> Result<(NanoTokens, NanoTokens)> {
+        self.local_send_storage_payment_from(price_map, Some(inputs), false)
+    }
+
+    fn local_send_storage_payment_from(
+        &mut self,
+        price_map: &BTreeMap<XorName, (MainPubkey, PaymentQuote, Vec<u8>)>,
+        inputs: Option<&[CashNote]>,
+        dry_run: bool,
     ) -> Result<(NanoTokens, NanoTokens)> {
         let mut rng = &mut rand::thread_rng();
         let mut storage_cost = NanoTokens::zero();
@@ -428,7 +518,16 @@ impl HotWallet {
         );
 
         let start = Instant::now();
-        let (available_cash_notes, exclusive_access) = self.available_cash_notes()?;
+        let (mut available_cash_notes, exclusive_access) = self.available_cash_notes()?;
+        if let Some(inputs) = inputs {
+            available_cash_notes = Self::select_provided_inputs(
+                available_cash_notes,
+                inputs,
+                storage_cost
+                    .checked_add(royalties_fees)
+                    .ok_or(WalletError::TotalPriceTooHigh)?,
+            )?;
+        }
         trace!(
             "local_send_storage_payment fetched {} cashnotes in {:?}",
             available_cash_notes.len(),
@@ -449,6 +548,11 @@ impl HotWallet {
             start.elapsed()
         );
 
+        if dry_run {
+            trace!("local_send_storage_payment dry run, leaving the wallet untouched");
+            return Ok((storage_cost, royalties_fees));
+        }
+
         let start = Instant::now();
         // cache transfer payments in the wallet
         let mut cashnotes_to_use: HashSet<CashNote> = offline_transfer
@@ -525,6 +629,51 @@ impl HotWallet {
         Ok((storage_cost, royalties_fees))
     }
 
+    /// Restricts the available cash_notes to the provided inputs.
+    /// Errors if any of the inputs is not available in the wallet,
+    /// or if their total value doesn't cover the `total_cost`.
+    fn select_provided_inputs(
+        available_cash_notes: CashNotesAndSecretKey,
+        inputs: &[CashNote],
+        total_cost: NanoTokens,
+    ) -> Result<CashNotesAndSecretKey> {
+        let requested: BTreeSet<UniquePubkey> = inputs
+            .iter()
+            .map(|cash_note| cash_note.unique_pubkey())
+            .collect();
+
+        let selected: CashNotesAndSecretKey = available_cash_notes
+            .into_iter()
+            .filter(|(cash_note, _)| requested.contains(&cash_note.unique_pubkey()))
+            .collect();
+
+        if selected.len() != requested.len() {
+            let available: BTreeSet<_> = selected
+                .iter()
+                .map(|(cash_note, _)| cash_note.unique_pubkey())
+                .collect();
+            let missing: Vec<_> = requested.difference(&available).collect();
+            return Err(Error::CouldNotSendMoney(format!(
+                "Provided input cash_notes are not available in the wallet: {missing:?}"
+            )));
+        }
+
+        let mut total_input = NanoTokens::zero();
+        for (cash_note, _) in selected.iter() {
+            total_input = total_input
+                .checked_add(cash_note.value()?)
+                .ok_or(WalletError::TotalPriceTooHigh)?;
+        }
+        if total_input < total_cost {
+            return Err(Error::Transfer(crate::Error::NotEnoughBalance(
+                total_input,
+                total_cost,
+            )));
+        }
+
+        Ok(selected)
+    }
+
     fn update_local_wallet(
         &mut self,
         transfer: OfflineTransfer,
@@ -541,39 +690,39 @@ impl HotWallet {
         self.watchonly_wallet
             .mark_notes_as_spent(spent_unique_pubkeys.clone());
 
-        if let Some(cash_note) = transfer.change_cash_note {
+        if let Some(cash_note) = &transfer.change_cash_note {
             let start = Instant::now();
             self.watchonly_wallet.deposit(&[cash_note.clone()])?;
             trace!(
                 "update_local_wallet completed deposit change cash_note in {:?}",
                 start.elapsed()
             );
-            let start = Instant::now();
-
-            // Only the change_cash_note, i.e. the pay-in one, needs to be stored to disk.
-            //
-            // Paying out cash_note doesn't need to be stored into disk.
-            // As it is the transfer, that generated from it, to be sent out to network,
-            // and be stored within the unconfirmed_spends, and to be re-sent in case of failure.
-            self.store_cash_notes_to_disk(&[cash_note])?;
-            trace!(
-                "update_local_wallet completed store change cash_note to disk in {:?}",
-                start.elapsed()
-            );
         }
 
         for request in transfer.all_spend_requests {
             self.unconfirmed_spend_requests.insert(request);
         }
 
-        // store wallet to disk
+        // Store the wallet to disk along with the change_cash_note, so a crash can't leave
+        // the inputs marked as spent without the change having been stored.
+        //
+        // Only the change_cash_note, i.e. the pay-in one, needs to be stored to disk.
+        // Paying out cash_note doesn't need to be stored into disk.
+        // As it is the transfer, that generated from it, to be sent out to network,
+        // and be stored within the unconfirmed_spends, and to be re-sent in case of failure.
         let start = Instant::now();
-        self.store(exclusive_access)?;
+        self.watchonly_wallet
+            .store_with_cash_notes(&transfer.change_cash_note, exclusive_access)?;
         trace!(
-            "update_local_wallet completed store self wallet to disk in {:?}",
+            "update_local_wallet completed store self wallet and change cash_note to disk in {:?}",
             start.elapsed()
         );
-        Ok(())
+
+        append_transfer_history(
+            self.watchonly_wallet.wallet_dir(),
+            TransferDirection::Send,
+            [transfer.tx.hash()],
+        )
     }
 
     /// Deposit the given cash_notes on the wallet (without storing them to disk).
@@ -590,15 +739,85 @@ impl HotWallet {
     }
 
     pub fn unwrap_transfer(&self, transfer: &Transfer) -> Result<Vec<CashNoteRedemption>> {
-        transfer
-            .cashnote_redemptions(&self.key)
-            .map_err(|_| Error::FailedToDecypherTransfer)
+        self.unwrap_transfer_with_address(transfer)
+            .map(|(_address, cashnote_redemptions)| cashnote_redemptions)
+    }
+
+    /// Unwraps a transfer sent to any of our addresses, i.e. the main one or any of the
+    /// receive addresses, returning the address it was sent to along with its redemptions.
+    pub fn unwrap_transfer_with_address(
+        &self,
+        transfer: &Transfer,
+    ) -> Result<(MainPubkey, Vec<CashNoteRedemption>)> {
+        if let Ok(cashnote_redemptions) = transfer.cashnote_redemptions(&self.key) {
+            return Ok((self.address(), cashnote_redemptions));
+        }
+        for (address, derivation_index) in self.watchonly_wallet.receive_addresses() {
+            let key = self.receive_key(derivation_index);
+            if let Ok(cashnote_redemptions) = transfer.cashnote_redemptions(&key) {
+                return Ok((*address, cashnote_redemptions));
+            }
+        }
+        Err(Error::FailedToDecypherTransfer)
     }
 
     pub fn derive_key(&self, derivation_index: &DerivationIndex) -> DerivedSecretKey {
         self.key.derive_key(derivation_index)
     }
 
+    /// Derives a fresh address to receive a payment to, e.g. one per invoice, so that the
+    /// payments can't be linked to each other nor to the main address by their recipient.
+    /// The address is recorded in the wallet, so that the cash_notes sent to it are deposited
+    /// and can be spent like any other.
+    pub fn new_receive_address(&mut self) -> Result<MainPubkey> {
+        let derivation_index = DerivationIndex::random(&mut rand::thread_rng());
+        let address = self.receive_key(&derivation_index).main_pubkey();
+        self.watchonly_wallet
+            .add_receive_address(address, derivation_index)?;
+        debug!("Added a new receive address {address:?} to the wallet");
+        Ok(address)
+    }
+
+    /// The addresses handed out to receive payments, besides the main one.
+    pub fn receive_addresses(&self) -> Vec<MainPubkey> {
+        self.watchonly_wallet
+            .receive_addresses()
+            .keys()
+            .copied()
+            .collect()
+    }
+
+    // The key of the receive address derived with the index.
+    fn receive_key(&self, derivation_index: &DerivationIndex) -> MainSecretKey {
+        MainSecretKey::new(self.key.secret_key().derive_child(&derivation_index.0))
+    }
+
+    // The key of the address, if it is one of ours.
+    fn receive_key_for(&self, address: &MainPubkey) -> Option<MainSecretKey> {
+        if address == &self.address() {
+            return Some(MainSecretKey::new(self.key.secret_key().clone()));
+        }
+        self.watchonly_wallet
+            .receive_addresses()
+            .get(address)
+            .map(|derivation_index| self.receive_key(derivation_index))
+    }
+
+    // The key to sign the spend of the cash_note with the unique pubkey with, falling back
+    // to the main key if the cash_note wasn't sent to any of our receive addresses.
+    fn derive_spend_key(
+        &self,
+        unique_pubkey: &UniquePubkey,
+        derivation_index: &DerivationIndex,
+    ) -> DerivedSecretKey {
+        self.watchonly_wallet
+            .receive_addresses()
+            .values()
+            .map(|receive_index| self.receive_key(receive_index).derive_key(derivation_index))
+            .find(|derived_sk| &derived_sk.unique_pubkey() == unique_pubkey)
+            .unwrap_or_else(|| self.key.derive_key(derivation_index))
+    }
+
     /// Loads a serialized wallet from a path.
     fn load_from_path_and_key(wallet_dir: &Path, main_key: Option<MainSecretKey>) -> Result<Self> {
         let key = match get_main_key(wallet_dir)? {
@@ -626,9 +845,9 @@ impl HotWallet {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeMap;
+    use std::collections::{BTreeMap, BTreeSet};
 
-    use super::HotWallet;
+    use super::{ActivitySummary, Error, HotWallet};
     use crate::{
         genesis::{create_first_cash_note_from_key, GENESIS_CASHNOTE_AMOUNT},
         wallet::{
@@ -736,6 +955,110 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn held_cash_notes_returns_deposited_cash_notes() -> Result<()> {
+        let key = MainSecretKey::random();
+        let main_pubkey = key.main_pubkey();
+        let genesis = create_first_cash_note_from_key(&key).expect("Genesis creation to succeed.");
+        let dir = create_temp_dir();
+
+        let mut local_wallet = HotWallet {
+            key,
+            watchonly_wallet: WatchOnlyWallet::new(main_pubkey, &dir, KeyLessWallet::default()),
+            unconfirmed_spend_requests: Default::default(),
+        };
+        assert!(local_wallet.held_cash_notes().is_empty());
+
+        local_wallet.deposit_and_store_to_disk(&vec![genesis.clone()])?;
+
+        assert_eq!(local_wallet.held_cash_notes(), vec![genesis]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn activity_summary_counts_deposits_as_receives() -> Result<()> {
+        let key = MainSecretKey::random();
+        let main_pubkey = key.main_pubkey();
+        let genesis = create_first_cash_note_from_key(&key).expect("Genesis creation to succeed.");
+        let dir = create_temp_dir();
+
+        let mut local_wallet = HotWallet {
+            key,
+            watchonly_wallet: WatchOnlyWallet::new(main_pubkey, &dir, KeyLessWallet::default()),
+            unconfirmed_spend_requests: Default::default(),
+        };
+        assert_eq!(local_wallet.activity_summary()?, ActivitySummary::default());
+
+        local_wallet.deposit_and_store_to_disk(&vec![genesis])?;
+
+        let summary = local_wallet.activity_summary()?;
+        assert_eq!(summary.total_sends, 0);
+        assert_eq!(summary.total_receives, 1);
+        assert!(summary.last_activity.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn activity_summary_counts_sends_but_not_their_change() -> Result<()> {
+        let dir = create_temp_dir();
+        let root_dir = dir.path().to_path_buf();
+
+        let mut sender = HotWallet::load_from(&root_dir)?;
+        let genesis =
+            create_first_cash_note_from_key(&sender.key).expect("Genesis creation to succeed.");
+        sender.deposit_and_store_to_disk(&vec![genesis])?;
+
+        let recipient_main_pubkey = MainSecretKey::random().main_pubkey();
+        for _ in 0..2 {
+            let to = vec![(NanoTokens::from(100), recipient_main_pubkey)];
+            let _created_cash_notes = sender.local_send(to, None)?;
+        }
+
+        // the summary is read back from disk, as by a later run
+        let summary = HotWallet::load_from(&root_dir)?.activity_summary()?;
+        assert_eq!(summary.total_sends, 2);
+        assert_eq!(summary.total_receives, 1);
+        assert!(summary.last_activity.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn concurrent_sends_on_the_same_wallet_are_rejected() -> Result<()> {
+        let dir = create_temp_dir();
+        let key = MainSecretKey::random();
+        let main_pubkey = key.main_pubkey();
+        let first_wallet = HotWallet {
+            key,
+            watchonly_wallet: WatchOnlyWallet::new(main_pubkey, &dir, KeyLessWallet::default()),
+            unconfirmed_spend_requests: Default::default(),
+        };
+        let key = MainSecretKey::random();
+        let main_pubkey = key.main_pubkey();
+        let second_wallet = HotWallet {
+            key,
+            watchonly_wallet: WatchOnlyWallet::new(main_pubkey, &dir, KeyLessWallet::default()),
+            unconfirmed_spend_requests: Default::default(),
+        };
+
+        let first_send = first_wallet.try_lock_sends()?;
+        assert!(matches!(
+            second_wallet.try_lock_sends(),
+            Err(Error::SendInProgress)
+        ));
+
+        drop(first_send);
+        let _second_send = second_wallet.try_lock_sends()?;
+        assert!(matches!(
+            first_wallet.try_lock_sends(),
+            Err(Error::SendInProgress)
+        ));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn deposit_does_not_add_cash_notes_not_belonging_to_the_wallet() -> Result<()> {
         let key = MainSecretKey::random();
@@ -861,6 +1184,60 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn sending_zero_amount_is_rejected() -> Result<()> {
+        let dir = create_temp_dir();
+        let root_dir = dir.path().to_path_buf();
+
+        let mut sender = HotWallet::load_from(&root_dir)?;
+        let sender_cash_note =
+            create_first_cash_note_from_key(&sender.key).expect("Genesis creation to succeed.");
+        sender.deposit_and_store_to_disk(&vec![sender_cash_note])?;
+
+        let recipient_main_pubkey = MainSecretKey::random().main_pubkey();
+        let to = vec![
+            (NanoTokens::from(100), recipient_main_pubkey),
+            (NanoTokens::zero(), recipient_main_pubkey),
+        ];
+        assert!(matches!(
+            sender.local_send(to, None),
+            Err(Error::ZeroAmount)
+        ));
+        assert_eq!(GENESIS_CASHNOTE_AMOUNT, sender.balance().as_nano());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cash_notes_sent_to_a_receive_address_can_be_deposited_and_spent() -> Result<()> {
+        let dir = create_temp_dir();
+        let root_dir = dir.path().to_path_buf();
+
+        let mut sender = HotWallet::load_from(&root_dir.join("sender"))?;
+        let sender_cash_note =
+            create_first_cash_note_from_key(&sender.key).expect("Genesis creation to succeed.");
+        sender.deposit_and_store_to_disk(&vec![sender_cash_note])?;
+
+        let mut recipient = HotWallet::load_from(&root_dir.join("recipient"))?;
+        let receive_address = recipient.new_receive_address()?;
+        assert_ne!(receive_address, recipient.address());
+        assert_eq!(vec![receive_address], recipient.receive_addresses());
+
+        let send_amount = 100;
+        let created_cash_notes =
+            sender.local_send(vec![(NanoTokens::from(send_amount), receive_address)], None)?;
+        recipient.deposit_and_store_to_disk(&created_cash_notes)?;
+        assert_eq!(send_amount, recipient.balance().as_nano());
+
+        // the receive address survives a reload, and its cash_notes can be spent
+        let mut recipient = HotWallet::load_from(&root_dir.join("recipient"))?;
+        assert_eq!(vec![receive_address], recipient.receive_addresses());
+        let _ = recipient.local_send(vec![(NanoTokens::from(40), sender.address())], None)?;
+        assert_eq!(send_amount - 40, recipient.balance().as_nano());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn send_wallet_to_and_from_file() -> Result<()> {
         let dir = create_temp_dir();
@@ -1019,6 +1396,138 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_local_send_storage_payment_dry_run_leaves_wallet_untouched() -> Result<()> {
+        let dir = create_temp_dir();
+        let root_dir = dir.path().to_path_buf();
+
+        let mut sender = HotWallet::load_from(&root_dir)?;
+        let sender_cash_note =
+            create_first_cash_note_from_key(&sender.key).expect("Genesis creation to succeed.");
+        sender.deposit_and_store_to_disk(&vec![sender_cash_note])?;
+        let balance = sender.balance();
+
+        let mut rng = bls::rand::thread_rng();
+        let xor1 = XorName::random(&mut rng);
+        let xor2 = XorName::random(&mut rng);
+        let key1a = MainSecretKey::random().main_pubkey();
+        let key2a = MainSecretKey::random().main_pubkey();
+
+        let map = BTreeMap::from([
+            (
+                xor1,
+                (key1a, PaymentQuote::test_dummy(xor1, 100.into()), vec![]),
+            ),
+            (
+                xor2,
+                (key2a, PaymentQuote::test_dummy(xor2, 200.into()), vec![]),
+            ),
+        ]);
+
+        let (price, royalties) = sender.local_send_storage_payment_dry_run(&map)?;
+
+        assert_eq!(price.as_nano(), 300);
+        assert_eq!(balance, sender.balance());
+        assert!(!sender.unconfirmed_spend_requests_exist());
+        assert!(sender.get_cached_payment_for_xorname(&xor1).is_none());
+
+        // the actual payment costs the same
+        assert_eq!((price, royalties), sender.local_send_storage_payment(&map)?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_send_storage_payment_with_inputs_only_spends_provided_inputs() -> Result<()>
+    {
+        let dir = create_temp_dir();
+        let root_dir = dir.path().to_path_buf();
+
+        let mut sender = HotWallet::load_from(&root_dir)?;
+        let sender_cash_note =
+            create_first_cash_note_from_key(&sender.key).expect("Genesis creation to succeed.");
+        sender.deposit_and_store_to_disk(&vec![sender_cash_note])?;
+
+        // segregate a small pool of funds into its own cash_note
+        let pool_amount = NanoTokens::from(1_000);
+        let pool = sender.local_send(vec![(pool_amount, sender.address())], None)?;
+        sender.deposit_and_store_to_disk(&pool)?;
+        sender.clear_confirmed_spend_requests();
+        let balance_before = sender.balance();
+
+        let mut rng = bls::rand::thread_rng();
+        let xor = XorName::random(&mut rng);
+        let key = MainSecretKey::random().main_pubkey();
+
+        // inputs that don't cover the cost must error before anything is spent
+        let expensive_map = BTreeMap::from([(
+            xor,
+            (key, PaymentQuote::test_dummy(xor, 2_000.into()), vec![]),
+        )]);
+        let result = sender.local_send_storage_payment_with_inputs(&expensive_map, &pool);
+        assert!(matches!(
+            result,
+            Err(Error::Transfer(crate::Error::NotEnoughBalance(_, _)))
+        ));
+        assert_eq!(sender.balance(), balance_before);
+        assert!(!sender.unconfirmed_spend_requests_exist());
+
+        // inputs covering the cost are the only ones spent
+        let cheap_map = BTreeMap::from([(
+            xor,
+            (key, PaymentQuote::test_dummy(xor, 100.into()), vec![]),
+        )]);
+        let (price, royalties) =
+            sender.local_send_storage_payment_with_inputs(&cheap_map, &pool)?;
+        assert_eq!(price.as_nano(), 100);
+
+        let spent: BTreeSet<_> = sender
+            .unconfirmed_spend_requests()
+            .iter()
+            .map(|spend| *spend.unique_pubkey())
+            .collect();
+        let pool_keys: BTreeSet<_> = pool
+            .iter()
+            .map(|cash_note| cash_note.unique_pubkey())
+            .collect();
+        assert_eq!(spent, pool_keys);
+        assert_eq!(
+            sender.balance().as_nano(),
+            balance_before.as_nano() - price.as_nano() - royalties.as_nano()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_send_storage_payment_with_unknown_inputs_fails() -> Result<()> {
+        let dir = create_temp_dir();
+        let root_dir = dir.path().to_path_buf();
+
+        let mut sender = HotWallet::load_from(&root_dir)?;
+        let sender_cash_note =
+            create_first_cash_note_from_key(&sender.key).expect("Genesis creation to succeed.");
+        sender.deposit_and_store_to_disk(&vec![sender_cash_note])?;
+
+        let foreign_key = MainSecretKey::random();
+        let foreign_cash_note =
+            create_first_cash_note_from_key(&foreign_key).expect("Genesis creation to succeed.");
+
+        let mut rng = bls::rand::thread_rng();
+        let xor = XorName::random(&mut rng);
+        let key = MainSecretKey::random().main_pubkey();
+        let map = BTreeMap::from([(
+            xor,
+            (key, PaymentQuote::test_dummy(xor, 100.into()), vec![]),
+        )]);
+
+        let result = sender.local_send_storage_payment_with_inputs(&map, &[foreign_cash_note]);
+        assert!(matches!(result, Err(Error::CouldNotSendMoney(_))));
+        assert!(!sender.unconfirmed_spend_requests_exist());
+
+        Ok(())
+    }
+
     fn create_temp_dir() -> TempDir {
         TempDir::new().expect("Should be able to create a temp dir.")
     }
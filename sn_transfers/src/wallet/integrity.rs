@@ -0,0 +1,225 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    error::{Error, Result},
+    keys::{get_main_key, get_main_pubkey, MAIN_PUBKEY_FILENAME, MAIN_SECRET_KEY_FILENAME},
+    wallet_file::{
+        cash_note_file_name, cash_notes_dir_name, get_unconfirmed_spend_requests, wallet_file_name,
+        UNCONFIRMED_TX_NAME,
+    },
+    KeyLessWallet,
+};
+use crate::{CashNote, MainPubkey, UniquePubkey};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The outcome of checking the files of a wallet dir with [`verify_wallet_dir`].
+#[derive(Debug, Clone, Default)]
+pub struct WalletIntegrityReport {
+    /// The main public key of the wallet, derived from its secret key, or read from its public
+    /// key file for a watch-only wallet. `None` if neither could be read.
+    pub main_pubkey: Option<MainPubkey>,
+    /// Whether the wallet holds its secret key, i.e. whether it can spend its cash notes.
+    pub has_secret_key: bool,
+    /// The cash notes whose file was read and is stored under their own address.
+    pub valid_cash_notes: BTreeSet<UniquePubkey>,
+    /// The problems found, by the file they were found in.
+    pub issues: BTreeMap<PathBuf, String>,
+}
+
+impl WalletIntegrityReport {
+    /// Whether no problem was found, i.e. the wallet can be loaded as it is.
+    pub fn is_intact(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks that the files of the wallet stored in `wallet_dir` are well-formed, without loading
+/// the wallet nor connecting to the network, e.g. to decide whether a backup is usable.
+///
+/// The wallet file and the unconfirmed spends have to parse, the keys have to be valid and
+/// match, and each cash note file has to be readable and stored under the address of the
+/// cash note it holds. The cash notes the wallet file records as available have to be on disk.
+///
+/// Nothing is written to the wallet dir. An error is only returned if the dir can't be read,
+/// the problems found in its files are listed in the report.
+pub fn verify_wallet_dir(wallet_dir: &Path) -> Result<WalletIntegrityReport> {
+    if !wallet_dir.is_dir() {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("No wallet dir found at {wallet_dir:?}"),
+        )));
+    }
+
+    let mut report = WalletIntegrityReport::default();
+    verify_keys(wallet_dir, &mut report);
+
+    let wallet_path = wallet_file_name(wallet_dir);
+    let recorded_cash_notes = match fs::read(&wallet_path) {
+        Ok(bytes) => match rmp_serde::from_slice::<KeyLessWallet>(&bytes) {
+            Ok(wallet) => wallet.available_cash_notes,
+            Err(err) => {
+                let _ = report
+                    .issues
+                    .insert(wallet_path, format!("Invalid wallet file: {err}"));
+                BTreeMap::new()
+            }
+        },
+        // a wallet which never held anything may have no wallet file yet
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+        Err(err) => {
+            let _ = report
+                .issues
+                .insert(wallet_path, format!("Unreadable wallet file: {err}"));
+            BTreeMap::new()
+        }
+    };
+
+    if let Err(err) = get_unconfirmed_spend_requests(wallet_dir) {
+        let _ = report.issues.insert(
+            wallet_dir.join(UNCONFIRMED_TX_NAME),
+            format!("Invalid unconfirmed spend requests: {err}"),
+        );
+    }
+
+    let cash_notes_dir = cash_notes_dir_name(wallet_dir);
+    for entry in walkdir::WalkDir::new(&cash_notes_dir).into_iter().flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        match verify_cash_note_file(path) {
+            Ok(unique_pubkey) => {
+                let _ = report.valid_cash_notes.insert(unique_pubkey);
+            }
+            Err(issue) => {
+                let _ = report.issues.insert(path.to_path_buf(), issue);
+            }
+        }
+    }
+
+    for unique_pubkey in recorded_cash_notes.keys() {
+        if !report.valid_cash_notes.contains(unique_pubkey) {
+            let path = cash_notes_dir.join(cash_note_file_name(unique_pubkey));
+            let _ = report.issues.entry(path).or_insert_with(|| {
+                format!("CashNote {unique_pubkey:?} is recorded as available but is missing")
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Checks the secret key of the wallet, or its public key for a watch-only wallet.
+fn verify_keys(wallet_dir: &Path, report: &mut WalletIntegrityReport) {
+    let main_key = match get_main_key(wallet_dir) {
+        Ok(main_key) => main_key,
+        Err(err) => {
+            let _ = report.issues.insert(
+                wallet_dir.join(MAIN_SECRET_KEY_FILENAME),
+                format!("Invalid secret key: {err}"),
+            );
+            None
+        }
+    };
+    let main_pubkey = match get_main_pubkey(wallet_dir) {
+        Ok(main_pubkey) => main_pubkey,
+        Err(err) => {
+            let _ = report.issues.insert(
+                wallet_dir.join(MAIN_PUBKEY_FILENAME),
+                format!("Invalid public key: {err}"),
+            );
+            None
+        }
+    };
+
+    report.has_secret_key = main_key.is_some();
+    report.main_pubkey = match (main_key, main_pubkey) {
+        (Some(main_key), Some(main_pubkey)) if main_key.main_pubkey() != main_pubkey => {
+            let _ = report.issues.insert(
+                wallet_dir.join(MAIN_PUBKEY_FILENAME),
+                Error::PubKeyMismatch(wallet_dir.to_path_buf()).to_string(),
+            );
+            Some(main_key.main_pubkey())
+        }
+        (Some(main_key), _) => Some(main_key.main_pubkey()),
+        (None, main_pubkey) => main_pubkey,
+    };
+}
+
+/// Reads a cash note file, checking it's named after the address of the cash note it holds.
+fn verify_cash_note_file(path: &Path) -> std::result::Result<UniquePubkey, String> {
+    let hex = fs::read_to_string(path).map_err(|err| format!("Unreadable CashNote: {err}"))?;
+    let cash_note =
+        CashNote::from_hex(hex.trim()).map_err(|err| format!("Invalid CashNote: {err}"))?;
+    let unique_pubkey = cash_note.unique_pubkey();
+
+    let expected_file_name = cash_note_file_name(&unique_pubkey);
+    if path.file_name().and_then(|name| name.to_str()) != Some(expected_file_name.as_str()) {
+        return Err(format!(
+            "CashNote {unique_pubkey:?} is not stored under its address, expected {expected_file_name}"
+        ));
+    }
+    Ok(unique_pubkey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{genesis::create_first_cash_note_from_key, HotWallet, MainSecretKey};
+    use assert_fs::TempDir;
+    use eyre::Result;
+
+    #[test]
+    fn intact_wallet_dir_passes_the_check() -> Result<()> {
+        let dir = TempDir::new()?;
+        let key = MainSecretKey::random();
+        let main_pubkey = key.main_pubkey();
+        let cash_note = create_first_cash_note_from_key(&key)?;
+        let mut wallet = HotWallet::load_from_path(dir.path(), Some(key))?;
+        wallet.deposit_and_store_to_disk(&vec![cash_note.clone()])?;
+
+        let report = verify_wallet_dir(dir.path())?;
+
+        assert!(report.is_intact(), "Unexpected issues: {:?}", report.issues);
+        assert!(report.has_secret_key);
+        assert_eq!(report.main_pubkey, Some(main_pubkey));
+        assert!(report.valid_cash_notes.contains(&cash_note.unique_pubkey()));
+        Ok(())
+    }
+
+    #[test]
+    fn corrupted_and_misplaced_cash_notes_are_reported() -> Result<()> {
+        let dir = TempDir::new()?;
+        let key = MainSecretKey::random();
+        let cash_note = create_first_cash_note_from_key(&key)?;
+        let mut wallet = HotWallet::load_from_path(dir.path(), Some(key))?;
+        wallet.deposit_and_store_to_disk(&vec![cash_note.clone()])?;
+
+        let cash_notes_dir = cash_notes_dir_name(dir.path());
+        let stored_path = cash_notes_dir.join(cash_note_file_name(&cash_note.unique_pubkey()));
+        let misplaced_path = cash_notes_dir.join("misplaced.cash_note");
+        let corrupted_path = cash_notes_dir.join("corrupted.cash_note");
+        fs::rename(&stored_path, &misplaced_path)?;
+        fs::write(&corrupted_path, "not a cash note")?;
+
+        let report = verify_wallet_dir(dir.path())?;
+
+        assert!(!report.is_intact());
+        assert!(report.valid_cash_notes.is_empty());
+        assert!(report.issues.contains_key(&misplaced_path));
+        assert!(report.issues.contains_key(&corrupted_path));
+        // the wallet file still records the cash note as available
+        assert!(report.issues.contains_key(&stored_path));
+        Ok(())
+    }
+}
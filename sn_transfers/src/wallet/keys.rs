@@ -14,9 +14,9 @@ use hex::{decode, encode};
 use std::path::Path;
 
 /// Filename for storing the node's reward (BLS hex-encoded) main secret key.
-const MAIN_SECRET_KEY_FILENAME: &str = "main_secret_key";
+pub(super) const MAIN_SECRET_KEY_FILENAME: &str = "main_secret_key";
 /// Filename for storing the node's reward (BLS hex-encoded) public key.
-const MAIN_PUBKEY_FILENAME: &str = "main_pubkey";
+pub(super) const MAIN_PUBKEY_FILENAME: &str = "main_pubkey";
 
 /// Writes the public address and main key (hex-encoded) to different locations at disk.
 pub(crate) fn store_new_keypair(wallet_dir: &Path, main_key: &MainSecretKey) -> Result<()> {
@@ -10,19 +10,24 @@ use super::{
     error::{Error, Result},
     KeyLessWallet,
 };
-use crate::{CashNote, SignedSpend, SpendAddress, UniquePubkey};
+use crate::{CashNote, Hash, SignedSpend, SpendAddress, UniquePubkey};
 use serde::Serialize;
 use std::{
     collections::BTreeSet,
     fs,
+    io::Write,
     path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 // Filename for storing a wallet.
 const WALLET_FILE_NAME: &str = "wallet";
 const WALLET_LOCK_FILE_NAME: &str = "wallet.lock";
+const WALLET_SEND_LOCK_FILE_NAME: &str = "wallet.send.lock";
 const CASHNOTES_DIR_NAME: &str = "cash_notes";
-const UNCONFIRMED_TX_NAME: &str = "unconfirmed_spend_requests";
+pub(super) const UNCONFIRMED_TX_NAME: &str = "unconfirmed_spend_requests";
+pub(super) const STAGING_DIR_NAME: &str = "staging";
+const TRANSFER_HISTORY_FILE_NAME: &str = "transfer_history";
 
 /// Writes the `KeyLessWallet` to the specified path.
 pub(super) fn store_wallet(wallet_dir: &Path, wallet: &KeyLessWallet) -> Result<()> {
@@ -33,16 +38,77 @@ pub(super) fn store_wallet(wallet_dir: &Path, wallet: &KeyLessWallet) -> Result<
     Ok(())
 }
 
+/// Writes the `KeyLessWallet` along with the given `CashNote`s, as a single step.
+/// Everything is first written to a staging dir, and only then moved into place, with the
+/// wallet file going last. This way a crash can't leave the wallet recording a spend
+/// while the resulting (change) cash_notes are missing from disk.
+pub(super) fn store_wallet_and_cash_notes<'a, T>(
+    wallet_dir: &Path,
+    wallet: &KeyLessWallet,
+    cash_notes: T,
+) -> Result<()>
+where
+    T: IntoIterator<Item = &'a CashNote>,
+{
+    let staging_dir = wallet_dir.join(STAGING_DIR_NAME);
+    let created_cash_notes_path = wallet_dir.join(CASHNOTES_DIR_NAME);
+    fs::create_dir_all(&staging_dir)?;
+    fs::create_dir_all(&created_cash_notes_path)?;
+
+    let mut staged = vec![];
+    for cash_note in cash_notes {
+        let file_name = cash_note_file_name(&cash_note.unique_pubkey());
+        let hex = cash_note
+            .to_hex()
+            .map_err(|_| Error::FailedToHexEncodeCashNote)?;
+        let staged_path = staging_dir.join(&file_name);
+        write_and_sync(&staged_path, hex.as_bytes())?;
+        staged.push((staged_path, created_cash_notes_path.join(file_name)));
+    }
+    let staged_wallet_path = staging_dir.join(WALLET_FILE_NAME);
+    write_and_sync(&staged_wallet_path, &rmp_serde::to_vec(wallet)?)?;
+    staged.push((staged_wallet_path, wallet_dir.join(WALLET_FILE_NAME)));
+
+    // commit, renames are atomic and the wallet file is the last one to be moved in
+    for (staged_path, path) in staged {
+        debug!("Committing staged {staged_path:?} to {path:?}");
+        fs::rename(staged_path, path)?;
+    }
+    Ok(())
+}
+
+fn write_and_sync(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Returns the name of the file a `CashNote` is stored in.
+pub(super) fn cash_note_file_name(unique_pubkey: &UniquePubkey) -> String {
+    let unique_pubkey_name = *SpendAddress::from_unique_pubkey(unique_pubkey).xorname();
+    format!("{}.cash_note", hex::encode(unique_pubkey_name))
+}
+
 /// Returns the wallet filename
 pub(super) fn wallet_file_name(wallet_dir: &Path) -> PathBuf {
     wallet_dir.join(WALLET_FILE_NAME)
 }
 
+/// Returns the dir the cash_notes of the wallet are stored in
+pub(super) fn cash_notes_dir_name(wallet_dir: &Path) -> PathBuf {
+    wallet_dir.join(CASHNOTES_DIR_NAME)
+}
+
 /// Returns the wallet lockfile filename
 pub(super) fn wallet_lockfile_name(wallet_dir: &Path) -> PathBuf {
     wallet_dir.join(WALLET_LOCK_FILE_NAME)
 }
 
+pub(super) fn wallet_send_lockfile_name(wallet_dir: &Path) -> PathBuf {
+    wallet_dir.join(WALLET_SEND_LOCK_FILE_NAME)
+}
+
 /// Writes the `unconfirmed_spend_requests` to the specified path.
 pub(super) fn store_unconfirmed_spend_requests(
     wallet_dir: &Path,
@@ -171,6 +237,69 @@ pub(super) fn load_cash_notes_from_disk(wallet_dir: &Path) -> Result<Vec<CashNot
     Ok(deposits)
 }
 
+/// Whether a transaction recorded in the transfer history spent or received cash_notes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum TransferDirection {
+    Send,
+    Receive,
+}
+
+/// Appends the transactions to the transfer history of the wallet, one line per transaction
+/// with its direction, hash and the time it was recorded at.
+pub(super) fn append_transfer_history(
+    wallet_dir: &Path,
+    direction: TransferDirection,
+    tx_hashes: impl IntoIterator<Item = Hash>,
+) -> Result<()> {
+    let recorded_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let direction = match direction {
+        TransferDirection::Send => "send",
+        TransferDirection::Receive => "receive",
+    };
+    let lines: String = tx_hashes
+        .into_iter()
+        .map(|tx_hash| format!("{direction} {} {recorded_at}\n", tx_hash.to_hex()))
+        .collect();
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(wallet_dir.join(TRANSFER_HISTORY_FILE_NAME))?;
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Loads the transfer history of the wallet, skipping the lines that can't be parsed.
+/// A wallet without history yet has an empty one.
+pub(super) fn load_transfer_history(
+    wallet_dir: &Path,
+) -> Result<Vec<(TransferDirection, Hash, SystemTime)>> {
+    let content = match fs::read_to_string(wallet_dir.join(TRANSFER_HISTORY_FILE_NAME)) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+
+    let history = content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let direction = match fields.next()? {
+                "send" => TransferDirection::Send,
+                "receive" => TransferDirection::Receive,
+                _ => return None,
+            };
+            let tx_hash = Hash::from_hex(fields.next()?).ok()?;
+            let recorded_at = UNIX_EPOCH + Duration::from_secs(fields.next()?.parse().ok()?);
+            Some((direction, tx_hash, recorded_at))
+        })
+        .collect();
+    Ok(history)
+}
+
 /// Loads a specific cash_note from path
 pub fn load_created_cash_note(unique_pubkey: &UniquePubkey, wallet_dir: &Path) -> Option<CashNote> {
     trace!("Loading cash_note from file with pubkey: {unique_pubkey:?}");
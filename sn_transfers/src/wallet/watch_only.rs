@@ -8,11 +8,12 @@
 
 use super::{
     error::{Error, Result},
-    hot_wallet::WalletExclusiveAccess,
+    hot_wallet::{WalletExclusiveAccess, WalletSendGuard},
     keys::{get_main_pubkey, store_new_pubkey},
     wallet_file::{
-        load_cash_notes_from_disk, load_created_cash_note, store_created_cash_notes, store_wallet,
-        wallet_lockfile_name,
+        append_transfer_history, load_cash_notes_from_disk, load_created_cash_note, store_wallet,
+        store_wallet_and_cash_notes, wallet_lockfile_name, wallet_send_lockfile_name,
+        TransferDirection,
     },
     KeyLessWallet,
 };
@@ -144,6 +145,35 @@ impl WatchOnlyWallet {
         self.main_pubkey
     }
 
+    /// The addresses handed out to receive payments, besides the main one, with the index
+    /// their key is derived from the main key with.
+    pub fn receive_addresses(&self) -> &BTreeMap<MainPubkey, DerivationIndex> {
+        &self.keyless_wallet.receive_addresses
+    }
+
+    /// Whether the cash_notes sent to the address belong to this wallet, i.e. it is either
+    /// the main address or one of the receive addresses.
+    pub fn is_own_address(&self, address: &MainPubkey) -> bool {
+        address == &self.main_pubkey || self.keyless_wallet.receive_addresses.contains_key(address)
+    }
+
+    /// Records a receive address and stores the wallet to disk.
+    /// This function locks the wallet to prevent concurrent processes from writing to it
+    pub(super) fn add_receive_address(
+        &mut self,
+        address: MainPubkey,
+        derivation_index: DerivationIndex,
+    ) -> Result<()> {
+        std::fs::create_dir_all(&self.wallet_dir)?;
+        let exclusive_access = self.lock()?;
+        self.reload()?;
+
+        self.keyless_wallet
+            .receive_addresses
+            .insert(address, derivation_index);
+        self.store(exclusive_access)
+    }
+
     pub fn balance(&self) -> NanoTokens {
         self.keyless_wallet.balance()
     }
@@ -160,7 +190,7 @@ impl WatchOnlyWallet {
         for cash_note in received_cash_notes {
             let id = cash_note.unique_pubkey();
 
-            if cash_note.derived_pubkey(&self.main_pubkey).is_err() {
+            if !self.is_own_address(cash_note.main_pubkey()) {
                 debug!("skipping: cash_note is not our key");
                 continue;
             }
@@ -187,21 +217,26 @@ impl WatchOnlyWallet {
         self.reload()?;
         trace!("Wallet locked and loaded!");
 
+        let mut our_cash_notes = vec![];
         for cash_note in received_cash_notes {
             let id = cash_note.unique_pubkey();
 
-            if cash_note.derived_pubkey(&self.main_pubkey).is_err() {
+            if !self.is_own_address(cash_note.main_pubkey()) {
                 debug!("skipping: cash_note is not our key");
                 continue;
             }
 
             let value = cash_note.value()?;
             self.keyless_wallet.available_cash_notes.insert(id, value);
-
-            store_created_cash_notes([cash_note], &self.wallet_dir)?;
+            our_cash_notes.push(cash_note);
         }
 
-        self.store(exclusive_access)
+        let received_txs: BTreeSet<_> = our_cash_notes
+            .iter()
+            .map(|cash_note| cash_note.src_tx.hash())
+            .collect();
+        self.store_with_cash_notes(our_cash_notes, exclusive_access)?;
+        append_transfer_history(&self.wallet_dir, TransferDirection::Receive, received_txs)
     }
 
     /// Reloads the wallet from disk.
@@ -317,6 +352,22 @@ impl WatchOnlyWallet {
         Ok(())
     }
 
+    /// Stores the wallet to disk together with the given cash_notes, so that
+    /// either both or neither are persisted.
+    pub(super) fn store_with_cash_notes<'a, T>(
+        &self,
+        cash_notes: T,
+        exclusive_access: WalletExclusiveAccess,
+    ) -> Result<()>
+    where
+        T: IntoIterator<Item = &'a CashNote>,
+    {
+        store_wallet_and_cash_notes(&self.wallet_dir, &self.keyless_wallet, cash_notes)?;
+        trace!("Releasing wallet lock");
+        std::mem::drop(exclusive_access);
+        Ok(())
+    }
+
     // Locks the wallet and returns exclusive access to the wallet
     // This lock prevents any other process from locking the wallet dir, effectively acts as a mutex for the wallet
     pub(super) fn lock(&self) -> Result<WalletExclusiveAccess> {
@@ -331,14 +382,35 @@ impl WatchOnlyWallet {
         file.lock_exclusive()?;
         Ok(file)
     }
+
+    // Marks a send as in progress on the wallet, failing if another one already is.
+    // This is held for the whole send, including the network round trips, so is kept apart
+    // from the wallet lock, which only guards the local wallet updates.
+    pub(super) fn try_lock_sends(&self) -> Result<WalletSendGuard> {
+        let lock = wallet_send_lockfile_name(&self.wallet_dir);
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(lock)?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(err) = file.try_lock_exclusive() {
+            if err.kind() == fs2::lock_contended_error().kind() {
+                return Err(Error::SendInProgress);
+            }
+            return Err(err.into());
+        }
+        Ok(file)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::WatchOnlyWallet;
+    use super::{load_created_cash_note, WatchOnlyWallet};
     use crate::{
         genesis::{create_first_cash_note_from_key, GENESIS_CASHNOTE_AMOUNT},
-        wallet::KeyLessWallet,
+        wallet::{wallet_file::STAGING_DIR_NAME, KeyLessWallet},
         MainSecretKey, NanoTokens,
     };
     use assert_fs::TempDir;
@@ -432,4 +504,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn watchonly_wallet_commits_staged_cash_notes_with_wallet() -> Result<()> {
+        let main_sk = MainSecretKey::random();
+        let main_pubkey = main_sk.main_pubkey();
+        let wallet_dir = TempDir::new()?;
+        let mut wallet = WatchOnlyWallet::new(main_pubkey, &wallet_dir, KeyLessWallet::default());
+
+        let cash_note = create_first_cash_note_from_key(&main_sk)?;
+        wallet.deposit_and_store_to_disk(&vec![cash_note.clone()])?;
+
+        // nothing shall be left behind in the staging dir once committed
+        let staging_dir = wallet_dir.path().join(STAGING_DIR_NAME);
+        assert_eq!(0, std::fs::read_dir(staging_dir)?.count());
+
+        let stored = load_created_cash_note(&cash_note.unique_pubkey(), wallet_dir.path());
+        assert_eq!(Some(cash_note), stored);
+
+        let deserialised = WatchOnlyWallet::load_from(&wallet_dir, main_pubkey)?;
+        assert_eq!(GENESIS_CASHNOTE_AMOUNT, deserialised.balance().as_nano());
+
+        Ok(())
+    }
 }